@@ -7,6 +7,7 @@ use chrono::Timelike;
 use reqwest::header::ACCEPT;
 use serde_json::{Value, json};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, Instant};
 use std::{
@@ -52,6 +53,18 @@ pub fn analytics_api_key(environment: &Environment) -> String {
     }
 }
 
+/// Set when nothing may be reported, e.g. during a dry run
+static ANALYTICS_SUPPRESSED: AtomicBool = AtomicBool::new(false);
+
+/// Stop sending analytics and proving reports for the rest of the process
+pub fn suppress_analytics() {
+    ANALYTICS_SUPPRESSED.store(true, Ordering::Relaxed);
+}
+
+fn analytics_suppressed() -> bool {
+    ANALYTICS_SUPPRESSED.load(Ordering::Relaxed)
+}
+
 /// Track an event with the Firebase Measurement Protocol
 ///
 /// # Arguments
@@ -67,7 +80,7 @@ pub async fn track(
 ) -> Result<(), TrackError> {
     let analytics_id = analytics_id(environment);
     let analytics_api_key = analytics_api_key(environment);
-    if analytics_id.is_empty() {
+    if analytics_id.is_empty() || analytics_suppressed() {
         return Ok(());
     }
     let local_now = chrono::offset::Local::now();
@@ -169,6 +182,9 @@ pub async fn report_proving_if_needed() {
     let Some(wallet_address) = REPORT_WALLET_ADDRESS.get() else {
         return;
     };
+    if analytics_suppressed() {
+        return;
+    }
     // Initialize map
     let map = LAST_REPORT_BY_ADDRESS.get_or_init(|| Mutex::new(HashMap::new()));
 
@@ -2,6 +2,7 @@
 
 use crate::cli_messages::{print_error, print_info, print_success};
 use crate::environment::Environment;
+use crate::hooks::HooksConfig;
 use crate::orchestrator::Orchestrator;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
@@ -35,6 +36,10 @@ pub struct Config {
     /// Node ID, resolved to a valid u64 during `Config::resolve`
     #[serde(default)]
     pub node_id: String,
+
+    /// Commands and webhooks run on lifecycle events
+    #[serde(default, skip_serializing_if = "HooksConfig::is_empty")]
+    pub hooks: HooksConfig,
 }
 
 impl Config {
@@ -50,6 +55,7 @@ impl Config {
             wallet_address,
             node_id,
             environment: environment.to_string(),
+            hooks: HooksConfig::default(),
         }
     }
 
@@ -61,6 +67,13 @@ impl Config {
         Ok(config)
     }
 
+    /// Hooks in the config file at `path`, if any, so a rewritten config keeps them.
+    pub fn existing_hooks(path: &Path) -> HooksConfig {
+        Self::load_from_file(path)
+            .map(|config| config.hooks)
+            .unwrap_or_default()
+    }
+
     /// Saves the configuration to a JSON file at the given path.
     pub fn save(&self, path: &Path) -> Result<(), std::io::Error> {
         if let Some(parent) = path.parent() {
@@ -110,6 +123,7 @@ impl Config {
                 wallet_address,
                 node_id: node_id.to_string(),
                 environment: "".to_string(),
+                hooks: HooksConfig::default(),
             };
 
             return Ok(config);
@@ -202,6 +216,7 @@ mod tests {
             user_id: "test_user_id".to_string(),
             wallet_address: "0x1234567890abcdef1234567890abcdef12345678".to_string(),
             node_id: "test_node_id".to_string(),
+            hooks: HooksConfig::default(),
         }
     }
 
@@ -341,6 +356,7 @@ mod tests {
             user_id: "".to_string(),
             wallet_address: "".to_string(),
             node_id: "12345".to_string(),
+            hooks: HooksConfig::default(),
         };
         config.save(&path).unwrap();
 
@@ -38,6 +38,253 @@ pub mod cli_consts {
         /// Time threshold for auto-promotion (seconds)
         /// Tasks completing faster than this will promote to next difficulty level
         pub const PROMOTION_THRESHOLD_SECS: u64 = 7 * 60; // 7 minutes
+
+        /// Tasks taking at least this long demote to the previous difficulty level.
+        /// The gap to the promotion threshold keeps the level from flapping.
+        pub const DEMOTION_THRESHOLD_SECS: u64 = 20 * 60; // 20 minutes
+
+        /// Proving time after which a task is given up and the difficulty demoted.
+        /// Time spent suspended does not count.
+        pub const PROVING_TIMEOUT_SECS: u64 = 60 * 60; // 1 hour
+
+        /// Consecutive proof failures (other than timeouts and OOM) before demoting
+        pub const FAILURES_BEFORE_DEMOTION: u32 = 3;
+
+        /// Fast tasks required after a demotion before promoting again
+        pub const PROMOTION_COOLDOWN_TASKS: u32 = 3;
+
+        /// Recent task durations kept per difficulty level
+        pub const DURATION_HISTORY_LEN: usize = 20;
+
+        /// History file name, created next to the config file in ~/.nexus
+        pub const HISTORY_FILE_NAME: &str = "difficulty.json";
+
+        /// Helper function to get the proving timeout
+        pub const fn proving_timeout() -> std::time::Duration {
+            std::time::Duration::from_secs(PROVING_TIMEOUT_SECS)
+        }
+    }
+
+    // =============================================================================
+    // SHUTDOWN CONFIGURATION
+    // =============================================================================
+
+    /// Graceful shutdown configuration
+    pub mod shutdown {
+        use std::time::Duration;
+
+        /// Default time allowed for the in-flight task to finish and submit during a drain
+        pub const DRAIN_TIMEOUT_SECS: u64 = 10 * 60; // 10 minutes
+
+        /// Exit code used when an in-flight task had to be abandoned during shutdown
+        /// (EX_TEMPFAIL, so supervisors can tell it apart from a crash)
+        pub const ABANDONED_TASK_EXIT_CODE: i32 = 75;
+
+        /// Exit code used when `--max-consecutive-failures` stopped the node
+        pub const FAILURE_LIMIT_EXIT_CODE: i32 = 1;
+
+        /// Exit code used when the orchestrator refused the node in a way only the
+        /// operator can fix, such as an unknown node or an outdated CLI
+        /// (EX_CONFIG, so supervisors don't restart it in a loop)
+        pub const FATAL_ERROR_EXIT_CODE: i32 = 78;
+
+        /// Helper function to get the default drain timeout
+        pub const fn drain_timeout() -> Duration {
+            Duration::from_secs(DRAIN_TIMEOUT_SECS)
+        }
+    }
+
+    // =============================================================================
+    // CONTROL API CONFIGURATION
+    // =============================================================================
+
+    /// Local control socket configuration
+    pub mod control {
+        /// Socket file name, created next to the config file in ~/.nexus
+        pub const SOCKET_FILE_NAME: &str = "control.sock";
+
+        /// Events buffered per `ctl tail` client before it starts missing some
+        pub const TAIL_BUFFER_SIZE: usize = 256;
+    }
+
+    // =============================================================================
+    // THROTTLING CONFIGURATION
+    // =============================================================================
+
+    /// Backing off when the host is busy
+    pub mod throttle {
+        use std::time::Duration;
+
+        /// How often host conditions are sampled
+        pub const SAMPLE_INTERVAL_SECS: u64 = 5;
+
+        /// How long load must stay below the threshold before proving speeds up again
+        pub const QUIET_PERIOD_SECS: u64 = 60;
+
+        /// Proving threads kept when reducing concurrency under load
+        pub const REDUCED_WORKERS: usize = 1;
+
+        /// Degrees Celsius below the limit a hot machine must cool to before proving resumes
+        pub const TEMPERATURE_HYSTERESIS_C: f32 = 5.0;
+
+        /// Percentage points above the minimum a battery must recover to before proving resumes
+        pub const BATTERY_HYSTERESIS_PERCENT: u8 = 5;
+
+        /// Where Linux exposes thermal sensors and power supplies
+        pub const SYSFS_ROOT: &str = "/sys";
+
+        /// Helper function to get the sample interval
+        pub const fn sample_interval() -> Duration {
+            Duration::from_secs(SAMPLE_INTERVAL_SECS)
+        }
+    }
+
+    // =============================================================================
+    // ENERGY ACCOUNTING CONFIGURATION
+    // =============================================================================
+
+    /// RAPL energy counters
+    pub mod energy {
+        /// How often counters are read while proving, so a wraparound is never missed
+        pub const RAPL_SAMPLE_INTERVAL_SECS: u64 = 10;
+
+        /// Joules in a kilowatt-hour
+        pub const JOULES_PER_KWH: f64 = 3_600_000.0;
+
+        /// Helper function to get the sample interval
+        pub const fn rapl_sample_interval() -> std::time::Duration {
+            std::time::Duration::from_secs(RAPL_SAMPLE_INTERVAL_SECS)
+        }
+    }
+
+    // =============================================================================
+    // TASK HISTORY CONFIGURATION
+    // =============================================================================
+
+    /// Local record of every task
+    pub mod history {
+        /// History file name, next to the config file
+        pub const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+        /// How often memory use is sampled while proving
+        pub const RSS_SAMPLE_INTERVAL_SECS: u64 = 2;
+
+        /// Helper function to get the memory sample interval
+        pub const fn rss_sample_interval() -> std::time::Duration {
+            std::time::Duration::from_secs(RSS_SAMPLE_INTERVAL_SECS)
+        }
+    }
+
+    // =============================================================================
+    // HOOKS CONFIGURATION
+    // =============================================================================
+
+    /// User-defined commands and webhooks run on lifecycle events
+    pub mod hooks {
+        /// How long a hook may run unless it sets `timeout_secs`
+        pub const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+        /// Hooks running at once unless `max_concurrent` is set
+        pub const DEFAULT_MAX_CONCURRENT: usize = 4;
+
+        /// Hooks waiting or running at once; beyond this, further invocations are dropped
+        pub const MAX_QUEUED: u32 = 64;
+
+        /// Helper function to get the default timeout
+        pub const fn default_timeout() -> std::time::Duration {
+            std::time::Duration::from_secs(DEFAULT_TIMEOUT_SECS)
+        }
+    }
+
+    // =============================================================================
+    // HEALTH CHECK CONFIGURATION
+    // =============================================================================
+
+    /// `/livez` and `/readyz` endpoints
+    pub mod health {
+        /// How often the liveness heartbeat ticks
+        pub const HEARTBEAT_INTERVAL_SECS: u64 = 1;
+
+        /// Heartbeat age, or time the event loop may keep events waiting, before `/livez` fails
+        pub const LIVENESS_TIMEOUT_SECS: u64 = 30;
+
+        /// Default longest time without a successful fetch, or submission, before `/readyz`
+        /// fails. Covers a task proving up to the proving timeout plus fetch backoff.
+        pub const READY_WITHIN_SECS: u64 = 2 * 60 * 60; // 2 hours
+
+        /// Submissions in a row that may fail before `/readyz` fails
+        pub const MAX_SUBMIT_FAILURES: u32 = 3;
+
+        /// How long a client may take to send its request
+        pub const REQUEST_TIMEOUT_SECS: u64 = 5;
+
+        /// Largest request head accepted
+        pub const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+        /// Helper function to get the heartbeat interval
+        pub const fn heartbeat_interval() -> std::time::Duration {
+            std::time::Duration::from_secs(HEARTBEAT_INTERVAL_SECS)
+        }
+
+        /// Helper function to get the liveness timeout
+        pub const fn liveness_timeout() -> std::time::Duration {
+            std::time::Duration::from_secs(LIVENESS_TIMEOUT_SECS)
+        }
+
+        /// Helper function to get the request timeout
+        pub const fn request_timeout() -> std::time::Duration {
+            std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS)
+        }
+    }
+
+    // =============================================================================
+    // SYSTEMD CONFIGURATION
+    // =============================================================================
+
+    /// `sd_notify` support and the unit written by `install-service`
+    pub mod systemd {
+        /// Unit name, without the `.service` suffix
+        pub const SERVICE_NAME: &str = "nexus-network";
+
+        /// `WatchdogSec=` in the generated unit; the worker pings at half this interval
+        pub const WATCHDOG_SECS: u64 = 5 * 60; // 5 minutes
+
+        /// Longest a single task may stay in flight before watchdog pings stop: the proving
+        /// timeout plus room for submission retries
+        pub const WATCHDOG_TASK_DEADLINE_SECS: u64 = 90 * 60; // 90 minutes
+
+        /// `RestartSec=` in the generated unit
+        pub const RESTART_SECS: u64 = 30;
+
+        /// Time allowed to stop on top of the drain timeout, for `TimeoutStopSec=`
+        pub const STOP_MARGIN_SECS: u64 = 60;
+
+        /// Helper function to get the in-flight task deadline
+        pub const fn watchdog_task_deadline() -> std::time::Duration {
+            std::time::Duration::from_secs(WATCHDOG_TASK_DEADLINE_SECS)
+        }
+    }
+
+    // =============================================================================
+    // LOG FILE CONFIGURATION
+    // =============================================================================
+
+    /// Rotating log file written alongside the dashboard or console
+    pub mod logs {
+        /// Directory under ~/.nexus holding the log file and its rotated segments
+        pub const LOG_DIR_NAME: &str = "logs";
+
+        /// Name of the active log file
+        pub const LOG_FILE_NAME: &str = "nexus-network.log";
+
+        /// Default size at which the log file is rotated, in megabytes
+        pub const DEFAULT_MAX_SIZE_MB: u64 = 10;
+
+        /// Default age at which the log file is rotated, in hours
+        pub const DEFAULT_MAX_AGE_HOURS: u64 = 24;
+
+        /// Default number of compressed segments kept
+        pub const DEFAULT_KEEP: usize = 14;
     }
 
     // =============================================================================
@@ -58,6 +305,10 @@ pub mod cli_consts {
         /// Modified for testnet stress testing
         pub const RATE_LIMIT_INTERVAL_MS: u64 = 0;
 
+        /// Most pages of assigned tasks read at startup or by `tasks list`,
+        /// in case an orchestrator never returns an empty cursor
+        pub const MAX_ASSIGNED_TASK_PAGES: usize = 50;
+
         /// Helper function to get initial backoff duration
         pub const fn initial_backoff() -> Duration {
             Duration::from_millis(INITIAL_BACKOFF_MS)
@@ -85,6 +336,17 @@ pub mod cli_consts {
         /// Modified for testnet stress testing
         pub const RATE_LIMIT_INTERVAL_MS: u64 = 0;
 
+        /// How long to wait for the orchestrator to acknowledge an abandoned task (seconds)
+        /// Short, since it is only a courtesy and may happen during shutdown
+        pub const ABANDON_TIMEOUT_SECS: u64 = 5;
+
+        /// Ledger of the inputs this machine submitted one by one, in ~/.nexus
+        pub const INPUT_LEDGER_FILE_NAME: &str = "submitted_inputs.json";
+
+        /// Tasks kept in the input ledger per node; older ones are forgotten
+        /// since the orchestrator will have reassigned them long since
+        pub const INPUT_LEDGER_MAX_TASKS: usize = 16;
+
         /// Helper function to get initial backoff duration
         pub const fn initial_backoff() -> Duration {
             Duration::from_millis(INITIAL_BACKOFF_MS)
@@ -94,6 +356,11 @@ pub mod cli_consts {
         pub const fn rate_limit_interval() -> Duration {
             Duration::from_millis(RATE_LIMIT_INTERVAL_MS)
         }
+
+        /// Helper function to get the abandonment timeout
+        pub const fn abandon_timeout() -> Duration {
+            Duration::from_secs(ABANDON_TIMEOUT_SECS)
+        }
     }
 
     /// Advanced rate limiting configuration
@@ -129,5 +396,56 @@ pub mod cli_consts {
         pub const fn extra_retry_delay() -> Duration {
             Duration::from_secs(EXTRA_RETRY_DELAY_SECS)
         }
+
+        /// Longest wait between requests, whether backing off or told to by the server
+        pub const MAX_RETRY_DELAY_SECS: u64 = 600; // 10 minutes
+
+        /// Helper function to get the longest retry delay
+        pub const fn max_retry_delay() -> Duration {
+            Duration::from_secs(MAX_RETRY_DELAY_SECS)
+        }
+    }
+
+    /// Orchestrator task signature configuration
+    pub mod task_signing {
+        /// Prefix of every signed task payload, so the signature can't be reused elsewhere
+        pub const DOMAIN: &[u8] = b"nexus-orchestrator-task-v2";
+
+        /// Hex-encoded Ed25519 keys the production orchestrator signs tasks with.
+        /// During a rotation both the outgoing and the incoming key are listed.
+        pub const PRODUCTION_KEYS: &[&str] = &[];
+
+        /// Whether production tasks are proved unchecked while `PRODUCTION_KEYS` is empty.
+        /// The production orchestrator doesn't sign tasks yet; once its keys are listed this
+        /// no longer applies, and with it off and no keys every production task is rejected.
+        pub const PRODUCTION_ACCEPTS_UNSIGNED: bool = true;
+    }
+
+    /// Proof submission signature configuration
+    pub mod submission_signing {
+        use std::time::Duration;
+
+        /// Prefix of every signed version 1 attestation, so the signature can't be reused elsewhere
+        pub const DOMAIN: &[u8] = b"nexus-submission-attestation-v1";
+
+        /// Prefix of every signed submission batch, distinct from single attestations
+        pub const BATCH_DOMAIN: &[u8] = b"nexus-submission-batch-v1";
+
+        /// Prefix of every signed per-input submission, distinct from whole-task ones
+        pub const INPUT_DOMAIN: &[u8] = b"nexus-input-submission-v1";
+
+        /// Prefix of every signed task abandonment
+        pub const ABANDON_DOMAIN: &[u8] = b"nexus-task-abandonment-v1";
+
+        /// Random bytes in each attestation
+        pub const NONCE_LEN: usize = 16;
+
+        /// How long an attestation stays valid after it is signed (seconds)
+        pub const ATTESTATION_TTL_SECS: u64 = 10 * 60; // 10 minutes
+
+        /// Helper function to get the attestation lifetime
+        pub const fn attestation_ttl() -> Duration {
+            Duration::from_secs(ATTESTATION_TTL_SECS)
+        }
     }
 }
@@ -0,0 +1,78 @@
+//! Control socket client used by `nexus-network ctl`
+
+use super::ControlError;
+use super::protocol::{EventRecord, Message, Request, methods};
+use serde_json::Value;
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::UnixStream;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+
+pub struct ControlClient {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    next_id: u64,
+}
+
+impl ControlClient {
+    pub async fn connect(path: &Path) -> Result<Self, ControlError> {
+        let stream = UnixStream::connect(path)
+            .await
+            .map_err(|source| ControlError::Connect {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        let (reader, writer) = stream.into_split();
+        Ok(Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            next_id: 1,
+        })
+    }
+
+    /// Send a request and wait for its result
+    pub async fn call(&mut self, method: &str, params: Value) -> Result<Value, ControlError> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut bytes = serde_json::to_vec(&Request::new(id, method, params))?;
+        bytes.push(b'\n');
+        self.writer.write_all(&bytes).await?;
+
+        loop {
+            let message = self.read_message().await?.ok_or(ControlError::Closed)?;
+            // Skip notifications that arrive ahead of our response
+            if message.id != Some(id) {
+                continue;
+            }
+            if let Some(error) = message.error {
+                return Err(ControlError::Rpc {
+                    code: error.code,
+                    message: error.message,
+                });
+            }
+            return Ok(message.result.unwrap_or(Value::Null));
+        }
+    }
+
+    /// Next event after a `tail_events` call, or None once the node goes away
+    pub async fn next_event(&mut self) -> Result<Option<EventRecord>, ControlError> {
+        while let Some(message) = self.read_message().await? {
+            if message.method.as_deref() == Some(methods::EVENT) {
+                let params = message.params.unwrap_or(Value::Null);
+                return Ok(Some(serde_json::from_value(params)?));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn read_message(&mut self) -> Result<Option<Message>, ControlError> {
+        loop {
+            match self.lines.next_line().await? {
+                Some(line) if line.trim().is_empty() => continue,
+                Some(line) => return Ok(Some(serde_json::from_str(&line)?)),
+                None => return Ok(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Local control API
+//!
+//! A running `start` session listens on a Unix domain socket (by default
+//! `~/.nexus/control.sock`) for newline-delimited JSON-RPC 2.0 requests.
+//! `nexus-network ctl` is the matching client.
+
+#[cfg(unix)]
+pub mod client;
+pub mod protocol;
+#[cfg(unix)]
+pub mod server;
+pub mod status;
+
+use crate::consts::cli_consts::control;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ControlError {
+    #[error("Another node is already listening on {0}")]
+    InUse(PathBuf),
+
+    #[error("Could not connect to {path}: {source}. Is `nexus-network start` running?")]
+    Connect {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid message: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("{message} (code {code})")]
+    Rpc { code: i64, message: String },
+
+    #[error("Connection closed before a response arrived")]
+    Closed,
+}
+
+/// Default socket path, next to the config file in ~/.nexus
+pub fn default_socket_path() -> Result<PathBuf, std::io::Error> {
+    Ok(crate::config::get_config_path()?.with_file_name(control::SOCKET_FILE_NAME))
+}
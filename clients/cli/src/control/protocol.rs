@@ -0,0 +1,206 @@
+//! Wire format for the control socket
+//!
+//! One JSON-RPC 2.0 message per line. Requests carry an `id`; the server answers
+//! each with a response, and `tail_events` subscribers additionally receive
+//! `event` notifications until they disconnect.
+
+use crate::events::{Event, ProverState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// Method names understood by the control server
+pub mod methods {
+    /// Node ID, state, current task, difficulty, threads and task counters
+    pub const STATUS: &str = "status";
+    /// Stop fetching new tasks; `suspend` also stops the current task's provers
+    pub const PAUSE: &str = "pause";
+    pub const RESUME: &str = "resume";
+    /// Finish the current task, then exit
+    pub const DRAIN: &str = "drain";
+    /// Abandon the current task and exit now
+    pub const STOP: &str = "stop";
+    /// Set or clear (`null`) the max difficulty override
+    pub const SET_MAX_DIFFICULTY: &str = "set_max_difficulty";
+    pub const SET_THREADS: &str = "set_threads";
+    /// Subscribe to `event` notifications
+    pub const TAIL_EVENTS: &str = "tail_events";
+    /// Notification carrying one worker event
+    pub const EVENT: &str = "event";
+}
+
+/// Standard JSON-RPC error codes
+pub mod error_codes {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const INTERNAL_ERROR: i64 = -32603;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    #[serde(default)]
+    pub id: Option<u64>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+impl Request {
+    pub fn new(id: u64, method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: Some(id),
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(error_codes::INVALID_PARAMS, message)
+    }
+}
+
+/// A response, or an `event` notification when `method` is set and `id` is absent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl Message {
+    pub fn response(id: Option<u64>, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(value) => (Some(value), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id,
+            method: None,
+            params: None,
+            result,
+            error,
+        }
+    }
+
+    pub fn notification(method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: None,
+            method: Some(method.to_string()),
+            params: Some(params),
+            result: None,
+            error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PauseParams {
+    /// Suspend the current task instead of letting it finish
+    #[serde(default)]
+    pub suspend: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetMaxDifficultyParams {
+    /// Difficulty name (e.g. "LARGE"), or null to go back to adaptive difficulty
+    pub difficulty: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetThreadsParams {
+    pub threads: usize,
+}
+
+/// A worker event as sent to `tail_events` subscribers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub timestamp: String,
+    pub worker: String,
+    pub event_type: String,
+    pub log_level: String,
+    pub msg: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prover_state: Option<ProverState>,
+}
+
+impl From<&Event> for EventRecord {
+    fn from(event: &Event) -> Self {
+        Self {
+            timestamp: event.timestamp.clone(),
+            worker: format!("{:?}", event.worker),
+            event_type: event.event_type.to_string(),
+            log_level: format!("{:?}", event.log_level),
+            msg: event.msg.clone(),
+            prover_state: event.prover_state,
+        }
+    }
+}
+
+impl std::fmt::Display for EventRecord {
+    /// Same layout as headless mode output
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] {}", self.event_type, self.timestamp, self.msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_omits_unset_fields() {
+        let ok = serde_json::to_value(Message::response(Some(1), Ok(Value::Bool(true)))).unwrap();
+        assert_eq!(
+            ok,
+            serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": true})
+        );
+
+        let err = serde_json::to_value(Message::response(
+            Some(2),
+            Err(RpcError::new(error_codes::METHOD_NOT_FOUND, "nope")),
+        ))
+        .unwrap();
+        assert_eq!(
+            err,
+            serde_json::json!({"jsonrpc": "2.0", "id": 2, "error": {"code": -32601, "message": "nope"}})
+        );
+    }
+
+    #[test]
+    fn test_request_params_default_to_null() {
+        let request: Request =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","id":3,"method":"status"}"#).unwrap();
+        assert_eq!(request.method, methods::STATUS);
+        assert_eq!(request.params, Value::Null);
+    }
+}
@@ -0,0 +1,538 @@
+//! Control socket server
+//!
+//! Accepts connections on a Unix socket and answers requests against the
+//! session's shutdown, pause and settings channels.
+
+use super::ControlError;
+use super::protocol::{
+    EventRecord, Message, PauseParams, Request, RpcError, SetMaxDifficultyParams, SetThreadsParams,
+    error_codes, methods,
+};
+use super::status::StatusTracker;
+use crate::consts::cli_consts::{EVENT_QUEUE_SIZE, control};
+use crate::events::Event;
+use crate::nexus_orchestrator::TaskDifficulty;
+use crate::workers::core::{LiveSettings, RunState, ShutdownMode};
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::OwnedReadHalf;
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// Bind a socket at `path` that only its owner can connect to.
+///
+/// Anyone who can connect can stop the node. A socket bound in place would be open to
+/// other users under the process umask until it is chmod-ed, so it is bound inside a
+/// directory only the owner can enter, restricted, and then moved into place.
+fn bind_private(path: &Path) -> std::io::Result<UnixListener> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let staging = path.with_file_name(format!(".{}.{}", file_name, std::process::id()));
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    // Fails rather than reuses a directory someone else created in the meantime
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+
+    let result = (|| {
+        let staged = staging.join("control.sock");
+        let listener = UnixListener::bind(&staged)?;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+/// Session handles the control API acts on
+pub struct ControlContext {
+    pub node_id: u64,
+    /// Upper bound accepted by `set_threads`
+    pub max_threads: usize,
+    pub shutdown_sender: broadcast::Sender<ShutdownMode>,
+    pub run_state_sender: watch::Sender<RunState>,
+    pub settings_sender: watch::Sender<LiveSettings>,
+}
+
+struct Shared {
+    context: ControlContext,
+    started_at: Instant,
+    status: Mutex<StatusTracker>,
+    shutdown: Mutex<Option<ShutdownMode>>,
+    events: broadcast::Sender<EventRecord>,
+}
+
+/// Listening control socket; removes the socket file when dropped
+pub struct ControlServer {
+    path: PathBuf,
+    shared: Arc<Shared>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    /// Bind the control socket at `path`, replacing a stale socket left by a previous run
+    pub fn bind(path: &Path, context: ControlContext) -> Result<Self, ControlError> {
+        if path.exists() {
+            if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                return Err(ControlError::InUse(path.to_path_buf()));
+            }
+            std::fs::remove_file(path)?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let listener = bind_private(path)?;
+
+        let (events, _) = broadcast::channel(control::TAIL_BUFFER_SIZE);
+        let mut shutdown_receiver = context.shutdown_sender.subscribe();
+        let shared = Arc::new(Shared {
+            context,
+            started_at: Instant::now(),
+            status: Mutex::new(StatusTracker::default()),
+            shutdown: Mutex::new(None),
+            events,
+        });
+
+        // Remember shutdown requests from any source so `status` can report them
+        let shutdown_shared = shared.clone();
+        let shutdown_task = tokio::spawn(async move {
+            while let Ok(mode) = shutdown_receiver.recv().await {
+                *shutdown_shared
+                    .shutdown
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()) = Some(mode);
+            }
+        });
+
+        let accept_shared = shared.clone();
+        let accept_task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, accept_shared.clone()));
+            }
+        });
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            shared,
+            tasks: vec![shutdown_task, accept_task],
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Pass worker events through, updating status and feeding `tail_events` subscribers
+    pub fn tap(&mut self, mut receiver: mpsc::Receiver<Event>) -> mpsc::Receiver<Event> {
+        let (sender, tapped) = mpsc::channel(EVENT_QUEUE_SIZE);
+        let shared = self.shared.clone();
+        self.tasks.push(tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                shared
+                    .status
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .observe(&event);
+                let _ = shared.events.send(EventRecord::from(&event));
+                if sender.send(event).await.is_err() {
+                    break;
+                }
+            }
+        }));
+        tapped
+    }
+}
+
+impl std::fmt::Debug for ControlServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ControlServer")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn serve_connection(stream: UnixStream, shared: Arc<Shared>) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request: Request = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError::new(error_codes::PARSE_ERROR, e.to_string());
+                if write_message(&mut writer, &Message::response(None, Err(error)))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+                continue;
+            }
+        };
+
+        if request.method == methods::TAIL_EVENTS {
+            let events = shared.events.subscribe();
+            let response = Message::response(request.id, Ok(json!({ "subscribed": true })));
+            if write_message(&mut writer, &response).await.is_ok() {
+                stream_events(&mut writer, events, &mut lines).await;
+            }
+            return;
+        }
+
+        let response = Message::response(request.id, handle_request(&shared, &request));
+        if write_message(&mut writer, &response).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Forward events until the client hangs up
+async fn stream_events<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    mut events: broadcast::Receiver<EventRecord>,
+    lines: &mut Lines<BufReader<OwnedReadHalf>>,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(record) => {
+                    let params = serde_json::to_value(&record).unwrap_or(Value::Null);
+                    let notification = Message::notification(methods::EVENT, params);
+                    if write_message(writer, &notification).await.is_err() {
+                        return;
+                    }
+                }
+                // A slow reader just misses some events
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            line = lines.next_line() => {
+                if !matches!(line, Ok(Some(_))) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    message: &Message,
+) -> Result<(), ControlError> {
+    let mut bytes = serde_json::to_vec(message)?;
+    bytes.push(b'\n');
+    writer.write_all(&bytes).await?;
+    Ok(())
+}
+
+fn handle_request(shared: &Shared, request: &Request) -> Result<Value, RpcError> {
+    if request.jsonrpc != super::protocol::JSONRPC_VERSION {
+        return Err(RpcError::new(
+            error_codes::INVALID_REQUEST,
+            "Only JSON-RPC 2.0 is supported",
+        ));
+    }
+
+    let context = &shared.context;
+    match request.method.as_str() {
+        methods::STATUS => {
+            let shutdown = *shared.shutdown.lock().unwrap_or_else(|e| e.into_inner());
+            let status = shared
+                .status
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .snapshot(
+                    context.node_id,
+                    shared.started_at.elapsed().as_secs(),
+                    *context.run_state_sender.borrow(),
+                    shutdown,
+                    *context.settings_sender.borrow(),
+                );
+            serde_json::to_value(status)
+                .map_err(|e| RpcError::new(error_codes::INTERNAL_ERROR, e.to_string()))
+        }
+        methods::PAUSE => {
+            let params: PauseParams = parse_params(&request.params)?;
+            let state = if params.suspend {
+                RunState::Suspended
+            } else {
+                RunState::Paused
+            };
+            context.run_state_sender.send_replace(state);
+            Ok(json!({ "run_state": state }))
+        }
+        methods::RESUME => {
+            context.run_state_sender.send_replace(RunState::Running);
+            Ok(json!({ "run_state": RunState::Running }))
+        }
+        methods::DRAIN | methods::STOP => {
+            let mode = if request.method == methods::DRAIN {
+                ShutdownMode::Drain
+            } else {
+                ShutdownMode::Immediate
+            };
+            let _ = context.shutdown_sender.send(mode);
+            Ok(json!({ "shutdown": mode }))
+        }
+        methods::SET_MAX_DIFFICULTY => {
+            let params: SetMaxDifficultyParams = parse_params(&request.params)?;
+            let difficulty = match params.difficulty {
+                None => None,
+                Some(name) => Some(
+                    TaskDifficulty::from_str_name(&name.trim().to_ascii_uppercase()).ok_or_else(
+                        || RpcError::invalid_params(format!("Unknown difficulty '{}'", name)),
+                    )?,
+                ),
+            };
+            context
+                .settings_sender
+                .send_modify(|settings| settings.max_difficulty = difficulty);
+            Ok(json!({
+                "max_difficulty": difficulty.map(|difficulty| difficulty.as_str_name()),
+            }))
+        }
+        methods::SET_THREADS => {
+            let params: SetThreadsParams = parse_params(&request.params)?;
+            if params.threads == 0 || params.threads > context.max_threads {
+                return Err(RpcError::invalid_params(format!(
+                    "threads must be between 1 and {}",
+                    context.max_threads
+                )));
+            }
+            context
+                .settings_sender
+                .send_modify(|settings| settings.num_workers = params.threads);
+            Ok(json!({ "threads": params.threads }))
+        }
+        other => Err(RpcError::new(
+            error_codes::METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", other),
+        )),
+    }
+}
+
+/// Deserialize params, treating missing params as an empty object
+fn parse_params<T: DeserializeOwned>(params: &Value) -> Result<T, RpcError> {
+    let params = if params.is_null() {
+        json!({})
+    } else {
+        params.clone()
+    };
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::client::ControlClient;
+    use super::*;
+    use crate::events::{EventType, ProverState};
+    use crate::logging::LogLevel;
+
+    struct Fixture {
+        _dir: tempfile::TempDir,
+        server: ControlServer,
+        shutdown_sender: broadcast::Sender<ShutdownMode>,
+        run_state: watch::Receiver<RunState>,
+        settings: watch::Receiver<LiveSettings>,
+    }
+
+    fn fixture() -> Fixture {
+        let dir = tempfile::tempdir().unwrap();
+        let (shutdown_sender, _) = broadcast::channel(4);
+        let (run_state_sender, run_state) = watch::channel(RunState::Running);
+        let (settings_sender, settings) = watch::channel(LiveSettings {
+            max_difficulty: None,
+            num_workers: 1,
+        });
+        let server = ControlServer::bind(
+            &dir.path().join("control.sock"),
+            ControlContext {
+                node_id: 42,
+                max_threads: 4,
+                shutdown_sender: shutdown_sender.clone(),
+                run_state_sender,
+                settings_sender,
+            },
+        )
+        .unwrap();
+        Fixture {
+            _dir: dir,
+            server,
+            shutdown_sender,
+            run_state,
+            settings,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_and_controls() {
+        let fixture = fixture();
+        let mut shutdown = fixture.shutdown_sender.subscribe();
+        let mut client = ControlClient::connect(fixture.server.path()).await.unwrap();
+
+        let status = client.call(methods::STATUS, Value::Null).await.unwrap();
+        assert_eq!(status["node_id"], 42);
+        assert_eq!(status["state"], "waiting");
+        assert_eq!(status["run_state"], "running");
+
+        client
+            .call(methods::PAUSE, json!({ "suspend": true }))
+            .await
+            .unwrap();
+        assert_eq!(*fixture.run_state.borrow(), RunState::Suspended);
+
+        client.call(methods::RESUME, Value::Null).await.unwrap();
+        assert_eq!(*fixture.run_state.borrow(), RunState::Running);
+
+        client
+            .call(
+                methods::SET_MAX_DIFFICULTY,
+                json!({ "difficulty": "large" }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            fixture.settings.borrow().max_difficulty,
+            Some(TaskDifficulty::Large)
+        );
+
+        client
+            .call(methods::SET_THREADS, json!({ "threads": 3 }))
+            .await
+            .unwrap();
+        assert_eq!(fixture.settings.borrow().num_workers, 3);
+
+        client.call(methods::DRAIN, Value::Null).await.unwrap();
+        assert_eq!(shutdown.recv().await.unwrap(), ShutdownMode::Drain);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_requests() {
+        let fixture = fixture();
+        let mut client = ControlClient::connect(fixture.server.path()).await.unwrap();
+
+        let err = client
+            .call(methods::SET_THREADS, json!({ "threads": 99 }))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ControlError::Rpc { code, .. } if code == error_codes::INVALID_PARAMS)
+        );
+
+        let err = client
+            .call(methods::SET_MAX_DIFFICULTY, json!({ "difficulty": "huge" }))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ControlError::Rpc { code, .. } if code == error_codes::INVALID_PARAMS)
+        );
+
+        let err = client.call("reboot", Value::Null).await.unwrap_err();
+        assert!(
+            matches!(err, ControlError::Rpc { code, .. } if code == error_codes::METHOD_NOT_FOUND)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tail_events_and_tap() {
+        let mut fixture = fixture();
+        let (worker_sender, worker_receiver) = mpsc::channel(8);
+        let mut tapped = fixture.server.tap(worker_receiver);
+
+        let mut client = ControlClient::connect(fixture.server.path()).await.unwrap();
+        client
+            .call(methods::TAIL_EVENTS, Value::Null)
+            .await
+            .unwrap();
+
+        worker_sender
+            .send(Event::state_change(
+                ProverState::Proving,
+                "Step 2 of 4: Proving task abc".to_string(),
+            ))
+            .await
+            .unwrap();
+        worker_sender
+            .send(Event::task_fetcher_with_level(
+                "hello".to_string(),
+                EventType::Refresh,
+                LogLevel::Info,
+            ))
+            .await
+            .unwrap();
+
+        // Events still reach the UI, and subscribers see them in order
+        assert_eq!(
+            tapped.recv().await.unwrap().msg,
+            "Step 2 of 4: Proving task abc"
+        );
+        let first = client.next_event().await.unwrap().unwrap();
+        assert_eq!(first.prover_state, Some(ProverState::Proving));
+        let second = client.next_event().await.unwrap().unwrap();
+        assert_eq!(second.msg, "hello");
+    }
+
+    #[tokio::test]
+    async fn test_refuses_live_socket_and_replaces_stale_one() {
+        let fixture = fixture();
+        let path = fixture.server.path().to_path_buf();
+        let context = || ControlContext {
+            node_id: 1,
+            max_threads: 1,
+            shutdown_sender: broadcast::channel(1).0,
+            run_state_sender: watch::channel(RunState::Running).0,
+            settings_sender: watch::channel(LiveSettings {
+                max_difficulty: None,
+                num_workers: 1,
+            })
+            .0,
+        };
+
+        assert!(matches!(
+            ControlServer::bind(&path, context()),
+            Err(ControlError::InUse(_))
+        ));
+
+        // Leave a socket file nobody listens on
+        drop(std::os::unix::net::UnixListener::bind(path.with_extension("stale")).unwrap());
+        let stale = path.with_extension("stale");
+        assert!(stale.exists());
+        let server = ControlServer::bind(&stale, context()).unwrap();
+        drop(server);
+        assert!(!stale.exists());
+    }
+
+    #[tokio::test]
+    async fn test_socket_is_private() {
+        let fixture = fixture();
+        let path = fixture.server.path();
+
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // The staging directory it was bound in is gone
+        let entries = std::fs::read_dir(path.parent().unwrap()).unwrap().count();
+        assert_eq!(entries, 1);
+        ControlClient::connect(path).await.unwrap();
+    }
+}
@@ -0,0 +1,190 @@
+//! Node status as reported by the control API
+//!
+//! Built from the same worker events the dashboard consumes.
+
+use crate::events::{Event, Lifecycle, ProverState};
+use crate::workers::core::{LiveSettings, RunState, ShutdownMode};
+use serde::{Deserialize, Serialize};
+
+/// Snapshot returned by the `status` method
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeStatus {
+    pub node_id: u64,
+    pub version: String,
+    pub uptime_secs: u64,
+    pub state: ProverState,
+    pub run_state: RunState,
+    pub shutdown: Option<ShutdownMode>,
+    pub current_task: Option<String>,
+    /// Max difficulty override, or None when difficulty is adaptive
+    pub max_difficulty: Option<String>,
+    /// Difficulty of the last successfully completed task
+    pub last_difficulty: Option<String>,
+    pub threads: usize,
+    pub tasks_fetched: u64,
+    pub tasks_submitted: u64,
+    pub last_submission: Option<String>,
+}
+
+/// Accumulates the event-derived parts of [`NodeStatus`]
+#[derive(Debug, Clone)]
+pub struct StatusTracker {
+    state: ProverState,
+    current_task: Option<String>,
+    last_difficulty: Option<String>,
+    tasks_fetched: u64,
+    tasks_submitted: u64,
+    last_submission: Option<String>,
+}
+
+impl Default for StatusTracker {
+    fn default() -> Self {
+        Self {
+            state: ProverState::Waiting,
+            current_task: None,
+            last_difficulty: None,
+            tasks_fetched: 0,
+            tasks_submitted: 0,
+            last_submission: None,
+        }
+    }
+}
+
+impl StatusTracker {
+    /// Update the tracked state from a worker event
+    pub fn observe(&mut self, event: &Event) {
+        if let Some(state) = event.prover_state {
+            self.state = state;
+        }
+
+        match event.lifecycle {
+            Some(Lifecycle::TaskFetched) => {
+                self.current_task = event.task_id.clone();
+                self.tasks_fetched += 1;
+            }
+            Some(Lifecycle::ProofSubmitted) => {
+                self.tasks_submitted += 1;
+                self.last_submission = Some(event.timestamp.clone());
+            }
+            Some(Lifecycle::TaskCompleted(difficulty)) => {
+                let difficulty =
+                    difficulty.map_or("Unknown", |difficulty| difficulty.as_str_name());
+                self.last_difficulty = Some(difficulty.to_string());
+            }
+            _ => {}
+        }
+        if event.prover_state == Some(ProverState::Waiting) {
+            self.current_task = None;
+        }
+    }
+
+    /// One line describing what the node is doing, e.g. for a service manager
+    pub fn summary(&self) -> String {
+        let mut summary = match (self.state, &self.current_task) {
+            (ProverState::Proving, Some(task_id)) => format!("Proving task {}", task_id),
+            (ProverState::Paused, _) => "Paused".to_string(),
+            _ => "Waiting for a task".to_string(),
+        };
+        summary.push_str(&format!(", {} proofs submitted", self.tasks_submitted));
+        if let Some(difficulty) = &self.last_difficulty {
+            summary.push_str(&format!(", last difficulty {}", difficulty));
+        }
+        summary
+    }
+
+    /// Combine tracked state with the session's current controls
+    pub fn snapshot(
+        &self,
+        node_id: u64,
+        uptime_secs: u64,
+        run_state: RunState,
+        shutdown: Option<ShutdownMode>,
+        settings: LiveSettings,
+    ) -> NodeStatus {
+        NodeStatus {
+            node_id,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs,
+            state: self.state,
+            run_state,
+            shutdown,
+            current_task: self.current_task.clone(),
+            max_difficulty: settings
+                .max_difficulty
+                .map(|difficulty| difficulty.as_str_name().to_string()),
+            last_difficulty: self.last_difficulty.clone(),
+            threads: settings.num_workers,
+            tasks_fetched: self.tasks_fetched,
+            tasks_submitted: self.tasks_submitted,
+            last_submission: self.last_submission.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventType;
+    use crate::logging::LogLevel;
+    use crate::nexus_orchestrator::TaskDifficulty;
+
+    fn settings() -> LiveSettings {
+        LiveSettings {
+            max_difficulty: None,
+            num_workers: 2,
+        }
+    }
+
+    #[test]
+    fn test_tracks_task_lifecycle() {
+        let mut tracker = StatusTracker::default();
+
+        tracker.observe(
+            &Event::task_fetcher_with_level(
+                "Step 1 of 4: Got task abc123".to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            )
+            .with_lifecycle(Lifecycle::TaskFetched, Some("abc123")),
+        );
+        tracker.observe(&Event::state_change(
+            ProverState::Proving,
+            "Step 2 of 4: Proving task abc123".to_string(),
+        ));
+        assert_eq!(tracker.summary(), "Proving task abc123, 0 proofs submitted");
+        let status = tracker.snapshot(7, 10, RunState::Running, None, settings());
+        assert_eq!(status.state, ProverState::Proving);
+        assert_eq!(status.current_task.as_deref(), Some("abc123"));
+        assert_eq!(status.tasks_fetched, 1);
+
+        tracker.observe(
+            &Event::proof_submitter_with_level(
+                "Step 4 of 4: Proof submitted successfully".to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            )
+            .with_lifecycle(Lifecycle::ProofSubmitted, Some("abc123")),
+        );
+        tracker.observe(
+            &Event::state_change(
+                ProverState::Waiting,
+                "abc123 completed, Task size: 3, Duration: 42s, Difficulty: LARGE".to_string(),
+            )
+            .with_lifecycle(
+                Lifecycle::TaskCompleted(Some(TaskDifficulty::Large)),
+                Some("abc123"),
+            ),
+        );
+        let status = tracker.snapshot(7, 20, RunState::Running, None, settings());
+        assert_eq!(status.state, ProverState::Waiting);
+        assert_eq!(status.current_task, None);
+        assert_eq!(status.tasks_submitted, 1);
+        assert_eq!(status.last_difficulty.as_deref(), Some("LARGE"));
+        assert!(status.last_submission.is_some());
+        assert_eq!(status.threads, 2);
+        assert_eq!(
+            tracker.summary(),
+            "Waiting for a task, 1 proofs submitted, last difficulty LARGE"
+        );
+    }
+}
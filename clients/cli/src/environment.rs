@@ -1,3 +1,7 @@
+use crate::consts::cli_consts::task_signing;
+use crate::hex::from_hex;
+use crate::task::TaskTrust;
+use ed25519_dalek::VerifyingKey;
 use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
 
@@ -8,7 +12,11 @@ pub enum Environment {
     #[default]
     Production,
     /// Custom environment with a specific orchestrator URL.
-    Custom { orchestrator_url: String },
+    Custom {
+        orchestrator_url: String,
+        /// Keys the custom orchestrator signs tasks with; empty accepts unsigned tasks.
+        task_keys: Vec<VerifyingKey>,
+    },
 }
 
 impl Environment {
@@ -16,11 +24,42 @@ impl Environment {
     pub fn orchestrator_url(&self) -> &str {
         match self {
             Environment::Production => "https://production.orchestrator.nexus.xyz",
-            Environment::Custom { orchestrator_url } => orchestrator_url,
+            Environment::Custom {
+                orchestrator_url, ..
+            } => orchestrator_url,
+        }
+    }
+
+    /// Which tasks from this environment's orchestrator are proved. Tasks must be signed by
+    /// one of its pinned keys, so keys can be rotated, unless it has opted out of checking.
+    pub fn task_trust(&self) -> TaskTrust {
+        match self {
+            Environment::Production => {
+                let keys: Vec<VerifyingKey> = task_signing::PRODUCTION_KEYS
+                    .iter()
+                    .map(|key| parse_task_key(key).expect("Invalid pinned orchestrator key"))
+                    .collect();
+                if keys.is_empty() && task_signing::PRODUCTION_ACCEPTS_UNSIGNED {
+                    TaskTrust::Unverified
+                } else {
+                    TaskTrust::Pinned(keys)
+                }
+            }
+            // Without --orchestrator-key, a custom orchestrator's tasks aren't checked
+            Environment::Custom { task_keys, .. } if task_keys.is_empty() => TaskTrust::Unverified,
+            Environment::Custom { task_keys, .. } => TaskTrust::Pinned(task_keys.clone()),
         }
     }
 }
 
+/// Parse a hex-encoded Ed25519 public key an orchestrator signs tasks with
+pub fn parse_task_key(hex: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = from_hex(hex.trim())?
+        .try_into()
+        .map_err(|_| "expected a 32-byte key".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| e.to_string())
+}
+
 impl FromStr for Environment {
     type Err = ();
 
@@ -36,7 +75,9 @@ impl Display for Environment {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Environment::Production => write!(f, "Production"),
-            Environment::Custom { orchestrator_url } => write!(f, "Custom({})", orchestrator_url),
+            Environment::Custom {
+                orchestrator_url, ..
+            } => write!(f, "Custom({})", orchestrator_url),
         }
     }
 }
@@ -46,3 +87,37 @@ impl Debug for Environment {
         write!(f, "Environment::{}, URL: {}", self, self.orchestrator_url())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::to_hex;
+    use ed25519_dalek::SigningKey;
+
+    #[test]
+    fn test_parse_task_key() {
+        let key = SigningKey::from_bytes(&[7; 32]).verifying_key();
+        assert_eq!(parse_task_key(&to_hex(key.as_bytes())), Ok(key));
+        assert!(parse_task_key("abcd").is_err());
+        assert!(parse_task_key("zz").is_err());
+    }
+
+    #[test]
+    fn test_pinned_production_keys_parse() {
+        match Environment::Production.task_trust() {
+            TaskTrust::Pinned(keys) => assert_eq!(keys.len(), task_signing::PRODUCTION_KEYS.len()),
+            TaskTrust::Unverified => assert_eq!(task_signing::PRODUCTION_KEYS.len(), 0),
+        }
+    }
+
+    #[test]
+    fn test_custom_task_trust() {
+        let custom = |task_keys| Environment::Custom {
+            orchestrator_url: "http://localhost".to_string(),
+            task_keys,
+        };
+        assert_eq!(custom(Vec::new()).task_trust(), TaskTrust::Unverified);
+        let key = SigningKey::from_bytes(&[7; 32]).verifying_key();
+        assert_eq!(custom(vec![key]).task_trust(), TaskTrust::Pinned(vec![key]));
+    }
+}
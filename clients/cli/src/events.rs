@@ -3,6 +3,8 @@
 //! Types and implementations for worker events and logging
 
 use crate::logging::{LogLevel, should_log_with_env};
+use crate::nexus_orchestrator::TaskDifficulty;
+use crate::prover::energy::TaskEnergy;
 use chrono::Local;
 use std::fmt::Display;
 
@@ -26,23 +28,51 @@ pub enum EventType {
 }
 
 /// Represents the current state in the proof pipeline
-#[derive(Debug, Copy, Clone, Eq, PartialEq, strum::Display)]
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, strum::Display, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
 pub enum ProverState {
     /// Computing the proof
     Proving,
     /// Waiting before fetching next task (idle state)
     Waiting,
+    /// Paused by the operator: no new tasks are fetched until resumed
+    Paused,
+}
+
+/// A step in the life of a task, for whatever reacts to events rather than shows them
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Lifecycle {
+    TaskFetched,
+    /// Every attempt to fetch a task failed
+    FetchFailed,
+    ProofSubmitted,
+    /// A dry run kept the proof locally in place of submitting it
+    ProofKept,
+    /// Every submission attempt for a proof failed
+    SubmissionFailed,
+    /// A prover subprocess was killed, most likely by the OOM killer
+    OutOfMemory,
+    /// A task was proved and submitted, at this difficulty if known
+    TaskCompleted(Option<TaskDifficulty>),
 }
 
 #[derive(Debug, Clone)]
 pub struct Event {
     pub worker: Worker,
+    /// For display only: match on `lifecycle` and the fields after it instead
     pub msg: String,
     pub timestamp: String,
     pub event_type: EventType,
     pub log_level: LogLevel,
     /// Optional state information for state change events
     pub prover_state: Option<ProverState>,
+    pub lifecycle: Option<Lifecycle>,
+    /// The task the event is about, if any
+    pub task_id: Option<String>,
+    /// Energy a completed task used, if it was measured
+    pub energy: Option<TaskEnergy>,
 }
 
 impl PartialEq for Event {
@@ -53,6 +83,9 @@ impl PartialEq for Event {
             && self.event_type == other.event_type
             && self.log_level == other.log_level
             && self.prover_state == other.prover_state
+            && self.lifecycle == other.lifecycle
+            && self.task_id == other.task_id
+            && self.energy == other.energy
         // Note: We don't compare state_start_time since Instant doesn't implement Eq
     }
 }
@@ -68,6 +101,9 @@ impl Event {
             event_type,
             log_level,
             prover_state: None,
+            lifecycle: None,
+            task_id: None,
+            energy: None,
         }
     }
 
@@ -79,9 +115,24 @@ impl Event {
             event_type: EventType::StateChange,
             log_level: LogLevel::Info,
             prover_state: Some(state),
+            lifecycle: None,
+            task_id: None,
+            energy: None,
         }
     }
 
+    /// Mark this as a step in the life of task `task_id`, if it is about one
+    pub fn with_lifecycle(mut self, lifecycle: Lifecycle, task_id: Option<&str>) -> Self {
+        self.lifecycle = Some(lifecycle);
+        self.task_id = task_id.map(str::to_string);
+        self
+    }
+
+    pub fn with_energy(mut self, energy: Option<TaskEnergy>) -> Self {
+        self.energy = energy;
+        self
+    }
+
     pub fn task_fetcher_with_level(
         msg: String,
         event_type: EventType,
@@ -0,0 +1,468 @@
+//! Liveness and readiness for container orchestration
+//!
+//! `/livez` fails when the runtime stops ticking or the event loop stops taking
+//! events. `/readyz` also fails until version requirements have passed and the
+//! node is resolved, and later when fetches or submissions stop succeeding.
+//! Both answer with JSON listing every check and why it failed.
+
+pub mod server;
+
+use crate::consts::cli_consts::health;
+use crate::events::{Event, Lifecycle, ProverState};
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// How long the node may go without progress before it is reported not ready
+#[derive(Debug, Copy, Clone)]
+pub struct HealthThresholds {
+    pub fetch_within: Duration,
+    pub submit_within: Duration,
+}
+
+/// One named check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    /// Why the check failed, or what it saw
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, ok: bool, detail: Option<String>) -> Self {
+        Self { name, ok, detail }
+    }
+}
+
+/// Body of `/livez` and `/readyz`
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub ok: bool,
+    pub checks: Vec<Check>,
+    pub node_id: Option<u64>,
+    pub paused: bool,
+    pub last_fetch_secs_ago: Option<u64>,
+    pub last_submit_secs_ago: Option<u64>,
+    pub consecutive_fetch_failures: u32,
+    pub consecutive_submit_failures: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct HealthState {
+    heartbeat: Instant,
+    /// Since when the event loop has kept an event waiting, if it has
+    events_blocked_since: Option<Instant>,
+    version_checked: bool,
+    node_id: Option<u64>,
+    last_fetch: Option<Instant>,
+    last_submit: Option<Instant>,
+    /// Start of the current stretch of work; progress is expected from here on
+    active_since: Instant,
+    paused: bool,
+    fetch_failures: u32,
+    submit_failures: u32,
+    last_error: Option<String>,
+}
+
+impl HealthState {
+    fn new(now: Instant) -> Self {
+        Self {
+            heartbeat: now,
+            events_blocked_since: None,
+            version_checked: false,
+            node_id: None,
+            last_fetch: None,
+            last_submit: None,
+            active_since: now,
+            paused: false,
+            fetch_failures: 0,
+            submit_failures: 0,
+            last_error: None,
+        }
+    }
+
+    fn observe(&mut self, event: &Event, now: Instant) {
+        match event.lifecycle {
+            Some(Lifecycle::TaskFetched) => {
+                self.last_fetch = Some(now);
+                self.fetch_failures = 0;
+            }
+            Some(Lifecycle::FetchFailed) => {
+                self.fetch_failures += 1;
+                self.last_error = Some(event.msg.trim().to_string());
+            }
+            // A dry run keeps its proofs, which is all the progress it makes
+            Some(Lifecycle::ProofSubmitted | Lifecycle::ProofKept) => {
+                self.last_submit = Some(now);
+                self.submit_failures = 0;
+            }
+            Some(Lifecycle::SubmissionFailed) => {
+                self.submit_failures += 1;
+                self.last_error = Some(event.msg.trim().to_string());
+            }
+            _ => {}
+        }
+
+        // Paused, throttled or outside an operating window: no progress is expected
+        if let Some(state) = event.prover_state {
+            let paused = state == ProverState::Paused;
+            if self.paused && !paused {
+                self.active_since = now;
+            }
+            self.paused = paused;
+        }
+    }
+
+    /// Seconds since `last`, counting from the start of the current stretch of work
+    fn age(&self, last: Option<Instant>, now: Instant) -> Duration {
+        let since = last.map_or(self.active_since, |last| last.max(self.active_since));
+        now.saturating_duration_since(since)
+    }
+
+    fn liveness_checks(&self, now: Instant) -> Vec<Check> {
+        let timeout = health::liveness_timeout();
+        let heartbeat = now.saturating_duration_since(self.heartbeat);
+        let blocked = self
+            .events_blocked_since
+            .map(|since| now.saturating_duration_since(since))
+            .unwrap_or_default();
+        vec![
+            Check::new(
+                "heartbeat",
+                heartbeat < timeout,
+                (heartbeat >= timeout)
+                    .then(|| format!("runtime last ticked {}s ago", heartbeat.as_secs())),
+            ),
+            Check::new(
+                "event_loop",
+                blocked < timeout,
+                (blocked >= timeout)
+                    .then(|| format!("events not taken for {}s", blocked.as_secs())),
+            ),
+        ]
+    }
+
+    fn readiness_checks(&self, now: Instant, thresholds: &HealthThresholds) -> Vec<Check> {
+        let mut checks = self.liveness_checks(now);
+        checks.push(Check::new(
+            "version",
+            self.version_checked,
+            (!self.version_checked).then(|| "version requirements not checked yet".to_string()),
+        ));
+        checks.push(Check::new(
+            "node",
+            self.node_id.is_some(),
+            self.node_id
+                .is_none()
+                .then(|| "node not resolved yet".to_string()),
+        ));
+
+        for (name, verb, last, within) in [
+            ("fetch", "fetched", self.last_fetch, thresholds.fetch_within),
+            (
+                "submit",
+                "submitted",
+                self.last_submit,
+                thresholds.submit_within,
+            ),
+        ] {
+            let age = self.age(last, now);
+            let ok = self.paused || age < within;
+            let detail = if self.paused {
+                Some("paused".to_string())
+            } else if !ok {
+                Some(match last {
+                    Some(_) => format!(
+                        "last {} {}s ago, threshold {}s",
+                        verb,
+                        age.as_secs(),
+                        within.as_secs()
+                    ),
+                    None => format!(
+                        "nothing {} in {}s, threshold {}s",
+                        verb,
+                        age.as_secs(),
+                        within.as_secs()
+                    ),
+                })
+            } else {
+                None
+            };
+            checks.push(Check::new(name, ok, detail));
+        }
+
+        let failing = self.submit_failures >= health::MAX_SUBMIT_FAILURES;
+        checks.push(Check::new(
+            "submissions",
+            !failing,
+            failing.then(|| format!("{} submissions failed in a row", self.submit_failures)),
+        ));
+        checks
+    }
+
+    fn report(&self, checks: Vec<Check>, now: Instant) -> HealthReport {
+        HealthReport {
+            ok: checks.iter().all(|check| check.ok),
+            checks,
+            node_id: self.node_id,
+            paused: self.paused,
+            last_fetch_secs_ago: self
+                .last_fetch
+                .map(|last| now.saturating_duration_since(last).as_secs()),
+            last_submit_secs_ago: self
+                .last_submit
+                .map(|last| now.saturating_duration_since(last).as_secs()),
+            consecutive_fetch_failures: self.fetch_failures,
+            consecutive_submit_failures: self.submit_failures,
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// Shared health state, fed by startup steps and worker events
+#[derive(Clone)]
+pub struct Health {
+    state: Arc<Mutex<HealthState>>,
+    thresholds: HealthThresholds,
+}
+
+impl Health {
+    pub fn new(thresholds: HealthThresholds) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(HealthState::new(Instant::now()))),
+            thresholds,
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn version_checked(&self) {
+        self.state().version_checked = true;
+    }
+
+    /// The node is known; progress is expected from now on
+    pub fn node_resolved(&self, node_id: u64) {
+        let mut state = self.state();
+        state.node_id = Some(node_id);
+        state.active_since = Instant::now();
+    }
+
+    fn heartbeat(&self) {
+        self.state().heartbeat = Instant::now();
+    }
+
+    pub fn liveness(&self) -> HealthReport {
+        let now = Instant::now();
+        let state = self.state();
+        state.report(state.liveness_checks(now), now)
+    }
+
+    pub fn readiness(&self) -> HealthReport {
+        let now = Instant::now();
+        let state = self.state();
+        state.report(state.readiness_checks(now, &self.thresholds), now)
+    }
+
+    /// Pass worker events through, noting progress and how long the event loop takes them
+    pub fn tap(&self, mut receiver: mpsc::Receiver<Event>) -> mpsc::Receiver<Event> {
+        let (sender, tapped) = mpsc::channel(crate::consts::cli_consts::EVENT_QUEUE_SIZE);
+        let health = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                let now = Instant::now();
+                {
+                    let mut state = health.state();
+                    state.observe(&event, now);
+                    state.events_blocked_since = Some(now);
+                }
+                if sender.send(event).await.is_err() {
+                    break;
+                }
+                health.state().events_blocked_since = None;
+            }
+        });
+        tapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventType;
+    use crate::logging::LogLevel;
+
+    const THRESHOLDS: HealthThresholds = HealthThresholds {
+        fetch_within: Duration::from_secs(600),
+        submit_within: Duration::from_secs(900),
+    };
+
+    fn failed(checks: &[Check]) -> Vec<&'static str> {
+        checks
+            .iter()
+            .filter(|check| !check.ok)
+            .map(|check| check.name)
+            .collect()
+    }
+
+    /// Readiness at `now`, with the runtime ticking along
+    fn readiness(state: &mut HealthState, now: Instant) -> Vec<Check> {
+        state.heartbeat = now;
+        state.readiness_checks(now, &THRESHOLDS)
+    }
+
+    fn ready_state(start: Instant) -> HealthState {
+        let mut state = HealthState::new(start);
+        state.version_checked = true;
+        state.node_id = Some(1);
+        state
+    }
+
+    #[test]
+    fn test_not_ready_until_startup_completes() {
+        let start = Instant::now();
+        let mut state = HealthState::new(start);
+        assert_eq!(
+            failed(&readiness(&mut state, start)),
+            vec!["version", "node"]
+        );
+        assert!(failed(&state.liveness_checks(start)).is_empty());
+
+        state.version_checked = true;
+        state.node_id = Some(1);
+        assert!(failed(&readiness(&mut state, start)).is_empty());
+    }
+
+    #[test]
+    fn test_stale_fetch_and_submit() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut state = ready_state(start);
+
+        state.observe(
+            &Event::task_fetcher_with_level(
+                "Step 1 of 4: Got task abc".to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            )
+            .with_lifecycle(Lifecycle::TaskFetched, Some("abc")),
+            at(500),
+        );
+        assert!(failed(&readiness(&mut state, at(800))).is_empty());
+
+        let checks = readiness(&mut state, at(1200));
+        assert_eq!(failed(&checks), vec!["fetch", "submit"]);
+        assert_eq!(
+            checks[4].detail.as_deref(),
+            Some("last fetched 700s ago, threshold 600s")
+        );
+        assert_eq!(
+            checks[5].detail.as_deref(),
+            Some("nothing submitted in 1200s, threshold 900s")
+        );
+        let report = state.report(checks, at(1200));
+        assert!(!report.ok);
+        assert_eq!(report.last_fetch_secs_ago, Some(700));
+    }
+
+    #[test]
+    fn test_paused_node_stays_ready() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut state = ready_state(start);
+
+        state.observe(
+            &Event::state_change(ProverState::Paused, "Paused".to_string()),
+            at(100),
+        );
+        assert!(failed(&readiness(&mut state, at(5000))).is_empty());
+
+        // Progress is expected again from the resume on
+        state.observe(
+            &Event::state_change(ProverState::Waiting, "Resumed".to_string()),
+            at(5000),
+        );
+        assert!(failed(&readiness(&mut state, at(5500))).is_empty());
+        assert_eq!(failed(&readiness(&mut state, at(5700))), vec!["fetch"]);
+    }
+
+    #[test]
+    fn test_repeated_submission_failures() {
+        let start = Instant::now();
+        let mut state = ready_state(start);
+        let failure = Event::proof_submitter_with_level(
+            "Failed to submit proof for task abc after 5 attempts: boom".to_string(),
+            EventType::Error,
+            LogLevel::Error,
+        )
+        .with_lifecycle(Lifecycle::SubmissionFailed, Some("abc"));
+        for _ in 0..health::MAX_SUBMIT_FAILURES {
+            state.observe(&failure, start);
+        }
+
+        let checks = readiness(&mut state, start);
+        assert_eq!(failed(&checks), vec!["submissions"]);
+        let report = state.report(checks, start);
+        assert_eq!(
+            report.last_error.as_deref(),
+            Some("Failed to submit proof for task abc after 5 attempts: boom")
+        );
+
+        state.observe(
+            &Event::proof_submitter_with_level(
+                "Step 4 of 4: Proof submitted successfully for task def\n".to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            )
+            .with_lifecycle(Lifecycle::ProofSubmitted, Some("def")),
+            start,
+        );
+        assert!(failed(&readiness(&mut state, start)).is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_keeps_ready() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut state = ready_state(start);
+
+        state.observe(
+            &Event::task_fetcher_with_level(
+                "Step 1 of 4: Got task abc".to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            )
+            .with_lifecycle(Lifecycle::TaskFetched, Some("abc")),
+            at(800),
+        );
+        state.observe(
+            &Event::proof_submitter_with_level(
+                "Step 4 of 4: Dry run: proof for task abc kept in proofs.jsonl, not submitted\n"
+                    .to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            )
+            .with_lifecycle(Lifecycle::ProofKept, Some("abc")),
+            at(850),
+        );
+        // Nothing is ever submitted, yet the node is past the submit threshold and ready
+        assert!(failed(&readiness(&mut state, at(1000))).is_empty());
+    }
+
+    #[test]
+    fn test_liveness() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut state = ready_state(start);
+
+        assert_eq!(failed(&state.liveness_checks(at(31))), vec!["heartbeat"]);
+        state.heartbeat = at(31);
+        state.events_blocked_since = Some(at(0));
+        assert_eq!(failed(&state.liveness_checks(at(31))), vec!["event_loop"]);
+    }
+}
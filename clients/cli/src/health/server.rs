@@ -0,0 +1,170 @@
+//! Minimal HTTP server for `/livez` and `/readyz`
+//!
+//! Probes send one small GET per connection, so each connection answers a
+//! single request and is closed.
+
+use super::{Health, HealthReport};
+use crate::consts::cli_consts::health;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Bind `addr` and serve health checks in the background, returning the bound address
+pub async fn start(addr: SocketAddr, health: Health) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+
+    // The heartbeat only ticks while the runtime is scheduling tasks
+    let heartbeat = health.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(health::heartbeat_interval());
+        loop {
+            ticker.tick().await;
+            heartbeat.heartbeat();
+        }
+    });
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                // e.g. out of file descriptors; don't spin while it lasts
+                tokio::time::sleep(health::heartbeat_interval()).await;
+                continue;
+            };
+            tokio::spawn(serve_connection(stream, health.clone()));
+        }
+    });
+    Ok(local_addr)
+}
+
+async fn serve_connection(mut stream: TcpStream, health: Health) {
+    let Ok(Some(head)) = tokio::time::timeout(health::request_timeout(), read_head(&mut stream))
+        .await
+        .map(Result::ok)
+        .map(Option::flatten)
+    else {
+        return;
+    };
+    let request_line = head.lines().next().unwrap_or_default();
+    let response = respond(request_line, &health);
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Read up to the end of the request head; None if the client sent too much or hung up
+async fn read_head(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 || head.len() + read > health::MAX_REQUEST_BYTES {
+            return Ok(None);
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    Ok(Some(String::from_utf8_lossy(&head).into_owned()))
+}
+
+/// The full response to a request line such as "GET /readyz HTTP/1.1"
+fn respond(request_line: &str, health: &Health) -> String {
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts
+        .next()
+        .unwrap_or_default()
+        .split('?')
+        .next()
+        .unwrap_or_default();
+
+    let (status, body) = if method != "GET" && method != "HEAD" {
+        (
+            "405 Method Not Allowed",
+            r#"{"error":"method not allowed"}"#.to_string(),
+        )
+    } else {
+        match path {
+            "/livez" => report(health.liveness()),
+            "/readyz" => report(health.readiness()),
+            _ => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        }
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    response
+}
+
+fn report(report: HealthReport) -> (&'static str, String) {
+    let status = if report.ok {
+        "200 OK"
+    } else {
+        "503 Service Unavailable"
+    };
+    (status, serde_json::to_string(&report).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::HealthThresholds;
+    use std::time::Duration;
+
+    async fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_endpoints() {
+        let health = Health::new(HealthThresholds {
+            fetch_within: Duration::from_secs(600),
+            submit_within: Duration::from_secs(600),
+        });
+        let addr = start("127.0.0.1:0".parse().unwrap(), health.clone())
+            .await
+            .unwrap();
+
+        let response = get(addr, "/livez").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+
+        let response = get(addr, "/readyz").await;
+        assert!(response.starts_with("HTTP/1.1 503 "), "{}", response);
+        let body = response.split_once("\r\n\r\n").unwrap().1;
+        let report: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(report["ok"], false);
+        assert_eq!(report["checks"][2]["name"], "version");
+        assert_eq!(
+            report["checks"][2]["detail"],
+            "version requirements not checked yet"
+        );
+
+        health.version_checked();
+        health.node_resolved(7);
+        let response = get(addr, "/readyz?verbose").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+
+        assert!(get(addr, "/metrics").await.starts_with("HTTP/1.1 404 "));
+    }
+
+    #[test]
+    fn test_method_and_head() {
+        let health = Health::new(HealthThresholds {
+            fetch_within: Duration::from_secs(600),
+            submit_within: Duration::from_secs(600),
+        });
+        assert!(respond("POST /livez HTTP/1.1", &health).starts_with("HTTP/1.1 405 "));
+        assert!(respond("HEAD /livez HTTP/1.1", &health).ends_with("\r\n\r\n"));
+    }
+}
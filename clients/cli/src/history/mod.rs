@@ -0,0 +1,225 @@
+//! Local task history
+//!
+//! Every task the node fetches is appended as one JSON line to
+//! `~/.nexus/history.jsonl` once it is submitted, fails or is abandoned.
+//! `nexus-network history` reads it back; see [`report`].
+
+pub mod report;
+
+use crate::consts::cli_consts::history;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How a task ended
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskResult {
+    /// Proved and accepted by the orchestrator
+    Submitted,
+    /// Proving failed
+    ProofFailed,
+    /// Proving ran out of memory
+    OutOfMemory,
+    /// Proving took too long
+    Timeout,
+    /// Proved, but every submission attempt failed
+    SubmitFailed,
+    /// Dropped on shutdown before it could be submitted
+    Abandoned,
+    /// Proved in a dry run and kept locally
+    DryRun,
+}
+
+impl TaskResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskResult::Submitted => "submitted",
+            TaskResult::ProofFailed => "proof_failed",
+            TaskResult::OutOfMemory => "out_of_memory",
+            TaskResult::Timeout => "timeout",
+            TaskResult::SubmitFailed => "submit_failed",
+            TaskResult::Abandoned => "abandoned",
+            TaskResult::DryRun => "dry_run",
+        }
+    }
+}
+
+/// The lifecycle of one task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub node_id: u64,
+    pub task_id: String,
+    pub fetched_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// What we asked the orchestrator for
+    #[serde(default)]
+    pub requested_difficulty: Option<String>,
+    /// What the orchestrator handed out
+    #[serde(default)]
+    pub assigned_difficulty: Option<String>,
+    pub inputs: usize,
+    /// Proving time of each input, in input order; empty unless proving succeeded
+    #[serde(default)]
+    pub input_proving_ms: Vec<u64>,
+    /// Highest resident memory of the node and its provers while proving
+    #[serde(default)]
+    pub peak_rss_bytes: Option<u64>,
+    #[serde(default)]
+    pub submission_attempts: u32,
+    /// Energy used while proving, when RAPL counters are available
+    #[serde(default)]
+    pub energy_joules: Option<f64>,
+    pub result: TaskResult,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl TaskRecord {
+    /// A task fetched just now; until it finishes it counts as abandoned
+    pub fn fetched(
+        node_id: u64,
+        task_id: String,
+        inputs: usize,
+        requested_difficulty: Option<String>,
+        assigned_difficulty: Option<String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            node_id,
+            task_id,
+            fetched_at: now,
+            finished_at: now,
+            requested_difficulty,
+            assigned_difficulty,
+            inputs,
+            input_proving_ms: Vec::new(),
+            peak_rss_bytes: None,
+            submission_attempts: 0,
+            energy_joules: None,
+            result: TaskResult::Abandoned,
+            error: None,
+        }
+    }
+
+    /// Mark the task finished just now
+    pub fn finish(&mut self, result: TaskResult, error: Option<String>) {
+        self.finished_at = Utc::now();
+        self.result = result;
+        self.error = error;
+    }
+
+    pub fn set_input_durations(&mut self, durations: &[Duration]) {
+        self.input_proving_ms = durations
+            .iter()
+            .map(|duration| duration.as_millis() as u64)
+            .collect();
+    }
+
+    /// Wall time from fetch to the end of the task
+    pub fn duration(&self) -> Duration {
+        (self.finished_at - self.fetched_at)
+            .to_std()
+            .unwrap_or_default()
+    }
+
+    /// Total proving time across inputs
+    pub fn proving_time(&self) -> Duration {
+        Duration::from_millis(self.input_proving_ms.iter().sum())
+    }
+}
+
+/// Append-only history file shared by every node run from this machine
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Default store location, next to the config file in ~/.nexus
+    pub fn default_path() -> Result<PathBuf, std::io::Error> {
+        Ok(crate::config::get_config_path()?.with_file_name(history::HISTORY_FILE_NAME))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one record as a single line
+    pub fn append(&self, record: &TaskRecord) -> Result<(), std::io::Error> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        // One write per record, so lines from concurrent nodes don't interleave
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)
+    }
+
+    /// Every record, oldest first. A missing file is an empty history; lines that
+    /// don't parse (e.g. cut short by a crash) are skipped.
+    pub fn load(&self) -> Result<Vec<TaskRecord>, std::io::Error> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join("nested").join("history.jsonl"));
+        assert!(store.load().unwrap().is_empty());
+
+        let mut first = TaskRecord::fetched(
+            7,
+            "task-1".to_string(),
+            3,
+            Some("LARGE".to_string()),
+            Some("MEDIUM".to_string()),
+        );
+        first.set_input_durations(&[Duration::from_millis(1500), Duration::from_secs(2)]);
+        first.finish(TaskResult::Submitted, None);
+        let mut second = TaskRecord::fetched(7, "task-2".to_string(), 1, None, None);
+        second.finish(TaskResult::ProofFailed, Some("boom".to_string()));
+
+        store.append(&first).unwrap();
+        store.append(&second).unwrap();
+        assert_eq!(store.load().unwrap(), vec![first.clone(), second]);
+        assert_eq!(first.proving_time(), Duration::from_millis(3500));
+    }
+
+    #[test]
+    fn test_load_skips_torn_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path().join("history.jsonl"));
+        let mut record = TaskRecord::fetched(1, "task".to_string(), 1, None, None);
+        record.finish(TaskResult::Timeout, None);
+        store.append(&record).unwrap();
+
+        let mut file = OpenOptions::new().append(true).open(store.path()).unwrap();
+        file.write_all(b"{\"node_id\":1,\"task_").unwrap();
+        assert_eq!(store.load().unwrap(), vec![record]);
+    }
+}
@@ -0,0 +1,420 @@
+//! Filtering, aggregating and printing the task history
+
+use super::{TaskRecord, TaskResult};
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Output format of `nexus-network history`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ValueEnum)]
+pub enum HistoryFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Which records to report on. Dates are local and inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    /// Assigned difficulty, e.g. "MEDIUM"
+    pub difficulty: Option<String>,
+    pub result: Option<TaskResult>,
+    pub node_id: Option<u64>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, record: &TaskRecord) -> bool {
+        let day = local_day(record);
+        self.since.is_none_or(|since| day >= since)
+            && self.until.is_none_or(|until| day <= until)
+            && self.difficulty.as_ref().is_none_or(|difficulty| {
+                record
+                    .assigned_difficulty
+                    .as_ref()
+                    .is_some_and(|assigned| assigned.eq_ignore_ascii_case(difficulty))
+            })
+            && self.result.is_none_or(|result| record.result == result)
+            && self.node_id.is_none_or(|node_id| record.node_id == node_id)
+    }
+}
+
+fn local_day(record: &TaskRecord) -> NaiveDate {
+    record.fetched_at.with_timezone(&Local).date_naive()
+}
+
+/// Tasks of one day
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DayStats {
+    pub day: NaiveDate,
+    pub tasks: usize,
+    pub submitted: usize,
+    /// Inputs of submitted tasks
+    pub inputs_proved: usize,
+    /// Proving time of submitted tasks
+    pub proving_secs: u64,
+}
+
+/// Tasks of one assigned difficulty
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DifficultyStats {
+    pub difficulty: String,
+    pub tasks: usize,
+    pub submitted: usize,
+    pub success_rate: f64,
+    /// Mean fetch-to-submit time of submitted tasks
+    pub mean_duration_secs: Option<u64>,
+}
+
+/// Aggregates over a set of records
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct HistoryStats {
+    pub per_day: Vec<DayStats>,
+    pub per_difficulty: Vec<DifficultyStats>,
+}
+
+impl HistoryStats {
+    pub fn compute(records: &[TaskRecord]) -> Self {
+        let mut days: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
+        // Tasks, submitted, total seconds of submitted tasks
+        let mut difficulties: BTreeMap<String, (usize, usize, u64)> = BTreeMap::new();
+
+        for record in records {
+            let submitted = record.result == TaskResult::Submitted;
+            let day = local_day(record);
+            let stats = days.entry(day).or_insert_with(|| DayStats {
+                day,
+                ..Default::default()
+            });
+            stats.tasks += 1;
+
+            let difficulty = record
+                .assigned_difficulty
+                .clone()
+                .unwrap_or_else(|| "UNKNOWN".to_string());
+            let totals = difficulties.entry(difficulty).or_default();
+            totals.0 += 1;
+
+            if submitted {
+                stats.submitted += 1;
+                stats.inputs_proved += record.inputs;
+                stats.proving_secs += record.proving_time().as_secs();
+                totals.1 += 1;
+                totals.2 += record.duration().as_secs();
+            }
+        }
+
+        Self {
+            per_day: days.into_values().collect(),
+            per_difficulty: difficulties
+                .into_iter()
+                .map(|(difficulty, (tasks, submitted, secs))| DifficultyStats {
+                    difficulty,
+                    tasks,
+                    submitted,
+                    success_rate: submitted as f64 / tasks as f64,
+                    mean_duration_secs: (submitted > 0).then(|| secs / submitted as u64),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Quote a CSV field if it needs it
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_row(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Render individual tasks
+pub fn render_records(records: &[TaskRecord], format: HistoryFormat) -> String {
+    match format {
+        HistoryFormat::Json => serde_json::to_string_pretty(records).unwrap_or_default(),
+        HistoryFormat::Csv => {
+            let mut lines = vec![
+                "node_id,task_id,fetched_at,finished_at,requested_difficulty,assigned_difficulty,\
+                 inputs,input_proving_ms,peak_rss_bytes,submission_attempts,energy_joules,result,error"
+                    .to_string(),
+            ];
+            lines.extend(records.iter().map(|record| {
+                csv_row(&[
+                    record.node_id.to_string(),
+                    record.task_id.clone(),
+                    record.fetched_at.to_rfc3339(),
+                    record.finished_at.to_rfc3339(),
+                    optional(record.requested_difficulty.as_ref()),
+                    optional(record.assigned_difficulty.as_ref()),
+                    record.inputs.to_string(),
+                    record
+                        .input_proving_ms
+                        .iter()
+                        .map(u64::to_string)
+                        .collect::<Vec<_>>()
+                        .join(";"),
+                    optional(record.peak_rss_bytes),
+                    record.submission_attempts.to_string(),
+                    optional(record.energy_joules),
+                    record.result.as_str().to_string(),
+                    optional(record.error.as_ref()),
+                ])
+            }));
+            lines.join("\n")
+        }
+        HistoryFormat::Table => {
+            let mut lines = vec![format!(
+                "{:<19}  {:<24}  {:<14}  {:>6}  {:>8}  {:>9}  {:>8}  {}",
+                "FETCHED", "TASK", "DIFFICULTY", "INPUTS", "TIME", "PEAK RSS", "ATTEMPTS", "RESULT"
+            )];
+            lines.extend(records.iter().map(|record| {
+                let mut result = record.result.as_str().to_string();
+                if let Some(error) = &record.error {
+                    result.push_str(": ");
+                    result.push_str(error);
+                }
+                format!(
+                    "{:<19}  {:<24}  {:<14}  {:>6}  {:>7}s  {:>9}  {:>8}  {}",
+                    record
+                        .fetched_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S"),
+                    record.task_id,
+                    record.assigned_difficulty.as_deref().unwrap_or("-"),
+                    record.inputs,
+                    record.duration().as_secs(),
+                    record
+                        .peak_rss_bytes
+                        .map(|bytes| format!("{} MB", bytes / 1_000_000))
+                        .unwrap_or_else(|| "-".to_string()),
+                    record.submission_attempts,
+                    result
+                )
+            }));
+            lines.join("\n")
+        }
+    }
+}
+
+/// Render aggregates
+pub fn render_stats(stats: &HistoryStats, format: HistoryFormat) -> String {
+    match format {
+        HistoryFormat::Json => serde_json::to_string_pretty(stats).unwrap_or_default(),
+        // One table, with the rows of each aggregate told apart by `kind`
+        HistoryFormat::Csv => {
+            let mut lines = vec!["kind,key,tasks,submitted,value".to_string()];
+            lines.extend(stats.per_day.iter().map(|day| {
+                csv_row(&[
+                    "inputs_per_day".to_string(),
+                    day.day.to_string(),
+                    day.tasks.to_string(),
+                    day.submitted.to_string(),
+                    day.inputs_proved.to_string(),
+                ])
+            }));
+            lines.extend(stats.per_difficulty.iter().map(|difficulty| {
+                csv_row(&[
+                    "success_rate".to_string(),
+                    difficulty.difficulty.clone(),
+                    difficulty.tasks.to_string(),
+                    difficulty.submitted.to_string(),
+                    format!("{:.3}", difficulty.success_rate),
+                ])
+            }));
+            lines.join("\n")
+        }
+        HistoryFormat::Table => {
+            let mut lines = vec![format!(
+                "{:<10}  {:>6}  {:>9}  {:>6}  {:>12}",
+                "DAY", "TASKS", "SUBMITTED", "INPUTS", "PROVING TIME"
+            )];
+            lines.extend(stats.per_day.iter().map(|day| {
+                format!(
+                    "{:<10}  {:>6}  {:>9}  {:>6}  {:>11}s",
+                    day.day, day.tasks, day.submitted, day.inputs_proved, day.proving_secs
+                )
+            }));
+            lines.push(String::new());
+            lines.push(format!(
+                "{:<14}  {:>6}  {:>9}  {:>7}  {:>13}",
+                "DIFFICULTY", "TASKS", "SUBMITTED", "SUCCESS", "MEAN DURATION"
+            ));
+            lines.extend(stats.per_difficulty.iter().map(|difficulty| {
+                format!(
+                    "{:<14}  {:>6}  {:>9}  {:>6.1}%  {:>13}",
+                    difficulty.difficulty,
+                    difficulty.tasks,
+                    difficulty.submitted,
+                    difficulty.success_rate * 100.0,
+                    difficulty
+                        .mean_duration_secs
+                        .map(|secs| format!("{}s", secs))
+                        .unwrap_or_else(|| "-".to_string())
+                )
+            }));
+            lines.join("\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    /// A task fetched at noon on `day` of June 2025, which is the same date in most time zones
+    fn record(day: u32, difficulty: &str, result: TaskResult, secs: i64) -> TaskRecord {
+        let fetched_at = Utc.with_ymd_and_hms(2025, 6, day, 12, 0, 0).unwrap();
+        TaskRecord {
+            node_id: 1,
+            task_id: format!("task-{}-{}", day, secs),
+            fetched_at,
+            finished_at: fetched_at + chrono::Duration::seconds(secs),
+            requested_difficulty: None,
+            assigned_difficulty: Some(difficulty.to_string()),
+            inputs: 2,
+            input_proving_ms: vec![1_000, 3_000],
+            peak_rss_bytes: Some(2_000_000_000),
+            submission_attempts: 1,
+            energy_joules: None,
+            result,
+            error: None,
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap()
+    }
+
+    #[test]
+    fn test_filter() {
+        let records = [
+            record(1, "SMALL", TaskResult::Submitted, 60),
+            record(2, "MEDIUM", TaskResult::Timeout, 60),
+            record(3, "medium", TaskResult::Submitted, 60),
+        ];
+        let count = |filter: HistoryFilter| {
+            records
+                .iter()
+                .filter(|record| filter.matches(record))
+                .count()
+        };
+
+        assert_eq!(count(HistoryFilter::default()), 3);
+        assert_eq!(
+            count(HistoryFilter {
+                since: Some(date(2)),
+                ..Default::default()
+            }),
+            2
+        );
+        assert_eq!(
+            count(HistoryFilter {
+                since: Some(date(2)),
+                until: Some(date(2)),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(HistoryFilter {
+                difficulty: Some("Medium".to_string()),
+                result: Some(TaskResult::Submitted),
+                ..Default::default()
+            }),
+            1
+        );
+        assert_eq!(
+            count(HistoryFilter {
+                node_id: Some(2),
+                ..Default::default()
+            }),
+            0
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let records = [
+            record(1, "SMALL", TaskResult::Submitted, 60),
+            record(1, "SMALL", TaskResult::Submitted, 120),
+            record(1, "MEDIUM", TaskResult::OutOfMemory, 30),
+            record(2, "MEDIUM", TaskResult::Submitted, 300),
+        ];
+        let stats = HistoryStats::compute(&records);
+
+        assert_eq!(
+            stats.per_day,
+            vec![
+                DayStats {
+                    day: date(1),
+                    tasks: 3,
+                    submitted: 2,
+                    inputs_proved: 4,
+                    proving_secs: 8,
+                },
+                DayStats {
+                    day: date(2),
+                    tasks: 1,
+                    submitted: 1,
+                    inputs_proved: 2,
+                    proving_secs: 4,
+                },
+            ]
+        );
+        assert_eq!(
+            stats.per_difficulty,
+            vec![
+                DifficultyStats {
+                    difficulty: "MEDIUM".to_string(),
+                    tasks: 2,
+                    submitted: 1,
+                    success_rate: 0.5,
+                    mean_duration_secs: Some(300),
+                },
+                DifficultyStats {
+                    difficulty: "SMALL".to_string(),
+                    tasks: 2,
+                    submitted: 2,
+                    success_rate: 1.0,
+                    mean_duration_secs: Some(90),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_csv_export_quotes_fields() {
+        let mut failed = record(1, "SMALL", TaskResult::SubmitFailed, 60);
+        failed.error = Some("Network error: HTTP 500, \"oops\"".to_string());
+        let csv = render_records(&[failed], HistoryFormat::Csv);
+
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("node_id,task_id,fetched_at,"));
+        assert!(lines[1].contains(",1000;3000,2000000000,1,,submit_failed,"));
+        assert!(lines[1].ends_with(",\"Network error: HTTP 500, \"\"oops\"\"\""));
+    }
+
+    #[test]
+    fn test_json_export_round_trips() {
+        let records = vec![record(1, "SMALL", TaskResult::Submitted, 60)];
+        let json = render_records(&records, HistoryFormat::Json);
+        let parsed: Vec<TaskRecord> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, records);
+    }
+}
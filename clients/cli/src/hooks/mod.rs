@@ -0,0 +1,395 @@
+//! User-defined hooks on lifecycle events
+//!
+//! The `hooks` section of `~/.nexus/config.json` maps lifecycle events to a
+//! shell command, which gets the event as JSON on stdin, or to a URL the event
+//! is POSTed to. Hooks run in their own tasks with a timeout and a cap on how
+//! many run at once, so a slow or failing hook never holds up the worker.
+
+mod runner;
+
+use crate::consts::cli_consts::hooks;
+use crate::events::{Event, EventType, Lifecycle};
+use crate::logging::LogLevel;
+use crate::workers::core::EventSender;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Semaphore, mpsc};
+
+/// Lifecycle events hooks can be attached to
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    TaskFetched,
+    ProofSubmitted,
+    /// Every submission attempt for a proof failed
+    SubmissionFailed,
+    /// A prover subprocess was killed, most likely by the OOM killer
+    OomSuspected,
+    VersionWarning,
+    /// This version may no longer be used; the CLI exits right after
+    VersionBlocking,
+    Shutdown,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::TaskFetched => "task_fetched",
+            HookEvent::ProofSubmitted => "proof_submitted",
+            HookEvent::SubmissionFailed => "submission_failed",
+            HookEvent::OomSuspected => "oom_suspected",
+            HookEvent::VersionWarning => "version_warning",
+            HookEvent::VersionBlocking => "version_blocking",
+            HookEvent::Shutdown => "shutdown",
+        }
+    }
+
+    /// The lifecycle event a worker event stands for, along with its task, if any
+    pub fn classify(event: &Event) -> Option<(HookEvent, Option<String>)> {
+        let hook_event = match event.lifecycle? {
+            Lifecycle::TaskFetched => HookEvent::TaskFetched,
+            Lifecycle::ProofSubmitted => HookEvent::ProofSubmitted,
+            Lifecycle::SubmissionFailed => HookEvent::SubmissionFailed,
+            Lifecycle::OutOfMemory => HookEvent::OomSuspected,
+            Lifecycle::FetchFailed | Lifecycle::ProofKept | Lifecycle::TaskCompleted(_) => {
+                return None;
+            }
+        };
+        Some((hook_event, event.task_id.clone()))
+    }
+}
+
+/// What a hook does
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookAction {
+    /// Run through the shell, with the event JSON on stdin
+    Command(String),
+    /// POST the event JSON here
+    Url(String),
+}
+
+/// One hook from the config file
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Hook {
+    pub events: Vec<HookEvent>,
+    #[serde(flatten)]
+    pub action: HookAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Hook {
+    fn timeout(&self) -> Duration {
+        self.timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(hooks::default_timeout())
+    }
+}
+
+/// The `hooks` section of the config file
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// How many hooks may run at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<usize>,
+    #[serde(default)]
+    pub on: Vec<Hook>,
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self.on.is_empty() && self.max_concurrent.is_none()
+    }
+}
+
+/// What a hook receives
+#[derive(Debug, Clone, Serialize)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    /// Unknown for events before the config is resolved
+    pub node_id: Option<u64>,
+    pub timestamp: String,
+    pub message: String,
+    pub task_id: Option<String>,
+}
+
+/// Dispatches lifecycle events to the configured hooks
+#[derive(Clone)]
+pub struct Hooks {
+    hooks: Arc<Vec<Hook>>,
+    node_id: Option<u64>,
+    /// Permits for hooks running at once
+    running: Arc<Semaphore>,
+    /// Permits for hooks waiting or running, so a burst can't pile up forever
+    queued: Arc<Semaphore>,
+    /// Where to report failing hooks; printed when there is no session yet
+    reporter: Option<EventSender>,
+}
+
+impl Hooks {
+    pub fn new(config: HooksConfig) -> Self {
+        let max_concurrent = config
+            .max_concurrent
+            .unwrap_or(hooks::DEFAULT_MAX_CONCURRENT)
+            .max(1);
+        Self {
+            hooks: Arc::new(config.on),
+            node_id: None,
+            running: Arc::new(Semaphore::new(max_concurrent)),
+            queued: Arc::new(Semaphore::new(hooks::MAX_QUEUED as usize)),
+            reporter: None,
+        }
+    }
+
+    /// Hooks configured at `config_path`; none if it is missing or unreadable
+    pub fn load(config_path: &std::path::Path) -> Self {
+        Self::new(crate::config::Config::existing_hooks(config_path))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Include the node in payloads from now on
+    pub fn with_node_id(&self, node_id: u64) -> Self {
+        Self {
+            node_id: Some(node_id),
+            ..self.clone()
+        }
+    }
+
+    /// Report failing hooks as session events rather than printing them
+    pub fn reporting_to(&self, event_sender: mpsc::Sender<Event>) -> Self {
+        Self {
+            reporter: Some(EventSender::new(event_sender)),
+            ..self.clone()
+        }
+    }
+
+    /// Run every hook attached to `event` in the background
+    pub fn fire(&self, event: HookEvent, message: String, task_id: Option<String>) {
+        let payload = HookPayload {
+            event,
+            node_id: self.node_id,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            message,
+            task_id,
+        };
+
+        for hook in self
+            .hooks
+            .iter()
+            .filter(|hook| hook.events.contains(&event))
+        {
+            let Ok(queued) = self.queued.clone().try_acquire_owned() else {
+                self.report(format!(
+                    "Hook for {} dropped: too many hooks pending",
+                    event.as_str()
+                ));
+                continue;
+            };
+            let hook = hook.clone();
+            let payload = payload.clone();
+            let running = self.running.clone();
+            let this = self.clone();
+            tokio::spawn(async move {
+                let _queued = queued;
+                let Ok(_running) = running.acquire_owned().await else {
+                    return;
+                };
+                if let Err(e) = runner::run(&hook.action, &payload, hook.timeout()).await {
+                    this.report(format!("Hook for {} failed: {}", event.as_str(), e));
+                }
+            });
+        }
+    }
+
+    /// Pass worker events through, firing hooks for the lifecycle events among them
+    pub fn tap(&self, mut receiver: mpsc::Receiver<Event>) -> mpsc::Receiver<Event> {
+        let (sender, tapped) = mpsc::channel(crate::consts::cli_consts::EVENT_QUEUE_SIZE);
+        let hooks = self.clone();
+        tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                if let Some((hook_event, task_id)) = HookEvent::classify(&event) {
+                    hooks.fire(hook_event, event.msg.trim().to_string(), task_id);
+                }
+                if sender.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        tapped
+    }
+
+    /// Wait for hooks still pending, up to the longest hook timeout
+    pub async fn finish(&self) {
+        let longest = self
+            .hooks
+            .iter()
+            .map(Hook::timeout)
+            .max()
+            .unwrap_or_default();
+        // Queued hooks may still be waiting for a running slot, so allow for one more round
+        let _ =
+            tokio::time::timeout(longest * 2, self.queued.acquire_many(hooks::MAX_QUEUED)).await;
+    }
+
+    fn report(&self, message: String) {
+        match &self.reporter {
+            Some(reporter) => {
+                let reporter = reporter.clone();
+                tokio::spawn(async move {
+                    reporter
+                        .send_task_event(message, EventType::Error, LogLevel::Warn)
+                        .await;
+                });
+            }
+            None => crate::print_cmd_warn!("Hooks", "{}", message),
+        }
+    }
+}
+
+impl std::fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hooks")
+            .field("hooks", &self.hooks)
+            .field("node_id", &self.node_id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_format() {
+        let config: HooksConfig = serde_json::from_str(
+            r#"{
+                "max_concurrent": 2,
+                "on": [
+                    {"events": ["submission_failed", "oom_suspected"], "command": "page-oncall", "timeout_secs": 5},
+                    {"events": ["version_blocking"], "url": "https://chat.example.com/hook"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.max_concurrent, Some(2));
+        assert_eq!(
+            config.on[0],
+            Hook {
+                events: vec![HookEvent::SubmissionFailed, HookEvent::OomSuspected],
+                action: HookAction::Command("page-oncall".to_string()),
+                timeout_secs: Some(5),
+            }
+        );
+        assert_eq!(
+            config.on[1].action,
+            HookAction::Url("https://chat.example.com/hook".to_string())
+        );
+        assert_eq!(config.on[1].timeout(), hooks::default_timeout());
+
+        // Round trips through the config file
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<HooksConfig>(&json).unwrap(), config);
+    }
+
+    #[test]
+    fn test_classify() {
+        let event = |lifecycle, task_id| {
+            Event::proof_submitter_with_level(
+                "anything".to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            )
+            .with_lifecycle(lifecycle, task_id)
+        };
+        let classify = |event: Event| HookEvent::classify(&event);
+
+        for (lifecycle, hook_event) in [
+            (Lifecycle::TaskFetched, HookEvent::TaskFetched),
+            (Lifecycle::ProofSubmitted, HookEvent::ProofSubmitted),
+            (Lifecycle::SubmissionFailed, HookEvent::SubmissionFailed),
+            (Lifecycle::OutOfMemory, HookEvent::OomSuspected),
+        ] {
+            assert_eq!(
+                classify(event(lifecycle, Some("abc"))),
+                Some((hook_event, Some("abc".to_string())))
+            );
+        }
+        assert_eq!(classify(event(Lifecycle::FetchFailed, None)), None);
+        // The message is for display only
+        assert_eq!(
+            classify(Event::task_fetcher_with_level(
+                "Step 1 of 4: Got task abc".to_string(),
+                EventType::Success,
+                LogLevel::Info
+            )),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_gets_event_on_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("event.json");
+        let env = dir.path().join("event.txt");
+        let hooks = Hooks::new(HooksConfig {
+            max_concurrent: None,
+            on: vec![Hook {
+                events: vec![HookEvent::Shutdown],
+                action: HookAction::Command(format!(
+                    "cat > {} && echo \"$NEXUS_HOOK_EVENT\" > {}",
+                    out.display(),
+                    env.display()
+                )),
+                timeout_secs: None,
+            }],
+        });
+
+        hooks.fire(HookEvent::ProofSubmitted, "ignored".to_string(), None);
+        hooks.fire(HookEvent::Shutdown, "bye".to_string(), None);
+        hooks.finish().await;
+
+        let payload: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&out).unwrap()).unwrap();
+        assert_eq!(payload["event"], "shutdown");
+        assert_eq!(payload["message"], "bye");
+        let env = std::fs::read_to_string(&env).unwrap();
+        assert_eq!(env.trim(), "shutdown");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_failing_hooks_are_isolated() {
+        let payload = HookPayload {
+            event: HookEvent::Shutdown,
+            node_id: Some(1),
+            timestamp: String::new(),
+            message: String::new(),
+            task_id: None,
+        };
+
+        let error = runner::run(
+            &HookAction::Command("echo nope >&2; exit 3".to_string()),
+            &payload,
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.ends_with(": nope"), "{}", error);
+
+        let error = runner::run(
+            &HookAction::Command("sleep 5".to_string()),
+            &payload,
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap_err();
+        assert!(error.starts_with("timed out"), "{}", error);
+    }
+}
@@ -0,0 +1,82 @@
+//! Running a single hook: a shell command or a webhook
+
+use super::{HookAction, HookPayload};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Run `action` with `payload`, giving up after `timeout`
+pub async fn run(
+    action: &HookAction,
+    payload: &HookPayload,
+    timeout: Duration,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+    let running = async {
+        match action {
+            HookAction::Command(command) => run_command(command, payload, &body).await,
+            HookAction::Url(url) => post(url, body, timeout).await,
+        }
+    };
+    tokio::time::timeout(timeout, running)
+        .await
+        .map_err(|_| format!("timed out after {}s", timeout.as_secs()))?
+}
+
+fn shell(command: &str) -> Command {
+    #[cfg(unix)]
+    {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+    #[cfg(not(unix))]
+    {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    }
+}
+
+/// Run `command` through the shell with the event JSON on stdin
+async fn run_command(command: &str, payload: &HookPayload, body: &[u8]) -> Result<(), String> {
+    let mut child = shell(command)
+        .env("NEXUS_HOOK_EVENT", payload.event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        // A hook that times out is killed along with its future
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A command that doesn't read its input is fine
+        let _ = stdin.write_all(body).await;
+    }
+
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(line) => Err(format!("{}: {}", output.status, line.trim())),
+        None => Err(output.status.to_string()),
+    }
+}
+
+/// POST the event JSON to `url`
+async fn post(url: &str, body: Vec<u8>, timeout: Duration) -> Result<(), String> {
+    reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .timeout(timeout)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,209 @@
+//! Rotating log file
+//!
+//! Worker events at or above the configured level, including debug events the
+//! dashboard hides, are appended to `~/.nexus/logs/nexus-network.log` in both
+//! TUI and headless modes, so there is something to read after a crash.
+
+pub mod rotation;
+
+use crate::config::get_config_path;
+use crate::consts::cli_consts::logs;
+use crate::events::{Event, EventType, Worker};
+use crate::logging::LogLevel;
+use chrono::Local;
+use rotation::RotatingFile;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Where the log goes and when it is rotated
+#[derive(Debug, Clone)]
+pub struct LogFileConfig {
+    /// The active log file; rotated segments are kept next to it
+    pub path: PathBuf,
+    /// Lowest level written
+    pub level: LogLevel,
+    pub max_bytes: u64,
+    pub max_age: Duration,
+    /// Rotated segments kept
+    pub keep: usize,
+}
+
+impl LogFileConfig {
+    /// ~/.nexus/logs
+    pub fn default_dir() -> std::io::Result<PathBuf> {
+        let config_path = get_config_path()?;
+        let nexus_dir = config_path.parent().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "No parent for config path")
+        })?;
+        Ok(nexus_dir.join(logs::LOG_DIR_NAME))
+    }
+
+    /// The active log file in `dir`
+    pub fn path_in(dir: &Path) -> PathBuf {
+        dir.join(logs::LOG_FILE_NAME)
+    }
+}
+
+/// Appends events to the rotating log file
+#[derive(Debug)]
+pub struct LogFile {
+    level: LogLevel,
+    keep: usize,
+    path: PathBuf,
+    file: RotatingFile,
+}
+
+impl LogFile {
+    pub fn open(config: LogFileConfig) -> std::io::Result<Self> {
+        Ok(Self {
+            level: config.level,
+            keep: config.keep,
+            path: config.path.clone(),
+            file: RotatingFile::open(config)?,
+        })
+    }
+
+    /// Write a line that isn't a worker event, such as the start of a session
+    pub fn note(&mut self, message: &str) -> std::io::Result<()> {
+        let now = Local::now();
+        let line = format!(
+            "{} {:<5} session: {}",
+            now.format("%Y-%m-%d %H:%M:%S"),
+            LogLevel::Info.as_str(),
+            message
+        );
+        self.write(&line)
+    }
+
+    fn write(&mut self, line: &str) -> std::io::Result<()> {
+        if let Some(segment) = self.file.write_line(line, Local::now())? {
+            // Compressing a full segment takes a moment; keep it off the event path
+            let path = self.path.clone();
+            let keep = self.keep;
+            tokio::task::spawn_blocking(move || {
+                let _ = rotation::compress(&segment);
+                let _ = rotation::prune(&path, keep);
+            });
+        }
+        Ok(())
+    }
+
+    /// Pass worker events through, writing those at or above the level to the file
+    pub fn tap(mut self, mut receiver: mpsc::Receiver<Event>) -> mpsc::Receiver<Event> {
+        let (sender, tapped) = mpsc::channel(crate::consts::cli_consts::EVENT_QUEUE_SIZE);
+        tokio::spawn(async move {
+            let mut failing = false;
+            while let Some(event) = receiver.recv().await {
+                let written = if event.log_level >= self.level {
+                    self.write(&format_event(&event))
+                } else {
+                    Ok(())
+                };
+                // Report the first failure of a run of them, not every event
+                let warning = match written {
+                    Err(e) if !failing => {
+                        failing = true;
+                        Some(Event::task_fetcher_with_level(
+                            format!("Failed to write log file {}: {}", self.path.display(), e),
+                            EventType::Error,
+                            LogLevel::Warn,
+                        ))
+                    }
+                    Err(_) => None,
+                    Ok(()) => {
+                        failing = false;
+                        None
+                    }
+                };
+                if sender.send(event).await.is_err() {
+                    break;
+                }
+                if let Some(warning) = warning {
+                    if sender.send(warning).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            let _ = self.note("Session ended");
+        });
+        tapped
+    }
+}
+
+/// One line per event: timestamp, level, worker and message
+fn format_event(event: &Event) -> String {
+    let worker = match event.worker {
+        Worker::TaskFetcher => "fetcher".to_string(),
+        Worker::Prover(thread) => format!("prover-{}", thread),
+        Worker::ProofSubmitter => "submitter".to_string(),
+    };
+    format!(
+        "{} {:<5} {}: {}",
+        event.timestamp,
+        event.log_level.as_str(),
+        worker,
+        event.msg.trim_end()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tap_writes_events_at_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log_file = LogFile::open(LogFileConfig {
+            path: LogFileConfig::path_in(dir.path()),
+            level: LogLevel::Debug,
+            max_bytes: 1024 * 1024,
+            max_age: Duration::from_secs(3600),
+            keep: 2,
+        })
+        .unwrap();
+        log_file.note("Started node 7").unwrap();
+
+        let (sender, receiver) = mpsc::channel(8);
+        let mut tapped = log_file.tap(receiver);
+        let events = [
+            Event::task_fetcher_with_level(
+                "Fetching a task".to_string(),
+                EventType::Refresh,
+                LogLevel::Debug,
+            ),
+            Event::prover_with_level(
+                2,
+                "Noisy detail".to_string(),
+                EventType::Refresh,
+                LogLevel::Trace,
+            ),
+            Event::proof_submitter_with_level(
+                "Step 4 of 4: Proof submitted successfully for task abc\n".to_string(),
+                EventType::Success,
+                LogLevel::Info,
+            ),
+        ];
+        for event in &events {
+            sender.send(event.clone()).await.unwrap();
+        }
+        drop(sender);
+        // Every event still reaches the UI, whatever its level
+        for event in &events {
+            assert_eq!(tapped.recv().await.as_ref(), Some(event));
+        }
+        assert_eq!(tapped.recv().await, None);
+
+        let contents = std::fs::read_to_string(dir.path().join(logs::LOG_FILE_NAME)).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        assert_eq!(lines.len(), 4, "{}", contents);
+        assert!(lines[0].ends_with(" INFO  session: Started node 7"));
+        assert!(lines[1].ends_with(" DEBUG fetcher: Fetching a task"));
+        assert!(
+            lines[2].ends_with(
+                " INFO  submitter: Step 4 of 4: Proof submitted successfully for task abc"
+            )
+        );
+        assert!(lines[3].ends_with(" INFO  session: Session ended"));
+    }
+}
@@ -0,0 +1,248 @@
+//! Size- and age-based rotation with gzip-compressed segments
+//!
+//! The active file is renamed to `<stem>-<YYYYmmdd-HHMMSS><ext>` when it grows
+//! past its size limit or outlives its age limit. Rotated segments are then
+//! compressed to `.gz` and the oldest ones beyond the retention count removed.
+
+use super::LogFileConfig;
+use chrono::{DateTime, Local};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The active log file, rotated as it is written
+#[derive(Debug)]
+pub struct RotatingFile {
+    config: LogFileConfig,
+    file: File,
+    size: u64,
+    opened_at: DateTime<Local>,
+}
+
+impl RotatingFile {
+    /// Open the active file for appending, creating its directory if needed
+    pub fn open(config: LogFileConfig) -> io::Result<Self> {
+        if let Some(parent) = config.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = open_append(&config.path)?;
+        let metadata = file.metadata()?;
+        // A file left by an earlier run keeps aging from when it was started
+        let opened_at = metadata
+            .created()
+            .or_else(|_| metadata.modified())
+            .map(DateTime::<Local>::from)
+            .unwrap_or_else(|_| Local::now());
+        Ok(Self {
+            size: metadata.len(),
+            config,
+            file,
+            opened_at,
+        })
+    }
+
+    /// Append `line`, rotating first if it's due; returns the segment rotated out, if any
+    pub fn write_line(&mut self, line: &str, now: DateTime<Local>) -> io::Result<Option<PathBuf>> {
+        let len = line.len() as u64 + 1;
+        let too_big = self.size + len > self.config.max_bytes;
+        let too_old = (now - self.opened_at)
+            .to_std()
+            .is_ok_and(|age| age >= self.config.max_age);
+        let rotated = if self.size > 0 && (too_big || too_old) {
+            Some(self.rotate(now)?)
+        } else {
+            None
+        };
+
+        // Unbuffered, so nothing is lost if the process dies
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(rotated)
+    }
+
+    fn rotate(&mut self, now: DateTime<Local>) -> io::Result<PathBuf> {
+        let segment = segment_path(&self.config.path, now);
+        fs::rename(&self.config.path, &segment)?;
+        self.file = open_append(&self.config.path)?;
+        self.size = 0;
+        self.opened_at = now;
+        Ok(segment)
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// `<stem>-` and `<ext>` of segments rotated out of `path`
+fn segment_affixes(path: &Path) -> (String, String) {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    (format!("{}-", stem), ext)
+}
+
+/// A name for the segment rotated out at `now` that isn't taken yet
+fn segment_path(path: &Path, now: DateTime<Local>) -> PathBuf {
+    let (prefix, ext) = segment_affixes(path);
+    let stamp = now.format("%Y%m%d-%H%M%S");
+    let mut n = 0;
+    loop {
+        let name = match n {
+            0 => format!("{}{}{}", prefix, stamp, ext),
+            n => format!("{}{}-{}{}", prefix, stamp, n, ext),
+        };
+        let candidate = path.with_file_name(name);
+        let compressed = gz_path(&candidate);
+        if !candidate.exists() && !compressed.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+fn gz_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    PathBuf::from(name)
+}
+
+/// Compress `segment` to `<segment>.gz` and remove the original
+pub fn compress(segment: &Path) -> io::Result<PathBuf> {
+    let compressed = gz_path(segment);
+    let partial = compressed.with_extension("gz.partial");
+    {
+        let mut encoder = GzEncoder::new(File::create(&partial)?, Compression::default());
+        io::copy(&mut File::open(segment)?, &mut encoder)?;
+        encoder.finish()?.sync_all()?;
+    }
+    fs::rename(&partial, &compressed)?;
+    fs::remove_file(segment)?;
+    Ok(compressed)
+}
+
+/// Segments rotated out of `path`, compressed or not, oldest first
+pub fn segments(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let Some(dir) = path.parent() else {
+        return Ok(Vec::new());
+    };
+    let (prefix, ext) = segment_affixes(path);
+    let compressed_ext = format!("{}.gz", ext);
+    let mut segments: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|candidate| {
+            candidate.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                name.starts_with(&prefix)
+                    && (name.ends_with(&compressed_ext) || name.ends_with(&ext))
+            })
+        })
+        .collect();
+    segments.sort();
+    Ok(segments)
+}
+
+/// Remove all but the newest `keep` segments of `path`
+pub fn prune(path: &Path, keep: usize) -> io::Result<()> {
+    let segments = segments(path)?;
+    let excess = segments.len().saturating_sub(keep);
+    for segment in &segments[..excess] {
+        fs::remove_file(segment)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::LogLevel;
+    use chrono::TimeZone;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::time::Duration;
+
+    fn config(dir: &Path, max_bytes: u64) -> LogFileConfig {
+        LogFileConfig {
+            path: dir.join("node.log"),
+            level: LogLevel::Debug,
+            max_bytes,
+            max_age: Duration::from_secs(3600),
+            keep: 2,
+        }
+    }
+
+    fn at(hour: u32, min: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2025, 6, 1, hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_rotates_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = RotatingFile::open(config(dir.path(), 20)).unwrap();
+        file.opened_at = at(12, 0);
+
+        assert_eq!(file.write_line("0123456789", at(12, 0)).unwrap(), None);
+        let rotated = file.write_line("abcdefghij", at(12, 1)).unwrap().unwrap();
+        assert_eq!(rotated, dir.path().join("node-20250601-120100.log"));
+        assert_eq!(fs::read_to_string(&rotated).unwrap(), "0123456789\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("node.log")).unwrap(),
+            "abcdefghij\n"
+        );
+
+        // A second rotation within the same second gets its own name
+        assert_eq!(file.write_line("x", at(12, 1)).unwrap(), None);
+        let rotated = file.write_line("0123456789", at(12, 1)).unwrap().unwrap();
+        assert_eq!(rotated, dir.path().join("node-20250601-120100-1.log"));
+    }
+
+    #[test]
+    fn test_rotates_by_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = RotatingFile::open(config(dir.path(), 1024)).unwrap();
+        file.opened_at = at(12, 0);
+
+        assert_eq!(file.write_line("first", at(12, 59)).unwrap(), None);
+        assert!(file.write_line("second", at(13, 0)).unwrap().is_some());
+        assert_eq!(file.write_line("third", at(13, 59)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_compress_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(dir.path(), 1024);
+        for minute in 0..3 {
+            let segment = segment_path(&config.path, at(12, minute));
+            fs::write(&segment, format!("segment {}\n", minute)).unwrap();
+            compress(&segment).unwrap();
+        }
+        fs::write(&config.path, "active\n").unwrap();
+        fs::write(dir.path().join("other.log"), "unrelated\n").unwrap();
+
+        prune(&config.path, config.keep).unwrap();
+        let kept = segments(&config.path).unwrap();
+        assert_eq!(
+            kept,
+            vec![
+                dir.path().join("node-20250601-120100.log.gz"),
+                dir.path().join("node-20250601-120200.log.gz"),
+            ]
+        );
+
+        let mut contents = String::new();
+        GzDecoder::new(File::open(&kept[1]).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "segment 2\n");
+        assert!(config.path.exists());
+        assert!(dir.path().join("other.log").exists());
+    }
+}
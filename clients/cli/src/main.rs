@@ -4,10 +4,16 @@ mod analytics;
 mod cli_messages;
 mod config;
 mod consts;
+mod control;
 mod environment;
 mod events;
+mod health;
+mod hex;
+mod history;
+mod hooks;
 mod keys;
 mod logging;
+mod logs;
 mod network;
 #[path = "proto/nexus.orchestrator.rs"]
 mod nexus_orchestrator;
@@ -15,25 +21,51 @@ mod orchestrator;
 mod prover;
 mod register;
 mod runtime;
+mod schedule;
 mod session;
 pub mod system;
+mod systemd;
 mod task;
+mod throttle;
 mod ui;
 mod version;
 mod workers;
 
 use crate::config::{Config, get_config_path};
 use crate::environment::Environment;
-use crate::orchestrator::OrchestratorClient;
+use crate::health::{Health, HealthThresholds};
+use crate::history::report::{HistoryFilter, HistoryFormat, HistoryStats};
+use crate::history::{HistoryStore, TaskResult};
+use crate::hooks::{HookEvent, Hooks};
+use crate::logging::LogLevel;
+use crate::logs::LogFileConfig;
+use crate::orchestrator::recording::Recorder;
+use crate::orchestrator::{Orchestrator, OrchestratorClient, get_all_tasks};
+use crate::prover::energy::EnergyConfig;
 use crate::prover::engine::ProvingEngine;
+use crate::prover::processes::{IoPriority, ProcessPriority};
 use crate::register::{register_node, register_user};
-use crate::session::{run_headless_mode, run_tui_mode, setup_session};
+use crate::schedule::{OutsideWindow, ScheduleTimezone};
+use crate::session::scheduler::RunLimits;
+use crate::session::{SessionOptions, run_headless_mode, run_replay, run_tui_mode, setup_session};
+use crate::systemd::unit::{Scope, ServiceUnit};
+use crate::task::TaskTrust;
+use crate::throttle::load::LoadPolicy;
+use crate::throttle::thermal::{PowerLimits, Sysfs, ThermalPolicy};
+use crate::throttle::{ThrottleAction, ThrottlePolicy};
+use crate::version::ConstraintType;
 use crate::version::manager::validate_version_requirements;
+use crate::workers::core::ResumeAssigned;
+use crate::workers::dry_run::DryRunSink;
 use clap::{ArgAction, Parser, Subcommand};
 use postcard::to_allocvec;
 use std::error::Error;
 use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// All available difficulty levels as (name, enum_value) pairs
 const DIFFICULTY_LEVELS: &[(&str, crate::nexus_orchestrator::TaskDifficulty)] = &[
@@ -93,6 +125,8 @@ struct Args {
 }
 
 #[derive(Subcommand)]
+// Parsed once; `start` simply has many flags
+#[allow(clippy::large_enum_variant)]
 enum Command {
     /// Start the prover
     Start {
@@ -112,6 +146,16 @@ enum Command {
         #[arg(long = "orchestrator-url", value_name = "URL")]
         orchestrator_url: Option<String>,
 
+        /// Hex-encoded Ed25519 key the custom orchestrator signs tasks with; repeat to accept
+        /// several during a key rotation. Unsigned or mis-signed tasks are then rejected.
+        #[arg(
+            long = "orchestrator-key",
+            value_name = "HEX",
+            requires = "orchestrator_url",
+            value_parser = environment::parse_task_key
+        )]
+        orchestrator_keys: Vec<ed25519_dalek::VerifyingKey>,
+
         /// Enable checking for risk of memory errors, may slow down CLI startup
         #[arg(long = "check-memory", default_value_t = false)]
         check_mem: bool,
@@ -127,6 +171,277 @@ enum Command {
         /// Override max difficulty to request. Auto-promotion occurs when tasks complete in < 7 min
         #[arg(long = "max-difficulty", value_name = "DIFFICULTY")]
         max_difficulty: Option<String>,
+
+        /// Lowest difficulty adaptive selection may demote to after slow tasks, timeouts or OOM kills
+        #[arg(long = "min-difficulty", value_name = "DIFFICULTY")]
+        min_difficulty: Option<String>,
+
+        /// Stop after this many tasks in a row fail to prove or submit
+        #[arg(long = "max-consecutive-failures", value_name = "N")]
+        max_consecutive_failures: Option<u32>,
+
+        /// Stop after running this long, e.g. 90m, 8h or 1h30m. The current task is finished first
+        #[arg(long = "max-duration", value_name = "DURATION")]
+        max_duration: Option<String>,
+
+        /// Stop at this time, e.g. 07:00 or "2025-06-02 07:00". The current task is finished first
+        #[arg(long = "stop-at", value_name = "TIME")]
+        stop_at: Option<String>,
+
+        /// Only prove within these windows, e.g. "22:00-07:00 weekdays, all weekend"
+        #[arg(long = "schedule", value_name = "WINDOWS")]
+        schedule: Option<String>,
+
+        /// Clock used by --schedule and --stop-at
+        #[arg(long = "schedule-tz", value_enum, default_value_t = ScheduleTimezone::Local)]
+        schedule_tz: ScheduleTimezone,
+
+        /// What to do with the current task when an operating window closes
+        #[arg(long = "outside-window", value_enum, default_value_t = OutsideWindow::Finish)]
+        outside_window: OutsideWindow,
+
+        /// Back off while other programs use more than this percentage of total CPU
+        #[arg(
+            long = "max-foreground-load",
+            value_name = "PERCENT",
+            value_parser = clap::value_parser!(u8).range(1..=100)
+        )]
+        max_foreground_load: Option<u8>,
+
+        /// What to do while foreground load is above --max-foreground-load
+        #[arg(long = "busy-action", value_enum, default_value_t = ThrottleAction::Reduce)]
+        busy_action: ThrottleAction,
+
+        /// Seconds load must stay below --max-foreground-load before proving at full speed again
+        #[arg(
+            long = "quiet-period",
+            value_name = "SECS",
+            default_value_t = consts::cli_consts::throttle::QUIET_PERIOD_SECS
+        )]
+        quiet_period: u64,
+
+        /// Niceness of prover subprocesses, from 0 (normal) to 19 (lowest priority)
+        #[arg(
+            long = "prover-nice",
+            value_name = "N",
+            value_parser = clap::value_parser!(i32).range(0..=19)
+        )]
+        prover_nice: Option<i32>,
+
+        /// I/O priority of prover subprocesses on Linux: idle, best-effort or best-effort:N
+        #[arg(long = "prover-ionice", value_name = "CLASS")]
+        prover_ionice: Option<IoPriority>,
+
+        /// Back off above this temperature in °C (Linux)
+        #[arg(
+            long = "max-temp",
+            value_name = "CELSIUS",
+            value_parser = clap::value_parser!(u8).range(1..=150)
+        )]
+        max_temp: Option<u8>,
+
+        /// Back off while running on battery (Linux)
+        #[arg(long = "throttle-on-battery", action = ArgAction::SetTrue)]
+        throttle_on_battery: bool,
+
+        /// Back off while on battery with less than this charge left (Linux)
+        #[arg(
+            long = "min-battery",
+            value_name = "PERCENT",
+            value_parser = clap::value_parser!(u8).range(1..=100)
+        )]
+        min_battery: Option<u8>,
+
+        /// What to do when --max-temp, --throttle-on-battery or --min-battery trigger
+        #[arg(long = "power-action", value_enum, default_value_t = ThrottleAction::Pause)]
+        power_action: ThrottleAction,
+
+        /// Where sysfs is mounted, for reading temperatures and power supplies
+        #[arg(
+            long = "sysfs-root",
+            value_name = "PATH",
+            default_value = consts::cli_consts::throttle::SYSFS_ROOT,
+            hide = true
+        )]
+        sysfs_root: PathBuf,
+
+        /// Electricity price per kWh, to estimate what each proof costs
+        #[arg(long = "energy-price", value_name = "PRICE")]
+        energy_price: Option<f64>,
+
+        /// Currency shown after estimated costs, e.g. EUR
+        #[arg(
+            long = "energy-currency",
+            value_name = "CODE",
+            requires = "energy_price"
+        )]
+        energy_currency: Option<String>,
+
+        /// Seconds to let the current task finish after SIGTERM/SIGINT or [Q] before abandoning it
+        #[arg(
+            long = "drain-timeout",
+            value_name = "SECS",
+            default_value_t = consts::cli_consts::shutdown::DRAIN_TIMEOUT_SECS
+        )]
+        drain_timeout: u64,
+
+        /// Control socket for `nexus-network ctl` (default: ~/.nexus/control.sock)
+        #[arg(long = "control-socket", value_name = "PATH")]
+        control_socket: Option<PathBuf>,
+
+        /// Don't listen on a control socket
+        #[arg(long = "no-control", action = ArgAction::SetTrue, conflicts_with = "control_socket")]
+        no_control: bool,
+
+        /// Serve /livez and /readyz health checks on this address, e.g. 0.0.0.0:8080
+        #[arg(long = "health-addr", value_name = "ADDR")]
+        health_addr: Option<SocketAddr>,
+
+        /// /readyz fails after this many seconds without fetching a task
+        #[arg(
+            long = "ready-fetch-within",
+            value_name = "SECONDS",
+            default_value_t = consts::cli_consts::health::READY_WITHIN_SECS,
+            requires = "health_addr"
+        )]
+        ready_fetch_within: u64,
+
+        /// /readyz fails after this many seconds without submitting a proof
+        #[arg(
+            long = "ready-submit-within",
+            value_name = "SECONDS",
+            default_value_t = consts::cli_consts::health::READY_WITHIN_SECS,
+            requires = "health_addr"
+        )]
+        ready_submit_within: u64,
+
+        /// Directory for the rotating log file (default: ~/.nexus/logs). The file is rotated
+        /// by --log-max-size and --log-max-age and only --log-keep compressed segments are
+        /// kept, so it stays within about 10 MB plus 14 compressed segments by default.
+        #[arg(long = "log-dir", value_name = "DIR", conflicts_with = "log_file")]
+        log_dir: Option<PathBuf>,
+
+        /// Write the log to this file instead; rotated segments are kept next to it
+        #[arg(long = "log-file", value_name = "PATH")]
+        log_file: Option<PathBuf>,
+
+        /// Lowest level written to the log file; `debug` adds the events the dashboard hides
+        #[arg(long = "log-level", value_enum, default_value = "info")]
+        log_level: LogLevel,
+
+        /// Rotate the log file once it reaches this many megabytes
+        #[arg(
+            long = "log-max-size",
+            value_name = "MB",
+            default_value_t = consts::cli_consts::logs::DEFAULT_MAX_SIZE_MB,
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        log_max_size: u64,
+
+        /// Rotate the log file once it is this many hours old
+        #[arg(
+            long = "log-max-age",
+            value_name = "HOURS",
+            default_value_t = consts::cli_consts::logs::DEFAULT_MAX_AGE_HOURS,
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        log_max_age: u64,
+
+        /// Number of rotated, compressed log files to keep
+        #[arg(long = "log-keep", value_name = "N", default_value_t = consts::cli_consts::logs::DEFAULT_KEEP)]
+        log_keep: usize,
+
+        /// Don't write a log file
+        #[arg(long = "no-log-file", action = ArgAction::SetTrue, conflicts_with_all = ["log_dir", "log_file"])]
+        no_log_file: bool,
+
+        /// Fetch and prove tasks as usual, but keep proofs locally instead of submitting them
+        #[arg(long = "dry-run", action = ArgAction::SetTrue)]
+        dry_run: bool,
+
+        /// Where a dry run keeps its proofs (default: ~/.nexus/dry-run.jsonl)
+        #[arg(long = "dry-run-output", value_name = "PATH", requires = "dry_run")]
+        dry_run_output: Option<PathBuf>,
+
+        /// Record every orchestrator request and response in this directory, for `replay`
+        #[arg(long = "record", value_name = "DIR")]
+        record: Option<PathBuf>,
+
+        /// What to do with tasks assigned to this node before a restart and never submitted
+        #[arg(long = "resume-assigned", value_enum, default_value_t = ResumeAssigned::Auto)]
+        resume_assigned: ResumeAssigned,
+    },
+    /// Query or steer a running node over its control socket
+    Ctl {
+        /// Control socket of the running node (default: ~/.nexus/control.sock)
+        #[arg(long, value_name = "PATH", global = true)]
+        socket: Option<PathBuf>,
+
+        #[command(subcommand)]
+        command: CtlCommand,
+    },
+    /// Show past tasks, throughput per day and success rate per difficulty
+    History {
+        /// Only tasks fetched on or after this local date (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        since: Option<chrono::NaiveDate>,
+
+        /// Only tasks fetched on or before this local date (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        until: Option<chrono::NaiveDate>,
+
+        /// Only tasks assigned this difficulty
+        #[arg(long, value_name = "DIFFICULTY")]
+        difficulty: Option<String>,
+
+        /// Only tasks that ended this way
+        #[arg(long, value_enum)]
+        result: Option<TaskResult>,
+
+        /// Only tasks of this node
+        #[arg(long, value_name = "NODE_ID")]
+        node_id: Option<u64>,
+
+        /// Only the most recent N tasks
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Show aggregates instead of individual tasks
+        #[arg(long, action = ArgAction::SetTrue)]
+        stats: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table")]
+        format: HistoryFormat,
+
+        /// History file (default: ~/.nexus/history.jsonl)
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+    },
+    /// Look at the tasks the orchestrator has assigned to a node
+    Tasks {
+        #[command(subcommand)]
+        command: TasksCommand,
+    },
+    /// Re-run the worker offline against a recording made with `start --record`
+    Replay {
+        /// Directory the recording was made in
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+    },
+    /// Write a systemd unit that runs this node as a service
+    InstallService {
+        /// Install for your own systemd instance (`systemctl --user`) instead of system-wide
+        #[arg(long, action = ArgAction::SetTrue)]
+        user: bool,
+
+        /// Write the unit here instead of the systemd unit directory
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Replace an existing unit file
+        #[arg(long, action = ArgAction::SetTrue)]
+        force: bool,
     },
     /// Register a new user
     RegisterUser {
@@ -151,6 +466,46 @@ enum Command {
     },
 }
 
+#[derive(Subcommand)]
+enum TasksCommand {
+    /// List tasks assigned to the node that were never submitted
+    List {
+        /// Node ID (default: the node in the config file)
+        #[arg(long, value_name = "NODE_ID")]
+        node_id: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CtlCommand {
+    /// Show node ID, state, current task, difficulty and task counters
+    Status,
+    /// Stop fetching new tasks once the current one is submitted
+    Pause {
+        /// Suspend the current task right away instead of letting it finish
+        #[arg(long, action = ArgAction::SetTrue)]
+        suspend: bool,
+    },
+    /// Resume fetching and proving tasks
+    Resume,
+    /// Finish the current task, then exit
+    Drain,
+    /// Abandon the current task and exit now
+    Stop,
+    /// Set the max difficulty override, or "adaptive" to clear it (applies from the next task)
+    SetDifficulty {
+        #[arg(value_name = "DIFFICULTY")]
+        difficulty: String,
+    },
+    /// Set the number of proving threads (applies from the next task)
+    SetThreads {
+        #[arg(value_name = "THREADS")]
+        threads: usize,
+    },
+    /// Stream the node's events until it exits
+    Tail,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // Set up panic hook to prevent core dumps
@@ -173,32 +528,244 @@ async fn main() -> Result<(), Box<dyn Error>> {
             headless,
             max_threads,
             orchestrator_url,
+            orchestrator_keys,
             check_mem,
             with_background,
             max_tasks,
             max_difficulty,
+            min_difficulty,
+            max_consecutive_failures,
+            max_duration,
+            stop_at,
+            schedule,
+            schedule_tz,
+            outside_window,
+            max_foreground_load,
+            busy_action,
+            quiet_period,
+            prover_nice,
+            prover_ionice,
+            max_temp,
+            throttle_on_battery,
+            min_battery,
+            power_action,
+            sysfs_root,
+            energy_price,
+            energy_currency,
+            drain_timeout,
+            control_socket,
+            no_control,
+            health_addr,
+            ready_fetch_within,
+            ready_submit_within,
+            log_dir,
+            log_file,
+            log_level,
+            log_max_size,
+            log_max_age,
+            log_keep,
+            no_log_file,
+            dry_run,
+            dry_run_output,
+            record,
+            resume_assigned,
         } => {
             // If a custom orchestrator URL is provided, create a custom environment
             let final_environment = if let Some(url) = orchestrator_url {
                 Environment::Custom {
                     orchestrator_url: url,
+                    task_keys: orchestrator_keys,
                 }
             } else {
                 environment
             };
+            let run_limits = RunLimits::from_args(
+                schedule.as_deref(),
+                schedule_tz,
+                outside_window,
+                max_duration.as_deref(),
+                stop_at.as_deref(),
+            )?;
+            prover::processes::set_priority(ProcessPriority {
+                nice: prover_nice,
+                io: prover_ionice,
+            });
+            let mut throttle_policies: Vec<Box<dyn ThrottlePolicy>> = Vec::new();
+            if let Some(max_load) = max_foreground_load {
+                throttle_policies.push(Box::new(LoadPolicy::new(
+                    f32::from(max_load),
+                    busy_action,
+                    Duration::from_secs(quiet_period),
+                )));
+            }
+            let energy = EnergyConfig {
+                sysfs_root: sysfs_root.clone(),
+                price_per_kwh: energy_price,
+                currency: energy_currency,
+            };
+            let power_limits = PowerLimits {
+                max_temperature_c: max_temp.map(f32::from),
+                on_battery: throttle_on_battery,
+                min_battery_percent: min_battery,
+                action: power_action,
+            };
+            if !power_limits.is_empty() {
+                let policy = ThermalPolicy::new(Sysfs::new(sysfs_root), power_limits);
+                for problem in policy.unsupported() {
+                    print_cmd_warn!("Power policy", "{}", problem);
+                }
+                throttle_policies.push(Box::new(policy));
+            }
+            // Listen right away, so probes see why the node isn't ready during startup
+            let health = match health_addr {
+                Some(addr) => {
+                    let health = Health::new(HealthThresholds {
+                        fetch_within: Duration::from_secs(ready_fetch_within),
+                        submit_within: Duration::from_secs(ready_submit_within),
+                    });
+                    let addr = health::server::start(addr, health.clone())
+                        .await
+                        .map_err(|e| format!("Cannot serve health checks on {}: {}", addr, e))?;
+                    print_cmd_info!("Health checks", "Serving /livez and /readyz on {}", addr);
+                    Some(health)
+                }
+                None => None,
+            };
+            let log_file = if no_log_file {
+                None
+            } else {
+                let path = match (log_file, log_dir) {
+                    (Some(path), _) => path,
+                    (None, Some(dir)) => LogFileConfig::path_in(&dir),
+                    (None, None) => LogFileConfig::path_in(&LogFileConfig::default_dir()?),
+                };
+                Some(LogFileConfig {
+                    path,
+                    level: log_level,
+                    max_bytes: log_max_size * 1024 * 1024,
+                    max_age: Duration::from_secs(log_max_age * 60 * 60),
+                    keep: log_keep,
+                })
+            };
+            let dry_run = if dry_run {
+                Some(match dry_run_output {
+                    Some(path) => path,
+                    None => DryRunSink::default_path()?,
+                })
+            } else {
+                None
+            };
+            let recorder = record
+                .map(|dir| Recorder::create(&dir).map(Arc::new))
+                .transpose()?;
+            // Parse and validate difficulty bounds (case-insensitive)
+            let max_difficulty = max_difficulty.as_deref().map(parse_difficulty_or_exit);
+            let min_difficulty = min_difficulty.as_deref().map(parse_difficulty_or_exit);
+            if let (Some(max), Some(min)) = (max_difficulty, min_difficulty) {
+                if workers::difficulty::harder(min, max) != max {
+                    eprintln!(
+                        "Error: --min-difficulty {} is above --max-difficulty {}",
+                        min.as_str_name(),
+                        max.as_str_name()
+                    );
+                    std::process::exit(1);
+                }
+            }
+            let options = SessionOptions {
+                check_mem,
+                max_threads,
+                max_tasks,
+                max_consecutive_failures,
+                max_difficulty,
+                min_difficulty,
+                drain_timeout: Duration::from_secs(drain_timeout),
+                control_socket: if no_control {
+                    None
+                } else {
+                    Some(match control_socket {
+                        Some(path) => path,
+                        None => control::default_socket_path()?,
+                    })
+                },
+                run_limits,
+                throttle_policies,
+                energy,
+                health,
+                log_file,
+                dry_run,
+                resume_assigned,
+            };
             start(
                 node_id,
                 final_environment,
                 config_path,
                 headless,
-                max_threads,
-                check_mem,
                 with_background,
-                max_tasks,
-                max_difficulty,
+                recorder,
+                options,
             )
             .await
         }
+        Command::Ctl { socket, command } => {
+            let socket = match socket {
+                Some(path) => path,
+                None => control::default_socket_path()?,
+            };
+            ctl(&socket, command).await
+        }
+        Command::History {
+            since,
+            until,
+            difficulty,
+            result,
+            node_id,
+            limit,
+            stats,
+            format,
+            file,
+        } => {
+            let store = HistoryStore::new(match file {
+                Some(path) => path,
+                None => HistoryStore::default_path()?,
+            });
+            let filter = HistoryFilter {
+                since,
+                until,
+                difficulty: difficulty.as_deref().map(|difficulty| {
+                    parse_difficulty_or_exit(difficulty)
+                        .as_str_name()
+                        .to_string()
+                }),
+                result,
+                node_id,
+            };
+            show_history(&store, &filter, limit, stats, format)
+        }
+        Command::Tasks {
+            command: TasksCommand::List { node_id },
+        } => {
+            let node_id = match node_id {
+                Some(node_id) => node_id.to_string(),
+                None => Config::load_from_file(&config_path)
+                    .ok()
+                    .map(|config| config.node_id)
+                    .filter(|node_id| !node_id.is_empty())
+                    .ok_or("No node registered; pass --node-id or register a node first")?,
+            };
+            list_assigned_tasks(&OrchestratorClient::new(environment), &node_id).await
+        }
+        Command::Replay { dir } => {
+            run_replay(&dir, environment).await?;
+            Ok(())
+        }
+        Command::InstallService {
+            user,
+            output,
+            force,
+        } => {
+            let scope = if user { Scope::User } else { Scope::System };
+            install_service(scope, output, force)
+        }
         Command::Logout => {
             print_cmd_info!("Logging out", "Clearing node configuration file...");
             Config::clear_node_config(&config_path).map_err(Into::into)
@@ -237,65 +804,310 @@ async fn main() -> Result<(), Box<dyn Error>> {
 /// * `env` - The environment to connect to.
 /// * `config_path` - Path to the configuration file.
 /// * `headless` - If true, runs without the terminal UI.
-/// * `max_threads` - Optional maximum number of threads to use for proving.
-/// * `check_mem` - Whether to check risky memory usage.
 /// * `with_background` - Whether to use the alternate TUI background color.
-/// * `max_tasks` - Optional maximum number of tasks to prove.
-#[allow(clippy::too_many_arguments)]
+/// * `recorder` - Where orchestrator traffic is recorded, with `--record`.
+/// * `options` - How the session runs, from the remaining `start` flags.
+///
+/// Hooks from the config file fire on version constraints, task lifecycle events and shutdown.
+///
+/// Exits with `ABANDONED_TASK_EXIT_CODE` if a task had to be abandoned on shutdown.
 async fn start(
     node_id: Option<u64>,
     env: Environment,
     config_path: std::path::PathBuf,
     headless: bool,
-    max_threads: Option<u32>,
-    check_mem: bool,
     with_background: bool,
-    max_tasks: Option<u32>,
-    max_difficulty: Option<String>,
+    recorder: Option<Arc<Recorder>>,
+    options: SessionOptions,
 ) -> Result<(), Box<dyn Error>> {
+    let hooks = Hooks::load(&config_path);
+
+    // A dry run leaves no trace on the network, analytics included
+    if let Some(path) = &options.dry_run {
+        analytics::suppress_analytics();
+        print_cmd_warn!(
+            "Dry run",
+            "Proofs are kept in {} and never submitted",
+            path.display()
+        );
+    }
+
     // 1. Version checking (will internally perform country detection without race)
-    validate_version_requirements().await?;
+    if let Some(violation) = validate_version_requirements().await? {
+        let event = match violation.constraint_type {
+            ConstraintType::Blocking => Some(HookEvent::VersionBlocking),
+            ConstraintType::Warning => Some(HookEvent::VersionWarning),
+            ConstraintType::Notice => None,
+        };
+        if let Some(event) = event {
+            hooks.fire(event, violation.message.clone(), None);
+        }
+        if violation.constraint_type == ConstraintType::Blocking {
+            hooks.finish().await;
+            exit(1);
+        }
+    }
+    if let Some(health) = &options.health {
+        health.version_checked();
+    }
+
+    // Proving unchecked tasks is an opt-out, so make it visible
+    if env.task_trust() == TaskTrust::Unverified {
+        print_cmd_warn!(
+            "Task signatures",
+            "Tasks from {} are not checked against orchestrator keys",
+            env
+        );
+    }
 
     // 2. Configuration resolution
-    let orchestrator_client = OrchestratorClient::new(env.clone());
+    let mut orchestrator_client = OrchestratorClient::new(env.clone());
+    if let Some(recorder) = recorder {
+        print_cmd_info!(
+            "Recording",
+            "Orchestrator traffic is recorded in {}",
+            recorder.path().display()
+        );
+        orchestrator_client = orchestrator_client.with_recorder(recorder);
+    }
     let config = Config::resolve(node_id, &config_path, &orchestrator_client).await?;
 
     // 3. Session setup (authenticated worker only)
-    // Parse and validate difficulty override (case-insensitive)
-    let max_difficulty_parsed = if let Some(difficulty_str) = &max_difficulty {
-        match validate_difficulty(difficulty_str) {
-            Some(difficulty) => Some(difficulty),
-            None => {
-                eprintln!(
-                    "Error: Invalid difficulty level '{}'",
-                    difficulty_str.trim()
-                );
-                print_available_difficulties();
-                eprintln!();
-                eprintln!("Note: Difficulty levels are case-insensitive.");
-                std::process::exit(1);
-            }
-        }
+    let session = setup_session(config, env, orchestrator_client, hooks.clone(), options).await?;
+    // The UI is gone by the time shutdown hooks run, so their failures are printed
+    let hooks = hooks.with_node_id(session.node_id);
+    let notifier = session.notifier.clone();
+
+    // 4. Run appropriate mode
+    let worker_exit = if headless {
+        run_headless_mode(session).await?
     } else {
-        None
+        run_tui_mode(session, with_background).await?
     };
+    if let Some(notifier) = &notifier {
+        notifier.stopping();
+        notifier.status(&format!("Shutting down: {}", worker_exit));
+    }
 
-    let session = setup_session(
-        config,
-        env,
-        check_mem,
-        max_threads,
-        max_tasks,
-        max_difficulty_parsed,
-    )
-    .await?;
+    // Give shutdown hooks, and any still running, a chance to finish
+    hooks.fire(
+        HookEvent::Shutdown,
+        format!("Shutting down: {}", worker_exit),
+        None,
+    );
+    hooks.finish().await;
 
-    // 4. Run appropriate mode
-    if headless {
-        run_headless_mode(session).await
+    // 5. Let supervisors know if work was abandoned
+    match worker_exit.exit_code() {
+        0 => Ok(()),
+        code => exit(code),
+    }
+}
+
+/// Parses a difficulty flag, exiting with the list of valid levels if it is not one.
+fn parse_difficulty_or_exit(difficulty: &str) -> crate::nexus_orchestrator::TaskDifficulty {
+    match validate_difficulty(difficulty) {
+        Some(parsed) => parsed,
+        None => {
+            eprintln!("Error: Invalid difficulty level '{}'", difficulty.trim());
+            print_available_difficulties();
+            eprintln!();
+            eprintln!("Note: Difficulty levels are case-insensitive.");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the recorded tasks that match `filter`, or aggregates over them.
+fn show_history(
+    store: &HistoryStore,
+    filter: &HistoryFilter,
+    limit: Option<usize>,
+    stats: bool,
+    format: HistoryFormat,
+) -> Result<(), Box<dyn Error>> {
+    let mut records: Vec<_> = store
+        .load()?
+        .into_iter()
+        .filter(|record| filter.matches(record))
+        .collect();
+    if let Some(limit) = limit {
+        records.drain(..records.len().saturating_sub(limit));
+    }
+
+    if records.is_empty() && format == HistoryFormat::Table {
+        println!("No tasks recorded in {}", store.path().display());
+    } else if stats {
+        println!(
+            "{}",
+            history::report::render_stats(&HistoryStats::compute(&records), format)
+        );
     } else {
-        run_tui_mode(session, with_background).await
+        println!("{}", history::report::render_records(&records, format));
+    }
+    Ok(())
+}
+
+/// Prints the tasks assigned to `node_id` that were never submitted.
+async fn list_assigned_tasks(
+    orchestrator: &dyn Orchestrator,
+    node_id: &str,
+) -> Result<(), Box<dyn Error>> {
+    let page = get_all_tasks(orchestrator, node_id).await?;
+    for e in &page.rejected {
+        eprintln!("Skipping untrusted task: {}", e);
+    }
+    let tasks = page.tasks;
+    if tasks.is_empty() {
+        println!("No tasks assigned to node {}", node_id);
+        return Ok(());
+    }
+
+    println!(
+        "{:<40} {:<16} {:<18} {:>6}",
+        "TASK", "DIFFICULTY", "TYPE", "INPUTS"
+    );
+    for task in &tasks {
+        println!(
+            "{:<40} {:<16} {:<18} {:>6}",
+            task.task_id,
+            task.difficulty.as_str_name(),
+            task.task_type.as_str_name(),
+            task.all_inputs().len()
+        );
+    }
+    println!(
+        "\n{} task(s); `nexus-cli start` resumes them before fetching new ones",
+        tasks.len()
+    );
+    Ok(())
+}
+
+/// Writes a systemd unit running this binary for the invoking user's node.
+fn install_service(
+    scope: Scope,
+    output: Option<PathBuf>,
+    force: bool,
+) -> Result<(), Box<dyn Error>> {
+    // Under sudo, the service still belongs to whoever ran sudo
+    let sudo_user = std::env::var("SUDO_USER")
+        .ok()
+        .filter(|user| !user.is_empty() && user != "root");
+    let (user, home) = match sudo_user {
+        Some(user) if scope == Scope::System => {
+            let passwd = std::fs::read_to_string("/etc/passwd")?;
+            let home = systemd::unit::home_of(&passwd, &user)
+                .ok_or_else(|| format!("Cannot find the home directory of {}", user))?;
+            (user, home)
+        }
+        _ => (
+            std::env::var("USER").unwrap_or_default(),
+            home::home_dir().ok_or("Home directory not found")?,
+        ),
+    };
+    if scope == Scope::System && (user.is_empty() || user == "root") {
+        return Err("Refusing to install a service running as root. Run this as the node's user (with sudo if needed), or use --user".into());
+    }
+
+    // The unit relies on the config file rather than flags
+    let config_path = home.join(".nexus").join("config.json");
+    let registered = Config::load_from_file(&config_path)
+        .map(|config| !config.node_id.is_empty())
+        .unwrap_or(false);
+    if !registered {
+        print_cmd_warn!(
+            "No node registered",
+            "The service reads its node ID from {}. Register a node before starting it.",
+            config_path.display()
+        );
+    }
+
+    let unit = ServiceUnit {
+        scope,
+        exec: std::env::current_exe()?.canonicalize()?,
+        user,
+        home: home.clone(),
+    };
+    let path = output.unwrap_or_else(|| ServiceUnit::default_path(scope, &home));
+    if path.exists() && !force {
+        return Err(format!(
+            "{} already exists; use --force to replace it",
+            path.display()
+        )
+        .into());
     }
+    let write = || -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, unit.render())
+    };
+    write().map_err(|e| {
+        let hint = match scope {
+            Scope::System => " (system units need sudo; use --user for your own systemd)",
+            Scope::User => "",
+        };
+        format!("Cannot write {}: {}{}", path.display(), e, hint)
+    })?;
+
+    print_cmd_info!("Installed service", "Wrote {}", path.display());
+    println!("Enable and start it with:");
+    println!("  {} daemon-reload", scope.systemctl());
+    println!(
+        "  {} enable --now {}",
+        scope.systemctl(),
+        consts::cli_consts::systemd::SERVICE_NAME
+    );
+    if scope == Scope::User {
+        println!("To keep it running after you log out:");
+        println!("  loginctl enable-linger");
+    }
+    Ok(())
+}
+
+/// Sends a command to a running node's control socket and prints the result.
+#[cfg(unix)]
+async fn ctl(socket: &Path, command: CtlCommand) -> Result<(), Box<dyn Error>> {
+    use crate::control::client::ControlClient;
+    use crate::control::protocol::methods;
+    use serde_json::{Value, json};
+
+    let mut client = ControlClient::connect(socket).await?;
+    let (method, params) = match command {
+        CtlCommand::Status => (methods::STATUS, Value::Null),
+        CtlCommand::Pause { suspend } => (methods::PAUSE, json!({ "suspend": suspend })),
+        CtlCommand::Resume => (methods::RESUME, Value::Null),
+        CtlCommand::Drain => (methods::DRAIN, Value::Null),
+        CtlCommand::Stop => (methods::STOP, Value::Null),
+        CtlCommand::SetDifficulty { difficulty } => {
+            let difficulty = if difficulty.trim().eq_ignore_ascii_case("adaptive") {
+                None
+            } else {
+                Some(parse_difficulty_or_exit(&difficulty).as_str_name())
+            };
+            (
+                methods::SET_MAX_DIFFICULTY,
+                json!({ "difficulty": difficulty }),
+            )
+        }
+        CtlCommand::SetThreads { threads } => (methods::SET_THREADS, json!({ "threads": threads })),
+        CtlCommand::Tail => {
+            client.call(methods::TAIL_EVENTS, Value::Null).await?;
+            while let Some(event) = client.next_event().await? {
+                println!("{}", event);
+            }
+            return Ok(());
+        }
+    };
+
+    let result = client.call(method, params).await?;
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn ctl(_socket: &Path, _command: CtlCommand) -> Result<(), Box<dyn Error>> {
+    Err("The control API is only available on Unix-like systems".into())
 }
 
 #[cfg(test)]
@@ -6,49 +6,89 @@ use crate::consts::cli_consts;
 use crate::logging::LogLevel;
 use crate::orchestrator::Orchestrator;
 use crate::orchestrator::error::OrchestratorError;
+use crate::prover::ProverResult;
+use crate::prover::verifier::{VerifiedProof, generate_proof_hash};
+use crate::task::Task;
+use chrono::Utc;
 use ed25519_dalek::{SigningKey, VerifyingKey};
 
-use std::{cmp::min, time::Duration};
+use std::cmp::min;
+use std::future::Future;
 
 /// Proof submission data grouped by business concern
+///
+/// Only built from a `ProverResult`, so everything submitted comes from verified proofs.
 #[derive(Debug, Clone)]
 pub struct ProofSubmission {
-    pub task_id: String,
-    pub proof_hash: String,
-    pub proof_bytes: Vec<u8>,
-    pub task_type: crate::nexus_orchestrator::TaskType,
-    pub individual_proof_hashes: Vec<String>,
-    pub proofs_bytes: Vec<Vec<u8>>, // new: full proofs array
+    task_id: String,
+    proof_hash: String,
+    proof_bytes: Vec<u8>,
+    task_type: crate::nexus_orchestrator::TaskType,
+    individual_proof_hashes: Vec<String>,
+    proofs_bytes: Vec<Vec<u8>>, // new: full proofs array
 }
 
 impl ProofSubmission {
-    pub fn new(
-        task_id: String,
-        proof_hash: String,
-        proof_bytes: Vec<u8>,
-        task_type: crate::nexus_orchestrator::TaskType,
-    ) -> Self {
-        Self {
-            task_id,
-            proof_hash,
+    /// What to submit for `task`, given the verified proofs of its inputs
+    pub fn new(task: &Task, result: &ProverResult) -> Result<Self, postcard::Error> {
+        let proofs_bytes: Vec<Vec<u8>> = result
+            .proofs()
+            .iter()
+            .map(VerifiedProof::to_bytes)
+            .collect::<Result<_, _>>()?;
+        let proof_bytes = proofs_bytes.first().cloned().unwrap_or_default();
+
+        // Individual hashes are only sent for ALL_PROOF_HASHES, full proofs only for PROOF_REQUIRED
+        let individual_proof_hashes = match task.task_type {
+            crate::nexus_orchestrator::TaskType::AllProofHashes => {
+                result.individual_proof_hashes().to_vec()
+            }
+            _ => Vec::new(),
+        };
+        let proofs_bytes = match task.task_type {
+            crate::nexus_orchestrator::TaskType::ProofRequired => proofs_bytes,
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            task_id: task.task_id.clone(),
+            proof_hash: result.combined_hash().to_string(),
             proof_bytes,
-            task_type,
-            individual_proof_hashes: Vec::new(),
-            proofs_bytes: Vec::new(),
-        }
+            task_type: task.task_type,
+            individual_proof_hashes,
+            proofs_bytes,
+        })
     }
 
-    pub fn with_individual_hashes(mut self, hashes: Vec<String>) -> Self {
-        self.individual_proof_hashes = hashes;
-        self
+    /// What is sent with the proof hash of one input of `task`: the full proof only
+    /// for tasks that need full proofs, nothing otherwise
+    fn input_proof_bytes(task: &Task, proof: &VerifiedProof) -> Result<Vec<u8>, postcard::Error> {
+        match task.task_type {
+            crate::nexus_orchestrator::TaskType::ProofRequired => proof.to_bytes(),
+            _ => Ok(Vec::new()),
+        }
     }
 
-    pub fn with_proofs(mut self, proofs: Vec<Vec<u8>>) -> Self {
-        self.proofs_bytes = proofs;
-        self
+    /// This submission as part of a batch; `None` for tasks that need full proofs
+    pub fn batched(&self) -> Option<crate::nexus_orchestrator::BatchedSubmission> {
+        (self.task_type != crate::nexus_orchestrator::TaskType::ProofRequired).then(|| {
+            crate::nexus_orchestrator::BatchedSubmission {
+                task_id: self.task_id.clone(),
+                task_type: self.task_type as i32,
+                proof_hash: self.proof_hash.clone(),
+                all_proof_hashes: self.individual_proof_hashes.clone(),
+            }
+        })
     }
 }
 
+/// The task whose inputs are submitted one by one, and the node submitting them
+pub struct InputContext<'a> {
+    pub node_id: &'a str,
+    pub task: &'a Task,
+    pub signing_key: &'a SigningKey,
+}
+
 /// Network client with built-in retry and request timing
 pub struct NetworkClient {
     error_handler: ErrorHandler,
@@ -72,42 +112,21 @@ impl NetworkClient {
         node_id: &str,
         verifying_key: VerifyingKey,
         max_difficulty: crate::nexus_orchestrator::TaskDifficulty,
+        capabilities: &crate::nexus_orchestrator::ClientCapabilities,
+        max_tasks: u32,
     ) -> Result<crate::orchestrator::client::ProofTaskResult, OrchestratorError> {
-        let mut attempts = 0;
-
-        loop {
-            // Make the request
-            // Default to Large; callers can adapt or override upstream
-            match orchestrator
-                .get_proof_task(node_id, verifying_key, max_difficulty)
-                .await
-            {
-                Ok(proof_task_result) => {
-                    self.request_timer.record_success();
-                    return Ok(proof_task_result);
-                }
-                Err(e) => {
-                    attempts += 1;
-
-                    // Get server-provided retry delay and record failure
-                    let server_retry_delay = e
-                        .get_retry_after_seconds()
-                        .map(|secs| Duration::from_secs(secs as u64))
-                        .map(|delay| {
-                            min(
-                                delay + cli_consts::rate_limiting::extra_retry_delay(),
-                                Duration::from_secs(60 * 10),
-                            )
-                        });
-                    self.request_timer.record_failure(server_retry_delay);
-
-                    // Check if we should retry
-                    if attempts >= self.max_retries || !self.error_handler.should_retry(&e) {
-                        return Err(e);
-                    }
-                }
-            }
-        }
+        self.with_retries(orchestrator, move || {
+            orchestrator.get_proof_task(
+                node_id,
+                verifying_key,
+                max_difficulty,
+                capabilities.clone(),
+                max_tasks,
+            )
+        })
+        .await
+        .map(|(proof_task_result, _)| proof_task_result)
+        .map_err(|(e, _)| e)
     }
 
     /// Submit a proof with automatic retry and server-controlled timing
@@ -116,55 +135,165 @@ impl NetworkClient {
         &mut self,
         orchestrator: &dyn Orchestrator,
         submission: ProofSubmission,
+        node_id: &str,
         signing_key: SigningKey,
         num_provers: usize,
     ) -> Result<u32, (OrchestratorError, u32)> {
+        let submission = &submission;
+        self.with_retries(orchestrator, move || {
+            orchestrator.submit_proof(
+                node_id,
+                &submission.task_id,
+                &submission.proof_hash,
+                submission.proof_bytes.clone(),
+                submission.proofs_bytes.clone(),
+                signing_key.clone(),
+                num_provers,
+                submission.task_type,
+                &submission.individual_proof_hashes,
+            )
+        })
+        .await
+        .map(|((), attempts)| attempts)
+    }
+
+    /// Submit several results in one signed request, with the same retries as a single proof.
+    /// Returns Ok((rejected, attempts)) once the batch is answered, or Err((error, attempts)).
+    pub async fn submit_proof_batch(
+        &mut self,
+        orchestrator: &dyn Orchestrator,
+        submissions: &[crate::nexus_orchestrator::BatchedSubmission],
+        node_id: &str,
+        signing_key: SigningKey,
+        num_provers: usize,
+    ) -> Result<(Vec<crate::nexus_orchestrator::RejectedSubmission>, u32), (OrchestratorError, u32)>
+    {
+        self.with_retries(orchestrator, move || {
+            orchestrator.submit_proof_batch(node_id, submissions, signing_key.clone(), num_provers)
+        })
+        .await
+    }
+
+    /// Submit the verified proof of input `index` of a task, with the same retries as a
+    /// whole proof. The full proof is only sent if the task needs full proofs.
+    /// Returns Ok(attempts) on success, Err((error, attempts)) on failure.
+    pub async fn submit_input_proof(
+        &mut self,
+        orchestrator: &dyn Orchestrator,
+        context: &InputContext<'_>,
+        index: usize,
+        proof: &VerifiedProof,
+    ) -> Result<u32, (OrchestratorError, u32)> {
+        let proof_hash = generate_proof_hash(proof);
+        let proof_bytes = ProofSubmission::input_proof_bytes(context.task, proof)
+            .map_err(|e| (OrchestratorError::from(e), 0))?;
+        let (proof_hash, proof_bytes) = (proof_hash.as_str(), &proof_bytes);
+        self.with_retries(orchestrator, move || {
+            orchestrator.submit_input_proof(
+                context.node_id,
+                &context.task.task_id,
+                index as u32,
+                proof_hash,
+                proof_bytes.clone(),
+                context.signing_key.clone(),
+            )
+        })
+        .await
+        .map(|((), attempts)| attempts)
+    }
+
+    /// Finalize a task whose inputs were submitted one by one, with the same retries
+    /// as a whole proof. Returns Ok(attempts) on success, Err((error, attempts)) on failure.
+    pub async fn finalize_task(
+        &mut self,
+        orchestrator: &dyn Orchestrator,
+        task: &Task,
+        result: &ProverResult,
+        node_id: &str,
+        signing_key: SigningKey,
+        num_provers: usize,
+    ) -> Result<u32, (OrchestratorError, u32)> {
+        self.with_retries(orchestrator, move || {
+            orchestrator.finalize_task(
+                node_id,
+                &task.task_id,
+                task.task_type,
+                result.combined_hash(),
+                result.individual_proof_hashes(),
+                signing_key.clone(),
+                num_provers,
+            )
+        })
+        .await
+        .map(|((), attempts)| attempts)
+    }
+
+    /// Make a request until it succeeds, fails for good, or runs out of retries.
+    /// Returns the response and the attempts made, or the last error and the attempts made.
+    async fn with_retries<T, F, Fut>(
+        &mut self,
+        orchestrator: &dyn Orchestrator,
+        mut request: F,
+    ) -> Result<(T, u32), (OrchestratorError, u32)>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, OrchestratorError>>,
+    {
         let mut attempts = 0;
 
         loop {
-            // Make the request
-            match orchestrator
-                .submit_proof(
-                    &submission.task_id,
-                    &submission.proof_hash,
-                    submission.proof_bytes.clone(),
-                    submission.proofs_bytes.clone(),
-                    signing_key.clone(),
-                    num_provers,
-                    submission.task_type,
-                    &submission.individual_proof_hashes,
-                )
-                .await
-            {
-                Ok(()) => {
-                    attempts += 1;
-                    self.request_timer.record_success();
-                    return Ok(attempts);
+            attempts += 1;
+            match request().await {
+                Ok(response) => {
+                    self.record_success(orchestrator);
+                    return Ok((response, attempts));
                 }
                 Err(e) => {
-                    attempts += 1;
-
-                    // Get server-provided retry delay and record failure
-                    let server_retry_delay = e
-                        .get_retry_after_seconds()
-                        .map(|secs| Duration::from_secs(secs as u64))
-                        .map(|delay| {
-                            min(
-                                delay + cli_consts::rate_limiting::extra_retry_delay(),
-                                Duration::from_secs(60 * 10),
-                            )
-                        });
-                    self.request_timer.record_failure(server_retry_delay);
+                    self.record_failure(&e);
 
                     // Check if we should retry
                     if attempts >= self.max_retries || !self.error_handler.should_retry(&e) {
                         return Err((e, attempts));
                     }
+                    self.wait_to_retry().await;
                 }
             }
         }
     }
 
+    /// Wait until the request timer allows the next attempt, which waits out the
+    /// server's Retry-After, a spent budget, or the backoff after a failure
+    async fn wait_to_retry(&mut self) {
+        while !self.request_timer.can_proceed() {
+            tokio::time::sleep(self.request_timer.time_until_next()).await;
+        }
+    }
+
+    /// Record the success, with the budget the response advertised: a spent one holds
+    /// the next request until its window resets, instead of waiting for a 429
+    fn record_success(&mut self, orchestrator: &dyn Orchestrator) {
+        self.request_timer.record_success();
+        if let Some(rate_limit) = orchestrator.take_rate_limit() {
+            self.request_timer.record_rate_limit(&rate_limit);
+        }
+    }
+
+    /// Get server-provided retry delay and budget, and record the failure
+    fn record_failure(&mut self, error: &OrchestratorError) {
+        let now = Utc::now();
+        let server_retry_delay = error.retry_after(now).map(|delay| {
+            min(
+                delay + cli_consts::rate_limiting::extra_retry_delay(),
+                cli_consts::rate_limiting::max_retry_delay(),
+            )
+        });
+        self.request_timer.record_failure(server_retry_delay);
+        // After the failure, so a spent budget can extend its delay to the window reset
+        if let Some(rate_limit) = error.rate_limit(now) {
+            self.request_timer.record_rate_limit(&rate_limit);
+        }
+    }
+
     /// Get error classification for logging
     pub fn classify_error(&self, error: &OrchestratorError) -> LogLevel {
         self.error_handler.classify_error(error)
@@ -1,7 +1,7 @@
 //! Centralized error handling and classification
 
 use crate::logging::LogLevel;
-use crate::orchestrator::error::OrchestratorError;
+use crate::orchestrator::error::{OrchestratorError, ServerError};
 
 /// Centralized error handler for all network operations
 #[derive(Debug, Clone)]
@@ -14,6 +14,16 @@ impl ErrorHandler {
 
     /// Classify error and determine appropriate log level
     pub fn classify_error(&self, error: &OrchestratorError) -> LogLevel {
+        // The orchestrator's own error code says more than the status it came with
+        match error.server_error() {
+            Some(server_error) if server_error.is_permanent() => return LogLevel::Error,
+            Some(ServerError::RateLimited | ServerError::NoTasksAvailable) => {
+                return LogLevel::Debug;
+            }
+            Some(ServerError::InvalidSignature) => return LogLevel::Error,
+            _ => {}
+        }
+
         match error {
             // Rate limiting - low priority
             OrchestratorError::Http { status, .. } if *status == 429 => LogLevel::Debug,
@@ -30,6 +40,9 @@ impl ErrorHandler {
             // Network issues - usually temporary
             OrchestratorError::Reqwest(_) => LogLevel::Warn,
 
+            // Tasks not issued by the pinned orchestrator - possible tampering
+            OrchestratorError::UntrustedTask(_) => LogLevel::Error,
+
             // Other errors
             _ => LogLevel::Warn,
         }
@@ -41,9 +54,18 @@ impl ErrorHandler {
             // Retry on network/connection errors
             OrchestratorError::Reqwest(_) => true,
             OrchestratorError::Decode(_) => true,
+            OrchestratorError::Incomplete(_) => true,
+            OrchestratorError::Recorded(_) => true,
+            OrchestratorError::ReplayExhausted(_) => false,
+            OrchestratorError::UntrustedTask(_) => false,
+            OrchestratorError::Serialization(_) => false,
 
-            // HTTP errors - check status code
+            // HTTP errors - the orchestrator's error code if it sent one,
+            // else the status code
             OrchestratorError::Http { status, .. } => {
+                if let Some(server_error) = error.server_error() {
+                    return server_error.is_retryable();
+                }
                 match *status {
                     // Don't retry client errors (except rate limiting)
                     429 => false,      // Rate limiting - don't retry
@@ -57,3 +79,55 @@ impl ErrorHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn http(status: u16, message: &str) -> OrchestratorError {
+        OrchestratorError::Http {
+            status,
+            message: message.to_string(),
+            headers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_server_error_codes_drive_retries() {
+        let handler = ErrorHandler::new();
+        let unknown_node = http(
+            404,
+            r#"{"name":"NodeNotFound","message":"no such node","httpCode":404}"#,
+        );
+        assert!(!handler.should_retry(&unknown_node));
+        assert_eq!(handler.classify_error(&unknown_node), LogLevel::Error);
+
+        let rate_limited = http(
+            400,
+            r#"{"name":"RateLimited","message":"slow down","httpCode":400}"#,
+        );
+        assert!(!handler.should_retry(&rate_limited));
+        assert_eq!(handler.classify_error(&rate_limited), LogLevel::Debug);
+
+        let internal = http(
+            400,
+            r#"{"name":"InternalServerError","message":"oops","httpCode":400}"#,
+        );
+        assert!(handler.should_retry(&internal));
+
+        // Unknown codes are retried whatever their status, never taken as permanent
+        for status in [400, 404, 429] {
+            let unknown = http(
+                status,
+                &format!(r#"{{"name":"SomethingNew","message":"?","httpCode":{status}}}"#),
+            );
+            assert!(handler.should_retry(&unknown), "{}", status);
+            assert_ne!(handler.classify_error(&unknown), LogLevel::Error);
+        }
+
+        // Plain bodies fall back to the status code
+        assert!(handler.should_retry(&http(503, "Service Unavailable")));
+        assert!(!handler.should_retry(&http(429, "Too Many Requests")));
+    }
+}
@@ -2,5 +2,5 @@ pub mod client;
 pub mod error_handler;
 pub mod request_timer;
 
-pub use client::{NetworkClient, ProofSubmission};
+pub use client::{InputContext, NetworkClient, ProofSubmission};
 pub use request_timer::{RequestTimer, RequestTimerConfig};
@@ -2,8 +2,13 @@
 //!
 //! This module replaces the separate backoff and rate limiter components with a
 //! unified approach that prioritizes server-provided retry delays over local timing strategies.
+//! Without a delay from the server, failures back off exponentially with jitter.
 
-use std::time::{Duration, Instant};
+use crate::consts::cli_consts::rate_limiting;
+use crate::orchestrator::error::RateLimit;
+use rand::Rng;
+use std::time::Duration;
+use tokio::time::Instant;
 
 /// Configuration for request timing behavior
 #[derive(Debug, Clone)]
@@ -14,8 +19,10 @@ pub struct RequestTimerConfig {
     pub max_requests: Option<u32>,
     /// Time window for max_requests
     pub time_window: Option<Duration>,
-    /// Default retry delay when server doesn't provide one
+    /// Default retry delay when server doesn't provide one, doubled on each further failure
     pub default_retry_delay: Duration,
+    /// Longest delay backing off can reach
+    pub max_retry_delay: Duration,
 }
 
 impl RequestTimerConfig {
@@ -26,6 +33,7 @@ impl RequestTimerConfig {
             max_requests: None,
             time_window: None,
             default_retry_delay: Duration::from_secs(1),
+            max_retry_delay: rate_limiting::max_retry_delay(),
         }
     }
 
@@ -36,6 +44,7 @@ impl RequestTimerConfig {
             max_requests: Some(max_requests),
             time_window: Some(time_window),
             default_retry_delay: Duration::from_secs(1),
+            max_retry_delay: rate_limiting::max_retry_delay(),
         }
     }
 
@@ -51,6 +60,16 @@ impl RequestTimerConfig {
             max_requests: Some(max_requests),
             time_window: Some(time_window),
             default_retry_delay,
+            max_retry_delay: rate_limiting::max_retry_delay(),
+        }
+    }
+
+    /// Take on the budget the server advertised in place of the local one.
+    /// Without a window there is nothing to count requests against, so it is ignored.
+    pub fn adopt(&mut self, rate_limit: &RateLimit) {
+        if let (Some(limit), Some(window)) = (rate_limit.limit, rate_limit.window) {
+            self.max_requests = Some(limit.max(1));
+            self.time_window = Some(window);
         }
     }
 }
@@ -63,6 +82,8 @@ pub struct RequestTimer {
     last_request_time: Option<Instant>,
     request_times: Vec<Instant>,
     server_retry_until: Option<Instant>,
+    /// Failures since the last success, which set how far to back off
+    consecutive_failures: u32,
 }
 
 impl RequestTimer {
@@ -72,9 +93,15 @@ impl RequestTimer {
             last_request_time: None,
             request_times: Vec::new(),
             server_retry_until: None,
+            consecutive_failures: 0,
         }
     }
 
+    /// The timing configuration, including any budget adopted from the server
+    pub fn config(&self) -> &RequestTimerConfig {
+        &self.config
+    }
+
     /// Check if a new request can proceed
     /// Server retry delay takes priority over all other constraints
     pub fn can_proceed(&mut self) -> bool {
@@ -116,6 +143,7 @@ impl RequestTimer {
     pub fn record_success(&mut self) {
         let now = Instant::now();
         self.last_request_time = Some(now);
+        self.consecutive_failures = 0;
         if self.config.max_requests.is_some() {
             self.request_times.push(now);
         }
@@ -132,6 +160,7 @@ impl RequestTimer {
     pub fn record_failure(&mut self, server_retry_delay: Option<Duration>) {
         let now = Instant::now();
         self.last_request_time = Some(now);
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
 
         if self.config.max_requests.is_some() {
             self.request_times.push(now);
@@ -141,11 +170,37 @@ impl RequestTimer {
         if let Some(delay) = server_retry_delay {
             self.server_retry_until = Some(now + delay);
         } else {
-            // Use default retry delay if no server delay provided
-            self.server_retry_until = Some(now + self.config.default_retry_delay);
+            // Back off from the default retry delay if no server delay provided
+            self.server_retry_until = Some(now + self.backoff_delay());
+        }
+    }
+
+    /// Record the rate-limit budget a response advertised.
+    /// An exhausted budget holds requests until its window resets.
+    pub fn record_rate_limit(&mut self, rate_limit: &RateLimit) {
+        self.config.adopt(rate_limit);
+
+        if let (true, Some(reset)) = (rate_limit.is_exhausted(), rate_limit.reset) {
+            let reset_at = Instant::now() + reset.min(self.config.max_retry_delay);
+            if self.server_retry_until.is_none_or(|until| until < reset_at) {
+                self.server_retry_until = Some(reset_at);
+            }
         }
     }
 
+    /// The default delay doubled for each failure after the first, up to the maximum,
+    /// less up to a quarter at random so clients that failed together don't retry together
+    fn backoff_delay(&self) -> Duration {
+        let doublings = self.consecutive_failures.saturating_sub(1).min(16);
+        let delay = self
+            .config
+            .default_retry_delay
+            .saturating_mul(1 << doublings)
+            .min(self.config.max_retry_delay);
+        let jitter = rand::thread_rng().gen_range(0.0..=0.25);
+        delay.mul_f64(1.0 - jitter)
+    }
+
     /// Get time until next request is allowed
     /// Server retry delay takes priority over all other constraints
     pub fn time_until_next(&mut self) -> Duration {
@@ -195,9 +250,10 @@ impl RequestTimer {
 mod tests {
     use super::*;
     use std::time::Duration;
+    use tokio::time::advance;
 
-    #[test]
-    fn test_server_retry_overrides_other_constraints() {
+    #[tokio::test(start_paused = true)]
+    async fn test_server_retry_overrides_other_constraints() {
         let config = RequestTimerConfig::combined(
             Duration::from_millis(100), // min interval
             3,                          // max requests
@@ -217,25 +273,54 @@ mod tests {
 
         // Should not be able to proceed due to server retry delay
         assert!(!timer.can_proceed());
+        assert_eq!(timer.time_until_next(), server_delay);
 
-        // Time until next should be the server delay (approximately)
-        let remaining = timer.time_until_next();
-        assert!(remaining.as_millis() > 4900); // Allow some timing tolerance
-        assert!(remaining.as_millis() <= 5000);
+        advance(server_delay).await;
+        assert!(timer.can_proceed());
     }
 
-    #[test]
-    fn test_default_retry_delay_when_no_server_delay() {
+    #[tokio::test(start_paused = true)]
+    async fn test_default_retry_delay_when_no_server_delay() {
         let config = RequestTimerConfig::_interval(Duration::from_millis(10));
         let mut timer = RequestTimer::new(config);
 
         // Record failure without server retry delay
         timer.record_failure(None);
 
-        // Should use default retry delay
+        // Should use default retry delay, less some jitter
         assert!(!timer.can_proceed());
         let remaining = timer.time_until_next();
-        assert!(remaining.as_millis() > 900); // Close to 1 second default
+        assert!(remaining >= Duration::from_millis(750));
+        assert!(remaining <= Duration::from_secs(1));
+
+        advance(remaining).await;
+        assert!(timer.can_proceed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_backoff_doubles_up_to_the_maximum() {
+        let mut config = RequestTimerConfig::_interval(Duration::ZERO);
+        config.max_retry_delay = Duration::from_secs(10);
+        let mut timer = RequestTimer::new(config);
+
+        for expected in [1, 2, 4, 8, 10, 10] {
+            timer.record_failure(None);
+            let expected = Duration::from_secs(expected);
+            let remaining = timer.time_until_next();
+            assert!(remaining <= expected, "{:?} > {:?}", remaining, expected);
+            assert!(
+                remaining >= expected.mul_f64(0.75),
+                "{:?} too short",
+                remaining
+            );
+            advance(remaining).await;
+        }
+
+        // A success starts the backoff over
+        timer.record_success();
+        advance(Duration::from_secs(1)).await;
+        timer.record_failure(None);
+        assert!(timer.time_until_next() <= Duration::from_secs(1));
     }
 
     #[test]
@@ -262,4 +347,42 @@ mod tests {
         // Immediate second request should be blocked by min interval
         assert!(!timer.can_proceed());
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_server_rate_limit_is_adopted() {
+        let config = RequestTimerConfig::_requests_per_window(1000, Duration::from_millis(10));
+        let mut timer = RequestTimer::new(config);
+
+        // Budget left: the window is adopted, and requests go on
+        timer.record_rate_limit(&RateLimit {
+            limit: Some(2),
+            remaining: Some(1),
+            reset: Some(Duration::from_secs(30)),
+            window: Some(Duration::from_secs(60)),
+        });
+        assert_eq!(timer.config().max_requests, Some(2));
+        assert_eq!(timer.config().time_window, Some(Duration::from_secs(60)));
+        assert!(timer.can_proceed());
+
+        timer.record_failure(Some(Duration::ZERO));
+        timer.record_failure(Some(Duration::ZERO));
+        assert!(!timer.can_proceed());
+        assert_eq!(timer.time_until_next(), Duration::from_secs(60));
+
+        // Budget spent: wait for the window to reset, even past a shorter retry delay
+        timer.record_failure(Some(Duration::from_secs(5)));
+        timer.record_rate_limit(&RateLimit {
+            limit: Some(2),
+            remaining: Some(0),
+            reset: Some(Duration::from_secs(20)),
+            window: None,
+        });
+        assert_eq!(timer.time_until_next(), Duration::from_secs(20));
+
+        // Then the adopted window still applies to the requests already made
+        advance(Duration::from_secs(20)).await;
+        assert!(!timer.can_proceed());
+        advance(Duration::from_secs(41)).await;
+        assert!(timer.can_proceed());
+    }
 }
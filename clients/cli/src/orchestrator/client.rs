@@ -3,19 +3,29 @@
 //! A client for the Nexus Orchestrator, allowing for proof task retrieval and submission.
 
 use crate::environment::Environment;
+use crate::hex::to_hex;
 use crate::nexus_orchestrator::{
-    GetProofTaskRequest, GetProofTaskResponse, NodeType, RegisterNodeRequest, RegisterNodeResponse,
-    RegisterUserRequest, SubmitProofRequest, UserResponse,
+    AbandonReason, AbandonTaskRequest, BatchLimits, BatchedSubmission, GetProofTaskRequest,
+    GetProofTaskResponse, GetTaskProgressResponse, GetTasksResponse, InputProof, NodeTelemetry,
+    NodeType, RegisterNodeRequest, RegisterNodeResponse, RegisterUserRequest, RejectedSubmission,
+    SubmitInputProofRequest, SubmitProofBatchRequest, SubmitProofBatchResponse, SubmitProofRequest,
+    UserResponse,
 };
 use crate::orchestrator::Orchestrator;
-use crate::orchestrator::error::OrchestratorError;
+use crate::orchestrator::error::{OrchestratorError, RateLimit};
+use crate::orchestrator::recording::{Exchange, Recorder};
+use crate::orchestrator::signing::{
+    AbandonmentSignature, BatchSignature, InputSignature, SignatureVersion, SubmissionClaim,
+    SubmissionSignature,
+};
 use crate::system::{estimate_peak_gflops, get_memory_info};
-use crate::task::Task;
-use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use crate::task::{Task, TaskSignatureError, TaskTrust};
+use ed25519_dalek::{SigningKey, VerifyingKey};
 use prost::Message;
-use reqwest::{Client, ClientBuilder, Response};
-use std::sync::OnceLock;
-use std::time::Duration;
+use reqwest::{Client, ClientBuilder, Method};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 /// Proof payload returned by `select_proof_payload`.
 ///
@@ -32,6 +42,15 @@ pub(crate) type ProofPayload = (Vec<u8>, Vec<Vec<u8>>, Vec<String>);
 pub struct ProofTaskResult {
     pub task: Task,
     pub actual_difficulty: crate::nexus_orchestrator::TaskDifficulty,
+    /// Further tasks returned with `task`, when more than one was asked for
+    pub additional_tasks: Vec<Task>,
+    /// Further tasks left out because their orchestrator signature didn't check out
+    pub rejected: Vec<TaskSignatureError>,
+    /// Batching the orchestrator supports; all zero if it doesn't
+    pub batch_limits: BatchLimits,
+    /// Fewest inputs a task needs to be submitted input by input, if the orchestrator
+    /// supports that at all
+    pub incremental_min_inputs: Option<u32>,
 }
 
 impl std::fmt::Display for ProofTaskResult {
@@ -44,6 +63,19 @@ impl std::fmt::Display for ProofTaskResult {
     }
 }
 
+/// One page of the tasks assigned to a node
+#[derive(Debug, Clone, Default)]
+pub struct TaskPage {
+    pub tasks: Vec<Task>,
+    /// Tasks left out because their orchestrator signature didn't check out
+    pub rejected: Vec<TaskSignatureError>,
+    /// Cursor for the next page; empty on the last one
+    pub next_cursor: String,
+    /// Fewest inputs a task needs to be submitted input by input, if the orchestrator
+    /// supports that at all
+    pub incremental_min_inputs: Option<u32>,
+}
+
 // Build timestamp in milliseconds since epoch
 static BUILD_TIMESTAMP: &str = match option_env!("BUILD_TIMESTAMP") {
     Some(timestamp) => timestamp,
@@ -63,6 +95,34 @@ pub(crate) static COUNTRY_CODE: OnceLock<String> = OnceLock::new();
 pub struct OrchestratorClient {
     client: Client,
     environment: Environment,
+    /// Where requests and responses are recorded, with `start --record`
+    recorder: Option<Arc<Recorder>>,
+    /// Submission signature scheme, as negotiated with the last task response.
+    /// Shared between clones, so the submitter follows what the fetcher learned.
+    signature_version: Arc<Mutex<SignatureVersion>>,
+    /// Budget advertised by the last successful response, until the caller adopts it
+    advertised: AdvertisedBudget,
+}
+
+/// The rate-limit budget of the last successful response, until taken. Clones start
+/// empty, so a budget goes to the request timer of whoever made the request.
+#[derive(Debug, Default)]
+pub(crate) struct AdvertisedBudget(Mutex<Option<RateLimit>>);
+
+impl AdvertisedBudget {
+    pub(crate) fn set(&self, rate_limit: Option<RateLimit>) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = rate_limit;
+    }
+
+    pub(crate) fn take(&self) -> Option<RateLimit> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+impl Clone for AdvertisedBudget {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
 }
 
 impl OrchestratorClient {
@@ -74,9 +134,18 @@ impl OrchestratorClient {
                 .build()
                 .expect("Failed to create HTTP client"),
             environment,
+            recorder: None,
+            signature_version: Arc::default(),
+            advertised: AdvertisedBudget::default(),
         }
     }
 
+    /// Record every request and response made through this client
+    pub fn with_recorder(mut self, recorder: Arc<Recorder>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
     /// Public accessor for privacy-preserving country code (cached during run)
     #[allow(dead_code)]
     pub async fn country(&self) -> String {
@@ -137,87 +206,124 @@ impl OrchestratorClient {
         }
     }
 
-    async fn handle_response_status(response: Response) -> Result<Response, OrchestratorError> {
-        if !response.status().is_success() {
-            return Err(OrchestratorError::from_response(response).await);
+    /// Send a request and return the response body, recording the exchange if asked to.
+    /// The rate-limit budget a successful response advertises is kept for the caller.
+    async fn send(
+        &self,
+        call: &'static str,
+        method: Method,
+        endpoint: &str,
+        body: Option<Vec<u8>>,
+    ) -> Result<Vec<u8>, OrchestratorError> {
+        let url = self.build_url(endpoint);
+        let mut request = self
+            .client
+            .request(method.clone(), &url)
+            .header("User-Agent", USER_AGENT)
+            .header("X-Build-Timestamp", BUILD_TIMESTAMP);
+        let recorded_request = match (&body, &self.recorder) {
+            (Some(body), Some(_)) => to_hex(body),
+            _ => String::new(),
+        };
+        if let Some(body) = body {
+            request = request
+                .header("Content-Type", "application/octet-stream")
+                .body(body);
+        }
+
+        let started_at = chrono::Utc::now();
+        let started = Instant::now();
+        let result = async {
+            let response = request.send().await?;
+            let status = response.status().as_u16();
+            let headers = header_map(response.headers());
+            let bytes = response.bytes().await?;
+            Ok::<_, reqwest::Error>((status, headers, bytes.to_vec()))
+        }
+        .await;
+
+        if let Some(recorder) = &self.recorder {
+            let (status, headers, response, error) = match &result {
+                Ok((status, headers, bytes)) => (
+                    Some(*status),
+                    headers.clone().into_iter().collect(),
+                    to_hex(bytes),
+                    None,
+                ),
+                Err(e) => (None, Default::default(), String::new(), Some(e.to_string())),
+            };
+            // A recording that can't be written must not fail the request itself
+            let _ = recorder.record(Exchange {
+                call: call.to_string(),
+                method: method.to_string(),
+                endpoint: endpoint.to_string(),
+                request: recorded_request,
+                status,
+                headers,
+                response,
+                error,
+                started_at,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+
+        let (status, headers, bytes) = result?;
+        if !(200..300).contains(&status) {
+            return Err(OrchestratorError::from_parts(status, headers, &bytes));
         }
-        Ok(response)
+        self.advertised
+            .set(RateLimit::from_headers(&headers, chrono::Utc::now()));
+        Ok(bytes)
     }
 
     async fn get_request<T: Message + Default>(
         &self,
+        call: &'static str,
         endpoint: &str,
     ) -> Result<T, OrchestratorError> {
-        let url = self.build_url(endpoint);
-        let response = self
-            .client
-            .get(&url)
-            .header("User-Agent", USER_AGENT)
-            .header("X-Build-Timestamp", BUILD_TIMESTAMP)
-            .send()
-            .await?;
-
-        let response = Self::handle_response_status(response).await?;
-        let response_bytes = response.bytes().await?;
+        let response_bytes = self.send(call, Method::GET, endpoint, None).await?;
         Self::decode_response(&response_bytes)
     }
 
     async fn post_request<T: Message + Default>(
         &self,
+        call: &'static str,
         endpoint: &str,
         body: Vec<u8>,
     ) -> Result<T, OrchestratorError> {
-        let url = self.build_url(endpoint);
-        let response = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/octet-stream")
-            .header("User-Agent", USER_AGENT)
-            .header("X-Build-Timestamp", BUILD_TIMESTAMP)
-            .body(body)
-            .send()
-            .await?;
-
-        let response = Self::handle_response_status(response).await?;
-        let response_bytes = response.bytes().await?;
+        let response_bytes = self.send(call, Method::POST, endpoint, Some(body)).await?;
         Self::decode_response(&response_bytes)
     }
 
     async fn post_request_no_response(
         &self,
+        call: &'static str,
         endpoint: &str,
         body: Vec<u8>,
     ) -> Result<(), OrchestratorError> {
-        let url = self.build_url(endpoint);
-        let response = self
-            .client
-            .post(&url)
-            .header("Content-Type", "application/octet-stream")
-            .header("User-Agent", USER_AGENT)
-            .header("X-Build-Timestamp", BUILD_TIMESTAMP)
-            .body(body)
-            .send()
-            .await?;
-
-        Self::handle_response_status(response).await?;
+        self.send(call, Method::POST, endpoint, Some(body)).await?;
         Ok(())
     }
 
-    fn create_signature(
-        &self,
-        signing_key: &SigningKey,
-        task_id: &str,
-        proof_hash: &str,
-    ) -> (Vec<u8>, Vec<u8>) {
-        let signature_version = 0;
-        let msg = format!("{} | {} | {}", signature_version, task_id, proof_hash);
-        let signature = signing_key.sign(msg.as_bytes());
-        let verifying_key: VerifyingKey = signing_key.verifying_key();
-
-        (
-            signature.to_bytes().to_vec(),
-            verifying_key.to_bytes().to_vec(),
-        )
+    /// Scheme to sign submissions with, as last negotiated
+    fn signature_version(&self) -> SignatureVersion {
+        *self.signature_version.lock().unwrap()
+    }
+
+    /// Telemetry sent with submissions
+    async fn node_telemetry(&self, num_provers: usize) -> NodeTelemetry {
+        let (program_memory, total_memory) = get_memory_info();
+        let flops = estimate_peak_gflops(num_provers);
+
+        // Detect country for network optimization (privacy-preserving: only country code, no precise location)
+        let location = self.get_country().await;
+        NodeTelemetry {
+            flops_per_sec: Some(flops as i32),
+            memory_used: Some(program_memory),
+            memory_capacity: Some(total_memory),
+            // Country code for network routing optimization (privacy-preserving)
+            location: Some(location),
+        }
     }
 
     /// Detects the user's country for network optimization purposes.
@@ -294,6 +400,73 @@ impl OrchestratorClient {
     }
 }
 
+/// Response headers by lowercase name
+fn header_map(headers: &reqwest::header::HeaderMap) -> HashMap<String, String> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = value.to_str().ok()?;
+            Some((name.to_string().to_lowercase(), value.to_string()))
+        })
+        .collect()
+}
+
+/// The tasks signed by one of the keys `trust` pins, and why the others were left out
+fn verified_tasks(
+    tasks: &[crate::nexus_orchestrator::Task],
+    trust: &TaskTrust,
+) -> (Vec<Task>, Vec<TaskSignatureError>) {
+    let mut verified = Vec::new();
+    let mut rejected = Vec::new();
+    for task in tasks {
+        match Task::verify_signature(task, trust) {
+            Ok(()) => verified.push(Task::from(task)),
+            Err(e) => rejected.push(e),
+        }
+    }
+    (verified, rejected)
+}
+
+/// Turn a `GetProofTaskResponse` into the task and the difficulty it was assigned at.
+/// The response is rejected if its task is not signed by `environment`'s orchestrator;
+/// further tasks that aren't are only left out.
+pub(crate) fn proof_task_result(
+    response: &GetProofTaskResponse,
+    environment: &Environment,
+) -> Result<ProofTaskResult, OrchestratorError> {
+    let task = Task::from_signed(response, environment)?;
+    let actual_difficulty = task.difficulty;
+    let (additional_tasks, rejected) =
+        verified_tasks(&response.additional_tasks, &environment.task_trust());
+    Ok(ProofTaskResult {
+        task,
+        actual_difficulty,
+        additional_tasks,
+        rejected,
+        batch_limits: response.batch_limits.unwrap_or_default(),
+        incremental_min_inputs: response
+            .incremental_submission
+            .map(|incremental| incremental.min_inputs),
+    })
+}
+
+/// Turn a `GetTasksResponse` into a page of tasks, leaving out any task not signed by
+/// `environment`'s orchestrator
+pub(crate) fn task_page(
+    response: &GetTasksResponse,
+    environment: &Environment,
+) -> Result<TaskPage, OrchestratorError> {
+    let (tasks, rejected) = verified_tasks(&response.tasks, &environment.task_trust());
+    Ok(TaskPage {
+        tasks,
+        rejected,
+        next_cursor: response.next_cursor.clone(),
+        incremental_min_inputs: response
+            .incremental_submission
+            .map(|incremental| incremental.min_inputs),
+    })
+}
+
 /// Detect country code once globally without requiring a client instance.
 /// This ensures callers don't need to sequence a warm-up before using the result.
 pub(crate) async fn detect_country_once() -> String {
@@ -348,11 +521,15 @@ impl Orchestrator for OrchestratorClient {
         &self.environment
     }
 
+    fn take_rate_limit(&self) -> Option<RateLimit> {
+        self.advertised.take()
+    }
+
     /// Get the user ID associated with a wallet address.
     async fn get_user(&self, wallet_address: &str) -> Result<String, OrchestratorError> {
         let wallet_path = urlencoding::encode(wallet_address).into_owned();
         let endpoint = format!("v3/users/{}", wallet_path);
-        let user_response: UserResponse = self.get_request(&endpoint).await?;
+        let user_response: UserResponse = self.get_request("get_user", &endpoint).await?;
         Ok(user_response.user_id)
     }
 
@@ -367,7 +544,7 @@ impl Orchestrator for OrchestratorClient {
             wallet_address: wallet_address.to_string(),
         };
         let request_bytes = Self::encode_request(&request);
-        self.post_request_no_response("v3/users", request_bytes)
+        self.post_request_no_response("register_user", "v3/users", request_bytes)
             .await
     }
 
@@ -378,7 +555,9 @@ impl Orchestrator for OrchestratorClient {
             user_id: user_id.to_string(),
         };
         let request_bytes = Self::encode_request(&request);
-        let response: RegisterNodeResponse = self.post_request("v3/nodes", request_bytes).await?;
+        let response: RegisterNodeResponse = self
+            .post_request("register_node", "v3/nodes", request_bytes)
+            .await?;
         Ok(response.node_id)
     }
 
@@ -386,7 +565,7 @@ impl Orchestrator for OrchestratorClient {
     async fn get_node(&self, node_id: &str) -> Result<String, OrchestratorError> {
         let endpoint = format!("v3/nodes/{}", node_id);
         let node_response: crate::nexus_orchestrator::GetNodeResponse =
-            self.get_request(&endpoint).await?;
+            self.get_request("get_node", &endpoint).await?;
         Ok(node_response.wallet_address)
     }
 
@@ -395,27 +574,46 @@ impl Orchestrator for OrchestratorClient {
         node_id: &str,
         verifying_key: VerifyingKey,
         max_difficulty: crate::nexus_orchestrator::TaskDifficulty,
+        capabilities: crate::nexus_orchestrator::ClientCapabilities,
+        max_tasks: u32,
     ) -> Result<ProofTaskResult, OrchestratorError> {
         let request = GetProofTaskRequest {
             node_id: node_id.to_string(),
             node_type: NodeType::CliProver as i32,
             ed25519_public_key: verifying_key.to_bytes().to_vec(),
             max_difficulty: max_difficulty as i32,
+            capabilities: Some(capabilities),
+            max_tasks,
         };
         let request_bytes = Self::encode_request(&request);
-        let response: GetProofTaskResponse = self.post_request("v3/tasks", request_bytes).await?;
-
-        let task = Task::from(&response);
-        let actual_difficulty = task.difficulty;
+        let response: GetProofTaskResponse = self
+            .post_request("get_proof_task", "v3/tasks", request_bytes)
+            .await?;
+        *self.signature_version.lock().unwrap() =
+            SignatureVersion::negotiate(&response.submission_signature_versions);
+        proof_task_result(&response, &self.environment)
+    }
 
-        Ok(ProofTaskResult {
-            task,
-            actual_difficulty,
-        })
+    async fn get_tasks(
+        &self,
+        node_id: &str,
+        next_cursor: &str,
+    ) -> Result<TaskPage, OrchestratorError> {
+        let mut endpoint = format!("v3/tasks/{}", urlencoding::encode(node_id));
+        if !next_cursor.is_empty() {
+            endpoint = format!(
+                "{}?next_cursor={}",
+                endpoint,
+                urlencoding::encode(next_cursor)
+            );
+        }
+        let response: GetTasksResponse = self.get_request("get_tasks", &endpoint).await?;
+        task_page(&response, &self.environment)
     }
 
     async fn submit_proof(
         &self,
+        node_id: &str,
         task_id: &str,
         proof_hash: &str,
         proof: Vec<u8>,
@@ -425,12 +623,7 @@ impl Orchestrator for OrchestratorClient {
         task_type: crate::nexus_orchestrator::TaskType,
         individual_proof_hashes: &[String],
     ) -> Result<(), OrchestratorError> {
-        let (program_memory, total_memory) = get_memory_info();
-        let flops = estimate_peak_gflops(num_provers);
-        let (signature, public_key) = self.create_signature(&signing_key, task_id, proof_hash);
-
-        // Detect country for network optimization (privacy-preserving: only country code, no precise location)
-        let location = self.get_country().await;
+        let node_telemetry = self.node_telemetry(num_provers).await;
         // Handle different task types
         let (proof_to_send, proofs_to_send, all_proof_hashes_to_send) =
             OrchestratorClient::select_proof_payload(
@@ -439,6 +632,19 @@ impl Orchestrator for OrchestratorClient {
                 proofs,
                 individual_proof_hashes,
             );
+        // Sign exactly what is sent
+        let signed = SubmissionSignature::sign(
+            &signing_key,
+            self.signature_version(),
+            &SubmissionClaim {
+                task_id,
+                node_id,
+                task_type,
+                proof_hash,
+                all_proof_hashes: &all_proof_hashes_to_send,
+                incremental: false,
+            },
+        );
 
         let request = SubmitProofRequest {
             task_id: task_id.to_string(),
@@ -446,19 +652,141 @@ impl Orchestrator for OrchestratorClient {
             proof_hash: proof_hash.to_string(),
             proof: proof_to_send,
             proofs: proofs_to_send,
-            node_telemetry: Some(crate::nexus_orchestrator::NodeTelemetry {
-                flops_per_sec: Some(flops as i32),
-                memory_used: Some(program_memory),
-                memory_capacity: Some(total_memory),
-                // Country code for network routing optimization (privacy-preserving)
-                location: Some(location),
-            }),
-            ed25519_public_key: public_key,
-            signature,
+            node_telemetry: Some(node_telemetry),
+            ed25519_public_key: signed.public_key,
+            signature: signed.signature,
             all_proof_hashes: all_proof_hashes_to_send,
+            signature_version: signed.version as u32,
+            attestation: signed.attestation,
+            incremental: false,
+        };
+        let request_bytes = Self::encode_request(&request);
+        self.post_request_no_response("submit_proof", "v3/tasks/submit", request_bytes)
+            .await
+    }
+
+    async fn submit_proof_batch(
+        &self,
+        node_id: &str,
+        submissions: &[BatchedSubmission],
+        signing_key: SigningKey,
+        num_provers: usize,
+    ) -> Result<Vec<RejectedSubmission>, OrchestratorError> {
+        let node_telemetry = self.node_telemetry(num_provers).await;
+        // Sign exactly what is sent
+        let signed = BatchSignature::sign(&signing_key, node_id, submissions);
+        let request = SubmitProofBatchRequest {
+            node_type: NodeType::CliProver as i32,
+            batch: signed.batch,
+            ed25519_public_key: signed.public_key,
+            signature: signed.signature,
+            node_telemetry: Some(node_telemetry),
+        };
+        let request_bytes = Self::encode_request(&request);
+        let response: SubmitProofBatchResponse = self
+            .post_request("submit_proof_batch", "v3/tasks/submit/batch", request_bytes)
+            .await?;
+        Ok(response.rejected)
+    }
+
+    async fn submit_input_proof(
+        &self,
+        node_id: &str,
+        task_id: &str,
+        input_index: u32,
+        proof_hash: &str,
+        proof: Vec<u8>,
+        signing_key: SigningKey,
+    ) -> Result<(), OrchestratorError> {
+        // Sign exactly what is sent
+        let signed = InputSignature::sign(&signing_key, node_id, task_id, input_index, proof_hash);
+        let request = SubmitInputProofRequest {
+            node_type: NodeType::CliProver as i32,
+            input: signed.input,
+            ed25519_public_key: signed.public_key,
+            signature: signed.signature,
+            proof,
         };
         let request_bytes = Self::encode_request(&request);
-        self.post_request_no_response("v3/tasks/submit", request_bytes)
+        self.post_request_no_response("submit_input_proof", "v3/tasks/submit/input", request_bytes)
+            .await
+    }
+
+    async fn get_task_progress(
+        &self,
+        node_id: &str,
+        task_id: &str,
+    ) -> Result<Vec<InputProof>, OrchestratorError> {
+        let endpoint = format!(
+            "v3/tasks/{}/{}/inputs",
+            urlencoding::encode(node_id),
+            urlencoding::encode(task_id)
+        );
+        let response: GetTaskProgressResponse =
+            self.get_request("get_task_progress", &endpoint).await?;
+        Ok(response.received_inputs)
+    }
+
+    async fn finalize_task(
+        &self,
+        node_id: &str,
+        task_id: &str,
+        task_type: crate::nexus_orchestrator::TaskType,
+        proof_hash: &str,
+        all_proof_hashes: &[String],
+        signing_key: SigningKey,
+        num_provers: usize,
+    ) -> Result<(), OrchestratorError> {
+        let node_telemetry = self.node_telemetry(num_provers).await;
+        // Every input's hash is sent, whatever the task type, since the proofs already were
+        let signed = SubmissionSignature::sign(
+            &signing_key,
+            self.signature_version(),
+            &SubmissionClaim {
+                task_id,
+                node_id,
+                task_type,
+                proof_hash,
+                all_proof_hashes,
+                incremental: true,
+            },
+        );
+        let request = SubmitProofRequest {
+            task_id: task_id.to_string(),
+            node_type: NodeType::CliProver as i32,
+            proof_hash: proof_hash.to_string(),
+            node_telemetry: Some(node_telemetry),
+            ed25519_public_key: signed.public_key,
+            signature: signed.signature,
+            all_proof_hashes: all_proof_hashes.to_vec(),
+            signature_version: signed.version as u32,
+            attestation: signed.attestation,
+            incremental: true,
+            ..Default::default()
+        };
+        let request_bytes = Self::encode_request(&request);
+        self.post_request_no_response("finalize_task", "v3/tasks/submit", request_bytes)
+            .await
+    }
+
+    async fn abandon_task(
+        &self,
+        node_id: &str,
+        task_id: &str,
+        reason: AbandonReason,
+        detail: &str,
+        signing_key: SigningKey,
+    ) -> Result<(), OrchestratorError> {
+        // Sign exactly what is sent
+        let signed = AbandonmentSignature::sign(&signing_key, node_id, task_id, reason, detail);
+        let request = AbandonTaskRequest {
+            node_type: NodeType::CliProver as i32,
+            abandonment: signed.abandonment,
+            ed25519_public_key: signed.public_key,
+            signature: signed.signature,
+        };
+        let request_bytes = Self::encode_request(&request);
+        self.post_request_no_response("abandon_task", "v3/tasks/abandon", request_bytes)
             .await
     }
 }
@@ -508,6 +836,8 @@ mod live_orchestrator_tests {
                 node_id,
                 verifying_key,
                 crate::nexus_orchestrator::TaskDifficulty::SmallMedium,
+                Default::default(),
+                1,
             )
             .await;
         match result {
@@ -555,7 +885,14 @@ mod live_orchestrator_tests {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::nexus_orchestrator::TaskType;
+    use crate::nexus_orchestrator::SubmissionAttestation;
+    use crate::nexus_orchestrator::{TaskDifficulty, TaskType};
+    use crate::orchestrator::signing::{
+        SignatureVersion, abandon_signed_bytes, batch_signed_bytes, input_signed_bytes,
+        signed_bytes,
+    };
+    use crate::task::TaskSignatureError;
+    use ed25519_dalek::{Signature, Signer, Verifier};
 
     #[tokio::test]
     /// select_proof_payload rules: only ProofRequired sets proof/proofs.
@@ -610,4 +947,494 @@ mod tests {
         assert_eq!(ps, proofs_single);
         assert!(hs.is_empty());
     }
+
+    /// Answer one HTTP request per body in `responses` on a local port; the handle
+    /// yields the request line and body of each request received
+    async fn serve(
+        responses: Vec<Vec<u8>>,
+    ) -> (String, tokio::task::JoinHandle<Vec<(String, Vec<u8>)>>) {
+        serve_with_headers(responses.into_iter().map(|body| (body, "")).collect()).await
+    }
+
+    /// Like [`serve`], with extra header lines sent alongside each body
+    async fn serve_with_headers(
+        responses: Vec<(Vec<u8>, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<(String, Vec<u8>)>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for (body, headers) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                // Read the whole request, so the client isn't reset mid-send
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                let mut body_start = 0;
+                loop {
+                    let read = stream.read(&mut buf).await.unwrap();
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..read]);
+                    let text = String::from_utf8_lossy(&request);
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .filter_map(|line| line.split_once(':'))
+                            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                            .unwrap_or(0);
+                        body_start = end + 4;
+                        if request.len() >= body_start + length {
+                            break;
+                        }
+                    }
+                }
+                let line = String::from_utf8_lossy(&request)
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                requests.push((line, request[body_start.min(request.len())..].to_vec()));
+
+                let head = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/octet-stream\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n",
+                    body.len(),
+                    headers
+                );
+                stream.write_all(head.as_bytes()).await.unwrap();
+                stream.write_all(&body).await.unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    fn signed_task(key: &SigningKey, task_id: &str) -> crate::nexus_orchestrator::Task {
+        let mut task = crate::nexus_orchestrator::Task {
+            task_id: task_id.to_string(),
+            program_id: "fib_input_initial".to_string(),
+            public_inputs_list: vec![vec![1, 2, 3]],
+            task_type: TaskType::ProofHash as i32,
+            ..Default::default()
+        };
+        task.signature = key.sign(&Task::signing_payload(&task)).to_bytes().to_vec();
+        task
+    }
+
+    fn signed_response(key: &SigningKey) -> GetProofTaskResponse {
+        GetProofTaskResponse {
+            task: Some(signed_task(key, "signed")),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_tasks_pages_through_assigned_tasks() {
+        let orchestrator_key = SigningKey::from_bytes(&[1; 32]);
+        let first = GetTasksResponse {
+            tasks: vec![
+                signed_task(&orchestrator_key, "a"),
+                signed_task(&orchestrator_key, "b"),
+            ],
+            next_cursor: "page 2".to_string(),
+        };
+        let last = GetTasksResponse {
+            tasks: vec![signed_task(&orchestrator_key, "c")],
+            next_cursor: String::new(),
+        };
+        // One tampered task among good ones costs only itself
+        let mut mixed = first.clone();
+        mixed.tasks[0].public_inputs_list = vec![vec![9, 9, 9]];
+        mixed.tasks.push(signed_task(&orchestrator_key, "d"));
+        mixed.next_cursor = String::new();
+
+        let (url, requests) = serve(vec![
+            first.encode_to_vec(),
+            last.encode_to_vec(),
+            mixed.encode_to_vec(),
+        ])
+        .await;
+        let client = OrchestratorClient::new(Environment::Custom {
+            orchestrator_url: url,
+            task_keys: vec![orchestrator_key.verifying_key()],
+        });
+
+        let page = client.get_tasks("42", "").await.unwrap();
+        let ids: Vec<_> = page
+            .tasks
+            .iter()
+            .map(|task| task.task_id.as_str())
+            .collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(page.next_cursor, "page 2");
+
+        let page = client.get_tasks("42", &page.next_cursor).await.unwrap();
+        assert_eq!(page.tasks[0].task_id, "c");
+        assert!(page.next_cursor.is_empty());
+        assert!(page.rejected.is_empty());
+
+        // Assigned tasks are held to the same signatures as new ones
+        let page = client.get_tasks("42", "").await.unwrap();
+        let ids: Vec<_> = page
+            .tasks
+            .iter()
+            .map(|task| task.task_id.as_str())
+            .collect();
+        assert_eq!(ids, ["b", "d"]);
+        assert_eq!(
+            page.rejected,
+            vec![TaskSignatureError::Invalid("a".to_string())]
+        );
+
+        let requests = requests.await.unwrap();
+        assert_eq!(requests[0].0, "GET /v3/tasks/42 HTTP/1.1");
+        assert_eq!(
+            requests[1].0,
+            "GET /v3/tasks/42?next_cursor=page%202 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_proof_task_checks_orchestrator_signature() {
+        let orchestrator_key = SigningKey::from_bytes(&[1; 32]);
+        let node_key = SigningKey::from_bytes(&[2; 32]).verifying_key();
+        let client = |orchestrator_url| {
+            OrchestratorClient::new(Environment::Custom {
+                orchestrator_url,
+                task_keys: vec![orchestrator_key.verifying_key()],
+            })
+        };
+
+        let (url, _) = serve(vec![signed_response(&orchestrator_key).encode_to_vec()]).await;
+        let result = client(url)
+            .get_proof_task("1", node_key, TaskDifficulty::Small, Default::default(), 1)
+            .await
+            .unwrap();
+        assert_eq!(result.task.task_id, "signed");
+
+        // A tampered task further in the batch is left out, the rest is kept
+        let mut mixed = signed_response(&orchestrator_key);
+        mixed.additional_tasks = vec![
+            signed_task(&orchestrator_key, "second"),
+            signed_task(&orchestrator_key, "third"),
+        ];
+        mixed.additional_tasks[0].public_inputs_list = vec![vec![9, 9, 9]];
+        let (url, _) = serve(vec![mixed.encode_to_vec()]).await;
+        let result = client(url)
+            .get_proof_task("1", node_key, TaskDifficulty::Small, Default::default(), 3)
+            .await
+            .unwrap();
+        assert_eq!(result.task.task_id, "signed");
+        assert_eq!(result.additional_tasks.len(), 1);
+        assert_eq!(result.additional_tasks[0].task_id, "third");
+        assert_eq!(
+            result.rejected,
+            vec![TaskSignatureError::Invalid("second".to_string())]
+        );
+
+        // A proxy rewriting the inputs can't keep the signature valid
+        let mut tampered = signed_response(&orchestrator_key);
+        tampered.task.as_mut().unwrap().public_inputs_list = vec![vec![9, 9, 9]];
+        let (url, _) = serve(vec![tampered.encode_to_vec()]).await;
+        assert!(matches!(
+            client(url)
+                .get_proof_task("1", node_key, TaskDifficulty::Small, Default::default(), 1)
+                .await,
+            Err(OrchestratorError::UntrustedTask(
+                TaskSignatureError::Invalid(_)
+            ))
+        ));
+
+        let mut unsigned = signed_response(&orchestrator_key);
+        unsigned.task.as_mut().unwrap().signature.clear();
+        let (url, _) = serve(vec![unsigned.encode_to_vec()]).await;
+        assert!(matches!(
+            client(url)
+                .get_proof_task("1", node_key, TaskDifficulty::Small, Default::default(), 1)
+                .await,
+            Err(OrchestratorError::UntrustedTask(
+                TaskSignatureError::Unsigned(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_successful_response_advertises_rate_limit() {
+        use crate::network::{NetworkClient, RequestTimer, RequestTimerConfig};
+
+        let orchestrator_key = SigningKey::from_bytes(&[1; 32]);
+        let node_key = SigningKey::from_bytes(&[2; 32]).verifying_key();
+        let (url, _) = serve_with_headers(vec![(
+            signed_response(&orchestrator_key).encode_to_vec(),
+            "ratelimit-limit: 10\r\nratelimit-remaining: 0\r\nratelimit-reset: 30\r\n",
+        )])
+        .await;
+        let client = OrchestratorClient::new(Environment::Custom {
+            orchestrator_url: url,
+            task_keys: vec![orchestrator_key.verifying_key()],
+        });
+        let timer = RequestTimer::new(RequestTimerConfig::combined(
+            Duration::ZERO,
+            100,
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+        ));
+        let mut network_client = NetworkClient::new(timer, 1);
+
+        network_client
+            .fetch_task(
+                &client,
+                "42",
+                node_key,
+                TaskDifficulty::Small,
+                &Default::default(),
+                1,
+            )
+            .await
+            .unwrap();
+        // The budget is spent, so the next request waits for the window to reset
+        let wait = network_client.request_timer_mut().time_until_next();
+        assert!(wait > Duration::from_secs(25), "waited only {:?}", wait);
+        assert!(client.take_rate_limit().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_batch_fetch_and_submission() {
+        let _ = COUNTRY_CODE.set("US".to_string());
+        let orchestrator_key = SigningKey::from_bytes(&[1; 32]);
+        let node_key = SigningKey::from_bytes(&[2; 32]);
+        let mut response = signed_response(&orchestrator_key);
+        response.additional_tasks = vec![signed_task(&orchestrator_key, "second")];
+        response.batch_limits = Some(BatchLimits {
+            max_tasks_per_fetch: 4,
+            max_submissions_per_batch: 8,
+        });
+        let rejected = SubmitProofBatchResponse {
+            rejected: vec![RejectedSubmission {
+                task_id: "second".to_string(),
+                reason: "expired".to_string(),
+            }],
+        };
+
+        let (url, requests) = serve(vec![response.encode_to_vec(), rejected.encode_to_vec()]).await;
+        let client = OrchestratorClient::new(Environment::Custom {
+            orchestrator_url: url,
+            task_keys: vec![orchestrator_key.verifying_key()],
+        });
+        let result = client
+            .get_proof_task(
+                "42",
+                node_key.verifying_key(),
+                TaskDifficulty::Small,
+                Default::default(),
+                4,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.task.task_id, "signed");
+        assert_eq!(result.additional_tasks[0].task_id, "second");
+        assert_eq!(result.batch_limits.max_submissions_per_batch, 8);
+
+        let submissions = ["signed", "second"].map(|task_id| BatchedSubmission {
+            task_id: task_id.to_string(),
+            task_type: TaskType::ProofHash as i32,
+            proof_hash: format!("{}-hash", task_id),
+            all_proof_hashes: Vec::new(),
+        });
+        let rejected = client
+            .submit_proof_batch("42", &submissions, node_key.clone(), 1)
+            .await
+            .unwrap();
+        assert_eq!(rejected[0].task_id, "second");
+
+        let requests = requests.await.unwrap();
+        let fetch = GetProofTaskRequest::decode(&requests[0].1[..]).unwrap();
+        assert_eq!(fetch.max_tasks, 4);
+        assert_eq!(requests[1].0, "POST /v3/tasks/submit/batch HTTP/1.1");
+        let request = SubmitProofBatchRequest::decode(&requests[1].1[..]).unwrap();
+        let batch = crate::nexus_orchestrator::SubmissionBatch::decode(&request.batch[..]).unwrap();
+        assert_eq!(batch.node_id, "42");
+        assert_eq!(batch.submissions, submissions);
+        let signature = Signature::from_slice(&request.signature).unwrap();
+        assert!(
+            node_key
+                .verifying_key()
+                .verify(&batch_signed_bytes(&request.batch), &signature)
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incremental_submission() {
+        let _ = COUNTRY_CODE.set("US".to_string());
+        let node_key = SigningKey::from_bytes(&[2; 32]);
+        let progress = GetTaskProgressResponse {
+            received_inputs: vec![InputProof {
+                input_index: 0,
+                proof_hash: "h0".to_string(),
+            }],
+        };
+
+        let (url, requests) = serve(vec![progress.encode_to_vec(), Vec::new(), Vec::new()]).await;
+        let client = OrchestratorClient::new(Environment::Custom {
+            orchestrator_url: url,
+            task_keys: Vec::new(),
+        });
+        let received = client.get_task_progress("42", "task-1").await.unwrap();
+        assert_eq!(received, progress.received_inputs);
+        client
+            .submit_input_proof("42", "task-1", 1, "h1", Vec::new(), node_key.clone())
+            .await
+            .unwrap();
+        let hashes = ["h0".to_string(), "h1".to_string()];
+        client
+            .finalize_task(
+                "42",
+                "task-1",
+                TaskType::ProofHash,
+                "combined",
+                &hashes,
+                node_key.clone(),
+                1,
+            )
+            .await
+            .unwrap();
+
+        let requests = requests.await.unwrap();
+        assert_eq!(requests[0].0, "GET /v3/tasks/42/task-1/inputs HTTP/1.1");
+        assert_eq!(requests[1].0, "POST /v3/tasks/submit/input HTTP/1.1");
+        let request = SubmitInputProofRequest::decode(&requests[1].1[..]).unwrap();
+        let input = crate::nexus_orchestrator::InputSubmission::decode(&request.input[..]).unwrap();
+        assert_eq!((input.input_index, input.proof_hash.as_str()), (1, "h1"));
+        let signature = Signature::from_slice(&request.signature).unwrap();
+        assert!(
+            node_key
+                .verifying_key()
+                .verify(&input_signed_bytes(&request.input), &signature)
+                .is_ok()
+        );
+
+        // The finalizing call carries every input's hash even for a PROOF_HASH task
+        let finalize = SubmitProofRequest::decode(&requests[2].1[..]).unwrap();
+        assert!(finalize.incremental);
+        assert_eq!(finalize.proof_hash, "combined");
+        assert_eq!(finalize.all_proof_hashes, hashes);
+        assert!(finalize.proofs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_abandon_task() {
+        let node_key = SigningKey::from_bytes(&[2; 32]);
+        let (url, requests) = serve(vec![Vec::new()]).await;
+        let client = OrchestratorClient::new(Environment::Custom {
+            orchestrator_url: url,
+            task_keys: Vec::new(),
+        });
+        client
+            .abandon_task(
+                "42",
+                "task-1",
+                AbandonReason::Shutdown,
+                "shutting down",
+                node_key.clone(),
+            )
+            .await
+            .unwrap();
+
+        let requests = requests.await.unwrap();
+        assert_eq!(requests[0].0, "POST /v3/tasks/abandon HTTP/1.1");
+        let request = AbandonTaskRequest::decode(&requests[0].1[..]).unwrap();
+        let abandonment =
+            crate::nexus_orchestrator::TaskAbandonment::decode(&request.abandonment[..]).unwrap();
+        assert_eq!(abandonment.task_id, "task-1");
+        assert_eq!(abandonment.reason, AbandonReason::Shutdown as i32);
+        assert_eq!(abandonment.detail, "shutting down");
+        let signature = Signature::from_slice(&request.signature).unwrap();
+        assert!(
+            node_key
+                .verifying_key()
+                .verify(&abandon_signed_bytes(&request.abandonment), &signature)
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submission_signature_follows_orchestrator_versions() {
+        let _ = COUNTRY_CODE.set("US".to_string());
+        let node_key = SigningKey::from_bytes(&[2; 32]);
+        let hashes = ["h1".to_string(), "h2".to_string()];
+        let mut v1_response = signed_response(&SigningKey::from_bytes(&[1; 32]));
+        v1_response.submission_signature_versions = vec![0, 1];
+        let v0_response = signed_response(&SigningKey::from_bytes(&[1; 32]));
+
+        let (url, requests) = serve(vec![
+            v1_response.encode_to_vec(),
+            Vec::new(),
+            v0_response.encode_to_vec(),
+            Vec::new(),
+        ])
+        .await;
+        // No pinned keys, so the tasks themselves aren't checked here
+        let client = OrchestratorClient::new(Environment::Custom {
+            orchestrator_url: url,
+            task_keys: Vec::new(),
+        });
+        for _ in 0..2 {
+            client
+                .get_proof_task(
+                    "42",
+                    node_key.verifying_key(),
+                    TaskDifficulty::Small,
+                    Default::default(),
+                    1,
+                )
+                .await
+                .unwrap();
+            client
+                .submit_proof(
+                    "42",
+                    "signed",
+                    "combined",
+                    Vec::new(),
+                    Vec::new(),
+                    node_key.clone(),
+                    1,
+                    TaskType::AllProofHashes,
+                    &hashes,
+                )
+                .await
+                .unwrap();
+        }
+        let requests = requests.await.unwrap();
+        let verifying_key = node_key.verifying_key();
+
+        // An orchestrator accepting version 1 gets an attestation binding the node and hashes
+        let v1 = SubmitProofRequest::decode(&requests[1].1[..]).unwrap();
+        assert_eq!(v1.signature_version, SignatureVersion::V1 as u32);
+        let attestation = SubmissionAttestation::decode(&v1.attestation[..]).unwrap();
+        assert_eq!(attestation.node_id, "42");
+        assert_eq!(attestation.task_id, "signed");
+        assert_eq!(attestation.proof_hash, "combined");
+        assert_eq!(attestation.all_proof_hashes, hashes);
+        let signature = Signature::from_slice(&v1.signature).unwrap();
+        assert!(
+            verifying_key
+                .verify(&signed_bytes(&v1.attestation), &signature)
+                .is_ok()
+        );
+
+        // One that doesn't advertise it still gets the original scheme
+        let v0 = SubmitProofRequest::decode(&requests[3].1[..]).unwrap();
+        assert_eq!(v0.signature_version, 0);
+        assert!(v0.attestation.is_empty());
+        let signature = Signature::from_slice(&v0.signature).unwrap();
+        assert!(
+            verifying_key
+                .verify(b"0 | signed | combined", &signature)
+                .is_ok()
+        );
+    }
 }
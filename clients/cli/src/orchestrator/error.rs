@@ -1,8 +1,11 @@
 //! Error handling for the orchestrator module
 
+use crate::task::TaskSignatureError;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use prost::DecodeError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use thiserror::Error;
 
 #[allow(non_snake_case)] // used for json parsing
@@ -13,6 +16,105 @@ struct RawError {
     httpCode: u16,
 }
 
+/// What went wrong according to the orchestrator, from the `name` of its JSON error body.
+///
+/// The orchestrator publishes no list of these names: orchestrator.proto has no error enum
+/// and no recording holds one, so the names below are only what its errors are expected to
+/// be called. A wrong guess must never stop a node, so a name only counts as a permanent
+/// error when the response's status is a client error too, and any other name is retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerError {
+    /// The node ID is not registered, or no longer is
+    NodeNotFound,
+    /// The wallet the node belongs to is not registered
+    UserNotFound,
+    /// The node or its key has been banned
+    Banned,
+    /// This version of the CLI is no longer accepted
+    ClientOutdated,
+    /// The request's signature didn't verify
+    InvalidSignature,
+    /// Too many requests; wait before the next one
+    RateLimited,
+    /// The task is unknown, expired, or already submitted
+    TaskUnavailable,
+    /// No task to hand out right now
+    NoTasksAvailable,
+    /// The orchestrator failed on its side
+    Internal,
+    /// A code this client doesn't know, handled by its HTTP status alone
+    Unknown(String),
+}
+
+impl ServerError {
+    /// The error for the `name` the orchestrator gave it, in a response with `status`
+    pub fn from_response(name: &str, status: u16) -> Self {
+        let error = Self::from_name(name);
+        // 429s and server-side failures are never a reason to give up for good
+        let client_error = (400..=499).contains(&status) && status != 429;
+        if error.is_permanent() && !client_error {
+            return Self::Unknown(name.to_string());
+        }
+        error
+    }
+
+    /// Aliases cover both the bare names and the `...Error` class names the orchestrator's
+    /// errors may carry; any name not listed here is `Unknown`
+    fn from_name(name: &str) -> Self {
+        match name {
+            "NodeNotFound" | "NodeNotFoundError" => Self::NodeNotFound,
+            "UserNotFound" | "UserNotFoundError" => Self::UserNotFound,
+            "NodeBanned" | "KeyBanned" | "Banned" | "BannedError" => Self::Banned,
+            "ClientOutdated" | "UnsupportedClientVersion" | "UpgradeRequired" => {
+                Self::ClientOutdated
+            }
+            "InvalidSignature" | "InvalidSignatureError" => Self::InvalidSignature,
+            "RateLimited" | "RateLimitExceeded" | "TooManyRequests" => Self::RateLimited,
+            "TaskNotFound" | "TaskExpired" | "TaskAlreadySubmitted" => Self::TaskUnavailable,
+            "NoTasksAvailable" | "NoTaskAvailable" => Self::NoTasksAvailable,
+            "InternalServerError" | "ServiceUnavailable" => Self::Internal,
+            _ => Self::Unknown(name.to_string()),
+        }
+    }
+
+    /// Whether the node can't go on until someone acts on this; retrying never helps
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::NodeNotFound | Self::UserNotFound | Self::Banned | Self::ClientOutdated
+        )
+    }
+
+    /// Whether the same request may succeed if sent again, once the request timer allows.
+    /// Codes this client doesn't know always are.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Internal | Self::Unknown(_) => true,
+            // Rate limits and empty queues are waited out by the request timer instead
+            _ => false,
+        }
+    }
+
+    /// What the operator should do about a permanent error
+    pub fn advice(&self) -> Option<&'static str> {
+        match self {
+            Self::NodeNotFound => Some(
+                "This node is not registered with the orchestrator. Register a new one with: nexus-cli register-node",
+            ),
+            Self::UserNotFound => Some(
+                "Your wallet is not registered with the orchestrator. Register it with: nexus-cli register-user --wallet-address <your-wallet-address>",
+            ),
+            Self::Banned => Some(
+                "This node has been banned by the orchestrator. Ask on Discord or open an issue if you think this is a mistake",
+            ),
+            Self::ClientOutdated => Some(
+                "This version of the CLI is no longer supported. Upgrade with: curl https://cli.nexus.xyz/ | sh",
+            ),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum OrchestratorError {
     /// Failed to decode a Protobuf message from the server
@@ -30,45 +132,83 @@ pub enum OrchestratorError {
         message: String,
         headers: HashMap<String, String>,
     },
-}
 
-impl OrchestratorError {
-    pub async fn from_response(response: reqwest::Response) -> OrchestratorError {
-        let status = response.status().as_u16();
-
-        // Capture headers before consuming the response
-        let mut headers = HashMap::new();
-        for (name, value) in response.headers().iter() {
-            if let Ok(value_str) = value.to_str() {
-                headers.insert(name.to_string().to_lowercase(), value_str.to_string());
-            }
-        }
+    /// A network error read back from a recording, in place of the `Reqwest` error it was
+    #[error("Recorded network error: {0}")]
+    Recorded(String),
+
+    /// A replayed recording has no more responses for this call
+    #[error("Recording has no more {0} responses")]
+    ReplayExhausted(String),
 
-        let message = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Failed to read response text".to_string());
+    /// A task whose orchestrator signature didn't check out, so it was not proved
+    #[error("Untrusted task: {0}")]
+    UntrustedTask(#[from] TaskSignatureError),
+
+    /// A well-formed response without a field the client can't do without
+    #[error("Response is missing its {0}")]
+    Incomplete(String),
+
+    /// A proof that couldn't be serialized for its request, so nothing was sent
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] postcard::Error),
+}
 
+impl OrchestratorError {
+    /// An error for a response that arrived with a non-success status
+    pub fn from_parts(status: u16, headers: HashMap<String, String>, body: &[u8]) -> Self {
         OrchestratorError::Http {
             status,
-            message,
+            message: String::from_utf8_lossy(body).into_owned(),
             headers,
         }
     }
 
-    /// Get the Retry-After header value in seconds, if present
-    pub fn get_retry_after_seconds(&self) -> Option<u32> {
+    /// How long the server asked us to wait, from a `Retry-After` header in either of
+    /// its RFC 9110 forms: delay-seconds, or an HTTP-date measured from `now`
+    pub fn retry_after(&self, now: DateTime<Utc>) -> Option<Duration> {
+        let value = self.header("retry-after")?.trim();
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        let date = parse_http_date(value)?;
+        // A date in the past means the request can be retried right away
+        Some((date - now).to_std().unwrap_or_default())
+    }
+
+    /// The rate-limit budget the server advertised along with the error
+    pub fn rate_limit(&self, now: DateTime<Utc>) -> Option<RateLimit> {
         match self {
-            Self::Http {
-                status, headers, ..
-            } => {
-                if (500..=599).contains(status) {
-                    return Some(30);
-                }
-                headers
-                    .get("retry-after")
-                    .and_then(|value| value.parse::<u32>().ok())
-            }
+            Self::Http { headers, .. } => RateLimit::from_headers(headers, now),
+            _ => None,
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        match self {
+            Self::Http { headers, .. } => headers.get(name).map(String::as_str),
+            _ => None,
+        }
+    }
+
+    /// The orchestrator's error, if the response body was one of its JSON errors
+    pub fn server_error(&self) -> Option<ServerError> {
+        let Self::Http { status, .. } = self else {
+            return None;
+        };
+        self.raw_error()
+            .map(|raw| ServerError::from_response(&raw.name, *status))
+    }
+
+    /// Why the node has to stop, with what to do about it, if retrying can't fix this
+    pub fn fatal_reason(&self) -> Option<String> {
+        let advice = self.server_error()?.advice()?;
+        Some(format!("{} ({})", advice, self.raw_error()?.message))
+    }
+
+    fn raw_error(&self) -> Option<RawError> {
+        match self {
+            Self::Http { message, .. } => serde_json::from_str(message).ok(),
             _ => None,
         }
     }
@@ -93,46 +233,329 @@ impl OrchestratorError {
     }
 }
 
+/// A request budget the server advertised in its rate-limit headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests allowed per window
+    pub limit: Option<u32>,
+    /// Requests left in the current window
+    pub remaining: Option<u32>,
+    /// Time until the current window resets
+    pub reset: Option<Duration>,
+    /// Length of the window, when the server's policy gives one
+    pub window: Option<Duration>,
+}
+
+impl RateLimit {
+    /// The budget advertised in `headers`, by lowercase name, from the `RateLimit-*`
+    /// headers or their older `X-RateLimit-*` spellings. Successful responses carry
+    /// it as well as errors.
+    pub fn from_headers(headers: &HashMap<String, String>, now: DateTime<Utc>) -> Option<Self> {
+        let header = |name: &str| headers.get(name).map(String::as_str);
+        let field = |name: &str| {
+            header(&format!("ratelimit-{}", name))
+                .or_else(|| header(&format!("x-ratelimit-{}", name)))
+        };
+        let limit = field("limit").and_then(first_number);
+        let remaining = field("remaining").and_then(first_number);
+        if limit.is_none() && remaining.is_none() {
+            return None;
+        }
+        let reset = field("reset")
+            .and_then(first_number)
+            .map(|reset| reset_delay(reset, now));
+        // The window is a `w` parameter, on the policy header or trailing the limit
+        let window = [field("policy"), field("limit")]
+            .into_iter()
+            .flatten()
+            .find_map(window_param);
+
+        Some(Self {
+            limit: limit.map(|limit| limit.min(u32::MAX as u64) as u32),
+            remaining: remaining.map(|remaining| remaining.min(u32::MAX as u64) as u32),
+            reset,
+            window,
+        })
+    }
+
+    /// Whether the current window has no requests left
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+}
+
+/// Parse an HTTP-date in the preferred IMF-fixdate form or either obsolete form,
+/// all of which RFC 9110 requires recipients to accept
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    // RFC 850, e.g. "Sunday, 06-Nov-94 08:49:37 GMT", and asctime, e.g. "Sun Nov  6 08:49:37 1994"
+    ["%A, %d-%b-%y %H:%M:%S GMT", "%a %b %e %H:%M:%S %Y"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|date| date.and_utc())
+}
+
+/// The leading number of a header value, e.g. `100` of `100, 100;w=60`
+fn first_number(value: &str) -> Option<u64> {
+    value
+        .split([',', ';'])
+        .next()
+        .and_then(|number| number.trim().parse().ok())
+}
+
+/// The `w` (window seconds) parameter of a rate-limit header value
+fn window_param(value: &str) -> Option<Duration> {
+    value
+        .split([',', ';'])
+        .filter_map(|param| param.trim().strip_prefix("w="))
+        .find_map(|secs| secs.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// A reset value as a delay. Some servers send a Unix timestamp instead of seconds left,
+/// which no real window is long enough to be confused with.
+fn reset_delay(reset: u64, now: DateTime<Utc>) -> Duration {
+    const TIMESTAMP_THRESHOLD: u64 = 1_000_000_000;
+    if reset < TIMESTAMP_THRESHOLD {
+        return Duration::from_secs(reset);
+    }
+    let now = now.timestamp().max(0) as u64;
+    Duration::from_secs(reset.saturating_sub(now))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network::error_handler::ErrorHandler;
+
+    fn server_error(status: u16, name: &str, message: &str) -> OrchestratorError {
+        OrchestratorError::Http {
+            status,
+            message: serde_json::json!({ "name": name, "message": message, "httpCode": status })
+                .to_string(),
+            headers: HashMap::new(),
+        }
+    }
 
     #[test]
-    fn test_get_retry_after_seconds() {
-        let mut headers = HashMap::new();
-        headers.insert("retry-after".to_string(), "120".to_string());
+    fn test_server_error() {
+        let error = server_error(404, "NodeNotFoundError", "node 42 not found");
+        assert_eq!(error.server_error(), Some(ServerError::NodeNotFound));
+        let reason = error.fatal_reason().unwrap();
+        assert!(reason.contains("nexus-cli register-node"));
+        assert!(reason.ends_with("(node 42 not found)"));
+
+        let error = server_error(426, "UpgradeRequired", "too old");
+        assert_eq!(error.server_error(), Some(ServerError::ClientOutdated));
+        assert!(error.fatal_reason().unwrap().contains("Upgrade"));
+
+        // Errors worth retrying, or waiting out, don't stop the node
+        let error = server_error(404, "TaskExpired", "expired");
+        assert_eq!(error.server_error(), Some(ServerError::TaskUnavailable));
+        assert_eq!(error.fatal_reason(), None);
 
+        // Unknown codes and bodies that aren't JSON errors are left to the status
+        let error = server_error(418, "Teapot", "short and stout");
+        assert_eq!(
+            error.server_error(),
+            Some(ServerError::Unknown("Teapot".to_string()))
+        );
+        assert_eq!(error.fatal_reason(), None);
         let error = OrchestratorError::Http {
-            status: 429,
-            message: "Rate limited".to_string(),
-            headers,
+            status: 404,
+            message: "Not Found".to_string(),
+            headers: HashMap::new(),
         };
+        assert_eq!(error.server_error(), None);
+        assert_eq!(error.fatal_reason(), None);
+    }
+
+    #[test]
+    fn test_unknown_server_errors_are_retried() {
+        // Names this client doesn't know are retried whatever their status
+        for status in [400, 403, 404, 426, 429, 500] {
+            let error = server_error(status, "NodeGone", "gone");
+            let server_error = error.server_error().unwrap();
+            assert_eq!(server_error, ServerError::Unknown("NodeGone".to_string()));
+            assert!(!server_error.is_permanent(), "{}", status);
+            assert!(server_error.is_retryable(), "{}", status);
+            assert_eq!(error.fatal_reason(), None);
+        }
 
-        assert_eq!(error.get_retry_after_seconds(), Some(120));
+        // Nor does a known name stop the node unless its status agrees
+        for status in [429, 500, 503] {
+            let error = server_error(status, "NodeNotFound", "no such node");
+            assert!(!error.server_error().unwrap().is_permanent(), "{}", status);
+            assert!(error.server_error().unwrap().is_retryable(), "{}", status);
+            assert_eq!(error.fatal_reason(), None);
+        }
     }
 
+    /// Every name `from_name` knows, and what it means in a client error
+    const KNOWN_NAMES: &[(&str, ServerError)] = &[
+        ("NodeNotFound", ServerError::NodeNotFound),
+        ("NodeNotFoundError", ServerError::NodeNotFound),
+        ("UserNotFound", ServerError::UserNotFound),
+        ("UserNotFoundError", ServerError::UserNotFound),
+        ("NodeBanned", ServerError::Banned),
+        ("KeyBanned", ServerError::Banned),
+        ("Banned", ServerError::Banned),
+        ("BannedError", ServerError::Banned),
+        ("ClientOutdated", ServerError::ClientOutdated),
+        ("UnsupportedClientVersion", ServerError::ClientOutdated),
+        ("UpgradeRequired", ServerError::ClientOutdated),
+        ("InvalidSignature", ServerError::InvalidSignature),
+        ("InvalidSignatureError", ServerError::InvalidSignature),
+        ("RateLimited", ServerError::RateLimited),
+        ("RateLimitExceeded", ServerError::RateLimited),
+        ("TooManyRequests", ServerError::RateLimited),
+        ("TaskNotFound", ServerError::TaskUnavailable),
+        ("TaskExpired", ServerError::TaskUnavailable),
+        ("TaskAlreadySubmitted", ServerError::TaskUnavailable),
+        ("NoTasksAvailable", ServerError::NoTasksAvailable),
+        ("NoTaskAvailable", ServerError::NoTasksAvailable),
+        ("InternalServerError", ServerError::Internal),
+        ("ServiceUnavailable", ServerError::Internal),
+    ];
+
     #[test]
-    fn test_get_retry_after_seconds_missing_header() {
+    fn test_known_names_by_status() {
+        let handler = ErrorHandler::new();
+        for (name, in_client_error) in KNOWN_NAMES {
+            for status in [403, 429, 503] {
+                let error = server_error(status, name, "?");
+                let server_error = error.server_error().unwrap();
+                // Only a client error can make a name permanent
+                let expected = if in_client_error.is_permanent() && status != 403 {
+                    ServerError::Unknown(name.to_string())
+                } else {
+                    in_client_error.clone()
+                };
+                assert_eq!(server_error, expected, "{} {}", name, status);
+                assert_eq!(
+                    error.fatal_reason().is_some(),
+                    expected.is_permanent(),
+                    "{} {}",
+                    name,
+                    status
+                );
+                assert_eq!(
+                    handler.should_retry(&error),
+                    expected.is_retryable(),
+                    "{} {}",
+                    name,
+                    status
+                );
+            }
+        }
+
+        // A 4xx whose body isn't a JSON error is left to its status: retried, never fatal
         let error = OrchestratorError::Http {
-            status: 429,
-            message: "Rate limited".to_string(),
+            status: 403,
+            message: "Forbidden".to_string(),
             headers: HashMap::new(),
         };
+        assert_eq!(error.server_error(), None);
+        assert_eq!(error.fatal_reason(), None);
+        assert!(handler.should_retry(&error));
+    }
 
-        assert_eq!(error.get_retry_after_seconds(), None);
+    fn with_headers(status: u16, headers: &[(&str, &str)]) -> OrchestratorError {
+        OrchestratorError::Http {
+            status,
+            message: "Rate limited".to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
     }
 
     #[test]
-    fn test_get_retry_after_seconds_invalid_value() {
-        let mut headers = HashMap::new();
-        headers.insert("retry-after".to_string(), "invalid".to_string());
+    fn test_retry_after_seconds() {
+        let error = with_headers(429, &[("retry-after", "120")]);
+        assert_eq!(
+            error.retry_after(Utc::now()),
+            Some(Duration::from_secs(120))
+        );
+    }
 
-        let error = OrchestratorError::Http {
-            status: 429,
-            message: "Rate limited".to_string(),
-            headers,
-        };
+    #[test]
+    fn test_retry_after_http_date() {
+        let now = DateTime::parse_from_rfc3339("1994-11-06T08:49:07Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        for date in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+        ] {
+            let error = with_headers(503, &[("retry-after", date)]);
+            assert_eq!(
+                error.retry_after(now),
+                Some(Duration::from_secs(30)),
+                "{}",
+                date
+            );
+        }
+
+        // A date that has already passed means no wait
+        let error = with_headers(503, &[("retry-after", "Sun, 06 Nov 1994 08:00:00 GMT")]);
+        assert_eq!(error.retry_after(now), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_retry_after_missing_or_invalid() {
+        // A server error without the header leaves the delay to the client
+        assert_eq!(with_headers(500, &[]).retry_after(Utc::now()), None);
+        for value in ["invalid", "-5", "1.5"] {
+            let error = with_headers(429, &[("retry-after", value)]);
+            assert_eq!(error.retry_after(Utc::now()), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_headers() {
+        let now = Utc::now();
+        let error = with_headers(
+            429,
+            &[
+                ("ratelimit-limit", "100"),
+                ("ratelimit-remaining", "0"),
+                ("ratelimit-reset", "42"),
+                ("ratelimit-policy", "100;w=60"),
+            ],
+        );
+        let limit = error.rate_limit(now).unwrap();
+        assert_eq!(
+            limit,
+            RateLimit {
+                limit: Some(100),
+                remaining: Some(0),
+                reset: Some(Duration::from_secs(42)),
+                window: Some(Duration::from_secs(60)),
+            }
+        );
+        assert!(limit.is_exhausted());
+
+        // The older spellings, with the reset as a Unix timestamp
+        let reset = (now.timestamp() + 15).to_string();
+        let error = with_headers(
+            429,
+            &[
+                ("x-ratelimit-limit", "10, 10;w=1"),
+                ("x-ratelimit-remaining", "3"),
+                ("x-ratelimit-reset", reset.as_str()),
+            ],
+        );
+        let limit = error.rate_limit(now).unwrap();
+        assert_eq!(limit.limit, Some(10));
+        assert_eq!(limit.window, Some(Duration::from_secs(1)));
+        assert_eq!(limit.reset, Some(Duration::from_secs(15)));
+        assert!(!limit.is_exhausted());
 
-        assert_eq!(error.get_retry_after_seconds(), None);
+        assert_eq!(with_headers(429, &[]).rate_limit(now), None);
     }
 }
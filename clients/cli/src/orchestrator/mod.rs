@@ -1,10 +1,15 @@
+use crate::consts::cli_consts::task_fetching;
 use crate::environment::Environment;
+use crate::orchestrator::client::TaskPage;
 use crate::orchestrator::error::OrchestratorError;
 use ed25519_dalek::{SigningKey, VerifyingKey};
 
 pub(crate) mod client;
 pub use client::OrchestratorClient;
 pub mod error;
+pub mod recording;
+pub mod replay;
+pub mod signing;
 
 #[cfg(test)]
 use mockall::{automock, predicate::*};
@@ -14,6 +19,10 @@ use mockall::{automock, predicate::*};
 pub trait Orchestrator: Send + Sync {
     fn environment(&self) -> &Environment;
 
+    /// The rate-limit budget the last successful response advertised, if it hasn't
+    /// been taken yet. Errors carry theirs in their headers instead.
+    fn take_rate_limit(&self) -> Option<crate::orchestrator::error::RateLimit>;
+
     /// Get the user ID associated with a wallet address.
     async fn get_user(&self, wallet_address: &str) -> Result<String, OrchestratorError>;
 
@@ -30,18 +39,30 @@ pub trait Orchestrator: Send + Sync {
     /// Get the wallet address associated with a node ID.
     async fn get_node(&self, node_id: &str) -> Result<String, OrchestratorError>;
 
-    /// Request a new proof task for the node.
+    /// Request a new proof task for the node, advertising what it can run.
+    /// Orchestrators that support batching may return up to `max_tasks` tasks.
     async fn get_proof_task(
         &self,
         node_id: &str,
         verifying_key: VerifyingKey,
         max_difficulty: crate::nexus_orchestrator::TaskDifficulty,
+        capabilities: crate::nexus_orchestrator::ClientCapabilities,
+        max_tasks: u32,
     ) -> Result<crate::orchestrator::client::ProofTaskResult, OrchestratorError>;
 
+    /// One page of the tasks already assigned to the node and not yet submitted.
+    /// Start with an empty cursor and pass each page's `next_cursor` until it is empty.
+    async fn get_tasks(
+        &self,
+        node_id: &str,
+        next_cursor: &str,
+    ) -> Result<crate::orchestrator::client::TaskPage, OrchestratorError>;
+
     /// Submits a proof to the orchestrator.
     #[allow(clippy::too_many_arguments)]
     async fn submit_proof(
         &self,
+        node_id: &str,
         task_id: &str,
         proof_hash: &str,
         proof: Vec<u8>,
@@ -51,4 +72,79 @@ pub trait Orchestrator: Send + Sync {
         task_type: crate::nexus_orchestrator::TaskType,
         individual_proof_hashes: &[String],
     ) -> Result<(), OrchestratorError>;
+
+    /// Submits several `ProofHash`/`AllProofHashes` results in one signed request,
+    /// returning the submissions that were not accepted.
+    async fn submit_proof_batch(
+        &self,
+        node_id: &str,
+        submissions: &[crate::nexus_orchestrator::BatchedSubmission],
+        signing_key: SigningKey,
+        num_provers: usize,
+    ) -> Result<Vec<crate::nexus_orchestrator::RejectedSubmission>, OrchestratorError>;
+
+    /// Submits the verified proof of one input of a task, as soon as it is ready.
+    /// `proof` is only sent for `ProofRequired` tasks.
+    async fn submit_input_proof(
+        &self,
+        node_id: &str,
+        task_id: &str,
+        input_index: u32,
+        proof_hash: &str,
+        proof: Vec<u8>,
+        signing_key: SigningKey,
+    ) -> Result<(), OrchestratorError>;
+
+    /// The inputs of a task the orchestrator already has proofs for.
+    async fn get_task_progress(
+        &self,
+        node_id: &str,
+        task_id: &str,
+    ) -> Result<Vec<crate::nexus_orchestrator::InputProof>, OrchestratorError>;
+
+    /// Finalizes a task whose inputs were all submitted with `submit_input_proof`,
+    /// with the combined hash and the hash of every input.
+    #[allow(clippy::too_many_arguments)]
+    async fn finalize_task(
+        &self,
+        node_id: &str,
+        task_id: &str,
+        task_type: crate::nexus_orchestrator::TaskType,
+        proof_hash: &str,
+        all_proof_hashes: &[String],
+        signing_key: SigningKey,
+        num_provers: usize,
+    ) -> Result<(), OrchestratorError>;
+
+    /// Tells the orchestrator the node gives up a task, so it can be reassigned
+    /// instead of expiring. `detail` only ends up in the orchestrator's logs.
+    async fn abandon_task(
+        &self,
+        node_id: &str,
+        task_id: &str,
+        reason: crate::nexus_orchestrator::AbandonReason,
+        detail: &str,
+        signing_key: SigningKey,
+    ) -> Result<(), OrchestratorError>;
+}
+
+/// Every task assigned to the node as a single page, reading pages until the cursor runs out
+pub async fn get_all_tasks(
+    orchestrator: &dyn Orchestrator,
+    node_id: &str,
+) -> Result<TaskPage, OrchestratorError> {
+    let mut all = TaskPage::default();
+    let mut cursor = String::new();
+    for _ in 0..task_fetching::MAX_ASSIGNED_TASK_PAGES {
+        let page = orchestrator.get_tasks(node_id, &cursor).await?;
+        all.tasks.extend(page.tasks);
+        all.rejected.extend(page.rejected);
+        all.incremental_min_inputs = page.incremental_min_inputs;
+        // A cursor that doesn't move would only return the same page again
+        if page.next_cursor.is_empty() || page.next_cursor == cursor {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+    Ok(all)
 }
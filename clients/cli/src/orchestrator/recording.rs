@@ -0,0 +1,279 @@
+//! Recordings of orchestrator traffic, for `start --record` and `replay`
+//!
+//! Every request the client makes is appended to `<dir>/orchestrator.jsonl` as
+//! one [`Exchange`]: the protobuf bodies (hex-encoded), status, response headers
+//! and timing. Wallet addresses, including those in error messages, and credential
+//! headers are redacted first.
+
+use crate::hex::{from_hex, to_hex};
+use crate::nexus_orchestrator::{GetNodeResponse, RegisterUserRequest, UserResponse};
+use chrono::{DateTime, Utc};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const RECORDING_FILE_NAME: &str = "orchestrator.jsonl";
+
+/// Stands in for anything redacted from a recording
+pub const REDACTED: &str = "<redacted>";
+
+/// Response headers that may carry credentials
+const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "cookie",
+    "proxy-authorization",
+    "set-cookie",
+];
+
+/// One request to the orchestrator and what came back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    /// The `Orchestrator` method that made the request, e.g. `get_proof_task`
+    pub call: String,
+    pub method: String,
+    pub endpoint: String,
+    /// Hex-encoded protobuf request body, if any
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub request: String,
+    /// HTTP status, or `None` if no response arrived
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Hex-encoded response body
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub response: String,
+    /// Why no response arrived, e.g. a timeout
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_ms: u64,
+}
+
+impl Exchange {
+    pub fn request_bytes(&self) -> Result<Vec<u8>, String> {
+        from_hex(&self.request)
+    }
+
+    pub fn response_bytes(&self) -> Result<Vec<u8>, String> {
+        from_hex(&self.response)
+    }
+
+    /// Remove wallet addresses and credentials before the exchange is written
+    fn redact(&mut self) {
+        for (name, value) in self.headers.iter_mut() {
+            if SECRET_HEADERS.contains(&name.as_str()) {
+                *value = REDACTED.to_string();
+            }
+        }
+        match self.call.as_str() {
+            "get_user" => {
+                // The wallet is the last segment of the endpoint, and so of the URL a
+                // failed request's error names
+                let wallet = self.endpoint.rsplit('/').next().unwrap_or_default();
+                if let (false, Some(error)) = (wallet.is_empty(), self.error.as_mut()) {
+                    *error = error.replace(wallet, REDACTED);
+                }
+                self.endpoint = format!("v3/users/{}", REDACTED);
+                self.response = redact_body(&self.response, |response: &mut UserResponse| {
+                    response.wallet_address = REDACTED.to_string();
+                });
+            }
+            "register_user" => {
+                self.request = redact_body(&self.request, |request: &mut RegisterUserRequest| {
+                    request.wallet_address = REDACTED.to_string();
+                });
+            }
+            "get_node" => {
+                self.response = redact_body(&self.response, |response: &mut GetNodeResponse| {
+                    response.wallet_address = REDACTED.to_string();
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Re-encode a hex protobuf body after `redact` has edited it; bodies that don't decode are dropped
+fn redact_body<T: Message + Default>(body: &str, redact: impl FnOnce(&mut T)) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+    match from_hex(body)
+        .ok()
+        .and_then(|bytes| T::decode(&bytes[..]).ok())
+    {
+        Some(mut message) => {
+            redact(&mut message);
+            to_hex(&message.encode_to_vec())
+        }
+        None => String::new(),
+    }
+}
+
+/// Appends exchanges to a recording
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    /// Start a recording in `dir`, which must not already hold one
+    pub fn create(dir: &Path) -> io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = recording_path(dir);
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::AlreadyExists => io::Error::new(
+                    e.kind(),
+                    format!("{} already holds a recording", dir.display()),
+                ),
+                _ => e,
+            })?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Redact and append `exchange`
+    pub fn record(&self, mut exchange: Exchange) -> io::Result<()> {
+        exchange.redact();
+        let mut line = serde_json::to_string(&exchange)?;
+        line.push('\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(line.as_bytes())
+    }
+}
+
+/// The recording file in `dir`
+pub fn recording_path(dir: &Path) -> PathBuf {
+    dir.join(RECORDING_FILE_NAME)
+}
+
+/// Every exchange recorded in `dir`, in the order they were made
+pub fn load(dir: &Path) -> io::Result<Vec<Exchange>> {
+    let file = File::open(recording_path(dir))?;
+    let mut exchanges = Vec::new();
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let invalid = |e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", number + 1, e),
+            )
+        };
+        let exchange: Exchange = serde_json::from_str(&line).map_err(|e| invalid(e.to_string()))?;
+        // Checked here so that replaying can take the bodies as given
+        exchange.request_bytes().map_err(invalid)?;
+        exchange.response_bytes().map_err(invalid)?;
+        exchanges.push(exchange);
+    }
+    Ok(exchanges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(call: &str, endpoint: &str) -> Exchange {
+        Exchange {
+            call: call.to_string(),
+            method: "GET".to_string(),
+            endpoint: endpoint.to_string(),
+            request: String::new(),
+            status: Some(200),
+            headers: BTreeMap::new(),
+            response: String::new(),
+            error: None,
+            started_at: Utc::now(),
+            elapsed_ms: 12,
+        }
+    }
+
+    #[test]
+    fn test_redacts_wallets_and_credentials() {
+        let wallet = "0x1234567890abcdef1234567890abcdef12345678";
+        let mut get_node = exchange("get_node", "v3/nodes/42");
+        get_node.response = to_hex(
+            &GetNodeResponse {
+                wallet_address: wallet.to_string(),
+            }
+            .encode_to_vec(),
+        );
+        get_node
+            .headers
+            .insert("set-cookie".to_string(), "session=secret".to_string());
+        get_node.headers.insert(
+            "content-type".to_string(),
+            "application/octet-stream".to_string(),
+        );
+        let mut get_user = exchange("get_user", &format!("v3/users/{}", wallet));
+        get_user.status = Some(404);
+
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Recorder::create(dir.path()).unwrap();
+        recorder.record(get_node).unwrap();
+        recorder.record(get_user).unwrap();
+        // A second recording can't clobber the first
+        assert!(Recorder::create(dir.path()).is_err());
+
+        let contents = std::fs::read_to_string(recorder.path()).unwrap();
+        assert!(!contents.contains(wallet));
+        assert!(!contents.contains(&to_hex(wallet.as_bytes())));
+        assert!(!contents.contains("secret"));
+
+        let exchanges = load(dir.path()).unwrap();
+        assert_eq!(exchanges.len(), 2);
+        let node = GetNodeResponse::decode(&exchanges[0].response_bytes().unwrap()[..]).unwrap();
+        assert_eq!(node.wallet_address, REDACTED);
+        assert_eq!(
+            exchanges[0].headers["content-type"],
+            "application/octet-stream"
+        );
+        assert_eq!(exchanges[1].endpoint, "v3/users/<redacted>");
+        assert_eq!(exchanges[1].status, Some(404));
+    }
+
+    #[tokio::test]
+    async fn test_redacts_wallet_from_failed_request() {
+        use crate::environment::Environment;
+        use crate::orchestrator::{Orchestrator, OrchestratorClient};
+        use std::sync::Arc;
+
+        let wallet = "0x1234567890abcdef1234567890abcdef12345678";
+        // Nothing listens on the port once the listener is dropped
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = Arc::new(Recorder::create(dir.path()).unwrap());
+        let client = OrchestratorClient::new(Environment::Custom {
+            orchestrator_url: url,
+            task_keys: Vec::new(),
+        })
+        .with_recorder(recorder.clone());
+        assert!(client.get_user(wallet).await.is_err());
+
+        let contents = std::fs::read_to_string(recorder.path()).unwrap();
+        assert!(!contents.contains(wallet));
+        let exchanges = load(dir.path()).unwrap();
+        assert_eq!(exchanges[0].status, None);
+        assert!(exchanges[0].error.as_ref().unwrap().contains(REDACTED));
+    }
+}
@@ -0,0 +1,463 @@
+//! An `Orchestrator` that answers from a recording instead of the network
+//!
+//! Each call takes the next exchange recorded for the same method, so the
+//! worker sees the tasks and errors of the recorded session in the same order,
+//! however its requests are interleaved.
+
+use crate::environment::Environment;
+use crate::nexus_orchestrator::{
+    AbandonReason, AbandonTaskRequest, BatchedSubmission, GetNodeResponse, GetProofTaskResponse,
+    GetTaskProgressResponse, GetTasksResponse, InputProof, InputSubmission, RegisterNodeResponse,
+    RejectedSubmission, SubmissionBatch, SubmitInputProofRequest, SubmitProofBatchRequest,
+    SubmitProofBatchResponse, SubmitProofRequest, TaskAbandonment, UserResponse,
+};
+use crate::orchestrator::Orchestrator;
+use crate::orchestrator::client::{
+    AdvertisedBudget, ProofTaskResult, TaskPage, proof_task_result, task_page,
+};
+use crate::orchestrator::error::{OrchestratorError, RateLimit};
+use crate::orchestrator::recording::Exchange;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use prost::Message;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    /// Exchanges not yet replayed, by call
+    pending: HashMap<String, VecDeque<Exchange>>,
+    /// Where the replayed worker didn't do what the recorded one did
+    divergences: Vec<String>,
+}
+
+/// Replays recorded orchestrator responses; clones share the same recording
+#[derive(Debug, Clone)]
+pub struct ReplayOrchestrator {
+    environment: Environment,
+    state: Arc<Mutex<ReplayState>>,
+    /// Notified once there are no more tasks to fetch
+    exhausted: Arc<Notify>,
+    /// Budget advertised by the last successful recorded response, until adopted
+    advertised: AdvertisedBudget,
+}
+
+impl ReplayOrchestrator {
+    pub fn new(environment: Environment, exchanges: Vec<Exchange>) -> Self {
+        let mut state = ReplayState::default();
+        for exchange in exchanges {
+            state
+                .pending
+                .entry(exchange.call.clone())
+                .or_default()
+                .push_back(exchange);
+        }
+        Self {
+            environment,
+            state: Arc::new(Mutex::new(state)),
+            exhausted: Arc::new(Notify::new()),
+            advertised: AdvertisedBudget::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ReplayState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Wait until every recorded task has been handed out
+    pub async fn exhausted(&self) {
+        self.exhausted.notified().await
+    }
+
+    /// Exchanges left to replay
+    pub fn remaining(&self) -> usize {
+        self.lock().pending.values().map(VecDeque::len).sum()
+    }
+
+    /// Requests that differed from the recorded ones, in order
+    pub fn divergences(&self) -> Vec<String> {
+        self.lock().divergences.clone()
+    }
+
+    fn next(&self, call: &str) -> Result<Exchange, OrchestratorError> {
+        let next = self
+            .lock()
+            .pending
+            .get_mut(call)
+            .and_then(VecDeque::pop_front);
+        next.ok_or_else(|| {
+            if call == "get_proof_task" {
+                self.exhausted.notify_one();
+            }
+            OrchestratorError::ReplayExhausted(call.to_string())
+        })
+    }
+
+    /// The next recorded response body for `call`, or the error that came back instead
+    fn respond(&self, call: &str) -> Result<(Exchange, Vec<u8>), OrchestratorError> {
+        let exchange = self.next(call)?;
+        // Bodies were checked when the recording was loaded
+        let body = exchange.response_bytes().unwrap_or_default();
+        match (&exchange.error, exchange.status) {
+            (Some(error), _) => Err(OrchestratorError::Recorded(error.clone())),
+            (None, Some(status)) if !(200..300).contains(&status) => {
+                Err(OrchestratorError::from_parts(
+                    status,
+                    exchange.headers.clone().into_iter().collect(),
+                    &body,
+                ))
+            }
+            _ => {
+                // Timed from when it was recorded, as the recording client timed it
+                let headers = exchange.headers.clone().into_iter().collect();
+                self.advertised
+                    .set(RateLimit::from_headers(&headers, exchange.started_at));
+                Ok((exchange, body))
+            }
+        }
+    }
+
+    fn decode<T: Message + Default>(&self, call: &str) -> Result<T, OrchestratorError> {
+        let (_, body) = self.respond(call)?;
+        T::decode(&body[..]).map_err(OrchestratorError::Decode)
+    }
+}
+
+#[async_trait::async_trait]
+impl Orchestrator for ReplayOrchestrator {
+    fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    fn take_rate_limit(&self) -> Option<RateLimit> {
+        self.advertised.take()
+    }
+
+    async fn get_user(&self, _wallet_address: &str) -> Result<String, OrchestratorError> {
+        let response: UserResponse = self.decode("get_user")?;
+        Ok(response.user_id)
+    }
+
+    async fn register_user(
+        &self,
+        _user_id: &str,
+        _wallet_address: &str,
+    ) -> Result<(), OrchestratorError> {
+        self.respond("register_user").map(|_| ())
+    }
+
+    async fn register_node(&self, _user_id: &str) -> Result<String, OrchestratorError> {
+        let response: RegisterNodeResponse = self.decode("register_node")?;
+        Ok(response.node_id)
+    }
+
+    async fn get_node(&self, _node_id: &str) -> Result<String, OrchestratorError> {
+        let response: GetNodeResponse = self.decode("get_node")?;
+        Ok(response.wallet_address)
+    }
+
+    async fn get_proof_task(
+        &self,
+        _node_id: &str,
+        _verifying_key: VerifyingKey,
+        _max_difficulty: crate::nexus_orchestrator::TaskDifficulty,
+        _capabilities: crate::nexus_orchestrator::ClientCapabilities,
+        _max_tasks: u32,
+    ) -> Result<ProofTaskResult, OrchestratorError> {
+        let response: GetProofTaskResponse = self.decode("get_proof_task")?;
+        proof_task_result(&response, &self.environment)
+    }
+
+    async fn get_tasks(
+        &self,
+        _node_id: &str,
+        _next_cursor: &str,
+    ) -> Result<TaskPage, OrchestratorError> {
+        let response: GetTasksResponse = self.decode("get_tasks")?;
+        task_page(&response, &self.environment)
+    }
+
+    async fn submit_proof(
+        &self,
+        _node_id: &str,
+        task_id: &str,
+        proof_hash: &str,
+        _proof: Vec<u8>,
+        _proofs: Vec<Vec<u8>>,
+        _signing_key: SigningKey,
+        _num_provers: usize,
+        _task_type: crate::nexus_orchestrator::TaskType,
+        _individual_proof_hashes: &[String],
+    ) -> Result<(), OrchestratorError> {
+        // Check the proof against the recorded submission before answering as the server did
+        let recorded = self
+            .lock()
+            .pending
+            .get("submit_proof")
+            .and_then(|queue| queue.front())
+            .and_then(|exchange| exchange.request_bytes().ok())
+            .and_then(|bytes| SubmitProofRequest::decode(&bytes[..]).ok());
+        if let Some(recorded) = recorded {
+            if recorded.task_id != task_id || recorded.proof_hash != proof_hash {
+                self.lock().divergences.push(format!(
+                    "Submitted {} with hash {}, but the recording submitted {} with hash {}",
+                    task_id, proof_hash, recorded.task_id, recorded.proof_hash
+                ));
+            }
+        }
+        self.respond("submit_proof").map(|_| ())
+    }
+
+    async fn submit_proof_batch(
+        &self,
+        _node_id: &str,
+        submissions: &[BatchedSubmission],
+        _signing_key: SigningKey,
+        _num_provers: usize,
+    ) -> Result<Vec<RejectedSubmission>, OrchestratorError> {
+        // Check the proofs against the recorded batch before answering as the server did
+        let recorded = self
+            .lock()
+            .pending
+            .get("submit_proof_batch")
+            .and_then(|queue| queue.front())
+            .and_then(|exchange| exchange.request_bytes().ok())
+            .and_then(|bytes| SubmitProofBatchRequest::decode(&bytes[..]).ok())
+            .and_then(|request| SubmissionBatch::decode(&request.batch[..]).ok());
+        if let Some(recorded) = recorded {
+            let summary = |submissions: &[BatchedSubmission]| {
+                submissions
+                    .iter()
+                    .map(|submission| format!("{} ({})", submission.task_id, submission.proof_hash))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            if recorded.submissions != submissions {
+                self.lock().divergences.push(format!(
+                    "Submitted batch {}, but the recording submitted {}",
+                    summary(submissions),
+                    summary(&recorded.submissions)
+                ));
+            }
+        }
+        let response: SubmitProofBatchResponse = self.decode("submit_proof_batch")?;
+        Ok(response.rejected)
+    }
+
+    async fn submit_input_proof(
+        &self,
+        _node_id: &str,
+        task_id: &str,
+        input_index: u32,
+        proof_hash: &str,
+        _proof: Vec<u8>,
+        _signing_key: SigningKey,
+    ) -> Result<(), OrchestratorError> {
+        // Check the input against the recorded one before answering as the server did
+        let recorded = self
+            .lock()
+            .pending
+            .get("submit_input_proof")
+            .and_then(|queue| queue.front())
+            .and_then(|exchange| exchange.request_bytes().ok())
+            .and_then(|bytes| SubmitInputProofRequest::decode(&bytes[..]).ok())
+            .and_then(|request| InputSubmission::decode(&request.input[..]).ok());
+        if let Some(recorded) = recorded {
+            if (
+                recorded.task_id.as_str(),
+                recorded.input_index,
+                recorded.proof_hash.as_str(),
+            ) != (task_id, input_index, proof_hash)
+            {
+                self.lock().divergences.push(format!(
+                    "Submitted input {} of {} with hash {}, but the recording submitted input {} of {} with hash {}",
+                    input_index,
+                    task_id,
+                    proof_hash,
+                    recorded.input_index,
+                    recorded.task_id,
+                    recorded.proof_hash
+                ));
+            }
+        }
+        self.respond("submit_input_proof").map(|_| ())
+    }
+
+    async fn get_task_progress(
+        &self,
+        _node_id: &str,
+        _task_id: &str,
+    ) -> Result<Vec<InputProof>, OrchestratorError> {
+        let response: GetTaskProgressResponse = self.decode("get_task_progress")?;
+        Ok(response.received_inputs)
+    }
+
+    async fn finalize_task(
+        &self,
+        _node_id: &str,
+        task_id: &str,
+        _task_type: crate::nexus_orchestrator::TaskType,
+        proof_hash: &str,
+        _all_proof_hashes: &[String],
+        _signing_key: SigningKey,
+        _num_provers: usize,
+    ) -> Result<(), OrchestratorError> {
+        let recorded = self
+            .lock()
+            .pending
+            .get("finalize_task")
+            .and_then(|queue| queue.front())
+            .and_then(|exchange| exchange.request_bytes().ok())
+            .and_then(|bytes| SubmitProofRequest::decode(&bytes[..]).ok());
+        if let Some(recorded) = recorded {
+            if recorded.task_id != task_id || recorded.proof_hash != proof_hash {
+                self.lock().divergences.push(format!(
+                    "Finalized {} with hash {}, but the recording finalized {} with hash {}",
+                    task_id, proof_hash, recorded.task_id, recorded.proof_hash
+                ));
+            }
+        }
+        self.respond("finalize_task").map(|_| ())
+    }
+
+    async fn abandon_task(
+        &self,
+        _node_id: &str,
+        task_id: &str,
+        reason: AbandonReason,
+        _detail: &str,
+        _signing_key: SigningKey,
+    ) -> Result<(), OrchestratorError> {
+        let recorded = self
+            .lock()
+            .pending
+            .get("abandon_task")
+            .and_then(|queue| queue.front())
+            .and_then(|exchange| exchange.request_bytes().ok())
+            .and_then(|bytes| AbandonTaskRequest::decode(&bytes[..]).ok())
+            .and_then(|request| TaskAbandonment::decode(&request.abandonment[..]).ok());
+        if let Some(recorded) = recorded {
+            if recorded.task_id != task_id || recorded.reason != reason as i32 {
+                let recorded_reason = AbandonReason::try_from(recorded.reason)
+                    .unwrap_or_default()
+                    .as_str_name();
+                self.lock().divergences.push(format!(
+                    "Abandoned {} ({}), but the recording abandoned {} ({})",
+                    task_id,
+                    reason.as_str_name(),
+                    recorded.task_id,
+                    recorded_reason
+                ));
+            }
+        }
+        self.respond("abandon_task").map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::to_hex;
+    use crate::nexus_orchestrator::{TaskDifficulty, TaskType};
+    use chrono::Utc;
+
+    fn exchange(call: &str, status: Option<u16>, response: &[u8]) -> Exchange {
+        Exchange {
+            call: call.to_string(),
+            method: "POST".to_string(),
+            endpoint: "v3/tasks".to_string(),
+            request: String::new(),
+            status,
+            headers: [("retry-after".to_string(), "7".to_string())].into(),
+            response: to_hex(response),
+            error: status.is_none().then(|| "operation timed out".to_string()),
+            started_at: Utc::now(),
+            elapsed_ms: 5,
+        }
+    }
+
+    fn task_response(task_id: &str) -> Vec<u8> {
+        GetProofTaskResponse {
+            task: Some(crate::nexus_orchestrator::Task {
+                task_id: task_id.to_string(),
+                program_id: "fib_input_initial".to_string(),
+                public_inputs_list: vec![vec![1, 2, 3]],
+                task_type: TaskType::ProofHash as i32,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_replays_tasks_and_errors_in_order() {
+        let mut submission = exchange("submit_proof", Some(200), &[]);
+        submission.request = to_hex(
+            &SubmitProofRequest {
+                task_id: "a".to_string(),
+                proof_hash: "recorded".to_string(),
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        );
+        let replay = ReplayOrchestrator::new(
+            Environment::Production,
+            vec![
+                exchange("get_proof_task", None, &[]),
+                exchange("get_proof_task", Some(429), b"slow down"),
+                exchange("get_proof_task", Some(200), &task_response("a")),
+                submission,
+            ],
+        );
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let fetch = || {
+            replay.get_proof_task(
+                "1",
+                key.verifying_key(),
+                TaskDifficulty::Small,
+                Default::default(),
+                1,
+            )
+        };
+
+        assert!(matches!(
+            fetch().await,
+            Err(OrchestratorError::Recorded(message)) if message == "operation timed out"
+        ));
+        match fetch().await {
+            Err(error @ OrchestratorError::Http { status: 429, .. }) => {
+                assert_eq!(
+                    error.retry_after(Utc::now()),
+                    Some(std::time::Duration::from_secs(7))
+                );
+            }
+            other => panic!("expected a recorded 429, got {:?}", other),
+        }
+        assert_eq!(fetch().await.unwrap().task.task_id, "a");
+
+        replay
+            .submit_proof(
+                "1",
+                "a",
+                "replayed",
+                Vec::new(),
+                Vec::new(),
+                key.clone(),
+                1,
+                TaskType::ProofHash,
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(replay.divergences().len(), 1);
+        assert_eq!(replay.remaining(), 0);
+
+        // Running out of tasks is reported to whoever is waiting for the end
+        assert!(matches!(
+            fetch().await,
+            Err(OrchestratorError::ReplayExhausted(_))
+        ));
+        replay.exhausted().await;
+    }
+}
@@ -0,0 +1,458 @@
+//! Signatures over proof submissions
+//!
+//! Version 0 signs `"0 | {task_id} | {proof_hash}"`, which is all older orchestrators check.
+//! Version 1 signs a domain-separated, protobuf-encoded `SubmissionAttestation` that also
+//! covers the node, the task type, the individual proof hashes, a nonce and an expiry.
+//! Batches, per-input submissions and task abandonments are signed the same way as
+//! version 1, each under its own domain.
+
+use crate::consts::cli_consts::submission_signing;
+use crate::nexus_orchestrator::{
+    AbandonReason, BatchedSubmission, InputSubmission, SubmissionAttestation, SubmissionBatch,
+    TaskAbandonment, TaskType,
+};
+use ed25519_dalek::{Signer, SigningKey};
+use prost::Message;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Scheme a submission is signed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum SignatureVersion {
+    /// `"0 | {task_id} | {proof_hash}"`
+    #[default]
+    V0 = 0,
+    /// Domain-separated `SubmissionAttestation`
+    V1 = 1,
+}
+
+impl SignatureVersion {
+    /// The newest version both sides support, given the versions the orchestrator advertised
+    pub fn negotiate(server_versions: &[u32]) -> Self {
+        if server_versions.contains(&(SignatureVersion::V1 as u32)) {
+            SignatureVersion::V1
+        } else {
+            SignatureVersion::V0
+        }
+    }
+}
+
+/// What a submission claims, as far as its signature is concerned
+pub struct SubmissionClaim<'a> {
+    pub task_id: &'a str,
+    pub node_id: &'a str,
+    pub task_type: TaskType,
+    pub proof_hash: &'a str,
+    /// The individual proof hashes as sent, so empty unless the task type sends them
+    pub all_proof_hashes: &'a [String],
+    /// Whether this finalizes a task whose inputs were submitted one by one
+    pub incremental: bool,
+}
+
+impl SubmissionClaim<'_> {
+    /// The attestation signed for this claim under version 1
+    pub fn attestation(
+        &self,
+        nonce: [u8; submission_signing::NONCE_LEN],
+        expires_at: SystemTime,
+    ) -> SubmissionAttestation {
+        SubmissionAttestation {
+            task_id: self.task_id.to_string(),
+            node_id: self.node_id.to_string(),
+            task_type: self.task_type as i32,
+            proof_hash: self.proof_hash.to_string(),
+            all_proof_hashes: self.all_proof_hashes.to_vec(),
+            nonce: nonce.to_vec(),
+            expires_at: expires_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            incremental: self.incremental,
+        }
+    }
+}
+
+/// The signature fields of a `SubmitProofRequest`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmissionSignature {
+    pub version: SignatureVersion,
+    pub signature: Vec<u8>,
+    pub public_key: Vec<u8>,
+    /// The encoded attestation that was signed; empty under version 0
+    pub attestation: Vec<u8>,
+}
+
+impl SubmissionSignature {
+    /// Sign `claim` under `version`, with a fresh nonce and expiry under version 1
+    pub fn sign(
+        signing_key: &SigningKey,
+        version: SignatureVersion,
+        claim: &SubmissionClaim,
+    ) -> Self {
+        match version {
+            SignatureVersion::V0 => Self::sign_v0(signing_key, claim.task_id, claim.proof_hash),
+            SignatureVersion::V1 => {
+                let expires_at = SystemTime::now() + submission_signing::attestation_ttl();
+                Self::sign_v1(signing_key, &claim.attestation(rand::random(), expires_at))
+            }
+        }
+    }
+
+    fn sign_v0(signing_key: &SigningKey, task_id: &str, proof_hash: &str) -> Self {
+        let msg = format!(
+            "{} | {} | {}",
+            SignatureVersion::V0 as u32,
+            task_id,
+            proof_hash
+        );
+        Self {
+            version: SignatureVersion::V0,
+            signature: signing_key.sign(msg.as_bytes()).to_bytes().to_vec(),
+            public_key: signing_key.verifying_key().to_bytes().to_vec(),
+            attestation: Vec::new(),
+        }
+    }
+
+    fn sign_v1(signing_key: &SigningKey, attestation: &SubmissionAttestation) -> Self {
+        let attestation = attestation.encode_to_vec();
+        Self {
+            version: SignatureVersion::V1,
+            signature: signing_key
+                .sign(&signed_bytes(&attestation))
+                .to_bytes()
+                .to_vec(),
+            public_key: signing_key.verifying_key().to_bytes().to_vec(),
+            attestation,
+        }
+    }
+}
+
+/// The bytes signed under version 1: the domain tag, then the encoded attestation
+pub fn signed_bytes(attestation: &[u8]) -> Vec<u8> {
+    [submission_signing::DOMAIN, attestation].concat()
+}
+
+/// The signature fields of a `SubmitProofBatchRequest`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchSignature {
+    /// The encoded `SubmissionBatch` that was signed
+    pub batch: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+impl BatchSignature {
+    /// Sign `submissions` for `node_id`, with a fresh nonce and expiry
+    pub fn sign(
+        signing_key: &SigningKey,
+        node_id: &str,
+        submissions: &[BatchedSubmission],
+    ) -> Self {
+        let expires_at = SystemTime::now() + submission_signing::attestation_ttl();
+        Self::sign_batch(
+            signing_key,
+            &SubmissionBatch {
+                node_id: node_id.to_string(),
+                submissions: submissions.to_vec(),
+                nonce: rand::random::<[u8; submission_signing::NONCE_LEN]>().to_vec(),
+                expires_at: expires_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            },
+        )
+    }
+
+    fn sign_batch(signing_key: &SigningKey, batch: &SubmissionBatch) -> Self {
+        let batch = batch.encode_to_vec();
+        Self {
+            signature: signing_key
+                .sign(&batch_signed_bytes(&batch))
+                .to_bytes()
+                .to_vec(),
+            public_key: signing_key.verifying_key().to_bytes().to_vec(),
+            batch,
+        }
+    }
+}
+
+/// The bytes signed for a batch: the batch domain tag, then the encoded batch
+pub fn batch_signed_bytes(batch: &[u8]) -> Vec<u8> {
+    [submission_signing::BATCH_DOMAIN, batch].concat()
+}
+
+/// The signature fields of a `SubmitInputProofRequest`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputSignature {
+    /// The encoded `InputSubmission` that was signed
+    pub input: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+impl InputSignature {
+    /// Sign the proof hash of input `input_index` of a task, with a fresh nonce and expiry
+    pub fn sign(
+        signing_key: &SigningKey,
+        node_id: &str,
+        task_id: &str,
+        input_index: u32,
+        proof_hash: &str,
+    ) -> Self {
+        let expires_at = SystemTime::now() + submission_signing::attestation_ttl();
+        let input = InputSubmission {
+            task_id: task_id.to_string(),
+            node_id: node_id.to_string(),
+            input_index,
+            proof_hash: proof_hash.to_string(),
+            nonce: rand::random::<[u8; submission_signing::NONCE_LEN]>().to_vec(),
+            expires_at: expires_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+        .encode_to_vec();
+        Self {
+            signature: signing_key
+                .sign(&input_signed_bytes(&input))
+                .to_bytes()
+                .to_vec(),
+            public_key: signing_key.verifying_key().to_bytes().to_vec(),
+            input,
+        }
+    }
+}
+
+/// The bytes signed for one input: the input domain tag, then the encoded submission
+pub fn input_signed_bytes(input: &[u8]) -> Vec<u8> {
+    [submission_signing::INPUT_DOMAIN, input].concat()
+}
+
+/// The signature fields of an `AbandonTaskRequest`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbandonmentSignature {
+    /// The encoded `TaskAbandonment` that was signed
+    pub abandonment: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+impl AbandonmentSignature {
+    /// Sign giving up `task_id` for `reason`, with a fresh nonce and expiry
+    pub fn sign(
+        signing_key: &SigningKey,
+        node_id: &str,
+        task_id: &str,
+        reason: AbandonReason,
+        detail: &str,
+    ) -> Self {
+        let expires_at = SystemTime::now() + submission_signing::attestation_ttl();
+        let abandonment = TaskAbandonment {
+            task_id: task_id.to_string(),
+            node_id: node_id.to_string(),
+            reason: reason as i32,
+            detail: detail.to_string(),
+            nonce: rand::random::<[u8; submission_signing::NONCE_LEN]>().to_vec(),
+            expires_at: expires_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+        .encode_to_vec();
+        Self {
+            signature: signing_key
+                .sign(&abandon_signed_bytes(&abandonment))
+                .to_bytes()
+                .to_vec(),
+            public_key: signing_key.verifying_key().to_bytes().to_vec(),
+            abandonment,
+        }
+    }
+}
+
+/// The bytes signed for an abandonment: the abandonment domain tag, then the encoded abandonment
+pub fn abandon_signed_bytes(abandonment: &[u8]) -> Vec<u8> {
+    [submission_signing::ABANDON_DOMAIN, abandonment].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::to_hex;
+    use ed25519_dalek::{Signature, Verifier};
+    use std::time::Duration;
+
+    // Known-answer vectors, so the orchestrator can check its verifier against this client
+    const PUBLIC_KEY: &str = "ea4a6c63e29c520abef5507b132ec5f9954776aebebe7b92421eea691446d22c";
+    const V0_SIGNATURE: &str = "741eadf690b00718bb7ec58415c3b4c7bc691f75be74008f75f8a5db91499a2e154880a837be458e89b772e4bd057f24acd25599d71af1c9c09facb6468f510e";
+    const V1_ATTESTATION: &str = "0a067461736b2d3112053132333435180222066162633132332a0268312a0268323210090909090909090909090909090909093880c3bbc206";
+    const V1_SIGNATURE: &str = "5a5de642b38b91663ea1b5c3043e11cfe0113eb025dacbebe40a3eebf2c758a8f68d9614c6be172d482ea4097d8c32ffc419459e617018cae3fba10ec30c3905";
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn claim(all_proof_hashes: &[String]) -> SubmissionClaim<'_> {
+        SubmissionClaim {
+            task_id: "task-1",
+            node_id: "12345",
+            task_type: TaskType::AllProofHashes,
+            proof_hash: "abc123",
+            all_proof_hashes,
+            incremental: false,
+        }
+    }
+
+    #[test]
+    fn test_v0_known_answer() {
+        let hashes = ["h1".to_string(), "h2".to_string()];
+        let signed = SubmissionSignature::sign(&key(), SignatureVersion::V0, &claim(&hashes));
+        assert_eq!(signed.version, SignatureVersion::V0);
+        assert_eq!(to_hex(&signed.public_key), PUBLIC_KEY);
+        assert_eq!(to_hex(&signed.signature), V0_SIGNATURE);
+        assert!(signed.attestation.is_empty());
+    }
+
+    #[test]
+    fn test_v1_known_answer() {
+        let hashes = ["h1".to_string(), "h2".to_string()];
+        let expires_at = UNIX_EPOCH + Duration::from_secs(1_750_000_000);
+        let attestation = claim(&hashes).attestation([9; 16], expires_at);
+        let signed = SubmissionSignature::sign_v1(&key(), &attestation);
+        assert_eq!(signed.version, SignatureVersion::V1);
+        assert_eq!(to_hex(&signed.public_key), PUBLIC_KEY);
+        assert_eq!(to_hex(&signed.attestation), V1_ATTESTATION);
+        assert_eq!(to_hex(&signed.signature), V1_SIGNATURE);
+    }
+
+    #[test]
+    fn test_v1_is_fresh_and_domain_separated() {
+        let hashes = ["h1".to_string()];
+        let first = SubmissionSignature::sign(&key(), SignatureVersion::V1, &claim(&hashes));
+        let second = SubmissionSignature::sign(&key(), SignatureVersion::V1, &claim(&hashes));
+        assert_ne!(first.attestation, second.attestation);
+
+        let attestation = SubmissionAttestation::decode(&first.attestation[..]).unwrap();
+        assert_eq!(attestation.node_id, "12345");
+        assert_eq!(attestation.all_proof_hashes, hashes);
+        assert_eq!(attestation.nonce.len(), submission_signing::NONCE_LEN);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        assert!(attestation.expires_at > now.as_secs());
+
+        // Only the tagged bytes verify, not the bare attestation
+        let signature = Signature::from_slice(&first.signature).unwrap();
+        let verifying_key = key().verifying_key();
+        assert!(
+            verifying_key
+                .verify(&signed_bytes(&first.attestation), &signature)
+                .is_ok()
+        );
+        assert!(
+            verifying_key
+                .verify(&first.attestation, &signature)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_batch_is_domain_separated() {
+        let submissions = [BatchedSubmission {
+            task_id: "task-1".to_string(),
+            task_type: TaskType::ProofHash as i32,
+            proof_hash: "abc123".to_string(),
+            all_proof_hashes: Vec::new(),
+        }];
+        let signed = BatchSignature::sign(&key(), "12345", &submissions);
+        assert_eq!(to_hex(&signed.public_key), PUBLIC_KEY);
+
+        let batch = SubmissionBatch::decode(&signed.batch[..]).unwrap();
+        assert_eq!(batch.node_id, "12345");
+        assert_eq!(batch.submissions, submissions);
+        assert_eq!(batch.nonce.len(), submission_signing::NONCE_LEN);
+
+        // A batch signature can't pass for a single attestation, or the other way around
+        let signature = Signature::from_slice(&signed.signature).unwrap();
+        let verifying_key = key().verifying_key();
+        assert!(
+            verifying_key
+                .verify(&batch_signed_bytes(&signed.batch), &signature)
+                .is_ok()
+        );
+        assert!(
+            verifying_key
+                .verify(&signed_bytes(&signed.batch), &signature)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_input_is_domain_separated() {
+        let signed = InputSignature::sign(&key(), "12345", "task-1", 3, "abc123");
+        assert_eq!(to_hex(&signed.public_key), PUBLIC_KEY);
+
+        let input = InputSubmission::decode(&signed.input[..]).unwrap();
+        assert_eq!(input.task_id, "task-1");
+        assert_eq!(input.input_index, 3);
+        assert_eq!(input.proof_hash, "abc123");
+        assert_eq!(input.nonce.len(), submission_signing::NONCE_LEN);
+
+        // An input signature can't pass for a whole task's, or for a batch
+        let signature = Signature::from_slice(&signed.signature).unwrap();
+        let verifying_key = key().verifying_key();
+        assert!(
+            verifying_key
+                .verify(&input_signed_bytes(&signed.input), &signature)
+                .is_ok()
+        );
+        assert!(
+            verifying_key
+                .verify(&signed_bytes(&signed.input), &signature)
+                .is_err()
+        );
+        assert!(
+            verifying_key
+                .verify(&batch_signed_bytes(&signed.input), &signature)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_abandonment_is_domain_separated() {
+        let signed = AbandonmentSignature::sign(
+            &key(),
+            "12345",
+            "task-1",
+            AbandonReason::OutOfMemory,
+            "out of memory",
+        );
+        assert_eq!(to_hex(&signed.public_key), PUBLIC_KEY);
+
+        let abandonment = TaskAbandonment::decode(&signed.abandonment[..]).unwrap();
+        assert_eq!(abandonment.task_id, "task-1");
+        assert_eq!(abandonment.node_id, "12345");
+        assert_eq!(abandonment.reason, AbandonReason::OutOfMemory as i32);
+        assert_eq!(abandonment.nonce.len(), submission_signing::NONCE_LEN);
+
+        // Giving up a task can't pass for submitting it
+        let signature = Signature::from_slice(&signed.signature).unwrap();
+        let verifying_key = key().verifying_key();
+        assert!(
+            verifying_key
+                .verify(&abandon_signed_bytes(&signed.abandonment), &signature)
+                .is_ok()
+        );
+        assert!(
+            verifying_key
+                .verify(&signed_bytes(&signed.abandonment), &signature)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(SignatureVersion::negotiate(&[]), SignatureVersion::V0);
+        assert_eq!(SignatureVersion::negotiate(&[0]), SignatureVersion::V0);
+        assert_eq!(SignatureVersion::negotiate(&[0, 1]), SignatureVersion::V1);
+        // Versions this client doesn't know are ignored
+        assert_eq!(SignatureVersion::negotiate(&[2]), SignatureVersion::V0);
+    }
+}
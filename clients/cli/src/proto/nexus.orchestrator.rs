@@ -48,6 +48,15 @@ pub struct Task {
     /// the actual difficulty they're receiving vs what they requested.
     #[prost(enumeration = "TaskDifficulty", tag = "7")]
     pub difficulty: i32,
+    /// Optional Ed25519 signature by the orchestrator over the task ID, program ID,
+    /// legacy and current inputs, type, difficulty and deadline, so clients can
+    /// reject tasks it didn't issue.
+    #[prost(bytes = "vec", tag = "8")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// Optional time after which the orchestrator stops accepting the task's proof
+    /// and hands it to another node.
+    #[prost(message, optional, tag = "9")]
+    pub deadline: ::core::option::Option<::prost_types::Timestamp>,
 }
 /// Get outstanding tasks for a node.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -64,6 +73,9 @@ pub struct GetTasksResponse {
     pub tasks: ::prost::alloc::vec::Vec<Task>,
     #[prost(string, tag = "2")]
     pub next_cursor: ::prost::alloc::string::String,
+    /// Which tasks may be submitted input by input. Unset means none.
+    #[prost(message, optional, tag = "3")]
+    pub incremental_submission: ::core::option::Option<IncrementalSubmission>,
 }
 /// Request a prover task.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -80,6 +92,48 @@ pub struct GetProofTaskRequest {
     /// The maximum difficulty level the client wants to handle
     #[prost(enumeration = "TaskDifficulty", tag = "4")]
     pub max_difficulty: i32,
+    /// What the client can run, so tasks can be routed to it accordingly.
+    /// Older clients leave it unset.
+    #[prost(message, optional, tag = "5")]
+    pub capabilities: ::core::option::Option<ClientCapabilities>,
+    /// Most tasks the client wants at once, up to BatchLimits.max_tasks_per_fetch.
+    /// 0 or 1 means a single task.
+    #[prost(uint32, tag = "6")]
+    pub max_tasks: u32,
+}
+/// What a client is able to prove.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientCapabilities {
+    /// Version of the CLI, e.g. "0.10.17".
+    #[prost(string, tag = "1")]
+    pub cli_version: ::prost::alloc::string::String,
+    /// Programs the client has built in.
+    #[prost(string, repeated, tag = "2")]
+    pub program_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Task types the client can complete.
+    #[prost(enumeration = "TaskType", repeated, tag = "3")]
+    pub task_types: ::prost::alloc::vec::Vec<i32>,
+    /// Threads the client proves with.
+    #[prost(uint32, tag = "4")]
+    pub thread_budget: u32,
+    /// Memory available to the client in MB, within any cgroup limit.
+    #[prost(uint64, tag = "5")]
+    pub available_memory_mb: u64,
+    /// Recent throughput, for each difficulty the client has completed tasks at.
+    #[prost(message, repeated, tag = "6")]
+    pub throughput: ::prost::alloc::vec::Vec<DifficultyThroughput>,
+}
+/// How quickly a client completes tasks at one difficulty.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DifficultyThroughput {
+    #[prost(enumeration = "TaskDifficulty", tag = "1")]
+    pub difficulty: i32,
+    /// Mean time to prove and submit a task, in seconds.
+    #[prost(uint64, tag = "2")]
+    pub mean_task_secs: u64,
+    /// Number of recent tasks the mean is taken over.
+    #[prost(uint32, tag = "3")]
+    pub samples: u32,
 }
 /// A Prover task.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -101,6 +155,37 @@ pub struct GetProofTaskResponse {
     pub task_id: ::prost::alloc::string::String,
     #[prost(message, optional, tag = "4")]
     pub task: ::core::option::Option<Task>,
+    /// Submission signature versions the orchestrator accepts, see SubmitProofRequest.
+    /// Empty means only version 0.
+    #[prost(uint32, repeated, tag = "5")]
+    pub submission_signature_versions: ::prost::alloc::vec::Vec<u32>,
+    /// Tasks beyond `task`, when the request asked for more than one.
+    #[prost(message, repeated, tag = "6")]
+    pub additional_tasks: ::prost::alloc::vec::Vec<Task>,
+    /// How far the orchestrator supports batching. Unset means it doesn't.
+    #[prost(message, optional, tag = "7")]
+    pub batch_limits: ::core::option::Option<BatchLimits>,
+    /// Which tasks may be submitted input by input. Unset means none.
+    #[prost(message, optional, tag = "8")]
+    pub incremental_submission: ::core::option::Option<IncrementalSubmission>,
+}
+/// Batching an orchestrator supports.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct BatchLimits {
+    /// Most tasks returned for one GetProofTaskRequest.
+    #[prost(uint32, tag = "1")]
+    pub max_tasks_per_fetch: u32,
+    /// Most submissions accepted in one SubmitProofBatchRequest.
+    #[prost(uint32, tag = "2")]
+    pub max_submissions_per_batch: u32,
+}
+/// Per-input submission an orchestrator supports.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct IncrementalSubmission {
+    /// Tasks with at least this many inputs may have each input's proof submitted
+    /// with SubmitInputProofRequest as it completes.
+    #[prost(uint32, tag = "1")]
+    pub min_inputs: u32,
 }
 /// Submit the result of a prover task.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -138,6 +223,203 @@ pub struct SubmitProofRequest {
     /// To be sent on PROOF_REQUIRED tasks, empty on other task types.
     #[prost(bytes = "vec", repeated, tag = "10")]
     pub proofs: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// Scheme the signature was made with. Version 0 signs
+    /// "0 | task_id | proof_hash"; version 1 signs `attestation`.
+    #[prost(uint32, tag = "11")]
+    pub signature_version: u32,
+    /// Encoded SubmissionAttestation, signed under version 1. Empty under version 0.
+    #[prost(bytes = "vec", tag = "12")]
+    pub attestation: ::prost::alloc::vec::Vec<u8>,
+    /// Finalizes a task whose inputs were submitted with SubmitInputProofRequest.
+    /// `proof` and `proofs` are empty and `all_proof_hashes` lists every input's hash.
+    #[prost(bool, tag = "13")]
+    pub incremental: bool,
+}
+/// What a node signs when submitting a proof under signature version 1.
+/// The signed bytes are a domain-separation tag followed by this message's
+/// protobuf encoding, exactly as sent in SubmitProofRequest.attestation.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmissionAttestation {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub node_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "TaskType", tag = "3")]
+    pub task_type: i32,
+    #[prost(string, tag = "4")]
+    pub proof_hash: ::prost::alloc::string::String,
+    /// Same as SubmitProofRequest.all_proof_hashes.
+    #[prost(string, repeated, tag = "5")]
+    pub all_proof_hashes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Random bytes so that no two attestations are alike.
+    #[prost(bytes = "vec", tag = "6")]
+    pub nonce: ::prost::alloc::vec::Vec<u8>,
+    /// Unix time in seconds after which the submission must be rejected.
+    #[prost(uint64, tag = "7")]
+    pub expires_at: u64,
+    /// Same as SubmitProofRequest.incremental.
+    #[prost(bool, tag = "8")]
+    pub incremental: bool,
+}
+/// Submit the proof of one input of a task, as soon as it is verified.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitInputProofRequest {
+    /// The type of this node.
+    #[prost(enumeration = "NodeType", tag = "1")]
+    pub node_type: i32,
+    /// Encoded InputSubmission. The signature covers a domain-separation tag
+    /// followed by exactly these bytes.
+    #[prost(bytes = "vec", tag = "2")]
+    pub input: ::prost::alloc::vec::Vec<u8>,
+    /// The node's Ed25519 public key for this request.
+    #[prost(bytes = "vec", tag = "3")]
+    pub ed25519_public_key: ::prost::alloc::vec::Vec<u8>,
+    /// Signature of the tagged input with the matching private key.
+    #[prost(bytes = "vec", tag = "4")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// ZK proof of the input, sent only on PROOF_REQUIRED tasks.
+    #[prost(bytes = "vec", tag = "5")]
+    pub proof: ::prost::alloc::vec::Vec<u8>,
+}
+/// One input's result, and what binds it to a node and a time.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InputSubmission {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub node_id: ::prost::alloc::string::String,
+    /// Position of the input in Task.public_inputs_list.
+    #[prost(uint32, tag = "3")]
+    pub input_index: u32,
+    /// Hash of the input's proof.
+    #[prost(string, tag = "4")]
+    pub proof_hash: ::prost::alloc::string::String,
+    /// Random bytes so that no two submissions are alike.
+    #[prost(bytes = "vec", tag = "5")]
+    pub nonce: ::prost::alloc::vec::Vec<u8>,
+    /// Unix time in seconds after which the submission must be rejected.
+    #[prost(uint64, tag = "6")]
+    pub expires_at: u64,
+}
+/// Get which inputs of a task the orchestrator already has.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTaskProgressRequest {
+    #[prost(string, tag = "1")]
+    pub node_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub task_id: ::prost::alloc::string::String,
+}
+/// The inputs of a task received so far.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetTaskProgressResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub received_inputs: ::prost::alloc::vec::Vec<InputProof>,
+}
+/// An input the orchestrator has received a proof for.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InputProof {
+    #[prost(uint32, tag = "1")]
+    pub input_index: u32,
+    #[prost(string, tag = "2")]
+    pub proof_hash: ::prost::alloc::string::String,
+}
+/// Give up a task, so the orchestrator can reassign it without waiting for it to expire.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AbandonTaskRequest {
+    /// The type of this node.
+    #[prost(enumeration = "NodeType", tag = "1")]
+    pub node_type: i32,
+    /// Encoded TaskAbandonment. The signature covers a domain-separation tag
+    /// followed by exactly these bytes.
+    #[prost(bytes = "vec", tag = "2")]
+    pub abandonment: ::prost::alloc::vec::Vec<u8>,
+    /// The node's Ed25519 public key for this request.
+    #[prost(bytes = "vec", tag = "3")]
+    pub ed25519_public_key: ::prost::alloc::vec::Vec<u8>,
+    /// Signature of the tagged abandonment with the matching private key.
+    #[prost(bytes = "vec", tag = "4")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+/// Which task a node gives up, why, and what binds it to a node and a time.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskAbandonment {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub node_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "AbandonReason", tag = "3")]
+    pub reason: i32,
+    /// Human-readable detail, for the orchestrator's logs only.
+    #[prost(string, tag = "4")]
+    pub detail: ::prost::alloc::string::String,
+    /// Random bytes so that no two abandonments are alike.
+    #[prost(bytes = "vec", tag = "5")]
+    pub nonce: ::prost::alloc::vec::Vec<u8>,
+    /// Unix time in seconds after which the abandonment must be rejected.
+    #[prost(uint64, tag = "6")]
+    pub expires_at: u64,
+}
+/// Submit the results of several PROOF_HASH or ALL_PROOF_HASHES tasks at once.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitProofBatchRequest {
+    /// The type of this node.
+    #[prost(enumeration = "NodeType", tag = "1")]
+    pub node_type: i32,
+    /// Encoded SubmissionBatch. The signature covers a domain-separation tag
+    /// followed by exactly these bytes.
+    #[prost(bytes = "vec", tag = "2")]
+    pub batch: ::prost::alloc::vec::Vec<u8>,
+    /// The node's Ed25519 public key for this request.
+    #[prost(bytes = "vec", tag = "3")]
+    pub ed25519_public_key: ::prost::alloc::vec::Vec<u8>,
+    /// Signature of the tagged batch with the matching private key.
+    #[prost(bytes = "vec", tag = "4")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// Telemetry data about the node
+    #[prost(message, optional, tag = "5")]
+    pub node_telemetry: ::core::option::Option<NodeTelemetry>,
+}
+/// The submissions in a batch, and what binds them to a node and a time.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmissionBatch {
+    #[prost(string, tag = "1")]
+    pub node_id: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub submissions: ::prost::alloc::vec::Vec<BatchedSubmission>,
+    /// Random bytes so that no two batches are alike.
+    #[prost(bytes = "vec", tag = "3")]
+    pub nonce: ::prost::alloc::vec::Vec<u8>,
+    /// Unix time in seconds after which the batch must be rejected.
+    #[prost(uint64, tag = "4")]
+    pub expires_at: u64,
+}
+/// One task's result within a batch.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchedSubmission {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    #[prost(enumeration = "TaskType", tag = "2")]
+    pub task_type: i32,
+    #[prost(string, tag = "3")]
+    pub proof_hash: ::prost::alloc::string::String,
+    /// Hash of each individual proof, on ALL_PROOF_HASHES tasks only.
+    #[prost(string, repeated, tag = "4")]
+    pub all_proof_hashes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Outcome of a batch submission.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubmitProofBatchResponse {
+    /// Submissions that were not accepted; every other one was.
+    #[prost(message, repeated, tag = "1")]
+    pub rejected: ::prost::alloc::vec::Vec<RejectedSubmission>,
+}
+/// A submission in a batch that was not accepted.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RejectedSubmission {
+    #[prost(string, tag = "1")]
+    pub task_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub reason: ::prost::alloc::string::String,
 }
 /// Performance stats of a node.
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -216,6 +498,54 @@ impl NodeType {
         }
     }
 }
+/// Why a node gave up a task.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum AbandonReason {
+    Unspecified = 0,
+    /// The prover ran out of memory.
+    OutOfMemory = 1,
+    /// Proving took longer than the client allows.
+    ProvingTimeout = 2,
+    /// The client can't run the task's program.
+    UnsupportedProgram = 3,
+    /// The client is shutting down before the task is done.
+    Shutdown = 4,
+    /// Proving or verifying the proof failed.
+    ProofFailed = 5,
+    /// The client can't finish the task before its deadline.
+    DeadlineUnreachable = 6,
+}
+impl AbandonReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Unspecified => "ABANDON_REASON_UNSPECIFIED",
+            Self::OutOfMemory => "OUT_OF_MEMORY",
+            Self::ProvingTimeout => "PROVING_TIMEOUT",
+            Self::UnsupportedProgram => "UNSUPPORTED_PROGRAM",
+            Self::Shutdown => "SHUTDOWN",
+            Self::ProofFailed => "PROOF_FAILED",
+            Self::DeadlineUnreachable => "DEADLINE_UNREACHABLE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ABANDON_REASON_UNSPECIFIED" => Some(Self::Unspecified),
+            "OUT_OF_MEMORY" => Some(Self::OutOfMemory),
+            "PROVING_TIMEOUT" => Some(Self::ProvingTimeout),
+            "UNSUPPORTED_PROGRAM" => Some(Self::UnsupportedProgram),
+            "SHUTDOWN" => Some(Self::Shutdown),
+            "PROOF_FAILED" => Some(Self::ProofFailed),
+            "DEADLINE_UNREACHABLE" => Some(Self::DeadlineUnreachable),
+            _ => None,
+        }
+    }
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum TaskDifficulty {
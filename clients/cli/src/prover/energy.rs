@@ -0,0 +1,287 @@
+//! Energy accounting from RAPL powercap counters (Linux)
+//!
+//! Package energy is read from `class/powercap/intel-rapl:N/energy_uj` (AMD
+//! exposes the same interface) while a task is proved. The counters cover the
+//! whole CPU package, so other load running alongside is included. Without
+//! RAPL, or without permission to read it, tasks simply carry no energy figure.
+
+use crate::consts::cli_consts::energy::{JOULES_PER_KWH, rapl_sample_interval};
+use crate::consts::cli_consts::throttle::SYSFS_ROOT;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::time::MissedTickBehavior;
+
+/// How energy is reported
+#[derive(Debug, Clone)]
+pub struct EnergyConfig {
+    /// Where sysfs is mounted
+    pub sysfs_root: PathBuf,
+    /// Electricity price per kWh, for estimating cost
+    pub price_per_kwh: Option<f64>,
+    /// Shown after costs, e.g. "EUR"
+    pub currency: Option<String>,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        Self {
+            sysfs_root: PathBuf::from(SYSFS_ROOT),
+            price_per_kwh: None,
+            currency: None,
+        }
+    }
+}
+
+impl EnergyConfig {
+    /// `joules` in kWh, priced if a price is configured
+    pub fn measure(&self, joules: f64) -> TaskEnergy {
+        let kwh = joules / JOULES_PER_KWH;
+        TaskEnergy {
+            kwh,
+            cost: self.price_per_kwh.map(|price| kwh * price),
+            currency: self.currency.clone(),
+        }
+    }
+
+    /// "Energy: 0.0123 kWh, Cost: 0.0031 EUR"
+    pub fn describe(&self, joules: f64) -> String {
+        self.measure(joules).to_string()
+    }
+}
+
+/// The energy a task used
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskEnergy {
+    pub kwh: f64,
+    /// Estimated cost, when a price is configured
+    pub cost: Option<f64>,
+    /// Shown after the cost
+    pub currency: Option<String>,
+}
+
+impl std::fmt::Display for TaskEnergy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Energy: {:.4} kWh", self.kwh)?;
+        if let Some(cost) = self.cost {
+            write!(f, ", Cost: {:.4}", cost)?;
+            if let Some(currency) = &self.currency {
+                write!(f, " {}", currency)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A package-level RAPL zone
+#[derive(Debug, Clone)]
+struct RaplZone {
+    energy_uj: PathBuf,
+    /// The counter wraps to zero past this value
+    max_energy_range_uj: u64,
+}
+
+/// The package-level RAPL zones of this machine
+#[derive(Debug, Clone)]
+pub struct Rapl {
+    zones: Vec<RaplZone>,
+}
+
+impl Rapl {
+    /// Finds readable package zones under `sysfs_root`, or None when there are none
+    pub fn discover(sysfs_root: &Path) -> Option<Self> {
+        let mut dirs: Vec<PathBuf> = fs::read_dir(sysfs_root.join("class/powercap"))
+            .ok()?
+            .filter_map(Result::ok)
+            // intel-rapl:0 is a package, intel-rapl:0:0 one of its subzones
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.strip_prefix("intel-rapl:")
+                    .is_some_and(|index| !index.contains(':'))
+            })
+            .map(|entry| entry.path())
+            .collect();
+        dirs.sort();
+
+        let zones: Vec<RaplZone> = dirs
+            .into_iter()
+            .filter_map(|dir| {
+                let energy_uj = dir.join("energy_uj");
+                // Unreadable without privileges on most recent kernels
+                read_u64(&energy_uj)?;
+                Some(RaplZone {
+                    max_energy_range_uj: read_u64(&dir.join("max_energy_range_uj"))?,
+                    energy_uj,
+                })
+            })
+            .collect();
+
+        (!zones.is_empty()).then_some(Self { zones })
+    }
+
+    /// Current counter of every zone, if all of them could be read
+    fn read(&self) -> Option<Vec<u64>> {
+        self.zones
+            .iter()
+            .map(|zone| read_u64(&zone.energy_uj))
+            .collect()
+    }
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Microjoules between two readings of a counter that wraps past `max_range`
+fn counter_delta(previous: u64, current: u64, max_range: u64) -> u64 {
+    if current >= previous {
+        current - previous
+    } else {
+        max_range.saturating_sub(previous) + current
+    }
+}
+
+/// Accumulates energy across readings
+struct EnergyMeter<'a> {
+    rapl: &'a Rapl,
+    last: Vec<u64>,
+    total_uj: u64,
+}
+
+impl<'a> EnergyMeter<'a> {
+    fn start(rapl: &'a Rapl) -> Option<Self> {
+        Some(Self {
+            last: rapl.read()?,
+            rapl,
+            total_uj: 0,
+        })
+    }
+
+    /// Fold in the current counters; a failed read is made up by the next one
+    fn sample(&mut self) {
+        let Some(current) = self.rapl.read() else {
+            return;
+        };
+        for ((zone, previous), now) in self.rapl.zones.iter().zip(&self.last).zip(&current) {
+            self.total_uj += counter_delta(*previous, *now, zone.max_energy_range_uj);
+        }
+        self.last = current;
+    }
+
+    fn joules(&self) -> f64 {
+        self.total_uj as f64 / 1_000_000.0
+    }
+}
+
+/// Runs `future`, returning its output along with the joules used meanwhile, if measurable.
+/// Counters are read periodically so a wraparound during a long task is not missed.
+pub async fn measure<F: Future>(rapl: Option<&Rapl>, future: F) -> (F::Output, Option<f64>) {
+    let Some(mut meter) = rapl.and_then(EnergyMeter::start) else {
+        return (future.await, None);
+    };
+
+    tokio::pin!(future);
+    let mut ticker = tokio::time::interval(rapl_sample_interval());
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            output = &mut future => {
+                meter.sample();
+                return (output, Some(meter.joules()));
+            }
+            _ = ticker.tick() => meter.sample(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn fake_rapl(root: &Path, package0_uj: u64, package1_uj: u64) {
+        for (zone, energy) in [("intel-rapl:0", package0_uj), ("intel-rapl:1", package1_uj)] {
+            write(
+                root,
+                &format!("class/powercap/{}/energy_uj", zone),
+                &format!("{}\n", energy),
+            );
+            write(
+                root,
+                &format!("class/powercap/{}/max_energy_range_uj", zone),
+                "262143328850\n",
+            );
+        }
+        // A subzone, already counted in its package
+        write(root, "class/powercap/intel-rapl:0:0/energy_uj", "5\n");
+        write(
+            root,
+            "class/powercap/intel-rapl:0:0/max_energy_range_uj",
+            "262143328850\n",
+        );
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        assert_eq!(counter_delta(100, 250, 1000), 150);
+        assert_eq!(counter_delta(900, 50, 1000), 150);
+        assert_eq!(counter_delta(100, 100, 1000), 0);
+    }
+
+    #[test]
+    fn test_discover_packages_only() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Rapl::discover(dir.path()).is_none());
+
+        fake_rapl(dir.path(), 1_000, 2_000);
+        let rapl = Rapl::discover(dir.path()).unwrap();
+        assert_eq!(rapl.zones.len(), 2);
+        assert_eq!(rapl.read(), Some(vec![1_000, 2_000]));
+    }
+
+    #[test]
+    fn test_meter_sums_zones_across_wrap() {
+        let dir = tempfile::tempdir().unwrap();
+        fake_rapl(dir.path(), 262_143_000_000, 2_000_000);
+        let rapl = Rapl::discover(dir.path()).unwrap();
+        let mut meter = EnergyMeter::start(&rapl).unwrap();
+
+        // Package 0 wraps, package 1 doesn't
+        fake_rapl(dir.path(), 671_150, 3_000_000);
+        meter.sample();
+        assert_eq!(meter.joules(), 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_measure_without_rapl() {
+        let (output, joules) = measure(None, async { 7 }).await;
+        assert_eq!(output, 7);
+        assert_eq!(joules, None);
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut config = EnergyConfig::default();
+        assert_eq!(config.describe(36_000.0), "Energy: 0.0100 kWh");
+
+        config.price_per_kwh = Some(0.3);
+        assert_eq!(
+            config.describe(36_000.0),
+            "Energy: 0.0100 kWh, Cost: 0.0030"
+        );
+
+        config.currency = Some("EUR".to_string());
+        assert_eq!(
+            config.describe(36_000.0),
+            "Energy: 0.0100 kWh, Cost: 0.0030 EUR"
+        );
+    }
+}
@@ -1,7 +1,8 @@
 //! Core proving engine
 
-use crate::prover::verifier;
+use crate::prover::verifier::{self, VerifiedProof};
 
+use super::processes::RegisteredProcess;
 use super::types::ProverError;
 use crate::analytics::track_likely_oom_error;
 use crate::environment::Environment;
@@ -53,7 +54,7 @@ impl ProvingEngine {
         task: &Task,
         environment: &Environment,
         client_id: &str,
-    ) -> Result<Proof, ProverError> {
+    ) -> Result<VerifiedProof, ProverError> {
         // Spawn a subprocess for proof generation to isolate memory usage
         let exe_path = env::current_exe()?;
         let mut cmd = tokio::process::Command::new(exe_path);
@@ -61,21 +62,30 @@ impl ProvingEngine {
             .arg("--inputs")
             .arg(serde_json::to_string(inputs)?)
             .stdout(Stdio::piped())
-            .stderr(Stdio::inherit());
+            .stderr(Stdio::inherit())
+            // Abandoning the proof (e.g. on immediate shutdown) must not leave the prover running
+            .kill_on_drop(true);
 
-        let output = cmd.output().await?;
+        let child = cmd.spawn()?;
+        // Registered so a pause can suspend it; deregistered once it has exited
+        let _registered = child.id().map(RegisteredProcess::register);
+        let output = child.wait_with_output().await?;
 
         if !output.status.success() {
-            if let Some(code) = output.status.code() {
-                if code == crate::consts::cli_consts::SUBPROCESS_SUSPECTED_OOM_CODE {
-                    // 128 + 9 = 137 means external sigkill, so likely killed by kernel due to OOM; track analytics event
-                    tokio::spawn(track_likely_oom_error(
-                        task.clone(),
-                        environment.clone(),
-                        client_id.to_string(),
-                    ));
-                }
+            if killed_outright(&output.status) {
+                // Likely killed by the kernel due to OOM; track analytics event
+                tokio::spawn(track_likely_oom_error(
+                    task.clone(),
+                    environment.clone(),
+                    client_id.to_string(),
+                ));
+                return Err(ProverError::OutOfMemory(format!(
+                    "Prover subprocess was killed ({}), likely out of memory",
+                    output.status
+                )));
+            }
 
+            if let Some(code) = output.status.code() {
                 if code == crate::consts::cli_consts::SUBPROCESS_INTERNAL_ERROR_CODE {
                     // error happened inside the subprocess, and so we know that it may be useful information to the user
                     return Err(ProverError::Subprocess(format!(
@@ -96,8 +106,48 @@ impl ProvingEngine {
 
         // Verify proof in main process
         let verify_prover = Self::create_fib_prover()?;
-        verifier::ProofVerifier::verify_proof(&proof, inputs, &verify_prover)?;
+        verifier::ProofVerifier::verify_proof(proof, inputs, &verify_prover)
+    }
+}
 
-        Ok(proof)
+/// Whether the prover subprocess was killed outright, as the kernel's OOM killer does.
+/// A direct child killed by SIGKILL has no exit code, only the signal; 128 + 9 = 137 is
+/// what a shell in between reports instead.
+fn killed_outright(status: &std::process::ExitStatus) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if status.signal() == Some(libc::SIGKILL) {
+            return true;
+        }
+    }
+    status.code() == Some(crate::consts::cli_consts::SUBPROCESS_SUSPECTED_OOM_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sigkill_counts_as_killed_outright() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("spawn sleep");
+        // SIGKILL, as the OOM killer sends
+        child.kill().unwrap();
+        let status = child.wait().unwrap();
+        assert_eq!(status.code(), None);
+        assert!(killed_outright(&status));
+
+        let exit = |code: &str| {
+            std::process::Command::new("sh")
+                .args(["-c", &format!("exit {}", code)])
+                .status()
+                .expect("run sh")
+        };
+        assert!(killed_outright(&exit("137")));
+        assert!(!killed_outright(&exit("1")));
     }
 }
@@ -1,17 +1,20 @@
 //! High-level proving interface
 
+use super::incremental::IncrementalProving;
 use super::pipeline::ProvingPipeline;
-use super::types::ProverError;
+use super::types::{ProverError, ProverResult};
 use crate::environment::Environment;
 use crate::task::Task;
-use nexus_sdk::stwo::seq::Proof;
 
-/// Proves a program with authenticated task inputs
+/// Proves a program with authenticated task inputs, submitting each input as it is
+/// verified if `incremental` is given
 pub async fn authenticated_proving(
     task: &Task,
     environment: &Environment,
     client_id: &str,
     num_workers: usize,
-) -> Result<(Vec<Proof>, String, Vec<String>), ProverError> {
-    ProvingPipeline::prove_authenticated(task, environment, client_id, num_workers).await
+    incremental: Option<IncrementalProving>,
+) -> Result<ProverResult, ProverError> {
+    ProvingPipeline::prove_authenticated(task, environment, client_id, num_workers, incremental)
+        .await
 }
@@ -0,0 +1,340 @@
+//! Per-input submission of long tasks
+//!
+//! When the orchestrator supports it, each input's proof is handed to an [`InputSink`]
+//! as soon as it is verified, so a crash late in a long task loses at most the inputs
+//! still in flight, and the proofs of accepted inputs needn't stay in memory.
+//!
+//! Only inputs this machine proved, verified and had accepted count as submitted: they
+//! are recorded in an [`InputLedger`], and after a restart an input is skipped only if
+//! both the ledger and the orchestrator have it, with the same proof hash.
+
+use super::types::ProvedInput;
+use super::verifier::{VerifiedProof, generate_proof_hash};
+use crate::consts::cli_consts::proof_submission;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+use tokio::task::{JoinError, JoinSet};
+
+/// Where each input's proof goes once it is verified
+#[async_trait::async_trait]
+pub trait InputSink: Send {
+    /// Submit the verified proof of input `index`.
+    /// Returns whether the orchestrator accepted it; if not, the proof is kept
+    /// and sent again when the task is finalized.
+    async fn submit(&mut self, index: usize, proof: &VerifiedProof) -> bool;
+}
+
+/// An input the orchestrator accepted from this machine, known only by its proof hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmittedInput {
+    index: usize,
+    hash: String,
+}
+
+impl SubmittedInput {
+    /// Only built from a verified proof the sink had accepted, or from the ledger of those
+    fn accepted(index: usize, hash: String) -> Self {
+        Self { index, hash }
+    }
+
+    /// Stand-in for an accepted input, for tests elsewhere in the crate
+    #[cfg(test)]
+    pub(crate) fn for_test(index: usize, hash: String) -> Self {
+        Self::accepted(index, hash)
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct LedgerTask {
+    task_id: String,
+    /// Proof hash of each accepted input, by input index
+    inputs: BTreeMap<usize, String>,
+}
+
+/// Inputs each node run from this machine submitted one by one, by task.
+/// Only the most recent tasks are kept, oldest first.
+pub struct InputLedger {
+    path: PathBuf,
+    node_id: u64,
+}
+
+impl InputLedger {
+    pub fn new(path: PathBuf, node_id: u64) -> Self {
+        Self { path, node_id }
+    }
+
+    /// Default ledger location, next to the config file in ~/.nexus
+    pub fn default_path() -> Result<PathBuf, std::io::Error> {
+        Ok(crate::config::get_config_path()?
+            .with_file_name(proof_submission::INPUT_LEDGER_FILE_NAME))
+    }
+
+    fn read_all(&self) -> BTreeMap<String, Vec<LedgerTask>> {
+        fs::read(&self.path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_all(&self, all: &BTreeMap<String, Vec<LedgerTask>>) -> Result<(), std::io::Error> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write then rename, so a crash never leaves a truncated file behind
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(all)?)?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    /// Inputs of `task_id` this node had accepted; none if the ledger is unreadable
+    fn load(&self, task_id: &str) -> Vec<SubmittedInput> {
+        self.read_all()
+            .remove(&self.node_id.to_string())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|task| task.task_id == task_id)
+            .flat_map(|task| task.inputs)
+            .map(|(index, hash)| SubmittedInput::accepted(index, hash))
+            .collect()
+    }
+
+    /// Add an accepted input of `task_id`, making it the most recent task
+    fn record(&self, task_id: &str, input: &SubmittedInput) -> Result<(), std::io::Error> {
+        let mut all = self.read_all();
+        let tasks = all.entry(self.node_id.to_string()).or_default();
+        let mut task = match tasks.iter().position(|task| task.task_id == task_id) {
+            Some(position) => tasks.remove(position),
+            None => LedgerTask {
+                task_id: task_id.to_string(),
+                ..Default::default()
+            },
+        };
+        task.inputs.insert(input.index, input.hash.clone());
+        tasks.push(task);
+        let excess = tasks
+            .len()
+            .saturating_sub(proof_submission::INPUT_LEDGER_MAX_TASKS);
+        tasks.drain(..excess);
+        self.write_all(&all)
+    }
+
+    /// Forget `task_id` once it is finalized
+    pub fn forget(&self, task_id: &str) -> Result<(), std::io::Error> {
+        let mut all = self.read_all();
+        let Some(tasks) = all.get_mut(&self.node_id.to_string()) else {
+            return Ok(());
+        };
+        let before = tasks.len();
+        tasks.retain(|task| task.task_id != task_id);
+        if tasks.len() == before {
+            return Ok(());
+        }
+        self.write_all(&all)
+    }
+}
+
+/// How to prove a task whose inputs are submitted one by one
+pub struct IncrementalProving {
+    task_id: String,
+    /// Inputs this node submitted before, e.g. before a restart, that the orchestrator
+    /// still has. These inputs aren't proved again.
+    received: BTreeMap<usize, SubmittedInput>,
+    sink: Box<dyn InputSink>,
+    ledger: Option<InputLedger>,
+}
+
+impl IncrementalProving {
+    /// Prove `task_id` through the sink `sink` builds, given how many inputs are
+    /// skipped: those `ledger` has that the orchestrator reports having too, as
+    /// `progress` (proof hash by input index). Inputs it reports without a matching
+    /// ledger entry are proved again.
+    pub fn new(
+        task_id: &str,
+        ledger: Option<InputLedger>,
+        progress: &HashMap<usize, String>,
+        sink: impl FnOnce(usize) -> Box<dyn InputSink>,
+    ) -> Self {
+        let received: BTreeMap<_, _> = ledger
+            .as_ref()
+            .map(|ledger| ledger.load(task_id))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|input| progress.get(&input.index) == Some(&input.hash))
+            .map(|input| (input.index, input))
+            .collect();
+        Self {
+            task_id: task_id.to_string(),
+            sink: sink(received.len()),
+            received,
+            ledger,
+        }
+    }
+
+    /// Whether input `index` was submitted before and needn't be proved
+    pub fn is_received(&self, index: usize) -> bool {
+        self.received.contains_key(&index)
+    }
+
+    /// How many inputs were submitted before
+    pub fn received_count(&self) -> usize {
+        self.received.len()
+    }
+
+    /// Start the task submitting the proofs sent to it one at a time, so submits never
+    /// hold up proving and their retries are paced together
+    pub fn start(self) -> InputSubmission {
+        let (proofs, receiver) = mpsc::unbounded_channel();
+        let mut task = JoinSet::new();
+        task.spawn(self.submit_all(receiver));
+        InputSubmission { proofs, task }
+    }
+
+    async fn submit_all(
+        mut self,
+        mut proofs: mpsc::UnboundedReceiver<(usize, VerifiedProof)>,
+    ) -> BTreeMap<usize, ProvedInput> {
+        let mut inputs: BTreeMap<usize, ProvedInput> = std::mem::take(&mut self.received)
+            .into_iter()
+            .map(|(index, input)| (index, ProvedInput::Submitted(input)))
+            .collect();
+        while let Some((index, proof)) = proofs.recv().await {
+            if !self.sink.submit(index, &proof).await {
+                inputs.insert(index, ProvedInput::Held(proof));
+                continue;
+            }
+            let input = SubmittedInput::accepted(index, generate_proof_hash(&proof));
+            if let Some(ledger) = &self.ledger {
+                // Without the entry the input is only proved again after a restart
+                let _ = ledger.record(&self.task_id, &input);
+            }
+            inputs.insert(index, ProvedInput::Submitted(input));
+        }
+        inputs
+    }
+}
+
+/// The running submit task of an [`IncrementalProving`]. Dropping it aborts the task.
+pub struct InputSubmission {
+    proofs: mpsc::UnboundedSender<(usize, VerifiedProof)>,
+    task: JoinSet<BTreeMap<usize, ProvedInput>>,
+}
+
+impl InputSubmission {
+    /// Queue the verified proof of input `index` for submission. Gives the proof back
+    /// if the submit task is gone.
+    pub fn send(&self, index: usize, proof: VerifiedProof) -> Result<(), VerifiedProof> {
+        self.proofs
+            .send((index, proof))
+            .map_err(|error| (error.0).1)
+    }
+
+    /// Wait for every queued proof to be submitted. Returns each input, by index:
+    /// submitted now or before, or held if the orchestrator turned it away.
+    pub async fn finish(self) -> Result<BTreeMap<usize, ProvedInput>, JoinError> {
+        let Self { proofs, mut task } = self;
+        drop(proofs);
+        match task.join_next().await {
+            Some(joined) => joined,
+            None => Ok(BTreeMap::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prover::verifier::tests::verified_fixture;
+
+    /// Accepts every proof but those of `rejected`
+    struct TestSink {
+        rejected: Vec<usize>,
+    }
+
+    #[async_trait::async_trait]
+    impl InputSink for TestSink {
+        async fn submit(&mut self, index: usize, _: &VerifiedProof) -> bool {
+            !self.rejected.contains(&index)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_inputs_submitted_here_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("submitted_inputs.json");
+        let hash = generate_proof_hash(&verified_fixture());
+
+        let proving = IncrementalProving::new(
+            "abc",
+            Some(InputLedger::new(path.clone(), 42)),
+            &HashMap::new(),
+            |_| Box::new(TestSink { rejected: vec![1] }),
+        );
+        assert_eq!(proving.received_count(), 0);
+        let submission = proving.start();
+        submission.send(0, verified_fixture()).unwrap();
+        submission.send(1, verified_fixture()).unwrap();
+        let inputs = submission.finish().await.unwrap();
+        assert!(matches!(&inputs[&0], ProvedInput::Submitted(input) if input.hash() == hash));
+        assert!(matches!(inputs[&1], ProvedInput::Held(_)));
+
+        // The orchestrator's word alone isn't enough, nor is a hash that doesn't match
+        let progress = HashMap::from([
+            (0, hash.clone()),
+            (1, hash.clone()),
+            (2, "forged".to_string()),
+        ]);
+        let resumed = |node_id| {
+            IncrementalProving::new(
+                "abc",
+                Some(InputLedger::new(path.clone(), node_id)),
+                &progress,
+                |_| Box::new(TestSink { rejected: vec![] }),
+            )
+        };
+        let proving = resumed(42);
+        assert!(proving.is_received(0));
+        assert!(!proving.is_received(1));
+        assert_eq!(proving.received_count(), 1);
+        assert_eq!(resumed(7).received_count(), 0);
+        assert_eq!(
+            IncrementalProving::new(
+                "abc",
+                Some(InputLedger::new(path.clone(), 42)),
+                &HashMap::from([(0, "forged".to_string())]),
+                |_| Box::new(TestSink { rejected: vec![] }),
+            )
+            .received_count(),
+            0
+        );
+
+        InputLedger::new(path.clone(), 42).forget("abc").unwrap();
+        assert_eq!(resumed(42).received_count(), 0);
+    }
+
+    #[test]
+    fn test_ledger_keeps_recent_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = InputLedger::new(dir.path().join("submitted_inputs.json"), 42);
+        for task in 0..=proof_submission::INPUT_LEDGER_MAX_TASKS {
+            ledger
+                .record(
+                    &task.to_string(),
+                    &SubmittedInput::accepted(0, "hash".to_string()),
+                )
+                .unwrap();
+        }
+        assert!(ledger.load("0").is_empty());
+        assert_eq!(ledger.load("1").len(), 1);
+    }
+}
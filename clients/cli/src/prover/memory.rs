@@ -0,0 +1,65 @@
+//! Peak memory use while a task is proved
+//!
+//! Resident memory of this process and its prover subprocesses is sampled
+//! periodically, so short spikes between samples can be missed.
+
+use crate::consts::cli_consts::history::rss_sample_interval;
+use crate::prover::processes;
+use std::future::Future;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::time::MissedTickBehavior;
+
+/// Resident bytes of this process and its running prover subprocesses
+fn resident_bytes(sysinfo: &mut System) -> u64 {
+    let mut pids: Vec<Pid> = processes::running_pids()
+        .into_iter()
+        .map(|pid| Pid::from(pid as usize))
+        .collect();
+    pids.push(Pid::from(std::process::id() as usize));
+
+    sysinfo.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    pids.iter()
+        .filter_map(|pid| sysinfo.process(*pid))
+        .map(|process| process.memory())
+        .sum()
+}
+
+/// Runs `future`, returning its output along with the highest resident memory seen
+/// meanwhile, or None if memory could not be read.
+pub async fn measure_peak_rss<F: Future>(future: F) -> (F::Output, Option<u64>) {
+    let mut sysinfo = System::new();
+    let mut peak = resident_bytes(&mut sysinfo);
+
+    tokio::pin!(future);
+    let mut ticker = tokio::time::interval(rss_sample_interval());
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            output = &mut future => {
+                peak = peak.max(resident_bytes(&mut sysinfo));
+                return (output, (peak > 0).then_some(peak));
+            }
+            _ = ticker.tick() => peak = peak.max(resident_bytes(&mut sysinfo)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_measures_own_process() {
+        let (output, peak) = measure_peak_rss(async { 7 }).await;
+        assert_eq!(output, 7);
+        assert!(peak.is_some_and(|bytes| bytes > 0));
+    }
+}
@@ -1,9 +1,14 @@
+pub mod energy;
 pub mod engine;
 pub mod handlers;
+pub mod incremental;
 pub mod input;
+pub mod memory;
 pub mod pipeline;
+pub mod processes;
 pub mod types;
 pub mod verifier;
 
 pub use handlers::authenticated_proving;
-pub use types::{ProverError, ProverResult};
+pub use incremental::{IncrementalProving, InputLedger, InputSink};
+pub use types::{ProvedInput, ProverError, ProverResult};
@@ -1,37 +1,41 @@
 //! Proving pipeline that orchestrates the full proving process
 
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use super::engine::ProvingEngine;
+use super::incremental::IncrementalProving;
 use super::input::InputParser;
-use super::types::ProverError;
+use super::types::{ProvedInput, ProverError, ProverResult};
+use super::verifier::VerifiedProof;
 use crate::analytics::track_verification_failed;
 use crate::environment::Environment;
 use crate::task::Task;
-use futures::future::join_all;
-use nexus_sdk::stwo::seq::Proof;
-use sha3::{Digest, Keccak256};
-use tokio_util::sync::CancellationToken;
+use tokio::task::JoinSet;
+
+/// Programs this client has built in and can prove
+pub const PROGRAM_IDS: &[&str] = &[FIB_PROGRAM_ID];
+
+const FIB_PROGRAM_ID: &str = "fib_input_initial";
 
 /// Orchestrates the complete proving pipeline
 pub struct ProvingPipeline;
 
 impl ProvingPipeline {
-    /// Execute authenticated proving for a task
+    /// Execute authenticated proving for a task, submitting each input as it is
+    /// verified if `incremental` is given
     pub async fn prove_authenticated(
         task: &Task,
         environment: &Environment,
         client_id: &str,
         num_workers: usize,
-    ) -> Result<(Vec<Proof>, String, Vec<String>), ProverError> {
+        incremental: Option<IncrementalProving>,
+    ) -> Result<ProverResult, ProverError> {
         match task.program_id.as_str() {
-            "fib_input_initial" => {
-                Self::prove_fib_task(task, environment, client_id, num_workers).await
+            FIB_PROGRAM_ID => {
+                Self::prove_fib_task(task, environment, client_id, num_workers, incremental).await
             }
-            _ => Err(ProverError::MalformedTask(format!(
-                "Unsupported program ID: {}",
-                task.program_id
-            ))),
+            _ => Err(ProverError::UnsupportedProgram(task.program_id.clone())),
         }
     }
 
@@ -41,7 +45,8 @@ impl ProvingPipeline {
         environment: &Environment,
         client_id: &str,
         num_workers: usize,
-    ) -> Result<(Vec<Proof>, String, Vec<String>), ProverError> {
+        incremental: Option<IncrementalProving>,
+    ) -> Result<ProverResult, ProverError> {
         let all_inputs = task.all_inputs();
 
         if all_inputs.is_empty() {
@@ -58,35 +63,36 @@ impl ProvingPipeline {
         // Create a semaphore with a specific number of permits
         let semaphore = Arc::new(tokio::sync::Semaphore::new(num_workers));
 
-        // Create cancellation token for graceful shutdown
-        let cancellation_token = CancellationToken::new();
-
-        // Spawn all tasks in parallel
-        let handles: Vec<_> = all_inputs
-            .iter()
-            .enumerate()
-            .map(|(input_index, input_data)| {
-                let task_ref = Arc::clone(&task_shared);
-                let environment_ref = Arc::clone(&environment_shared);
-                let client_id_ref = Arc::clone(&client_id_shared);
-                let input_data = input_data.clone();
-                let semaphore_ref = Arc::clone(&semaphore);
-                let cancellation_ref = cancellation_token.clone();
-
-                tokio::spawn(async move {
-                    // Check for cancellation before starting
-                    if cancellation_ref.is_cancelled() {
-                        return Err(ProverError::MalformedTask("Task cancelled".to_string()));
-                    }
-
-                    // Acquire a permit from the semaphore. This waits if the limit is reached.
-                    let _permit = semaphore_ref.acquire_owned().await;
-
-                    // Check for cancellation after acquiring permit
-                    if cancellation_ref.is_cancelled() {
-                        return Err(ProverError::MalformedTask("Task cancelled".to_string()));
-                    }
+        // Inputs submitted before aren't proved again; the rest are handed to the
+        // submit task as they are verified, once their proving slot is free
+        let received: Vec<bool> = (0..all_inputs.len())
+            .map(|index| {
+                incremental
+                    .as_ref()
+                    .is_some_and(|incremental| incremental.is_received(index))
+            })
+            .collect();
+        let submission = incremental.map(IncrementalProving::start);
 
+        // Spawn all inputs into a JoinSet: dropping the pipeline (e.g. on an immediate
+        // shutdown) aborts every in-flight proof and, with it, its prover subprocess
+        let mut proving_tasks = JoinSet::new();
+        for (input_index, input_data) in all_inputs.iter().enumerate() {
+            if received[input_index] {
+                continue;
+            }
+            let task_ref = Arc::clone(&task_shared);
+            let environment_ref = Arc::clone(&environment_shared);
+            let client_id_ref = Arc::clone(&client_id_shared);
+            let input_data = input_data.clone();
+            let semaphore_ref = Arc::clone(&semaphore);
+
+            proving_tasks.spawn(async move {
+                // Acquire a permit from the semaphore. This waits if the limit is reached.
+                let _permit = semaphore_ref.acquire_owned().await;
+                let started = Instant::now();
+
+                let result: Result<(VerifiedProof, Duration), ProverError> = async {
                     // Step 1: Parse and validate input
                     let inputs = InputParser::parse_triple_input(&input_data)?;
 
@@ -98,45 +104,42 @@ impl ProvingPipeline {
                         &client_id_ref,
                     )
                     .await?;
+                    Ok((proof, started.elapsed()))
+                }
+                .await;
 
-                    // Step 3: Generate proof hash
-                    let proof_hash = Self::generate_proof_hash(&proof);
-
-                    Ok((proof, proof_hash, input_index))
-                })
-            })
-            .collect();
-
-        // Use join_all for better parallelization
-        let results = join_all(handles).await;
+                (input_index, result)
+            });
+        }
 
-        // Process results and collect verification failures for batch handling
-        let mut all_proofs = Vec::new();
-        let mut proof_hashes = Vec::new();
+        // Process results as they complete and collect verification failures for batch handling
+        let mut held: Vec<Option<VerifiedProof>> = (0..all_inputs.len()).map(|_| None).collect();
+        let mut durations: Vec<Option<Duration>> = vec![None; all_inputs.len()];
         let mut verification_failures = Vec::new();
 
-        for (result_index, result) in results.into_iter().enumerate() {
-            match result {
-                Ok(Ok((proof, proof_hash, _input_index))) => {
-                    all_proofs.push(proof);
-                    proof_hashes.push(proof_hash);
+        while let Some(joined) = proving_tasks.join_next().await {
+            match joined {
+                Ok((input_index, Ok((proof, duration)))) => {
+                    durations[input_index] = Some(duration);
+                    // Step 3: Hand the proof over now if the task is submitted input by input
+                    held[input_index] = match &submission {
+                        Some(submission) => submission.send(input_index, proof).err(),
+                        None => Some(proof),
+                    };
                 }
-                Ok(Err(e)) => {
+                Ok((input_index, Err(e))) => {
                     // Collect verification failures for batch processing
                     match e {
                         ProverError::Stwo(_) | ProverError::GuestProgram(_) => {
                             verification_failures.push((
                                 task_shared.clone(),
-                                format!("Input {}: {}", result_index, e),
+                                format!("Input {}: {}", input_index, e),
                                 environment_shared.clone(),
                                 client_id_shared.clone(),
                             ));
                         }
-                        _ => {
-                            // Cancel remaining tasks on critical errors
-                            cancellation_token.cancel();
-                            return Err(e);
-                        }
+                        // Returning drops the JoinSets, which cancels the remaining inputs
+                        _ => return Err(e),
                     }
                 }
                 Err(join_error) => {
@@ -164,72 +167,24 @@ impl ProvingPipeline {
             )));
         }
 
-        let final_proof_hash = Self::combine_proof_hashes(&task_shared, &proof_hashes);
-
-        Ok((all_proofs, final_proof_hash, proof_hashes))
-    }
-
-    /// Generate hash for a proof
-    fn generate_proof_hash(proof: &Proof) -> String {
-        let proof_bytes = postcard::to_allocvec(proof).expect("Failed to serialize proof");
-        format!("{:x}", Keccak256::digest(&proof_bytes))
-    }
-
-    /// Combine multiple proof hashes based on task type
-    fn combine_proof_hashes(task: &Task, proof_hashes: &[String]) -> String {
-        match task.task_type {
-            crate::nexus_orchestrator::TaskType::AllProofHashes
-            | crate::nexus_orchestrator::TaskType::ProofHash => {
-                Task::combine_proof_hashes(proof_hashes)
+        // Keep proofs and timings in input order; hashes are derived from the verified proofs
+        let input_durations = durations.into_iter().flatten().collect();
+        let Some(submission) = submission else {
+            let all_proofs = held.into_iter().flatten().collect();
+            return ProverResult::new(&task_shared, all_proofs, input_durations);
+        };
+
+        // Inputs submitted before this run only have their hashes, and no timings
+        let mut inputs = submission.finish().await.map_err(ProverError::JoinError)?;
+        for (input_index, proof) in held.into_iter().enumerate() {
+            if let Some(proof) = proof {
+                inputs.insert(input_index, ProvedInput::Held(proof));
             }
-            _ => proof_hashes.first().cloned().unwrap_or_default(),
         }
-    }
-
-    /// EXPLOIT: Generate fake proof hashes without doing actual computation
-    /// This simulates what malicious users do to avoid expensive proving
-    async fn exploit_proof_hash_task(
-        task: &Task,
-    ) -> Result<(Vec<Proof>, String, Vec<String>), ProverError> {
-        let all_inputs = task.all_inputs();
-        let mut proof_hashes = Vec::new();
-        let mut all_proofs: Vec<Proof> = Vec::new();
-
-        for (input_index, input_data) in all_inputs.iter().enumerate() {
-            // Parse input to get the fibonacci values
-            let inputs = InputParser::parse_triple_input(input_data)?;
-            
-            // Generate fake but deterministic hash based on task and input data
-            // This ensures consistency if the same task is seen again
-            let fake_hash = Self::generate_fake_hash(&task.task_id, input_index, &inputs);
-            proof_hashes.push(fake_hash);
-
-            // Create empty proof since ProofHash tasks don't send proof data anyway
-            let empty_proof = Self::create_minimal_fake_proof()?;
-            all_proofs.push(empty_proof);
-        }
-
-        let final_proof_hash = Self::combine_proof_hashes(task, &proof_hashes);
-        
-        // Instant return - no 2+ minute proving delay!
-        Ok((all_proofs, final_proof_hash, proof_hashes))
-    }
-
-    /// Generate a fake but deterministic hash for ProofHash exploitation
-    fn generate_fake_hash(task_id: &str, input_index: usize, inputs: &(u32, u32, u32)) -> String {
-        // Create deterministic fake hash using task data
-        // This looks legitimate but requires no computation
-        let fake_data = format!("{}:{}:{}:{}:{}", task_id, input_index, inputs.0, inputs.1, inputs.2);
-        format!("{:x}", Keccak256::digest(fake_data.as_bytes()))
-    }
-
-    /// Create a minimal fake proof that won't be sent anyway (ProofHash tasks)
-    fn create_minimal_fake_proof() -> Result<Proof, ProverError> {
-        // Create the smallest possible proof object
-        // Since ProofHash tasks don't send proof data, this won't be validated
-        let empty_bytes = vec![0u8; 32]; // Minimal proof-like structure
-        postcard::from_bytes(&empty_bytes).map_err(|_| {
-            ProverError::Subprocess("Failed to create fake proof".to_string())
-        })
+        ProverResult::incremental(
+            &task_shared,
+            inputs.into_values().collect(),
+            input_durations,
+        )
     }
 }
@@ -0,0 +1,286 @@
+//! Registry of running prover subprocesses
+//!
+//! Lets the worker suspend and resume in-flight proofs without owning the
+//! `Child` handles, which stay with the proving future that awaits them.
+//! Registration also applies the configured CPU and I/O priority.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Whether provers are currently suspended, so late-starting ones are stopped too
+static SUSPENDED: AtomicBool = AtomicBool::new(false);
+
+/// Scheduling priority given to prover subprocesses, set once at startup
+static PRIORITY: OnceLock<ProcessPriority> = OnceLock::new();
+
+/// CPU and I/O priority for prover subprocesses
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct ProcessPriority {
+    /// Niceness, 0 (normal) to 19 (lowest)
+    pub nice: Option<i32>,
+    /// I/O scheduling class (Linux only)
+    pub io: Option<IoPriority>,
+}
+
+/// I/O scheduling class, as understood by `ionice`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IoPriority {
+    /// Only use the disk when nobody else does
+    Idle,
+    /// Normal scheduling at a level from 0 (highest) to 7 (lowest); 4 by default
+    BestEffort(u8),
+}
+
+impl FromStr for IoPriority {
+    type Err = String;
+
+    /// Parses `idle`, `best-effort` or `best-effort:N`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (class, level) = match s.split_once(':') {
+            Some((class, level)) => (class, Some(level)),
+            None => (s, None),
+        };
+        match (class.trim().to_ascii_lowercase().as_str(), level) {
+            ("idle", None) => Ok(IoPriority::Idle),
+            ("best-effort", None) => Ok(IoPriority::BestEffort(4)),
+            ("best-effort", Some(level)) => match level.trim().parse::<u8>() {
+                Ok(level) if level <= 7 => Ok(IoPriority::BestEffort(level)),
+                _ => Err(format!(
+                    "invalid best-effort level '{}', expected 0-7",
+                    level
+                )),
+            },
+            _ => Err(format!(
+                "invalid I/O priority '{}', expected idle, best-effort or best-effort:N",
+                s
+            )),
+        }
+    }
+}
+
+/// Sets the priority applied to every prover subprocess started from now on.
+/// Only the first call takes effect.
+pub fn set_priority(priority: ProcessPriority) {
+    let _ = PRIORITY.set(priority);
+}
+
+fn registry() -> &'static Mutex<HashSet<u32>> {
+    static PIDS: OnceLock<Mutex<HashSet<u32>>> = OnceLock::new();
+    PIDS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Keeps a subprocess registered for as long as it is alive.
+pub struct RegisteredProcess {
+    pid: u32,
+}
+
+impl RegisteredProcess {
+    /// Registers `pid`, applying the configured priority and stopping it right away
+    /// if provers are currently suspended.
+    pub fn register(pid: u32) -> Self {
+        if let Some(priority) = PRIORITY.get() {
+            apply_priority(pid, priority);
+        }
+        registry()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(pid);
+        if SUSPENDED.load(Ordering::SeqCst) {
+            send_signal(pid, Signal::Stop);
+        }
+        Self { pid }
+    }
+}
+
+impl Drop for RegisteredProcess {
+    fn drop(&mut self) {
+        registry()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.pid);
+    }
+}
+
+/// PIDs of the prover subprocesses currently running.
+pub fn running_pids() -> Vec<u32> {
+    registry()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .copied()
+        .collect()
+}
+
+/// Stops every running prover subprocess (SIGSTOP). Returns how many were stopped.
+pub fn suspend_all() -> usize {
+    SUSPENDED.store(true, Ordering::SeqCst);
+    signal_all(Signal::Stop)
+}
+
+/// Continues every stopped prover subprocess (SIGCONT). Returns how many were resumed.
+pub fn resume_all() -> usize {
+    SUSPENDED.store(false, Ordering::SeqCst);
+    signal_all(Signal::Continue)
+}
+
+/// Whether prover subprocesses are currently suspended.
+pub fn is_suspended() -> bool {
+    SUSPENDED.load(Ordering::SeqCst)
+}
+
+fn signal_all(signal: Signal) -> usize {
+    running_pids()
+        .into_iter()
+        .filter(|&pid| send_signal(pid, signal))
+        .count()
+}
+
+#[derive(Copy, Clone)]
+enum Signal {
+    Stop,
+    Continue,
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: Signal) -> bool {
+    // Never let a PID turn into a process-group target
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    let signal = match signal {
+        Signal::Stop => libc::SIGSTOP,
+        Signal::Continue => libc::SIGCONT,
+    };
+    // SAFETY: kill(2) has no memory-safety preconditions; a stale PID just fails
+    unsafe { libc::kill(pid, signal) == 0 }
+}
+
+/// Suspending processes is not supported off Unix; proofs keep running.
+#[cfg(not(unix))]
+fn send_signal(_pid: u32, _signal: Signal) -> bool {
+    false
+}
+
+/// Best effort: a process that has already exited, or a platform without
+/// the call, just keeps the default priority.
+fn apply_priority(pid: u32, priority: &ProcessPriority) {
+    if let Some(nice) = priority.nice {
+        set_nice(pid, nice);
+    }
+    if let Some(io) = priority.io {
+        set_io_priority(pid, io);
+    }
+}
+
+#[cfg(unix)]
+fn set_nice(pid: u32, nice: i32) -> bool {
+    // PID 0 would mean the calling process
+    if pid == 0 {
+        return false;
+    }
+    // SAFETY: setpriority(2) has no memory-safety preconditions
+    unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) == 0 }
+}
+
+#[cfg(not(unix))]
+fn set_nice(_pid: u32, _nice: i32) -> bool {
+    false
+}
+
+#[cfg(target_os = "linux")]
+fn set_io_priority(pid: u32, priority: IoPriority) -> bool {
+    // From linux/ioprio.h
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    const IOPRIO_CLASS_BE: libc::c_int = 2;
+    const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+
+    let Ok(pid) = libc::c_int::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    let ioprio = match priority {
+        IoPriority::Idle => IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+        IoPriority::BestEffort(level) => {
+            (IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | libc::c_int::from(level)
+        }
+    };
+    // SAFETY: ioprio_set(2) takes plain integers and has no memory-safety preconditions
+    unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, pid, ioprio) == 0 }
+}
+
+/// I/O priorities are a Linux feature; elsewhere only niceness applies.
+#[cfg(not(target_os = "linux"))]
+fn set_io_priority(_pid: u32, _priority: IoPriority) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_is_scoped_to_guard() {
+        // Above the Linux PID limit, so signalling it can never hit a real process
+        let pid = 0x3fff_fff0;
+        {
+            let _guard = RegisteredProcess::register(pid);
+            assert!(running_pids().contains(&pid));
+        }
+        assert!(!running_pids().contains(&pid));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_suspend_and_resume_child_process() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("spawn sleep");
+        let _guard = RegisteredProcess::register(child.id());
+
+        assert!(suspend_all() >= 1);
+        assert!(is_suspended());
+        assert!(resume_all() >= 1);
+        assert!(!is_suspended());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_parse_io_priority() {
+        assert_eq!("idle".parse(), Ok(IoPriority::Idle));
+        assert_eq!("best-effort".parse(), Ok(IoPriority::BestEffort(4)));
+        assert_eq!("Best-Effort:3".parse(), Ok(IoPriority::BestEffort(3)));
+        assert!("best-effort:8".parse::<IoPriority>().is_err());
+        assert!("realtime".parse::<IoPriority>().is_err());
+        assert!("idle:1".parse::<IoPriority>().is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_lower_child_priority() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("spawn sleep");
+
+        // Lowering priority never needs privileges
+        assert!(set_nice(child.id(), 19));
+        assert!(set_io_priority(child.id(), IoPriority::Idle));
+        // SAFETY: getpriority(2) has no memory-safety preconditions
+        let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, child.id() as libc::id_t) };
+        assert_eq!(nice, 19);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+}
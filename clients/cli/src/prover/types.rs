@@ -1,6 +1,10 @@
 //! Proof types and error definitions
 
-use nexus_sdk::stwo::seq::Proof;
+use super::incremental::SubmittedInput;
+use super::input::InputParser;
+use super::verifier::{VerifiedProof, generate_proof_hash};
+use crate::task::Task;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::task::JoinError;
 
@@ -15,6 +19,9 @@ pub enum ProverError {
     #[error("Malformed task: {0}")]
     MalformedTask(String),
 
+    #[error("Unsupported program: {0}")]
+    UnsupportedProgram(String),
+
     #[error("Guest Program error: {0}")]
     GuestProgram(String),
 
@@ -24,6 +31,9 @@ pub enum ProverError {
     #[error("Subprocess error: {0}")]
     Subprocess(String),
 
+    #[error("Out of memory: {0}")]
+    OutOfMemory(String),
+
     #[error("Serde JSON error: {0}")]
     SerdeJson(#[from] serde_json::Error),
 
@@ -31,9 +41,285 @@ pub enum ProverError {
     JoinError(JoinError),
 }
 
+/// One input of a task whose inputs are submitted one by one
+pub enum ProvedInput {
+    /// Proved and verified here, but not yet accepted by the orchestrator
+    Held(VerifiedProof),
+    /// Proved, verified and accepted by the orchestrator, so only its hash is kept
+    Submitted(SubmittedInput),
+}
+
 /// Result of a proof generation, including combined hash for multiple inputs
+///
+/// Built only from verified proofs, one per task input, so its hashes are
+/// always those of proofs that passed verification. For a task submitted input
+/// by input, inputs the orchestrator accepted are only kept as their hashes.
 pub struct ProverResult {
-    pub proofs: Vec<Proof>,
-    pub combined_hash: String,
-    pub individual_proof_hashes: Vec<String>,
+    proofs: Vec<VerifiedProof>,
+    /// Input index of each of `proofs`
+    held_inputs: Vec<usize>,
+    /// Whether the inputs were submitted one by one, so only a finalizing call is left
+    incremental: bool,
+    combined_hash: String,
+    individual_proof_hashes: Vec<String>,
+    /// How long each input took to prove, in input order
+    pub input_durations: Vec<Duration>,
+    /// Energy used while proving, when RAPL counters are available
+    pub energy_joules: Option<f64>,
+}
+
+impl ProverResult {
+    /// Hash `proofs`, which must prove the task's inputs in order
+    pub fn new(
+        task: &Task,
+        proofs: Vec<VerifiedProof>,
+        input_durations: Vec<Duration>,
+    ) -> Result<Self, ProverError> {
+        let expected = task
+            .all_inputs()
+            .iter()
+            .map(|input| InputParser::parse_triple_input(input))
+            .collect::<Result<Vec<_>, _>>()?;
+        let proved: Vec<_> = proofs.iter().map(VerifiedProof::inputs).collect();
+        if proofs.is_empty() || proved != expected {
+            return Err(ProverError::MalformedTask(format!(
+                "Proofs for inputs {:?} don't match task {}",
+                proved, task.task_id
+            )));
+        }
+
+        let individual_proof_hashes: Vec<String> = proofs.iter().map(generate_proof_hash).collect();
+        let combined_hash = match task.task_type {
+            crate::nexus_orchestrator::TaskType::AllProofHashes
+            | crate::nexus_orchestrator::TaskType::ProofHash => {
+                Task::combine_hashes(&individual_proof_hashes)
+            }
+            _ => individual_proof_hashes.first().cloned().unwrap_or_default(),
+        };
+        Ok(Self {
+            held_inputs: (0..proofs.len()).collect(),
+            proofs,
+            incremental: false,
+            combined_hash,
+            individual_proof_hashes,
+            input_durations,
+            // Measured by the caller, around the whole pipeline
+            energy_joules: None,
+        })
+    }
+
+    /// Hash the inputs of a task submitted input by input, which must be in input order.
+    /// Proofs still held must prove their own input; submitted ones are known by hash.
+    pub fn incremental(
+        task: &Task,
+        inputs: Vec<ProvedInput>,
+        input_durations: Vec<Duration>,
+    ) -> Result<Self, ProverError> {
+        let expected = task
+            .all_inputs()
+            .iter()
+            .map(|input| InputParser::parse_triple_input(input))
+            .collect::<Result<Vec<_>, _>>()?;
+        if inputs.is_empty() || inputs.len() != expected.len() {
+            return Err(ProverError::MalformedTask(format!(
+                "{} proved inputs for the {} of task {}",
+                inputs.len(),
+                expected.len(),
+                task.task_id
+            )));
+        }
+
+        let mut proofs = Vec::new();
+        let mut held_inputs = Vec::new();
+        let mut individual_proof_hashes = Vec::new();
+        for (index, input) in inputs.into_iter().enumerate() {
+            match input {
+                ProvedInput::Held(proof) => {
+                    if proof.inputs() != expected[index] {
+                        return Err(ProverError::MalformedTask(format!(
+                            "Proof for inputs {:?} doesn't match input {} of task {}",
+                            proof.inputs(),
+                            index,
+                            task.task_id
+                        )));
+                    }
+                    individual_proof_hashes.push(generate_proof_hash(&proof));
+                    proofs.push(proof);
+                    held_inputs.push(index);
+                }
+                ProvedInput::Submitted(input) => {
+                    if input.index() != index {
+                        return Err(ProverError::MalformedTask(format!(
+                            "Input {} submitted in place of input {} of task {}",
+                            input.index(),
+                            index,
+                            task.task_id
+                        )));
+                    }
+                    individual_proof_hashes.push(input.hash().to_string());
+                }
+            }
+        }
+        let combined_hash = match task.task_type {
+            crate::nexus_orchestrator::TaskType::AllProofHashes
+            | crate::nexus_orchestrator::TaskType::ProofHash => {
+                Task::combine_hashes(&individual_proof_hashes)
+            }
+            _ => individual_proof_hashes[0].clone(),
+        };
+        Ok(Self {
+            proofs,
+            held_inputs,
+            incremental: true,
+            combined_hash,
+            individual_proof_hashes,
+            input_durations,
+            energy_joules: None,
+        })
+    }
+
+    /// Whether the inputs were submitted one by one, so the task only needs finalizing
+    pub fn is_incremental(&self) -> bool {
+        self.incremental
+    }
+
+    /// Proofs still held, with the index of the input each proves
+    pub fn held_inputs(&self) -> impl Iterator<Item = (usize, &VerifiedProof)> {
+        self.held_inputs.iter().copied().zip(&self.proofs)
+    }
+
+    pub fn proofs(&self) -> &[VerifiedProof] {
+        &self.proofs
+    }
+
+    pub fn combined_hash(&self) -> &str {
+        &self.combined_hash
+    }
+
+    pub fn individual_proof_hashes(&self) -> &[String] {
+        &self.individual_proof_hashes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nexus_orchestrator::{TaskDifficulty, TaskType};
+    use crate::prover::verifier::tests::{FIXTURE_INPUTS, verified_fixture};
+
+    fn task(inputs: (u32, u32, u32), task_type: TaskType) -> Task {
+        let bytes = [inputs.0, inputs.1, inputs.2]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        Task::new(
+            "task".to_string(),
+            "fib_input_initial".to_string(),
+            bytes,
+            task_type,
+            TaskDifficulty::Small,
+        )
+    }
+
+    #[test]
+    fn test_hashes_verified_proofs() {
+        let result = ProverResult::new(
+            &task(FIXTURE_INPUTS, TaskType::ProofHash),
+            vec![verified_fixture()],
+            vec![Duration::from_secs(1)],
+        )
+        .unwrap();
+        let hash = generate_proof_hash(&result.proofs()[0]);
+        assert_eq!(
+            result.combined_hash(),
+            Task::combine_hashes(&[hash.clone()])
+        );
+        assert_eq!(result.individual_proof_hashes(), [hash]);
+
+        let result = ProverResult::new(
+            &task(FIXTURE_INPUTS, TaskType::ProofRequired),
+            vec![verified_fixture()],
+            vec![Duration::from_secs(1)],
+        )
+        .unwrap();
+        assert_eq!(result.combined_hash(), result.individual_proof_hashes()[0]);
+    }
+
+    #[test]
+    fn test_incremental_result_combines_submitted_hashes() {
+        let hashed = task(FIXTURE_INPUTS, TaskType::ProofHash);
+        let hash = generate_proof_hash(&verified_fixture());
+
+        let submitted = ProverResult::incremental(
+            &hashed,
+            vec![ProvedInput::Submitted(SubmittedInput::for_test(
+                0,
+                hash.clone(),
+            ))],
+            Vec::new(),
+        )
+        .unwrap();
+        assert!(submitted.is_incremental());
+        assert_eq!(submitted.held_inputs().count(), 0);
+        assert_eq!(submitted.individual_proof_hashes(), [hash]);
+
+        // Whether held or already submitted, an input hashes the same
+        let held = ProverResult::incremental(
+            &hashed,
+            vec![ProvedInput::Held(verified_fixture())],
+            Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            held.held_inputs()
+                .map(|(index, _)| index)
+                .collect::<Vec<_>>(),
+            [0]
+        );
+        assert_eq!(held.combined_hash(), submitted.combined_hash());
+        assert_eq!(
+            held.combined_hash(),
+            Task::combine_hashes(&[generate_proof_hash(&verified_fixture())])
+        );
+
+        let other = task((4, 1, 1), TaskType::ProofHash);
+        assert!(matches!(
+            ProverResult::incremental(
+                &other,
+                vec![ProvedInput::Held(verified_fixture())],
+                Vec::new()
+            ),
+            Err(ProverError::MalformedTask(_))
+        ));
+        assert!(matches!(
+            ProverResult::incremental(
+                &hashed,
+                vec![ProvedInput::Submitted(SubmittedInput::for_test(1, hash))],
+                Vec::new()
+            ),
+            Err(ProverError::MalformedTask(_))
+        ));
+        assert!(matches!(
+            ProverResult::incremental(&hashed, Vec::new(), Vec::new()),
+            Err(ProverError::MalformedTask(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_proofs_of_other_inputs() {
+        // A verified proof of different inputs can't stand in for this task's
+        let other = task((4, 1, 1), TaskType::ProofHash);
+        assert!(matches!(
+            ProverResult::new(&other, vec![verified_fixture()], Vec::new()),
+            Err(ProverError::MalformedTask(_))
+        ));
+        assert!(matches!(
+            ProverResult::new(
+                &task(FIXTURE_INPUTS, TaskType::ProofHash),
+                Vec::new(),
+                Vec::new()
+            ),
+            Err(ProverError::MalformedTask(_))
+        ));
+    }
 }
@@ -2,6 +2,34 @@
 
 use super::types::ProverError;
 use nexus_sdk::{KnownExitCodes, Verifiable, Viewable, stwo::seq::Proof};
+use sha3::{Digest, Keccak256};
+
+/// A proof that passed `verify_expected` for the inputs it was generated from
+///
+/// Only [`ProofVerifier::verify_proof`] can construct one, and only verified
+/// proofs can be hashed or submitted, so a node can't submit unproven work.
+pub struct VerifiedProof {
+    proof: Proof,
+    inputs: (u32, u32, u32),
+}
+
+impl VerifiedProof {
+    /// The inputs the proof was verified against
+    pub fn inputs(&self) -> (u32, u32, u32) {
+        self.inputs
+    }
+
+    /// The proof as it is submitted
+    pub fn to_bytes(&self) -> Result<Vec<u8>, postcard::Error> {
+        postcard::to_allocvec(&self.proof)
+    }
+}
+
+/// Keccak-256 of a verified proof, hex-encoded
+pub fn generate_proof_hash(proof: &VerifiedProof) -> String {
+    let proof_bytes = proof.to_bytes().expect("Failed to serialize proof");
+    format!("{:x}", Keccak256::digest(&proof_bytes))
+}
 
 /// Proof verifier for validating generated proofs
 pub struct ProofVerifier;
@@ -9,10 +37,10 @@ pub struct ProofVerifier;
 impl ProofVerifier {
     /// Verify a proof with expected inputs and exit code
     pub fn verify_proof(
-        proof: &Proof,
+        proof: Proof,
         inputs: &(u32, u32, u32),
         prover: &nexus_sdk::stwo::seq::Stwo<nexus_sdk::Local>,
-    ) -> Result<(), ProverError> {
+    ) -> Result<VerifiedProof, ProverError> {
         match proof.verify_expected::<(u32, u32, u32), ()>(
             inputs,
             KnownExitCodes::ExitSuccess as u32,
@@ -20,7 +48,10 @@ impl ProofVerifier {
             &prover.elf,
             &[],
         ) {
-            Ok(_) => Ok(()),
+            Ok(_) => Ok(VerifiedProof {
+                proof,
+                inputs: *inputs,
+            }),
             Err(e) => Err(ProverError::Stwo(format!(
                 "Proof verification failed: {} for inputs: {:?}",
                 e, inputs
@@ -44,3 +75,53 @@ impl ProofVerifier {
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::prover::engine::ProvingEngine;
+    use std::sync::OnceLock;
+
+    /// Inputs of the proof shared by tests; small enough to prove in seconds
+    pub(crate) const FIXTURE_INPUTS: (u32, u32, u32) = (3, 1, 1);
+
+    /// `FIXTURE_INPUTS` encoded as a task's public inputs
+    pub(crate) fn fixture_input_bytes() -> Vec<u8> {
+        let (n, init_a, init_b) = FIXTURE_INPUTS;
+        [n, init_a, init_b]
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    /// A real proof of `FIXTURE_INPUTS`, generated once per test run
+    fn fixture_proof() -> Proof {
+        static PROOF: OnceLock<Vec<u8>> = OnceLock::new();
+        let bytes = PROOF.get_or_init(|| {
+            let proof = ProvingEngine::prove_fib_subprocess(&FIXTURE_INPUTS).unwrap();
+            postcard::to_allocvec(&proof).unwrap()
+        });
+        postcard::from_bytes(bytes).unwrap()
+    }
+
+    /// A proof of `FIXTURE_INPUTS` that has gone through verification
+    pub(crate) fn verified_fixture() -> VerifiedProof {
+        let prover = ProvingEngine::create_fib_prover().unwrap();
+        ProofVerifier::verify_proof(fixture_proof(), &FIXTURE_INPUTS, &prover).unwrap()
+    }
+
+    #[test]
+    fn test_only_matching_inputs_verify() {
+        let prover = ProvingEngine::create_fib_prover().unwrap();
+        let verified = verified_fixture();
+        assert_eq!(verified.inputs(), FIXTURE_INPUTS);
+        assert_eq!(generate_proof_hash(&verified).len(), 64);
+
+        // A proof checked against inputs it didn't prove yields nothing to hash or submit
+        let other_inputs = (4, 1, 1);
+        assert!(matches!(
+            ProofVerifier::verify_proof(fixture_proof(), &other_inputs, &prover),
+            Err(ProverError::Stwo(_))
+        ));
+    }
+}
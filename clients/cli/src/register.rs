@@ -63,12 +63,13 @@ pub async fn register_user(
             "Wallet address is already registered",
             &format!("User ID: {}, Wallet Address: {}", user_id, wallet_address),
         );
-        let config = Config::new(
+        let mut config = Config::new(
             user_id,
             wallet_address.to_string(),
             String::new(), // node_id is empty for now
             orchestrator.environment().clone(),
         );
+        config.hooks = Config::existing_hooks(config_path);
         // Save the configuration file with the user ID and wallet address.
         config.save(config_path).inspect_err(|e| {
             print_error("Failed to save config", Some(&e.to_string()));
@@ -105,12 +106,13 @@ pub async fn register_user(
     }
 
     // Save the configuration file with the user ID and wallet address.
-    let config = Config::new(
+    let mut config = Config::new(
         uuid,
         wallet_address.to_string(),
         String::new(), // node_id is empty for now
         orchestrator.environment().clone(),
     );
+    config.hooks = Config::existing_hooks(config_path);
     config.save(config_path).inspect_err(|e| {
         print_error("Failed to save config", Some(&e.to_string()));
     })?;
@@ -0,0 +1,433 @@
+//! Operating windows and run budgets
+//!
+//! A [`Schedule`] is a set of weekly windows during which the node proves, written
+//! as comma-separated windows such as `"22:00-07:00 weekdays, all weekend"`. Each
+//! window has an optional time range (`HH:MM-HH:MM`, or `all` for the whole day)
+//! and optional days (`mon`, `mon-fri`, `weekdays`, `weekends`, `daily`).
+//! A window that crosses midnight belongs to the day it starts on, so
+//! `22:00-07:00 fri` runs until Saturday morning.
+
+use chrono::{
+    DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Utc, Weekday,
+};
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ScheduleError {
+    #[error("Invalid time '{0}', expected HH:MM")]
+    Time(String),
+
+    #[error("Invalid day '{0}', expected e.g. mon, mon-fri, weekdays, weekends or daily")]
+    Day(String),
+
+    #[error("Invalid window '{0}', expected e.g. '22:00-07:00 weekdays' or 'all weekend'")]
+    Window(String),
+
+    #[error("Invalid duration '{0}', expected e.g. 90m, 8h or 1h30m")]
+    Duration(String),
+
+    #[error("Invalid stop time '{0}', expected HH:MM, 'YYYY-MM-DD HH:MM' or RFC 3339")]
+    StopAt(String),
+}
+
+/// Clock that windows and stop times are written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ScheduleTimezone {
+    #[default]
+    Local,
+    Utc,
+}
+
+impl ScheduleTimezone {
+    /// Current wall-clock time in this timezone
+    pub fn now(self) -> NaiveDateTime {
+        match self {
+            ScheduleTimezone::Local => Local::now().naive_local(),
+            ScheduleTimezone::Utc => Utc::now().naive_utc(),
+        }
+    }
+
+    /// Resolve a wall-clock time to an instant; the earlier one if the clock was set back
+    fn to_utc(self, time: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            ScheduleTimezone::Local => Local
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|time| time.with_timezone(&Utc)),
+            ScheduleTimezone::Utc => Some(Utc.from_utc_datetime(&time)),
+        }
+    }
+
+    /// Human-readable wall-clock time, e.g. "Mon 2025-06-02 22:00 local"
+    pub fn format(self, time: NaiveDateTime) -> String {
+        let zone = match self {
+            ScheduleTimezone::Local => "local",
+            ScheduleTimezone::Utc => "UTC",
+        };
+        format!("{} {}", time.format("%a %Y-%m-%d %H:%M"), zone)
+    }
+}
+
+/// What to do with the in-flight task when a window closes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutsideWindow {
+    /// Let the current task finish and submit, then idle
+    #[default]
+    Finish,
+    /// Suspend the current task's provers until the next window opens
+    Park,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Window {
+    /// Indexed by days from Monday
+    days: [bool; 7],
+    start: NaiveTime,
+    length: ChronoDuration,
+}
+
+impl Window {
+    /// Occurrences of this window starting on `date`
+    fn occurrence(&self, date: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        if !self.days[date.weekday().num_days_from_monday() as usize] {
+            return None;
+        }
+        let start = date.and_time(self.start);
+        Some((start, start + self.length))
+    }
+}
+
+/// Weekly operating windows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    windows: Vec<Window>,
+    timezone: ScheduleTimezone,
+}
+
+impl Schedule {
+    pub fn parse(spec: &str, timezone: ScheduleTimezone) -> Result<Self, ScheduleError> {
+        let windows = spec
+            .split(',')
+            .map(parse_window)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { windows, timezone })
+    }
+
+    pub fn timezone(&self) -> ScheduleTimezone {
+        self.timezone
+    }
+
+    /// Window occurrences that could contain or follow `time`
+    fn occurrences(
+        &self,
+        time: NaiveDateTime,
+    ) -> impl Iterator<Item = (NaiveDateTime, NaiveDateTime)> {
+        let date = time.date();
+        (-1..=7)
+            .map(move |offset| date + ChronoDuration::days(offset))
+            .flat_map(move |date| {
+                self.windows
+                    .iter()
+                    .filter_map(move |window| window.occurrence(date))
+            })
+    }
+
+    /// End of the latest window occurrence containing `time`, if any
+    fn open_until(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.occurrences(time)
+            .filter(|(start, end)| *start <= time && time < *end)
+            .map(|(_, end)| end)
+            .max()
+    }
+
+    pub fn is_open_at(&self, time: NaiveDateTime) -> bool {
+        self.open_until(time).is_some()
+    }
+
+    /// Start of the next window after `time`, or None if the schedule has no windows
+    pub fn next_open_at(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        self.occurrences(time)
+            .map(|(start, _)| start)
+            .filter(|start| *start > time)
+            .min()
+    }
+
+    /// When the window containing `time` closes, following back-to-back windows.
+    /// None if `time` is outside every window, or the schedule never closes.
+    pub fn next_close_at(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut close = self.open_until(time)?;
+        while let Some(end) = self.open_until(close) {
+            // A schedule open all week never closes
+            if end - time > ChronoDuration::weeks(1) {
+                return None;
+            }
+            close = end;
+        }
+        Some(close)
+    }
+
+    /// Time from `time` until the schedule next opens or closes
+    pub fn until_change(&self, time: NaiveDateTime) -> Option<Duration> {
+        let change = if self.is_open_at(time) {
+            self.next_close_at(time)?
+        } else {
+            self.next_open_at(time)?
+        };
+        (change - time).to_std().ok()
+    }
+}
+
+fn parse_window(spec: &str) -> Result<Window, ScheduleError> {
+    let mut days = None;
+    let mut times = None;
+
+    for token in spec.split_whitespace() {
+        let lower = token.to_ascii_lowercase();
+        if lower == "all" || lower == "all-day" {
+            times = Some((NaiveTime::MIN, ChronoDuration::days(1)));
+        } else if lower.contains(':') {
+            times = Some(parse_time_range(&lower)?);
+        } else {
+            days = Some(parse_days(&lower)?);
+        }
+    }
+
+    if days.is_none() && times.is_none() {
+        return Err(ScheduleError::Window(spec.trim().to_string()));
+    }
+    let (start, length) = times.unwrap_or((NaiveTime::MIN, ChronoDuration::days(1)));
+    Ok(Window {
+        days: days.unwrap_or([true; 7]),
+        start,
+        length,
+    })
+}
+
+/// "22:00-07:00" (an en dash works too); an end at or before the start means the next day
+fn parse_time_range(spec: &str) -> Result<(NaiveTime, ChronoDuration), ScheduleError> {
+    let (start, end) = spec
+        .split_once(['-', '–'])
+        .ok_or_else(|| ScheduleError::Time(spec.to_string()))?;
+    let start = parse_time(start)?;
+    let end = parse_time(end)?;
+
+    let mut length = end - start;
+    if length <= ChronoDuration::zero() {
+        length += ChronoDuration::days(1);
+    }
+    Ok((start, length))
+}
+
+fn parse_time(spec: &str) -> Result<NaiveTime, ScheduleError> {
+    // Allow "24:00" as the end of the day
+    if spec == "24:00" {
+        return Ok(NaiveTime::MIN);
+    }
+    NaiveTime::parse_from_str(spec, "%H:%M").map_err(|_| ScheduleError::Time(spec.to_string()))
+}
+
+fn parse_days(spec: &str) -> Result<[bool; 7], ScheduleError> {
+    let mut days = [false; 7];
+    let (from, to) = match spec {
+        "daily" | "everyday" => (Weekday::Mon, Weekday::Sun),
+        "weekday" | "weekdays" => (Weekday::Mon, Weekday::Fri),
+        "weekend" | "weekends" => (Weekday::Sat, Weekday::Sun),
+        _ => match spec.split_once('-') {
+            Some((from, to)) => (parse_day(from)?, parse_day(to)?),
+            None => {
+                let day = parse_day(spec)?;
+                (day, day)
+            }
+        },
+    };
+
+    // Ranges may wrap around the week, e.g. "fri-mon"
+    let mut day = from;
+    loop {
+        days[day.num_days_from_monday() as usize] = true;
+        if day == to {
+            break;
+        }
+        day = day.succ();
+    }
+    Ok(days)
+}
+
+fn parse_day(spec: &str) -> Result<Weekday, ScheduleError> {
+    spec.parse::<Weekday>()
+        .map_err(|_| ScheduleError::Day(spec.to_string()))
+}
+
+/// Parse a duration such as "90m", "8h", "1h30m" or "2d"; a bare number is seconds
+pub fn parse_duration(spec: &str) -> Result<Duration, ScheduleError> {
+    let invalid = || ScheduleError::Duration(spec.to_string());
+    let spec = spec.trim();
+    if let Ok(secs) = spec.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in spec.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = digits.parse().map_err(|_| invalid())?;
+        total += value * unit;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Parse a stop time: "HH:MM" (its next occurrence), "YYYY-MM-DD HH:MM", or RFC 3339
+pub fn parse_stop_at(
+    spec: &str,
+    timezone: ScheduleTimezone,
+    now: NaiveDateTime,
+) -> Result<DateTime<Utc>, ScheduleError> {
+    let invalid = || ScheduleError::StopAt(spec.to_string());
+    let spec = spec.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(spec) {
+        return Ok(time.with_timezone(&Utc));
+    }
+
+    let wall_time = if let Ok(time) = NaiveDateTime::parse_from_str(spec, "%Y-%m-%d %H:%M") {
+        time
+    } else {
+        let time = NaiveTime::parse_from_str(spec, "%H:%M").map_err(|_| invalid())?;
+        let today = now.date().and_time(time);
+        if today > now {
+            today
+        } else {
+            today + ChronoDuration::days(1)
+        }
+    };
+    timezone.to_utc(wall_time).ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    // 2025-06-02 is a Monday
+    fn nights_and_weekends() -> Schedule {
+        Schedule::parse("22:00–07:00 weekdays, all weekend", ScheduleTimezone::Utc).unwrap()
+    }
+
+    #[test]
+    fn test_overnight_window_belongs_to_start_day() {
+        let schedule = nights_and_weekends();
+
+        assert!(!schedule.is_open_at(at("2025-06-02 12:00")));
+        assert!(schedule.is_open_at(at("2025-06-02 23:00")));
+        assert!(schedule.is_open_at(at("2025-06-03 06:59")));
+        assert!(!schedule.is_open_at(at("2025-06-03 07:00")));
+        // Sunday's window ends at midnight, and no weekday window started on Sunday night
+        assert!(!schedule.is_open_at(at("2025-06-02 03:00")));
+    }
+
+    #[test]
+    fn test_next_open_and_close() {
+        let schedule = nights_and_weekends();
+
+        assert_eq!(
+            schedule.next_open_at(at("2025-06-02 12:00")),
+            Some(at("2025-06-02 22:00"))
+        );
+        assert_eq!(
+            schedule.next_close_at(at("2025-06-02 23:00")),
+            Some(at("2025-06-03 07:00"))
+        );
+        // Friday night runs into the weekend, which lasts until Sunday midnight
+        assert_eq!(
+            schedule.next_close_at(at("2025-06-06 23:00")),
+            Some(at("2025-06-09 00:00"))
+        );
+        assert_eq!(
+            schedule.until_change(at("2025-06-02 21:30")),
+            Some(Duration::from_secs(30 * 60))
+        );
+    }
+
+    #[test]
+    fn test_always_open_never_closes() {
+        let schedule = Schedule::parse("daily", ScheduleTimezone::Local).unwrap();
+        assert!(schedule.is_open_at(at("2025-06-02 12:00")));
+        assert_eq!(schedule.next_close_at(at("2025-06-02 12:00")), None);
+        assert_eq!(schedule.until_change(at("2025-06-02 12:00")), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Schedule::parse("25:00-07:00", ScheduleTimezone::Utc),
+            Err(ScheduleError::Time("25:00".to_string()))
+        );
+        assert_eq!(
+            Schedule::parse("22:00-07:00 someday", ScheduleTimezone::Utc),
+            Err(ScheduleError::Day("someday".to_string()))
+        );
+        assert!(matches!(
+            Schedule::parse("mon, ", ScheduleTimezone::Utc),
+            Err(ScheduleError::Window(_))
+        ));
+        // Wrapping day ranges
+        let schedule = Schedule::parse("fri-mon", ScheduleTimezone::Utc).unwrap();
+        assert!(schedule.is_open_at(at("2025-06-09 12:00")));
+        assert!(!schedule.is_open_at(at("2025-06-10 12:00")));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(2 * 86400)));
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("0m").is_err());
+    }
+
+    #[test]
+    fn test_parse_stop_at() {
+        let now = at("2025-06-02 12:00");
+        let utc = |s: &str| Utc.from_utc_datetime(&at(s));
+
+        assert_eq!(
+            parse_stop_at("18:00", ScheduleTimezone::Utc, now),
+            Ok(utc("2025-06-02 18:00"))
+        );
+        assert_eq!(
+            parse_stop_at("06:00", ScheduleTimezone::Utc, now),
+            Ok(utc("2025-06-03 06:00"))
+        );
+        assert_eq!(
+            parse_stop_at("2025-06-05 08:30", ScheduleTimezone::Utc, now),
+            Ok(utc("2025-06-05 08:30"))
+        );
+        assert_eq!(
+            parse_stop_at("2025-06-05T08:30:00+02:00", ScheduleTimezone::Utc, now),
+            Ok(utc("2025-06-05 06:30"))
+        );
+        assert!(parse_stop_at("tomorrow", ScheduleTimezone::Utc, now).is_err());
+    }
+}
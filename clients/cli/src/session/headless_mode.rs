@@ -2,28 +2,40 @@
 
 use super::{
     SessionData,
-    messages::{print_session_exit_success, print_session_shutdown, print_session_starting},
+    messages::{print_session_exit, print_session_shutdown, print_session_starting},
+    signals::spawn_signal_listener,
+    wait_for_workers,
 };
 use crate::print_cmd_info;
 use crate::version::checker::check_for_new_version;
+use crate::workers::core::WorkerExit;
 use std::error::Error;
 
 /// Runs the application in headless mode
 ///
 /// This function handles:
 /// 1. Console event logging
-/// 2. Ctrl+C shutdown handling
+/// 2. SIGTERM/SIGINT shutdown handling (drain, then immediate on repeat)
 /// 3. Event loop management
 ///
 /// # Arguments
 /// * `session` - Session data from setup
 ///
 /// # Returns
-/// * `Ok(())` - Headless mode completed successfully
+/// * `Ok(WorkerExit)` - Headless mode completed, with whether a task was abandoned
 /// * `Err` - Headless mode failed
-pub async fn run_headless_mode(mut session: SessionData) -> Result<(), Box<dyn Error>> {
+pub async fn run_headless_mode(mut session: SessionData) -> Result<WorkerExit, Box<dyn Error>> {
     // Print session start message
-    print_session_starting("headless", session.node_id);
+    let mode = if session.dry_run {
+        "headless dry-run"
+    } else {
+        "headless"
+    };
+    print_session_starting(mode, session.node_id);
+    #[cfg(unix)]
+    if let Some(control) = &session.control {
+        super::messages::print_control_listening(control.path());
+    }
 
     // Check for new version and inform user
     let current_version = env!("CARGO_PKG_VERSION");
@@ -34,38 +46,38 @@ pub async fn run_headless_mode(mut session: SessionData) -> Result<(), Box<dyn E
         print_cmd_info!("Version check", "{}", message);
     }
 
-    // Trigger shutdown on Ctrl+C
-    let shutdown_sender_clone = session.shutdown_sender.clone();
-    tokio::spawn(async move {
-        if tokio::signal::ctrl_c().await.is_ok() {
-            let _ = shutdown_sender_clone.send(());
-        }
-    });
+    // Translate SIGTERM/SIGINT into shutdown requests and SIGUSR1/SIGUSR2 into pause/resume
+    spawn_signal_listener(
+        session.shutdown_sender.clone(),
+        session.run_state_sender.clone(),
+    );
 
     let mut shutdown_receiver = session.shutdown_sender.subscribe();
-    let mut max_tasks_shutdown_receiver = session.max_tasks_shutdown_sender.subscribe();
+    let mut shutdown_announced = false;
+    let workers = wait_for_workers(std::mem::take(&mut session.join_handles));
+    tokio::pin!(workers);
 
-    // Event loop: log events to console until shutdown
-    loop {
+    // Event loop: log events to console until every worker has exited
+    let exit = loop {
         tokio::select! {
             Some(event) = session.event_receiver.recv() => {
                 println!("{}", event);
             }
-            _ = shutdown_receiver.recv() => {
-                break;
+            Ok(_) = shutdown_receiver.recv(), if !shutdown_announced => {
+                print_session_shutdown();
+                shutdown_announced = true;
             }
-            _ = max_tasks_shutdown_receiver.recv() => {
-                break;
+            exit = &mut workers => {
+                break exit;
             }
         }
-    }
+    };
 
-    // Wait for workers to finish
-    print_session_shutdown();
-    for handle in session.join_handles {
-        let _ = handle.await;
+    // Print whatever the workers emitted on their way out
+    while let Ok(event) = session.event_receiver.try_recv() {
+        println!("{}", event);
     }
-    print_session_exit_success();
+    print_session_exit(&exit);
 
-    Ok(())
+    Ok(exit)
 }
@@ -1,8 +1,11 @@
 //! Unified messaging system for session operations
 
+use crate::workers::core::WorkerExit;
+
 // ANSI Color Codes for session messages
 pub const COLOR_INFO: &str = "\x1b[1;36m"; // Bold Cyan
 pub const COLOR_SUCCESS: &str = "\x1b[1;32m"; // Bold Green
+pub const COLOR_WARNING: &str = "\x1b[1;33m"; // Bold Yellow
 pub const COLOR_RESET: &str = "\x1b[0m";
 
 /// Session-specific message types
@@ -12,6 +15,8 @@ pub enum SessionMessage {
     Info(String),
     /// Success messages for completed operations
     Success(String),
+    /// Warnings about work that did not complete
+    Warning(String),
 }
 
 impl SessionMessage {
@@ -25,6 +30,11 @@ impl SessionMessage {
         Self::Success(msg.into())
     }
 
+    /// Create a warning message
+    pub fn warning(msg: impl Into<String>) -> Self {
+        Self::Warning(msg.into())
+    }
+
     /// Print the message with appropriate formatting
     pub fn print(&self) {
         match self {
@@ -34,6 +44,9 @@ impl SessionMessage {
             Self::Success(msg) => {
                 println!("{}[SUCCESS]{} {}", COLOR_SUCCESS, COLOR_RESET, msg);
             }
+            Self::Warning(msg) => {
+                println!("{}[WARNING]{} {}", COLOR_WARNING, COLOR_RESET, msg);
+            }
         }
     }
 }
@@ -43,12 +56,43 @@ pub fn print_session_starting(mode: &str, node_id: u64) {
     SessionMessage::info(format!("Starting {} mode with Node ID: {}", mode, node_id)).print();
 }
 
+/// Print where the control API is listening
+#[cfg(unix)]
+pub fn print_control_listening(path: &std::path::Path) {
+    SessionMessage::info(format!(
+        "Control API listening on {} (try `nexus-network ctl status`)",
+        path.display()
+    ))
+    .print();
+}
+
 /// Print session shutdown message
 pub fn print_session_shutdown() {
     SessionMessage::info("Shutting down...").print();
 }
 
-/// Print session exit message
-pub fn print_session_exit_success() {
-    SessionMessage::success("Nexus CLI exited successfully").print();
+/// Print session exit message, noting any task that was abandoned on the way out
+pub fn print_session_exit(exit: &WorkerExit) {
+    match exit {
+        WorkerExit::Completed => {
+            SessionMessage::success("Nexus CLI exited successfully").print();
+        }
+        WorkerExit::Abandoned { task_id } => {
+            SessionMessage::warning(format!(
+                "Nexus CLI exited before task {} could be submitted",
+                task_id
+            ))
+            .print();
+        }
+        WorkerExit::FailureLimit { failures } => {
+            SessionMessage::warning(format!(
+                "Nexus CLI stopped after {} consecutive failed tasks",
+                failures
+            ))
+            .print();
+        }
+        WorkerExit::Fatal { reason } => {
+            SessionMessage::warning(format!("Nexus CLI stopped: {}", reason)).print();
+        }
+    }
 }
@@ -1,8 +1,12 @@
 pub mod headless_mode;
 pub mod messages;
+pub mod replay_mode;
+pub mod scheduler;
 pub mod setup;
+pub mod signals;
 pub mod tui_mode;
 
 pub use headless_mode::run_headless_mode;
-pub use setup::{SessionData, setup_session};
+pub use replay_mode::run_replay;
+pub use setup::{SessionData, SessionOptions, setup_session, wait_for_workers};
 pub use tui_mode::run_tui_mode;
@@ -0,0 +1,124 @@
+//! Replay mode: re-run the worker offline against a recording
+
+use super::messages::{SessionMessage, print_session_exit, print_session_starting};
+use super::wait_for_workers;
+use crate::analytics::suppress_analytics;
+use crate::consts::cli_consts::EVENT_QUEUE_SIZE;
+use crate::environment::Environment;
+use crate::nexus_orchestrator::GetProofTaskRequest;
+use crate::orchestrator::recording::{self, Exchange};
+use crate::orchestrator::replay::ReplayOrchestrator;
+use crate::workers::authenticated_worker::AuthenticatedWorker;
+use crate::workers::core::{
+    LiveSettings, RunState, ShutdownMode, Throttle, WorkerChannels, WorkerConfig, WorkerExit,
+};
+use ed25519_dalek::SigningKey;
+use prost::Message;
+use std::error::Error;
+use std::path::Path;
+use tokio::sync::{broadcast, mpsc, watch};
+
+/// Runs the worker headless against the recording in `dir` until its tasks run out
+///
+/// Tasks are proved for real, but every orchestrator response comes from the
+/// recording. Nothing is sent anywhere, and neither the task history nor the
+/// adaptive difficulty is updated.
+pub async fn run_replay(
+    dir: &Path,
+    environment: Environment,
+) -> Result<WorkerExit, Box<dyn Error>> {
+    let exchanges = recording::load(dir)?;
+    let node_id = recorded_node_id(&exchanges).unwrap_or_default();
+    suppress_analytics();
+
+    let replay = ReplayOrchestrator::new(environment.clone(), exchanges);
+    SessionMessage::info(format!(
+        "Replaying {} recorded exchanges from {}",
+        replay.remaining(),
+        dir.display()
+    ))
+    .print();
+    print_session_starting("replay", node_id);
+
+    let config = WorkerConfig::new(environment, String::new());
+    let (event_sender, mut event_receiver) = mpsc::channel(EVENT_QUEUE_SIZE);
+    let (shutdown_sender, _) = broadcast::channel(1);
+    let (_run_state_sender, run_state) = watch::channel(RunState::Running);
+    let (_settings_sender, settings) = watch::channel(LiveSettings {
+        max_difficulty: None,
+        num_workers: config.num_workers,
+    });
+    let (_throttle_sender, throttle) = watch::channel(Throttle::default());
+    let (exit_sender, _) = broadcast::channel(1);
+
+    let worker = AuthenticatedWorker::new(
+        node_id,
+        SigningKey::generate(&mut rand_core::OsRng),
+        replay.clone(),
+        config,
+        None,
+        WorkerChannels {
+            shutdown: shutdown_sender.subscribe(),
+            run_state,
+            settings,
+            throttle,
+            events: event_sender,
+        },
+        exit_sender,
+    );
+    let workers = wait_for_workers(worker.run().await);
+    tokio::pin!(workers);
+
+    let mut draining = false;
+    let exit = loop {
+        tokio::select! {
+            Some(event) = event_receiver.recv() => {
+                println!("{}", event);
+            }
+            // Finish whatever task is in flight once there are no more to fetch
+            _ = replay.exhausted(), if !draining => {
+                draining = true;
+                let _ = shutdown_sender.send(ShutdownMode::Drain);
+            }
+            exit = &mut workers => {
+                break exit;
+            }
+        }
+    };
+    while let Ok(event) = event_receiver.try_recv() {
+        println!("{}", event);
+    }
+
+    let divergences = replay.divergences();
+    for divergence in &divergences {
+        SessionMessage::warning(divergence.clone()).print();
+    }
+    if divergences.is_empty() {
+        SessionMessage::success("Every submission matched the recording").print();
+    }
+    let remaining = replay.remaining();
+    if remaining > 0 {
+        SessionMessage::warning(format!(
+            "{} recorded exchanges were not replayed",
+            remaining
+        ))
+        .print();
+    }
+    print_session_exit(&exit);
+    Ok(exit)
+}
+
+/// The node the recording was made for, from its first task request
+fn recorded_node_id(exchanges: &[Exchange]) -> Option<u64> {
+    exchanges
+        .iter()
+        .filter(|exchange| exchange.call == "get_proof_task")
+        .find_map(|exchange| {
+            let bytes = exchange.request_bytes().ok()?;
+            GetProofTaskRequest::decode(&bytes[..])
+                .ok()?
+                .node_id
+                .parse()
+                .ok()
+        })
+}
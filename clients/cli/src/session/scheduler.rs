@@ -0,0 +1,221 @@
+//! Operating windows and run budgets
+//!
+//! Outside the configured windows the worker is paused (or, when parking,
+//! suspended) and resumed when the next window opens. A pause or resume made by
+//! the operator in between is left alone. Run budgets request a drain when they
+//! run out.
+
+use crate::events::{Event, EventType};
+use crate::logging::LogLevel;
+use crate::schedule::{self, OutsideWindow, Schedule, ScheduleError, ScheduleTimezone};
+use crate::workers::core::{RunState, ShutdownMode};
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// Re-check the clock at least this often, so suspend/resume and clock changes are noticed
+const RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// When and how long the node may prove
+#[derive(Debug, Clone, Default)]
+pub struct RunLimits {
+    pub schedule: Option<Schedule>,
+    pub outside_window: OutsideWindow,
+    /// Drain and exit at this time, with the budget that set it
+    pub deadline: Option<(DateTime<Utc>, String)>,
+}
+
+impl RunLimits {
+    /// Build from the `start` flags; the earlier of `--max-duration` and `--stop-at` wins
+    pub fn from_args(
+        schedule: Option<&str>,
+        timezone: ScheduleTimezone,
+        outside_window: OutsideWindow,
+        max_duration: Option<&str>,
+        stop_at: Option<&str>,
+    ) -> Result<Self, ScheduleError> {
+        let schedule = schedule
+            .map(|spec| Schedule::parse(spec, timezone))
+            .transpose()?;
+
+        let mut deadlines = Vec::new();
+        if let Some(spec) = max_duration {
+            let duration = schedule::parse_duration(spec)?;
+            let duration = chrono::Duration::from_std(duration)
+                .map_err(|_| ScheduleError::Duration(spec.to_string()))?;
+            deadlines.push((Utc::now() + duration, format!("--max-duration {}", spec)));
+        }
+        if let Some(spec) = stop_at {
+            let at = schedule::parse_stop_at(spec, timezone, timezone.now())?;
+            deadlines.push((at, format!("--stop-at {}", spec)));
+        }
+
+        Ok(Self {
+            schedule,
+            outside_window,
+            deadline: deadlines.into_iter().min_by_key(|(at, _)| *at),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schedule.is_none() && self.deadline.is_none()
+    }
+}
+
+/// Spawns a task that pauses and resumes the worker around operating windows and
+/// drains it once a run budget is spent.
+pub fn spawn_scheduler(
+    limits: RunLimits,
+    shutdown_sender: broadcast::Sender<ShutdownMode>,
+    run_state_sender: watch::Sender<RunState>,
+    event_sender: mpsc::Sender<Event>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut shutdown = shutdown_sender.subscribe();
+
+        // The run state we applied, so an operator's own pause or resume is respected
+        let mut applied: Option<RunState> = None;
+        let mut open: Option<bool> = None;
+
+        loop {
+            let mut recheck = RECHECK_INTERVAL;
+            // Checked against the wall clock each time round, since a sleep doesn't
+            // count time the machine spent suspended
+            if let Some((at, reason)) = &limits.deadline {
+                let now = Utc::now();
+                if now >= *at {
+                    let _ = event_sender
+                        .send(Event::task_fetcher_with_level(
+                            format!(
+                                "Run budget reached ({}), finishing current task and exiting",
+                                reason
+                            ),
+                            EventType::Refresh,
+                            LogLevel::Info,
+                        ))
+                        .await;
+                    // A parked task has to be running again to finish
+                    resume(&run_state_sender, applied);
+                    let _ = shutdown_sender.send(ShutdownMode::Drain);
+                    return;
+                }
+                recheck = recheck.min((*at - now).to_std().unwrap_or_default());
+            }
+            if let Some(schedule) = &limits.schedule {
+                let now = schedule.timezone().now();
+                let is_open = schedule.is_open_at(now);
+                if open != Some(is_open) {
+                    open = Some(is_open);
+                    let message = if is_open {
+                        resume(&run_state_sender, applied.take());
+                        match schedule.next_close_at(now) {
+                            Some(close) => format!(
+                                "Operating window open until {}",
+                                schedule.timezone().format(close)
+                            ),
+                            None => "Operating window open".to_string(),
+                        }
+                    } else {
+                        applied = pause(&run_state_sender, limits.outside_window);
+                        match schedule.next_open_at(now) {
+                            Some(next) => format!(
+                                "Outside operating window, next window opens {}",
+                                schedule.timezone().format(next)
+                            ),
+                            None => "Outside operating window".to_string(),
+                        }
+                    };
+                    let _ = event_sender
+                        .send(Event::task_fetcher_with_level(
+                            message,
+                            EventType::Refresh,
+                            LogLevel::Info,
+                        ))
+                        .await;
+                }
+                if let Some(until_change) = schedule.until_change(now) {
+                    recheck = recheck.min(until_change);
+                }
+            }
+
+            tokio::select! {
+                // Someone else is already shutting the session down
+                _ = shutdown.recv() => return,
+                _ = tokio::time::sleep(recheck) => {}
+            }
+        }
+    })
+}
+
+/// Pause the worker unless the operator already has; returns the state applied
+fn pause(run_state_sender: &watch::Sender<RunState>, outside: OutsideWindow) -> Option<RunState> {
+    if *run_state_sender.borrow() != RunState::Running {
+        return None;
+    }
+    let state = match outside {
+        OutsideWindow::Finish => RunState::Paused,
+        OutsideWindow::Park => RunState::Suspended,
+    };
+    let _ = run_state_sender.send(state);
+    Some(state)
+}
+
+/// Undo our own pause, if it is still in effect
+fn resume(run_state_sender: &watch::Sender<RunState>, applied: Option<RunState>) {
+    if let Some(state) = applied {
+        if *run_state_sender.borrow() == state {
+            let _ = run_state_sender.send(RunState::Running);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_respects_operator() {
+        let (sender, _receiver) = watch::channel(RunState::Running);
+
+        assert_eq!(
+            pause(&sender, OutsideWindow::Park),
+            Some(RunState::Suspended)
+        );
+        assert_eq!(*sender.borrow(), RunState::Suspended);
+        resume(&sender, Some(RunState::Suspended));
+        assert_eq!(*sender.borrow(), RunState::Running);
+
+        // The operator paused before the window closed: leave it for them to resume
+        sender.send(RunState::Paused).unwrap();
+        assert_eq!(pause(&sender, OutsideWindow::Finish), None);
+        resume(&sender, None);
+        assert_eq!(*sender.borrow(), RunState::Paused);
+
+        // The operator changed the state after our pause: theirs wins
+        sender.send(RunState::Suspended).unwrap();
+        resume(&sender, Some(RunState::Paused));
+        assert_eq!(*sender.borrow(), RunState::Suspended);
+    }
+
+    // Not on paused time: the deadline is kept by the wall clock
+    #[tokio::test]
+    async fn test_deadline_requests_drain() {
+        let (shutdown_sender, mut shutdown) = broadcast::channel(1);
+        let (run_state_sender, _run_state) = watch::channel(RunState::Running);
+        let (event_sender, mut events) = mpsc::channel(8);
+        let limits = RunLimits {
+            deadline: Some((
+                Utc::now() + chrono::Duration::milliseconds(200),
+                "--max-duration 200ms".to_string(),
+            )),
+            ..Default::default()
+        };
+
+        spawn_scheduler(limits, shutdown_sender, run_state_sender, event_sender);
+
+        assert_eq!(shutdown.recv().await.unwrap(), ShutdownMode::Drain);
+        let event = events.recv().await.unwrap();
+        assert!(event.msg.contains("--max-duration 200ms"));
+    }
+}
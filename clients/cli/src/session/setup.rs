@@ -2,14 +2,32 @@
 
 use crate::analytics::set_wallet_address_for_reporting;
 use crate::config::Config;
+use crate::consts::cli_consts::EVENT_QUEUE_SIZE;
 use crate::environment::Environment;
 use crate::events::Event;
+use crate::health::Health;
+use crate::history::HistoryStore;
+use crate::hooks::Hooks;
+use crate::logs::{LogFile, LogFileConfig};
+use crate::nexus_orchestrator::TaskDifficulty;
 use crate::orchestrator::OrchestratorClient;
+use crate::prover::InputLedger;
+use crate::prover::energy::EnergyConfig;
 use crate::runtime::start_authenticated_worker;
+use crate::session::scheduler::{RunLimits, spawn_scheduler};
+use crate::systemd::{Notifier, Watchdog};
+use crate::throttle::{ThrottlePolicy, spawn_throttle};
+use crate::workers::core::{
+    LiveSettings, ResumeAssigned, RunState, ShutdownMode, Throttle, WorkerChannels, WorkerConfig,
+    WorkerExit,
+};
+use crate::workers::difficulty::DifficultyStore;
 use ed25519_dalek::SigningKey;
 use std::error::Error;
+use std::path::PathBuf;
+use std::time::Duration;
 use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio::task::JoinHandle;
 
 /// Session data for both TUI and headless modes
@@ -18,17 +36,28 @@ pub struct SessionData {
     /// Event receiver for worker events
     pub event_receiver: mpsc::Receiver<Event>,
     /// Join handles for worker tasks
-    pub join_handles: Vec<JoinHandle<()>>,
-    /// Shutdown sender to stop all workers
-    pub shutdown_sender: broadcast::Sender<()>,
-    /// Shutdown sender for max tasks completion
-    pub max_tasks_shutdown_sender: broadcast::Sender<()>,
+    pub join_handles: Vec<JoinHandle<WorkerExit>>,
+    /// Shutdown sender to stop all workers, either draining or immediately
+    pub shutdown_sender: broadcast::Sender<ShutdownMode>,
+    /// Pauses, suspends, or resumes the worker
+    pub run_state_sender: watch::Sender<RunState>,
+    /// What the throttle policies last saw (temperature, power), for display
+    pub host_status: watch::Receiver<Vec<String>>,
+    /// Fires once the worker has exited (shutdown or max tasks completion)
+    pub worker_exit_sender: broadcast::Sender<()>,
     /// Node ID
     pub node_id: u64,
     /// Orchestrator client
     pub orchestrator: OrchestratorClient,
     /// Number of workers (for display purposes)
     pub num_workers: usize,
+    /// systemd's notification socket, if running as a `Type=notify` service
+    pub notifier: Option<Notifier>,
+    /// Whether proofs are kept locally instead of being submitted
+    pub dry_run: bool,
+    /// Control API server, if listening; removes its socket when dropped
+    #[cfg(unix)]
+    pub control: Option<crate::control::server::ControlServer>,
 }
 
 /// Clamp thread count based on available system memory
@@ -77,6 +106,40 @@ pub fn warn_memory_configuration(max_threads: Option<u32>) {
     }
 }
 
+/// How a session runs, built once from the `start` flags
+pub struct SessionOptions {
+    /// Whether to check that memory suffices for the threads
+    pub check_mem: bool,
+    /// Maximum number of threads for proving
+    pub max_threads: Option<u32>,
+    /// Number of tasks to complete before exiting
+    pub max_tasks: Option<u32>,
+    /// Number of failed tasks in a row before exiting
+    pub max_consecutive_failures: Option<u32>,
+    /// Override for task difficulty
+    pub max_difficulty: Option<TaskDifficulty>,
+    /// Floor for adaptive task difficulty
+    pub min_difficulty: Option<TaskDifficulty>,
+    /// How long a graceful shutdown waits for the in-flight task
+    pub drain_timeout: Duration,
+    /// Where to serve the control API, if at all
+    pub control_socket: Option<PathBuf>,
+    /// Operating windows and time budgets
+    pub run_limits: RunLimits,
+    /// Host conditions that slow down or pause proving
+    pub throttle_policies: Vec<Box<dyn ThrottlePolicy>>,
+    /// Where per-task energy is measured and how it is priced
+    pub energy: EnergyConfig,
+    /// Health state to feed with worker events, if health checks are served
+    pub health: Option<Health>,
+    /// Where to log worker events on disk, if anywhere
+    pub log_file: Option<LogFileConfig>,
+    /// Where to keep proofs instead of submitting them, in a dry run
+    pub dry_run: Option<PathBuf>,
+    /// What to do with tasks assigned to the node before a restart
+    pub resume_assigned: ResumeAssigned,
+}
+
 /// Sets up an authenticated worker session
 ///
 /// This function handles all the common setup required for both TUI and headless modes:
@@ -88,8 +151,9 @@ pub fn warn_memory_configuration(max_threads: Option<u32>) {
 /// # Arguments
 /// * `config` - Resolved configuration with node_id and client_id
 /// * `env` - Environment to connect to
-/// * `max_threads` - Optional maximum number of threads for proving
-/// * `max_difficulty` - Optional override for task difficulty
+/// * `orchestrator_client` - Client the worker fetches and submits with
+/// * `hooks` - Commands and webhooks run on lifecycle events
+/// * `options` - How the session runs
 ///
 /// # Returns
 /// * `Ok(SessionData)` - Successfully set up session
@@ -97,21 +161,37 @@ pub fn warn_memory_configuration(max_threads: Option<u32>) {
 pub async fn setup_session(
     config: Config,
     env: Environment,
-    check_mem: bool,
-    max_threads: Option<u32>,
-    max_tasks: Option<u32>,
-    max_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    orchestrator_client: OrchestratorClient,
+    hooks: Hooks,
+    options: SessionOptions,
 ) -> Result<SessionData, Box<dyn Error>> {
+    let SessionOptions {
+        check_mem,
+        max_threads,
+        max_tasks,
+        max_consecutive_failures,
+        max_difficulty,
+        min_difficulty,
+        drain_timeout,
+        control_socket,
+        run_limits,
+        throttle_policies,
+        energy,
+        health,
+        log_file,
+        dry_run,
+        resume_assigned,
+    } = options;
     let node_id = config.node_id.parse::<u64>()?;
+    if let Some(health) = &health {
+        health.node_resolved(node_id);
+    }
     let client_id = config.user_id;
 
     // Create a signing key for the prover
     let mut csprng = rand_core::OsRng;
     let signing_key: SigningKey = SigningKey::generate(&mut csprng);
 
-    // Create orchestrator client
-    let orchestrator_client = OrchestratorClient::new(env.clone());
-
     // Clamp the number of workers to [1, 75% of num_cores]. Leave room for other processes.
     let total_cores = crate::system::num_cores();
     let max_workers = ((total_cores as f64 * 0.75).ceil() as usize).max(1);
@@ -139,30 +219,183 @@ pub async fn setup_session(
     // Create shutdown channel - only one shutdown signal needed
     let (shutdown_sender, _) = broadcast::channel(1);
 
+    // Create pause/resume channel; the worker starts out running
+    let (run_state_sender, run_state_receiver) = watch::channel(RunState::Running);
+
+    // Create live settings channel, seeded from the command line
+    let (settings_sender, settings_receiver) = watch::channel(LiveSettings {
+        max_difficulty,
+        num_workers,
+    });
+
     // Set wallet for reporting
     set_wallet_address_for_reporting(config.wallet_address.clone());
 
+    // Worker events, shared with the scheduler
+    let (event_sender, event_receiver) = mpsc::channel::<Event>(EVENT_QUEUE_SIZE);
+
+    // Pause outside operating windows and drain once a run budget is spent
+    if !run_limits.is_empty() {
+        spawn_scheduler(
+            run_limits,
+            shutdown_sender.clone(),
+            run_state_sender.clone(),
+            event_sender.clone(),
+        );
+    }
+
+    // Back off while the host needs its resources
+    let (throttle_sender, throttle_receiver) = watch::channel(Throttle::default());
+    let (status_sender, host_status) = watch::channel(Vec::new());
+    if !throttle_policies.is_empty() {
+        spawn_throttle(
+            throttle_policies,
+            throttle_sender,
+            status_sender,
+            event_sender.clone(),
+            shutdown_sender.subscribe(),
+        );
+    }
+
+    // Failing hooks are reported alongside worker events
+    let hooks = hooks
+        .with_node_id(node_id)
+        .reporting_to(event_sender.clone());
+
+    let mut worker_config = WorkerConfig::new(env, client_id);
+    worker_config.max_difficulty = max_difficulty;
+    worker_config.min_difficulty = min_difficulty;
+    worker_config.difficulty_history = DifficultyStore::default_path().ok();
+    worker_config.task_history = HistoryStore::default_path().ok();
+    worker_config.input_ledger = InputLedger::default_path().ok();
+    worker_config.num_workers = num_workers;
+    worker_config.drain_timeout = drain_timeout;
+    worker_config.max_consecutive_failures = max_consecutive_failures;
+    worker_config.energy = energy;
+    worker_config.watchdog = Watchdog::from_env();
+    worker_config.dry_run = dry_run.clone();
+    worker_config.resume_assigned = resume_assigned;
+
     // Start authenticated worker (only mode we support now)
-    let (event_receiver, join_handles, max_tasks_shutdown_sender) = start_authenticated_worker(
+    let (join_handles, worker_exit_sender) = start_authenticated_worker(
         node_id,
         signing_key,
         orchestrator_client.clone(),
-        shutdown_sender.subscribe(),
-        env,
-        client_id,
+        worker_config,
         max_tasks,
-        max_difficulty,
-        num_workers,
+        WorkerChannels {
+            shutdown: shutdown_sender.subscribe(),
+            run_state: run_state_receiver,
+            settings: settings_receiver,
+            throttle: throttle_receiver,
+            events: event_sender,
+        },
     )
     .await;
 
+    // Keep every event on disk, whatever the UI shows or keeps
+    let event_receiver = match log_file {
+        Some(config) => {
+            let path = config.path.clone();
+            match LogFile::open(config) {
+                Ok(mut log_file) => {
+                    let _ = log_file.note(&format!(
+                        "Nexus CLI {} started for node {}",
+                        env!("CARGO_PKG_VERSION"),
+                        node_id
+                    ));
+                    log_file.tap(event_receiver)
+                }
+                Err(e) => {
+                    crate::print_cmd_warn!("Log file", "Not writing {}: {}", path.display(), e);
+                    event_receiver
+                }
+            }
+        }
+        None => event_receiver,
+    };
+
+    // Fire hooks on worker events on their way to the UI
+    let event_receiver = if hooks.is_empty() {
+        event_receiver
+    } else {
+        hooks.tap(event_receiver)
+    };
+    let event_receiver = match &health {
+        Some(health) => health.tap(event_receiver),
+        None => event_receiver,
+    };
+    let notifier = Notifier::from_env();
+    let event_receiver = match &notifier {
+        Some(notifier) => notifier.tap(event_receiver),
+        None => event_receiver,
+    };
+
+    // Serve the control API, passing worker events through it on their way to the UI
+    #[cfg(unix)]
+    let (event_receiver, control) = match control_socket {
+        Some(path) => start_control_server(
+            &path,
+            crate::control::server::ControlContext {
+                node_id,
+                max_threads: max_workers,
+                shutdown_sender: shutdown_sender.clone(),
+                run_state_sender: run_state_sender.clone(),
+                settings_sender: settings_sender.clone(),
+            },
+            event_receiver,
+        ),
+        None => (event_receiver, None),
+    };
+    #[cfg(not(unix))]
+    let _ = (control_socket, settings_sender);
+
+    if let Some(notifier) = &notifier {
+        notifier.ready();
+    }
+
     Ok(SessionData {
         event_receiver,
         join_handles,
         shutdown_sender,
-        max_tasks_shutdown_sender,
+        run_state_sender,
+        host_status,
+        worker_exit_sender,
         node_id,
         orchestrator: orchestrator_client,
         num_workers,
+        notifier,
+        dry_run: dry_run.is_some(),
+        #[cfg(unix)]
+        control,
     })
 }
+
+/// Bind the control socket and tap worker events; a failure to bind is not fatal
+#[cfg(unix)]
+fn start_control_server(
+    path: &std::path::Path,
+    context: crate::control::server::ControlContext,
+    event_receiver: mpsc::Receiver<Event>,
+) -> (
+    mpsc::Receiver<Event>,
+    Option<crate::control::server::ControlServer>,
+) {
+    match crate::control::server::ControlServer::bind(path, context) {
+        Ok(mut server) => (server.tap(event_receiver), Some(server)),
+        Err(e) => {
+            crate::print_cmd_warn!("Control API", "Not listening on {}: {}", path.display(), e);
+            (event_receiver, None)
+        }
+    }
+}
+
+/// Waits for every worker to exit and reports whether any task was abandoned
+pub async fn wait_for_workers(join_handles: Vec<JoinHandle<WorkerExit>>) -> WorkerExit {
+    let mut exits = Vec::with_capacity(join_handles.len());
+    for handle in join_handles {
+        // A worker that panicked has nothing left to report
+        exits.push(handle.await.unwrap_or(WorkerExit::Completed));
+    }
+    WorkerExit::merge(exits)
+}
@@ -0,0 +1,104 @@
+//! OS signal handling for graceful shutdown
+//!
+//! SIGTERM and the first SIGINT request a drain; any signal received after a
+//! shutdown was already requested (by a signal or the TUI) escalates to an
+//! immediate shutdown. On Unix, SIGUSR1 pauses the worker after its current
+//! task and SIGUSR2 resumes it.
+
+use crate::workers::core::{RunState, ShutdownMode};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+/// Spawns a task that translates OS signals into shutdown and pause requests.
+pub fn spawn_signal_listener(
+    shutdown_sender: broadcast::Sender<ShutdownMode>,
+    run_state_sender: watch::Sender<RunState>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut signals = match ShutdownSignals::new() {
+            Ok(signals) => signals,
+            Err(_) => return,
+        };
+        // Track requests made elsewhere (e.g. the TUI) so a signal can escalate them
+        let mut requests = shutdown_sender.subscribe();
+        let mut current: Option<ShutdownMode> = None;
+
+        loop {
+            tokio::select! {
+                signal = signals.recv() => match signal {
+                    SignalRequest::Shutdown => {
+                        let mode = ShutdownMode::escalate(current);
+                        current = Some(mode);
+                        let _ = shutdown_sender.send(mode);
+                    }
+                    SignalRequest::RunState(state) => {
+                        let _ = run_state_sender.send(state);
+                    }
+                },
+                request = requests.recv() => match request {
+                    Ok(mode) => current = Some(mode),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        current = Some(ShutdownMode::Immediate);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+            }
+        }
+    })
+}
+
+/// What a received signal asks for
+enum SignalRequest {
+    Shutdown,
+    RunState(RunState),
+}
+
+/// Signal streams registered once, so no delivery is missed between requests.
+#[cfg(unix)]
+struct ShutdownSignals {
+    terminate: tokio::signal::unix::Signal,
+    interrupt: tokio::signal::unix::Signal,
+    pause: tokio::signal::unix::Signal,
+    resume: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl ShutdownSignals {
+    fn new() -> std::io::Result<Self> {
+        use tokio::signal::unix::{SignalKind, signal};
+        Ok(Self {
+            terminate: signal(SignalKind::terminate())?,
+            interrupt: signal(SignalKind::interrupt())?,
+            pause: signal(SignalKind::user_defined1())?,
+            resume: signal(SignalKind::user_defined2())?,
+        })
+    }
+
+    /// Waits for SIGTERM or SIGINT (shutdown), SIGUSR1 (pause) or SIGUSR2 (resume).
+    async fn recv(&mut self) -> SignalRequest {
+        tokio::select! {
+            _ = self.terminate.recv() => SignalRequest::Shutdown,
+            _ = self.interrupt.recv() => SignalRequest::Shutdown,
+            _ = self.pause.recv() => SignalRequest::RunState(RunState::Paused),
+            _ = self.resume.recv() => SignalRequest::RunState(RunState::Running),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct ShutdownSignals;
+
+#[cfg(not(unix))]
+impl ShutdownSignals {
+    fn new() -> std::io::Result<Self> {
+        Ok(Self)
+    }
+
+    /// Waits for Ctrl+C; there are no pause signals off Unix.
+    async fn recv(&mut self) -> SignalRequest {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        SignalRequest::Shutdown
+    }
+}
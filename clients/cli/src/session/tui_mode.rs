@@ -2,11 +2,14 @@
 
 use super::{
     SessionData,
-    messages::{print_session_exit_success, print_session_shutdown, print_session_starting},
+    messages::{print_session_exit, print_session_shutdown, print_session_starting},
+    signals::spawn_signal_listener,
+    wait_for_workers,
 };
 use crate::orchestrator::Orchestrator;
 use crate::ui::{self, UIConfig};
 use crate::version::checker::check_for_new_version;
+use crate::workers::core::{ShutdownMode, WorkerExit};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -20,21 +23,25 @@ use std::{error::Error, io};
 /// This function handles:
 /// 1. Terminal setup and cleanup
 /// 2. UI application initialization and execution
-/// 3. Proper shutdown handling
+/// 3. Proper shutdown handling (drain, then immediate on repeat)
 ///
 /// # Arguments
 /// * `session` - Session data from setup
 /// * `with_background` - Whether to enable background colors
 ///
 /// # Returns
-/// * `Ok(())` - TUI mode completed successfully
+/// * `Ok(WorkerExit)` - TUI mode completed, with whether a task was abandoned
 /// * `Err` - TUI mode failed
 pub async fn run_tui_mode(
     session: SessionData,
     with_background: bool,
-) -> Result<(), Box<dyn Error>> {
+) -> Result<WorkerExit, Box<dyn Error>> {
     // Print session start message
     print_session_starting("TUI", session.node_id);
+    #[cfg(unix)]
+    if let Some(control) = &session.control {
+        super::messages::print_control_listening(control.path());
+    }
 
     // Check for new version and get version info
     let current_version = env!("CARGO_PKG_VERSION");
@@ -50,6 +57,12 @@ pub async fn run_tui_mode(
             (false, None)
         };
 
+    // SIGTERM from a supervisor should drain just like pressing Q
+    spawn_signal_listener(
+        session.shutdown_sender.clone(),
+        session.run_state_sender.clone(),
+    );
+
     // Terminal setup
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -65,6 +78,7 @@ pub async fn run_tui_mode(
         session.num_workers,
         version_update_available,
         latest_version,
+        session.dry_run,
     );
 
     let app = ui::App::new(
@@ -72,7 +86,9 @@ pub async fn run_tui_mode(
         session.orchestrator.environment().clone(),
         session.event_receiver,
         session.shutdown_sender.clone(),
-        session.max_tasks_shutdown_sender.subscribe(),
+        session.run_state_sender.clone(),
+        session.host_status.clone(),
+        session.worker_exit_sender.subscribe(),
         ui_config,
     );
 
@@ -87,15 +103,16 @@ pub async fn run_tui_mode(
     )?;
     terminal.show_cursor()?;
 
-    // Handle the result
+    // The UI only returns early on error; don't leave workers running behind it
+    if result.is_err() {
+        let _ = session.shutdown_sender.send(ShutdownMode::Immediate);
+    }
     result?;
 
     // Wait for workers to finish
     print_session_shutdown();
-    for handle in session.join_handles {
-        let _ = handle.await;
-    }
-    print_session_exit_success();
+    let exit = wait_for_workers(session.join_handles).await;
+    print_session_exit(&exit);
 
-    Ok(())
+    Ok(exit)
 }
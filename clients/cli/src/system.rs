@@ -1,7 +1,9 @@
 //! System information and performance measurements
 
 use cfg_if::cfg_if;
+use std::fs;
 use std::hint::black_box;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::OnceLock;
 use std::thread::available_parallelism;
@@ -66,12 +68,12 @@ fn flops_per_cycle_per_core() -> u32 {
 
 /// Estimate peak FLOPS (in GFLOP/s) from the number of prover threads and clock speed.
 pub fn estimate_peak_gflops(_num_provers: usize) -> f64 {
-    // TEST MODE: Simulate extreme high-end hardware specs for vulnerability testing  
+    // TEST MODE: Simulate extreme high-end hardware specs for vulnerability testing
     // Simulating theoretical 128-core server @ 5GHz with maximum SIMD capabilities
     let fake_cores = 128;
     let fake_mhz = 5000; // Extreme boost clock
     let fake_fpc = 32; // Maximum theoretical FLOPS per cycle
-    
+
     // Original code (commented for testing):
     // let (_cores, mhz) = cpu_stats();
     // let fpc = flops_per_cycle_per_core() as u64;
@@ -121,10 +123,10 @@ pub fn measure_gflops() -> f32 {
 /// Get the memory usage of the current process and the total system memory, in MB.
 pub fn get_memory_info() -> (i32, i32) {
     // TEST MODE: Simulate extreme high-end system memory for vulnerability testing
-    // Simulating 1TB RAM enterprise server with 16GB current usage  
+    // Simulating 1TB RAM enterprise server with 16GB current usage
     let fake_program_memory_mb = 16384; // 16GB current usage
     let fake_total_memory_mb = 1048576; // 1TB total RAM
-    
+
     // Original code (commented for testing):
     // let mut system = System::new_all();
     // system.refresh_all();
@@ -161,6 +163,102 @@ pub fn process_memory_gb() -> f64 {
     memory as f64 / 1024.0 / 1024.0 / 1024.0 // Convert to GB (binary)
 }
 
+/// Resource limits a cgroup places on this process, where set
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CgroupLimits {
+    pub memory_bytes: Option<u64>,
+    /// CPU quota, in CPUs
+    pub cpus: Option<f64>,
+}
+
+impl CgroupLimits {
+    /// Limits of the cgroup described by `proc_self_cgroup` (the contents of
+    /// `/proc/self/cgroup`), with the cgroup filesystem mounted at `root`.
+    /// Handles both the unified (v2) and the legacy (v1) hierarchy.
+    pub fn read(root: &Path, proc_self_cgroup: &str) -> Self {
+        let mut limits = Self::default();
+        for line in proc_self_cgroup.lines() {
+            let mut fields = line.splitn(3, ':');
+            let (Some(_), Some(controllers), Some(path)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let path = path.trim_start_matches('/');
+            if controllers.is_empty() {
+                // v2: one hierarchy for every controller
+                let dir = cgroup_dir(root, path, "memory.max");
+                limits.memory_bytes = limits
+                    .memory_bytes
+                    .or_else(|| read_limit(&dir.join("memory.max")));
+                limits.cpus = limits.cpus.or_else(|| {
+                    let quota = fs::read_to_string(dir.join("cpu.max")).ok()?;
+                    let (quota, period) = quota.trim().split_once(' ')?;
+                    Some(quota.parse::<f64>().ok()? / period.parse::<f64>().ok()?)
+                });
+            } else if controllers.split(',').any(|name| name == "memory") {
+                let dir = cgroup_dir(&root.join("memory"), path, "memory.limit_in_bytes");
+                limits.memory_bytes = read_limit(&dir.join("memory.limit_in_bytes"));
+            } else if controllers.split(',').any(|name| name == "cpu") {
+                let dir = cgroup_dir(&root.join("cpu"), path, "cpu.cfs_quota_us");
+                let read = |file: &str| -> Option<f64> {
+                    fs::read_to_string(dir.join(file)).ok()?.trim().parse().ok()
+                };
+                limits.cpus = match (read("cpu.cfs_quota_us"), read("cpu.cfs_period_us")) {
+                    (Some(quota), Some(period)) if quota > 0.0 && period > 0.0 => {
+                        Some(quota / period)
+                    }
+                    _ => None,
+                };
+            }
+        }
+        limits
+    }
+
+    /// Limits of this process's own cgroup
+    pub fn current() -> Self {
+        let proc_self_cgroup = fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
+        Self::read(Path::new("/sys/fs/cgroup"), &proc_self_cgroup)
+    }
+}
+
+/// Directory of the cgroup at `path`. Inside a container the path listed in
+/// `/proc/self/cgroup` is often not mounted, and the container's own cgroup is the root.
+fn cgroup_dir(root: &Path, path: &str, probe: &str) -> PathBuf {
+    let dir = root.join(path);
+    if dir.join(probe).exists() {
+        dir
+    } else {
+        root.to_path_buf()
+    }
+}
+
+/// A byte limit, where "max" and the huge v1 sentinel both mean no limit
+fn read_limit(path: &Path) -> Option<u64> {
+    let value = fs::read_to_string(path).ok()?;
+    let bytes = value.trim().parse::<u64>().ok()?;
+    (bytes < 1 << 62).then_some(bytes)
+}
+
+/// Memory this process may use in MB: the machine's total, capped by any cgroup limit
+pub fn available_memory_mb() -> u64 {
+    let mut sys = System::new();
+    sys.refresh_memory();
+    let total = sys.total_memory();
+    let available = CgroupLimits::current()
+        .memory_bytes
+        .map_or(total, |limit| limit.min(total));
+    available / 1_048_576
+}
+
+/// Logical cores this process may use, capped by any cgroup CPU quota
+pub fn available_cpus() -> usize {
+    let cores = num_cores();
+    CgroupLimits::current()
+        .cpus
+        .map_or(cores, |cpus| (cpus.ceil() as usize).clamp(1, cores))
+}
+
 // We encode the memory usage to i32 type at client
 fn bytes_to_mb_i32(bytes: u64) -> i32 {
     // Convert to MB with 3 decimal places of precision
@@ -170,6 +268,7 @@ fn bytes_to_mb_i32(bytes: u64) -> i32 {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
 
     #[test]
     fn test_estimate_peak_gflops() {
@@ -186,4 +285,62 @@ mod tests {
         assert!(mhz > 0, "Expected non-zero MHz");
         // println!("Cores: {}, Base Frequency: {} MHz", cores, mhz);
     }
+
+    fn write(root: &Path, file: &str, contents: &str) {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_cgroup_v2_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "system.slice/nexus.service/memory.max",
+            "2147483648\n",
+        );
+        write(
+            dir.path(),
+            "system.slice/nexus.service/cpu.max",
+            "250000 100000\n",
+        );
+        let limits = CgroupLimits::read(dir.path(), "0::/system.slice/nexus.service\n");
+        assert_eq!(limits.memory_bytes, Some(2 << 30));
+        assert_eq!(limits.cpus, Some(2.5));
+
+        // Unlimited, and a container whose own cgroup is mounted at the root
+        write(dir.path(), "memory.max", "max\n");
+        write(dir.path(), "cpu.max", "max 100000\n");
+        let limits = CgroupLimits::read(dir.path(), "0::/docker/abc\n");
+        assert_eq!(limits, CgroupLimits::default());
+    }
+
+    #[test]
+    fn test_cgroup_v1_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "memory/memory.limit_in_bytes", "1073741824\n");
+        write(dir.path(), "cpu/cpu.cfs_quota_us", "-1\n");
+        write(dir.path(), "cpu/cpu.cfs_period_us", "100000\n");
+        let proc_self_cgroup = "5:memory:/docker/abc\n4:cpu,cpuacct:/docker/abc\n";
+        let limits = CgroupLimits::read(dir.path(), proc_self_cgroup);
+        assert_eq!(limits.memory_bytes, Some(1 << 30));
+        assert_eq!(limits.cpus, None);
+
+        write(
+            dir.path(),
+            "memory/memory.limit_in_bytes",
+            "9223372036854771712\n",
+        );
+        write(dir.path(), "cpu/cpu.cfs_quota_us", "50000\n");
+        let limits = CgroupLimits::read(dir.path(), proc_self_cgroup);
+        assert_eq!(limits.memory_bytes, None);
+        assert_eq!(limits.cpus, Some(0.5));
+    }
+
+    #[test]
+    fn test_available_resources() {
+        assert!(available_memory_mb() > 0);
+        assert!((1..=num_cores()).contains(&available_cpus()));
+    }
 }
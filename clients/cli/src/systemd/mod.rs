@@ -0,0 +1,299 @@
+//! systemd integration
+//!
+//! When started by systemd with `Type=notify`, the node reports readiness once
+//! the session is set up, keeps `STATUS=` current from worker events, and pings
+//! the watchdog from the worker so a hung prover gets the service restarted.
+//! Outside systemd (no `NOTIFY_SOCKET`) all of this is a no-op.
+
+pub mod unit;
+
+use crate::consts::cli_consts::systemd;
+use crate::control::status::StatusTracker;
+use crate::events::Event;
+use std::ffi::OsString;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Sends state changes to the service manager over `NOTIFY_SOCKET`
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    socket: OsString,
+}
+
+impl Notifier {
+    /// The service manager's socket, if we were started with one
+    pub fn from_env() -> Option<Self> {
+        Self::from_var(std::env::var_os("NOTIFY_SOCKET"))
+    }
+
+    fn from_var(socket: Option<OsString>) -> Option<Self> {
+        let socket = socket?;
+        // Only filesystem and abstract sockets are meaningful
+        let bytes = socket.as_encoded_bytes();
+        if !bytes.starts_with(b"/") && !bytes.starts_with(b"@") {
+            return None;
+        }
+        if cfg!(unix) {
+            Some(Self { socket })
+        } else {
+            None
+        }
+    }
+
+    /// Send one or more newline-separated `KEY=VALUE` assignments
+    pub fn notify(&self, state: &str) -> std::io::Result<()> {
+        #[cfg(unix)]
+        {
+            let socket = std::os::unix::net::UnixDatagram::unbound()?;
+            // Never hold up the caller if the manager stops reading
+            socket.set_nonblocking(true)?;
+            #[cfg(target_os = "linux")]
+            if let Some(name) = self.socket.as_encoded_bytes().strip_prefix(b"@") {
+                use std::os::linux::net::SocketAddrExt;
+                let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+                return socket.send_to_addr(state.as_bytes(), &addr).map(|_| ());
+            }
+            socket.send_to(state.as_bytes(), &self.socket).map(|_| ())
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = state;
+            Err(std::io::ErrorKind::Unsupported.into())
+        }
+    }
+
+    /// Startup is complete; notifications are best-effort
+    pub fn ready(&self) {
+        let _ = self.notify("READY=1");
+    }
+
+    /// The service is shutting down on its own
+    pub fn stopping(&self) {
+        let _ = self.notify("STOPPING=1");
+    }
+
+    /// Free-form status shown by `systemctl status`
+    pub fn status(&self, status: &str) {
+        let _ = self.notify(&format!("STATUS={}", status.replace('\n', " ")));
+    }
+
+    /// Pass worker events through, keeping `STATUS=` up to date
+    pub fn tap(&self, mut receiver: mpsc::Receiver<Event>) -> mpsc::Receiver<Event> {
+        let (sender, tapped) = mpsc::channel(crate::consts::cli_consts::EVENT_QUEUE_SIZE);
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            let mut tracker = StatusTracker::default();
+            let mut last_status = String::new();
+            while let Some(event) = receiver.recv().await {
+                tracker.observe(&event);
+                let status = tracker.summary();
+                if status != last_status {
+                    notifier.status(&status);
+                    last_status = status;
+                }
+                if sender.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        tapped
+    }
+}
+
+/// Pings the service manager's watchdog while the worker is making progress
+///
+/// Pings come from a task on the runtime, so they stop if the runtime stalls. They
+/// also stop once a single task has been in flight for longer than any task should
+/// take, so a prover that hangs past its own timeout gets the service restarted.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    notifier: Notifier,
+    interval: Duration,
+    deadline: Duration,
+    busy_since: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Watchdog {
+    /// The watchdog systemd expects us to ping, if it enabled one for this process
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars(
+            Notifier::from_env()?,
+            std::env::var("WATCHDOG_USEC").ok(),
+            std::env::var("WATCHDOG_PID").ok(),
+            std::process::id(),
+        )
+    }
+
+    fn from_vars(
+        notifier: Notifier,
+        usec: Option<String>,
+        pid: Option<String>,
+        own_pid: u32,
+    ) -> Option<Self> {
+        let usec: u64 = usec?.parse().ok().filter(|usec| *usec > 0)?;
+        // The watchdog may be meant for another process, e.g. a wrapper script
+        if let Some(pid) = pid {
+            if pid.parse::<u32>().ok()? != own_pid {
+                return None;
+            }
+        }
+        Some(Self {
+            notifier,
+            interval: Duration::from_micros(usec / 2),
+            deadline: systemd::watchdog_task_deadline(),
+            busy_since: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// A task is in flight; since now, unless it already was
+    pub fn busy(&self) {
+        self.busy_since
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(Instant::now);
+    }
+
+    /// No task is in flight, or it is deliberately suspended
+    pub fn idle(&self) {
+        *self.busy_since.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    fn stuck(&self, now: Instant) -> bool {
+        let busy_since = *self.busy_since.lock().unwrap_or_else(|e| e.into_inner());
+        busy_since.is_some_and(|since| now.duration_since(since) > self.deadline)
+    }
+
+    /// Ping at half the watchdog interval until the returned handle is aborted
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let watchdog = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(watchdog.interval);
+            loop {
+                ticker.tick().await;
+                if !watchdog.stuck(Instant::now()) {
+                    let _ = watchdog.notifier.notify("WATCHDOG=1");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixDatagram;
+
+    /// A fake service manager listening on a temporary NOTIFY_SOCKET
+    fn manager() -> (tempfile::TempDir, UnixDatagram, Notifier) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notify.sock");
+        let socket = UnixDatagram::bind(&path).unwrap();
+        socket.set_nonblocking(true).unwrap();
+        let notifier = Notifier::from_var(Some(path.into_os_string())).unwrap();
+        (dir, socket, notifier)
+    }
+
+    fn received(socket: &UnixDatagram) -> Vec<String> {
+        let mut buf = [0u8; 1024];
+        let mut messages = Vec::new();
+        while let Ok(len) = socket.recv(&mut buf) {
+            messages.push(String::from_utf8_lossy(&buf[..len]).into_owned());
+        }
+        messages
+    }
+
+    #[test]
+    fn test_from_var() {
+        assert!(Notifier::from_var(None).is_none());
+        assert!(Notifier::from_var(Some("relative.sock".into())).is_none());
+        assert!(Notifier::from_var(Some("@abstract".into())).is_some());
+    }
+
+    #[test]
+    fn test_notifications() {
+        let (_dir, socket, notifier) = manager();
+        notifier.ready();
+        notifier.status("Proving task abc\nstill");
+        notifier.stopping();
+        assert_eq!(
+            received(&socket),
+            vec!["READY=1", "STATUS=Proving task abc still", "STOPPING=1"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_from_events() {
+        let (_dir, socket, notifier) = manager();
+        let (sender, receiver) = mpsc::channel(8);
+        let mut tapped = notifier.tap(receiver);
+
+        let event = Event::task_fetcher_with_level(
+            "Step 1 of 4: Got task abc".to_string(),
+            crate::events::EventType::Success,
+            crate::logging::LogLevel::Info,
+        )
+        .with_lifecycle(crate::events::Lifecycle::TaskFetched, Some("abc"));
+        sender.send(event.clone()).await.unwrap();
+        sender
+            .send(Event::state_change(
+                crate::events::ProverState::Proving,
+                "Step 2 of 4: Proving task abc".to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(tapped.recv().await, Some(event));
+        tapped.recv().await.unwrap();
+
+        assert_eq!(
+            received(&socket),
+            vec![
+                "STATUS=Waiting for a task, 0 proofs submitted",
+                "STATUS=Proving task abc, 0 proofs submitted"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watchdog_from_vars() {
+        let (_dir, _socket, notifier) = manager();
+        let watchdog = Watchdog::from_vars(notifier.clone(), Some("4000000".into()), None, 42);
+        assert_eq!(watchdog.unwrap().interval, Duration::from_secs(2));
+        assert!(Watchdog::from_vars(notifier.clone(), None, None, 42).is_none());
+        assert!(Watchdog::from_vars(notifier.clone(), Some("0".into()), None, 42).is_none());
+        assert!(
+            Watchdog::from_vars(notifier.clone(), Some("1000".into()), Some("42".into()), 42)
+                .is_some()
+        );
+        assert!(Watchdog::from_vars(notifier, Some("1000".into()), Some("7".into()), 42).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_watchdog_stops_pinging_a_stuck_task() {
+        let (_dir, socket, notifier) = manager();
+        let mut watchdog = Watchdog::from_vars(notifier, Some("2000000".into()), None, 1).unwrap();
+        watchdog.deadline = Duration::from_secs(10);
+        let pinger = watchdog.spawn();
+
+        // Idle: pings every second
+        tokio::time::sleep(Duration::from_millis(4500)).await;
+        assert_eq!(received(&socket), vec!["WATCHDOG=1"; 5]);
+
+        // A task in flight keeps pings coming until it passes the deadline
+        watchdog.busy();
+        tokio::time::sleep(Duration::from_secs(8)).await;
+        assert_eq!(received(&socket).len(), 8);
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert_eq!(received(&socket).len(), 2);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        assert!(received(&socket).is_empty());
+
+        // Finishing or suspending the task resumes them
+        watchdog.idle();
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert_eq!(received(&socket).len(), 3);
+        pinger.abort();
+    }
+}
@@ -0,0 +1,195 @@
+//! The unit file written by `install-service`
+//!
+//! The unit runs `start --headless` and nothing else: the node ID and hooks come
+//! from `~/.nexus/config.json`, and any further flags belong in a drop-in
+//! (`systemctl edit nexus-network`) rather than a long `ExecStart=` line.
+
+use crate::consts::cli_consts::{shutdown, systemd};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// Whether the unit is installed for the system or the current user's manager
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Scope {
+    /// `/etc/systemd/system`, running as a given user
+    System,
+    /// `~/.config/systemd/user`, managed with `systemctl --user`
+    User,
+}
+
+impl Scope {
+    /// Arguments to prefix `systemctl` commands with
+    pub fn systemctl(&self) -> &'static str {
+        match self {
+            Scope::System => "sudo systemctl",
+            Scope::User => "systemctl --user",
+        }
+    }
+}
+
+/// What goes into the unit file
+#[derive(Debug, Clone)]
+pub struct ServiceUnit {
+    pub scope: Scope,
+    /// The binary to run
+    pub exec: PathBuf,
+    /// Account a system unit runs as; its `~/.nexus` is the only writable path
+    pub user: String,
+    pub home: PathBuf,
+}
+
+impl ServiceUnit {
+    /// File name of the unit, e.g. `nexus-network.service`
+    pub fn file_name() -> String {
+        format!("{}.service", systemd::SERVICE_NAME)
+    }
+
+    /// Where the unit is installed for `scope`
+    pub fn default_path(scope: Scope, home: &Path) -> PathBuf {
+        let dir = match scope {
+            Scope::System => PathBuf::from("/etc/systemd/system"),
+            Scope::User => std::env::var_os("XDG_CONFIG_HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home.join(".config"))
+                .join("systemd")
+                .join("user"),
+        };
+        dir.join(Self::file_name())
+    }
+
+    /// The unit file contents
+    pub fn render(&self) -> String {
+        let mut unit = String::new();
+        let _ = writeln!(unit, "[Unit]");
+        let _ = writeln!(unit, "Description=Nexus network prover node");
+        let _ = writeln!(unit, "Documentation=https://docs.nexus.xyz/");
+        let _ = writeln!(unit, "Wants=network-online.target");
+        let _ = writeln!(unit, "After=network-online.target");
+        let _ = writeln!(unit);
+
+        let _ = writeln!(unit, "[Service]");
+        let _ = writeln!(unit, "Type=notify");
+        let _ = writeln!(unit, "NotifyAccess=main");
+        let _ = writeln!(
+            unit,
+            "ExecStart={} start --headless",
+            quote(&self.exec.display().to_string())
+        );
+        let _ = writeln!(unit, "Restart=on-failure");
+        let _ = writeln!(unit, "RestartSec={}", systemd::RESTART_SECS);
+        // Restarting can't fix what the orchestrator refused the node for
+        let _ = writeln!(
+            unit,
+            "RestartPreventExitStatus={}",
+            shutdown::FATAL_ERROR_EXIT_CODE
+        );
+        let _ = writeln!(unit, "WatchdogSec={}", systemd::WATCHDOG_SECS);
+        // SIGTERM drains the in-flight task; only the CLI itself should receive it
+        let _ = writeln!(unit, "KillMode=mixed");
+        let _ = writeln!(
+            unit,
+            "TimeoutStopSec={}",
+            shutdown::DRAIN_TIMEOUT_SECS + systemd::STOP_MARGIN_SECS
+        );
+        let _ = writeln!(unit, "NoNewPrivileges=yes");
+        if self.scope == Scope::System {
+            let _ = writeln!(unit, "User={}", self.user);
+            let _ = writeln!(unit, "ProtectSystem=strict");
+            let _ = writeln!(unit, "ProtectHome=read-only");
+            let _ = writeln!(
+                unit,
+                "ReadWritePaths={}",
+                quote(&self.home.join(".nexus").display().to_string())
+            );
+            let _ = writeln!(unit, "PrivateTmp=yes");
+            let _ = writeln!(unit, "PrivateDevices=yes");
+            let _ = writeln!(unit, "ProtectKernelTunables=yes");
+            let _ = writeln!(unit, "ProtectKernelModules=yes");
+            let _ = writeln!(unit, "ProtectControlGroups=yes");
+            let _ = writeln!(unit, "RestrictNamespaces=yes");
+            let _ = writeln!(unit, "RestrictRealtime=yes");
+            let _ = writeln!(unit, "RestrictSUIDSGID=yes");
+            let _ = writeln!(unit, "LockPersonality=yes");
+            let _ = writeln!(unit, "SystemCallArchitectures=native");
+        }
+        let _ = writeln!(unit);
+
+        let _ = writeln!(unit, "[Install]");
+        let target = match self.scope {
+            Scope::System => "multi-user.target",
+            Scope::User => "default.target",
+        };
+        let _ = writeln!(unit, "WantedBy={}", target);
+        unit
+    }
+}
+
+/// Quote a path for a unit file if it contains spaces or quotes
+fn quote(value: &str) -> String {
+    if value.contains([' ', '"', '\\']) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Home directory of `user` according to `/etc/passwd`-formatted `passwd`
+pub fn home_of(passwd: &str, user: &str) -> Option<PathBuf> {
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() >= 7 && fields[0] == user).then(|| PathBuf::from(fields[5]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(scope: Scope) -> ServiceUnit {
+        ServiceUnit {
+            scope,
+            exec: PathBuf::from("/home/alice/.nexus/bin/nexus-network"),
+            user: "alice".to_string(),
+            home: PathBuf::from("/home/alice"),
+        }
+    }
+
+    #[test]
+    fn test_system_unit() {
+        let rendered = unit(Scope::System).render();
+        assert!(rendered.contains("Type=notify\n"));
+        assert!(
+            rendered.contains("ExecStart=/home/alice/.nexus/bin/nexus-network start --headless\n")
+        );
+        assert!(rendered.contains("User=alice\n"));
+        assert!(rendered.contains("ReadWritePaths=/home/alice/.nexus\n"));
+        assert!(rendered.contains("TimeoutStopSec=660\n"));
+        assert!(rendered.contains("RestartPreventExitStatus=78\n"));
+        assert!(rendered.ends_with("WantedBy=multi-user.target\n"));
+    }
+
+    #[test]
+    fn test_user_unit() {
+        let rendered = unit(Scope::User).render();
+        assert!(rendered.contains("WatchdogSec=300\n"));
+        // Sandboxing that needs privileges is left out of user units
+        assert!(!rendered.contains("User="));
+        assert!(!rendered.contains("ProtectSystem"));
+        assert!(rendered.ends_with("WantedBy=default.target\n"));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("/usr/bin/nexus"), "/usr/bin/nexus");
+        assert_eq!(quote("/opt/my node/nexus"), "\"/opt/my node/nexus\"");
+    }
+
+    #[test]
+    fn test_home_of() {
+        let passwd =
+            "root:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000:Alice:/home/alice:/bin/zsh\n";
+        assert_eq!(home_of(passwd, "alice"), Some(PathBuf::from("/home/alice")));
+        assert_eq!(home_of(passwd, "bob"), None);
+    }
+}
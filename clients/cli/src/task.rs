@@ -4,8 +4,35 @@
 //! * Task (Returned by GetTasks)
 //! * GetProofTaskResponse.
 
+use crate::consts::cli_consts::task_signing;
+use crate::environment::Environment;
+use crate::orchestrator::error::OrchestratorError;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, VerifyingKey};
 use sha3::{Digest, Keccak256};
 use std::fmt::Display;
+use thiserror::Error;
+
+/// Why a task from the orchestrator was rejected before proving
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TaskSignatureError {
+    /// The environment pins orchestrator keys but the task carries no signature
+    #[error("Task {0} is not signed by the orchestrator")]
+    Unsigned(String),
+
+    /// The signature doesn't match the task under any pinned orchestrator key
+    #[error("Task {0} has an invalid orchestrator signature")]
+    Invalid(String),
+}
+
+/// Which tasks from an orchestrator are trusted enough to prove
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskTrust {
+    /// Only tasks signed by one of these keys; with none, no task is
+    Pinned(Vec<VerifyingKey>),
+    /// Every task, signed or not, for an environment that opted out of checking
+    Unverified,
+}
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Task {
@@ -28,6 +55,9 @@ pub struct Task {
     /// This accounts for reputation-based gating and allows clients to track
     /// the actual difficulty they're receiving vs what they requested.
     pub difficulty: crate::nexus_orchestrator::TaskDifficulty,
+
+    /// When the orchestrator stops accepting a proof for this task, if it set a deadline
+    pub deadline: Option<DateTime<Utc>>,
 }
 
 impl Task {
@@ -47,12 +77,13 @@ impl Task {
             public_inputs_list: vec![public_inputs],
             task_type,
             difficulty,
+            deadline: None,
         }
     }
 
-    /// Combines multiple proof hashes into a single hash using Keccak-256,
+    /// Combines proof hashes, in input order, into a single hash using Keccak-256,
     /// mimicking the JavaScript Buffer.concat approach.
-    pub fn combine_proof_hashes(hashes: &[String]) -> String {
+    pub(crate) fn combine_hashes(hashes: &[String]) -> String {
         if hashes.is_empty() {
             return String::new();
         }
@@ -72,6 +103,12 @@ impl Task {
     pub fn all_inputs(&self) -> &[Vec<u8>] {
         &self.public_inputs_list
     }
+
+    /// Time left before the deadline at `now`; `None` without a deadline, zero once past it
+    pub fn time_to_deadline(&self, now: DateTime<Utc>) -> Option<std::time::Duration> {
+        self.deadline
+            .map(|deadline| (deadline - now).to_std().unwrap_or_default())
+    }
 }
 
 // Display
@@ -98,30 +135,99 @@ impl From<&crate::nexus_orchestrator::Task> for Task {
             task_type: crate::nexus_orchestrator::TaskType::try_from(task.task_type).unwrap(),
             difficulty: crate::nexus_orchestrator::TaskDifficulty::try_from(task.difficulty)
                 .unwrap_or_default(),
+            deadline: task.deadline.as_ref().and_then(|deadline| {
+                DateTime::from_timestamp(deadline.seconds, deadline.nanos.max(0) as u32)
+            }),
         }
     }
 }
 
-// From GetProofTaskResponse
-impl From<&crate::nexus_orchestrator::GetProofTaskResponse> for Task {
-    fn from(response: &crate::nexus_orchestrator::GetProofTaskResponse) -> Self {
+impl Task {
+    /// The task from a `GetProofTaskResponse`, once its signature checks out against
+    /// the orchestrator keys pinned for `environment`
+    pub fn from_signed(
+        response: &crate::nexus_orchestrator::GetProofTaskResponse,
+        environment: &Environment,
+    ) -> Result<Self, OrchestratorError> {
         // Use the task field instead of deprecated fields
-        Task::from(response.task.as_ref().unwrap())
+        let task = response
+            .task
+            .as_ref()
+            .ok_or_else(|| OrchestratorError::Incomplete("task".to_string()))?;
+        Self::verify_signature(task, &environment.task_trust())?;
+        Ok(Task::from(task))
+    }
+
+    /// Check `task` was signed by one of the keys `trust` pins
+    pub fn verify_signature(
+        task: &crate::nexus_orchestrator::Task,
+        trust: &TaskTrust,
+    ) -> Result<(), TaskSignatureError> {
+        let keys = match trust {
+            TaskTrust::Pinned(keys) => keys,
+            TaskTrust::Unverified => return Ok(()),
+        };
+        if task.signature.is_empty() {
+            return Err(TaskSignatureError::Unsigned(task.task_id.clone()));
+        }
+        let signature = Signature::from_slice(&task.signature)
+            .map_err(|_| TaskSignatureError::Invalid(task.task_id.clone()))?;
+        let payload = Self::signing_payload(task);
+        if keys
+            .iter()
+            .any(|key| key.verify_strict(&payload, &signature).is_ok())
+        {
+            Ok(())
+        } else {
+            Err(TaskSignatureError::Invalid(task.task_id.clone()))
+        }
+    }
+
+    /// The bytes the orchestrator signs: the domain, then each field length-prefixed,
+    /// including the legacy single input, then whether the task has a deadline and if so
+    /// its Unix seconds and nanoseconds
+    pub fn signing_payload(task: &crate::nexus_orchestrator::Task) -> Vec<u8> {
+        fn push_bytes(payload: &mut Vec<u8>, bytes: &[u8]) {
+            payload.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            payload.extend_from_slice(bytes);
+        }
+
+        let mut payload = task_signing::DOMAIN.to_vec();
+        push_bytes(&mut payload, task.task_id.as_bytes());
+        push_bytes(&mut payload, task.program_id.as_bytes());
+        #[allow(deprecated)]
+        push_bytes(&mut payload, &task.public_inputs);
+        payload.extend_from_slice(&(task.public_inputs_list.len() as u32).to_le_bytes());
+        for input in &task.public_inputs_list {
+            push_bytes(&mut payload, input);
+        }
+        payload.extend_from_slice(&task.task_type.to_le_bytes());
+        payload.extend_from_slice(&task.difficulty.to_le_bytes());
+        match &task.deadline {
+            Some(deadline) => {
+                payload.push(1);
+                payload.extend_from_slice(&deadline.seconds.to_le_bytes());
+                payload.extend_from_slice(&deadline.nanos.to_le_bytes());
+            }
+            None => payload.push(0),
+        }
+        payload
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
 
     #[test]
     fn test_combine_proof_hashes() {
         // Test with empty array
-        assert_eq!(Task::combine_proof_hashes(&[]), "");
+        assert_eq!(Task::combine_hashes(&[]), "");
 
         // Test with single hash
         let single_hash = "a1b2c3d4e5f6";
-        let result = Task::combine_proof_hashes(&[single_hash.to_string()]);
+        let result = Task::combine_hashes(&[single_hash.to_string()]);
         assert!(!result.is_empty());
         assert_eq!(result.len(), 64); // Keccak-256 produces 32 bytes = 64 hex chars
         assert_eq!(
@@ -135,7 +241,7 @@ mod tests {
             "7890abcdef12".to_string(),
             "345678901234".to_string(),
         ];
-        let combined = Task::combine_proof_hashes(&hashes);
+        let combined = Task::combine_hashes(&hashes);
         assert!(!combined.is_empty());
         assert_eq!(combined.len(), 64);
         assert_eq!(
@@ -144,7 +250,7 @@ mod tests {
         );
 
         // Verify that the same hashes produce the same result
-        let combined2 = Task::combine_proof_hashes(&hashes);
+        let combined2 = Task::combine_hashes(&hashes);
         assert_eq!(combined, combined2);
 
         // Verify that different order produces different result
@@ -153,7 +259,7 @@ mod tests {
             "7890abcdef12".to_string(),
             "a1b2c3d4e5f6".to_string(),
         ];
-        let combined_reversed = Task::combine_proof_hashes(&hashes_reversed);
+        let combined_reversed = Task::combine_hashes(&hashes_reversed);
         assert_ne!(combined, combined_reversed);
     }
 
@@ -231,4 +337,151 @@ mod tests {
 
         println!("Backward compatibility test passed");
     }
+
+    fn signed_task(key: &SigningKey) -> crate::nexus_orchestrator::Task {
+        let mut task = crate::nexus_orchestrator::Task {
+            task_id: "signed".to_string(),
+            program_id: "fib_input_initial".to_string(),
+            public_inputs_list: vec![vec![1, 2, 3], vec![4, 5, 6]],
+            task_type: crate::nexus_orchestrator::TaskType::ProofHash as i32,
+            difficulty: crate::nexus_orchestrator::TaskDifficulty::Large as i32,
+            ..Default::default()
+        };
+        task.signature = key.sign(&Task::signing_payload(&task)).to_bytes().to_vec();
+        task
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let current = SigningKey::from_bytes(&[1; 32]);
+        let next = SigningKey::from_bytes(&[2; 32]);
+        let pinned = TaskTrust::Pinned(vec![current.verifying_key(), next.verifying_key()]);
+
+        // Either pinned key is accepted, so the orchestrator can rotate keys
+        assert_eq!(
+            Task::verify_signature(&signed_task(&current), &pinned),
+            Ok(())
+        );
+        assert_eq!(Task::verify_signature(&signed_task(&next), &pinned), Ok(()));
+
+        let stranger = SigningKey::from_bytes(&[3; 32]);
+        assert_eq!(
+            Task::verify_signature(&signed_task(&stranger), &pinned),
+            Err(TaskSignatureError::Invalid("signed".to_string()))
+        );
+
+        let mut unsigned = signed_task(&current);
+        unsigned.signature.clear();
+        assert_eq!(
+            Task::verify_signature(&unsigned, &pinned),
+            Err(TaskSignatureError::Unsigned("signed".to_string()))
+        );
+        // Nothing is checked only for an environment that opted out
+        assert_eq!(
+            Task::verify_signature(&unsigned, &TaskTrust::Unverified),
+            Ok(())
+        );
+        assert!(Task::verify_signature(&unsigned, &TaskTrust::Pinned(Vec::new())).is_err());
+        assert!(
+            Task::verify_signature(&signed_task(&current), &TaskTrust::Pinned(Vec::new())).is_err()
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)] // tampers with the legacy single input too
+    fn test_signature_covers_every_field() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let pinned = TaskTrust::Pinned(vec![key.verifying_key()]);
+        let tampered: [fn(&mut crate::nexus_orchestrator::Task); 7] = [
+            |task| task.task_id.push('x'),
+            |task| task.program_id = "other".to_string(),
+            |task| task.public_inputs_list[1][0] = 9,
+            |task| task.task_type = crate::nexus_orchestrator::TaskType::ProofRequired as i32,
+            |task| task.difficulty = crate::nexus_orchestrator::TaskDifficulty::Small as i32,
+            |task| task.public_inputs = vec![9],
+            |task| {
+                task.deadline = Some(prost_types::Timestamp {
+                    seconds: 1,
+                    nanos: 0,
+                })
+            },
+        ];
+        for tamper in tampered {
+            let mut task = signed_task(&key);
+            tamper(&mut task);
+            assert!(matches!(
+                Task::verify_signature(&task, &pinned),
+                Err(TaskSignatureError::Invalid(_))
+            ));
+        }
+
+        // Moving bytes between inputs changes the payload too
+        let mut task = signed_task(&key);
+        task.public_inputs_list = vec![vec![1, 2], vec![3, 4, 5, 6]];
+        assert!(Task::verify_signature(&task, &pinned).is_err());
+
+        // The deadline is covered down to the nanosecond
+        let mut task = signed_task(&key);
+        task.deadline = Some(prost_types::Timestamp {
+            seconds: 1_750_000_600,
+            nanos: 0,
+        });
+        task.signature = key.sign(&Task::signing_payload(&task)).to_bytes().to_vec();
+        assert_eq!(Task::verify_signature(&task, &pinned), Ok(()));
+        task.deadline.as_mut().unwrap().nanos = 1;
+        assert!(Task::verify_signature(&task, &pinned).is_err());
+    }
+
+    #[test]
+    fn test_deadline() {
+        let mut proto = signed_task(&SigningKey::from_bytes(&[1; 32]));
+        assert_eq!(Task::from(&proto).deadline, None);
+        assert_eq!(Task::from(&proto).time_to_deadline(Utc::now()), None);
+
+        proto.deadline = Some(prost_types::Timestamp {
+            seconds: 1_750_000_600,
+            nanos: 0,
+        });
+        let task = Task::from(&proto);
+        let now = DateTime::from_timestamp(1_750_000_000, 0).unwrap();
+        assert_eq!(
+            task.time_to_deadline(now),
+            Some(std::time::Duration::from_secs(600))
+        );
+        // Past the deadline there is no time left, rather than a negative amount
+        let later = DateTime::from_timestamp(1_750_001_000, 0).unwrap();
+        assert_eq!(
+            task.time_to_deadline(later),
+            Some(std::time::Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_from_signed_uses_environment_keys() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let environment = Environment::Custom {
+            orchestrator_url: "http://localhost".to_string(),
+            task_keys: vec![key.verifying_key()],
+        };
+        let mut response = crate::nexus_orchestrator::GetProofTaskResponse {
+            task: Some(signed_task(&key)),
+            ..Default::default()
+        };
+        let task = Task::from_signed(&response, &environment).unwrap();
+        assert_eq!(task.task_id, "signed");
+        assert_eq!(task.all_inputs().len(), 2);
+
+        response.task.as_mut().unwrap().signature = vec![0; 64];
+        assert!(matches!(
+            Task::from_signed(&response, &environment),
+            Err(OrchestratorError::UntrustedTask(TaskSignatureError::Invalid(id))) if id == "signed"
+        ));
+
+        // A response without a task is an error, not a panic
+        response.task = None;
+        assert!(matches!(
+            Task::from_signed(&response, &environment),
+            Err(OrchestratorError::Incomplete(_))
+        ));
+    }
 }
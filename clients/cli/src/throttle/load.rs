@@ -0,0 +1,144 @@
+//! Back off while the user is busy
+//!
+//! Foreground load is system-wide CPU usage minus this process and its prover
+//! subprocesses. Above the threshold proving is reduced or paused; it picks up
+//! again once load has stayed below the threshold for the quiet period.
+
+use super::{ThrottleAction, ThrottlePolicy};
+use crate::prover::processes;
+use crate::workers::core::Throttle;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+/// Throttles proving while other processes keep the CPU busy
+pub struct LoadPolicy {
+    sysinfo: System,
+    /// Foreground CPU usage, in percent of all cores, above which to back off
+    max_load: f32,
+    action: ThrottleAction,
+    quiet_period: Duration,
+    /// The load that triggered the current throttle, if throttled
+    busy: Option<f32>,
+    /// When load last dropped below the threshold while throttled
+    quiet_since: Option<Instant>,
+    /// Our own processes as of the last sample
+    seen: HashSet<u32>,
+}
+
+impl LoadPolicy {
+    pub fn new(max_load: f32, action: ThrottleAction, quiet_period: Duration) -> Self {
+        Self {
+            sysinfo: System::new(),
+            max_load,
+            action,
+            quiet_period,
+            busy: None,
+            quiet_since: None,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Fold in a load reading taken at `now` with our own processes `own` left out,
+    /// unless one of them is new: sysinfo reports a process idle on its first refresh,
+    /// so a prover just spawned would count as foreground load until the next sample
+    fn observe(&mut self, load: f32, own: Vec<u32>, now: Instant) -> Throttle {
+        let first_seen = own.iter().any(|pid| !self.seen.contains(pid));
+        self.seen = own.into_iter().collect();
+        if first_seen {
+            return self.throttle();
+        }
+        self.update(load, now)
+    }
+
+    /// Fold in a load reading taken at `now`
+    fn update(&mut self, load: f32, now: Instant) -> Throttle {
+        if load > self.max_load {
+            self.busy.get_or_insert(load);
+            self.quiet_since = None;
+        } else if self.busy.is_some() {
+            let quiet_since = *self.quiet_since.get_or_insert(now);
+            if now.duration_since(quiet_since) >= self.quiet_period {
+                self.busy = None;
+                self.quiet_since = None;
+            }
+        }
+        self.throttle()
+    }
+
+    /// The throttle for the load that triggered it, if any
+    fn throttle(&self) -> Throttle {
+        let Some(load) = self.busy else {
+            return Throttle::default();
+        };
+        self.action
+            .throttle(format!("foreground CPU load {:.0}%", load))
+    }
+}
+
+impl ThrottlePolicy for LoadPolicy {
+    fn sample(&mut self) -> Throttle {
+        let mut own = processes::running_pids();
+        own.push(std::process::id());
+        let load = crate::ui::foreground_cpu_percent(&mut self.sysinfo, &own);
+        self.observe(load, own, Instant::now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::cli_consts::throttle::REDUCED_WORKERS;
+
+    #[test]
+    fn test_reduce_until_quiet() {
+        let mut policy = LoadPolicy::new(50.0, ThrottleAction::Reduce, Duration::from_secs(60));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(!policy.update(30.0, at(0)).is_active());
+
+        let throttle = policy.update(80.0, at(5));
+        assert_eq!(throttle.max_workers, Some(REDUCED_WORKERS));
+        assert!(!throttle.paused);
+        assert_eq!(throttle.reason.as_deref(), Some("foreground CPU load 80%"));
+
+        // Quiet, but not for long enough
+        assert!(policy.update(10.0, at(10)).is_active());
+        assert!(policy.update(10.0, at(60)).is_active());
+        // A spike restarts the quiet period
+        assert!(policy.update(90.0, at(65)).is_active());
+        assert!(policy.update(10.0, at(70)).is_active());
+        assert!(policy.update(10.0, at(125)).is_active());
+        assert!(!policy.update(10.0, at(130)).is_active());
+    }
+
+    #[test]
+    fn test_new_prover_is_not_foreground_load() {
+        let mut policy = LoadPolicy::new(50.0, ThrottleAction::Pause, Duration::ZERO);
+        let now = Instant::now();
+
+        assert!(!policy.observe(20.0, vec![1], now).is_active());
+        assert!(!policy.observe(20.0, vec![1], now).is_active());
+        // The new prover's usage shows up as foreground load on its first sample only
+        assert!(!policy.observe(90.0, vec![1, 2], now).is_active());
+        assert!(!policy.observe(20.0, vec![1, 2], now).is_active());
+        assert!(policy.observe(90.0, vec![1, 2], now).is_active());
+        // A skipped sample leaves the throttle as it was
+        assert!(policy.observe(20.0, vec![1, 3], now).is_active());
+        assert!(!policy.observe(20.0, vec![1, 3], now).is_active());
+    }
+
+    #[test]
+    fn test_pause_action() {
+        let mut policy = LoadPolicy::new(50.0, ThrottleAction::Pause, Duration::ZERO);
+        let now = Instant::now();
+
+        let throttle = policy.update(75.0, now);
+        assert!(throttle.paused);
+        assert_eq!(throttle.max_workers, None);
+        // The reason stays put while throttled, so it isn't republished every sample
+        assert_eq!(policy.update(95.0, now), throttle);
+        assert!(!policy.update(20.0, now).is_active());
+    }
+}
@@ -0,0 +1,217 @@
+//! Host-aware throttling
+//!
+//! Policies watch the machine and ask the worker to prove with fewer threads, or
+//! to stop fetching, while the host needs its resources back. Limits apply from
+//! the next task; the task in flight is left to finish.
+
+pub mod load;
+pub mod thermal;
+
+use crate::consts::cli_consts::throttle::{REDUCED_WORKERS, sample_interval};
+use crate::events::{Event, EventType};
+use crate::logging::LogLevel;
+use crate::workers::core::{ShutdownMode, Throttle};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// How to back off when a policy triggers
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ValueEnum)]
+pub enum ThrottleAction {
+    /// Prove with a single thread
+    Reduce,
+    /// Stop fetching new tasks
+    Pause,
+}
+
+impl ThrottleAction {
+    /// The throttle this action asks for
+    pub fn throttle(self, reason: String) -> Throttle {
+        match self {
+            ThrottleAction::Reduce => Throttle {
+                max_workers: Some(REDUCED_WORKERS),
+                paused: false,
+                reason: Some(reason),
+            },
+            ThrottleAction::Pause => Throttle {
+                max_workers: None,
+                paused: true,
+                reason: Some(reason),
+            },
+        }
+    }
+}
+
+/// A source of throttling decisions, sampled periodically
+pub trait ThrottlePolicy: Send {
+    /// Sample the host and return the throttle this policy wants right now.
+    /// Hysteresis is up to the policy.
+    fn sample(&mut self) -> Throttle;
+
+    /// What the policy last saw, for display
+    fn status(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Merge what every policy wants: the lowest thread cap, and a pause if any asks for one
+pub fn combine(throttles: impl IntoIterator<Item = Throttle>) -> Throttle {
+    let mut combined = Throttle::default();
+    let mut reasons = Vec::new();
+    for throttle in throttles.into_iter().filter(Throttle::is_active) {
+        combined.paused |= throttle.paused;
+        combined.max_workers = match (combined.max_workers, throttle.max_workers) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        reasons.extend(throttle.reason);
+    }
+    if !reasons.is_empty() {
+        combined.reason = Some(reasons.join("; "));
+    }
+    combined
+}
+
+/// Spawns a task that samples `policies` and publishes the combined throttle
+/// whenever it changes, along with each policy's status, until a shutdown is requested.
+pub fn spawn_throttle(
+    mut policies: Vec<Box<dyn ThrottlePolicy>>,
+    throttle_sender: watch::Sender<Throttle>,
+    status_sender: watch::Sender<Vec<String>>,
+    event_sender: mpsc::Sender<Event>,
+    mut shutdown: broadcast::Receiver<ShutdownMode>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let throttle = combine(policies.iter_mut().map(|policy| policy.sample()));
+            let status: Vec<String> = policies
+                .iter()
+                .filter_map(|policy| policy.status())
+                .collect();
+            status_sender.send_if_modified(|current| {
+                let modified = *current != status;
+                *current = status;
+                modified
+            });
+
+            if throttle != *throttle_sender.borrow() {
+                let _ = event_sender
+                    .send(Event::task_fetcher_with_level(
+                        describe(&throttle),
+                        EventType::Refresh,
+                        LogLevel::Info,
+                    ))
+                    .await;
+                throttle_sender.send_replace(throttle);
+            }
+
+            tokio::select! {
+                _ = shutdown.recv() => return,
+                _ = tokio::time::sleep(sample_interval()) => {}
+            }
+        }
+    })
+}
+
+/// Event text for a throttle change; the dashboard keys off the "Throttling: " prefix
+fn describe(throttle: &Throttle) -> String {
+    let reason = throttle.reason.as_deref().unwrap_or("host busy");
+    match (throttle.paused, throttle.max_workers) {
+        (true, _) => format!("Throttling: {}, pausing after the current task", reason),
+        (false, Some(workers)) => format!(
+            "Throttling: {}, proving with {} thread(s) from the next task",
+            reason, workers
+        ),
+        (false, None) => "Throttle lifted, full speed from the next task".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Replays scripted throttles, then holds the last one
+    struct Scripted(VecDeque<Throttle>);
+
+    impl ThrottlePolicy for Scripted {
+        fn sample(&mut self) -> Throttle {
+            if self.0.len() > 1 {
+                self.0.pop_front().unwrap()
+            } else {
+                self.0.front().cloned().unwrap_or_default()
+            }
+        }
+    }
+
+    fn reduce(workers: usize, reason: &str) -> Throttle {
+        Throttle {
+            max_workers: Some(workers),
+            paused: false,
+            reason: Some(reason.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_combine_takes_strictest() {
+        let pause = Throttle {
+            max_workers: None,
+            paused: true,
+            reason: Some("hot".to_string()),
+        };
+        let combined = combine([
+            reduce(2, "busy"),
+            Throttle::default(),
+            pause,
+            reduce(1, "on battery"),
+        ]);
+        assert!(combined.paused);
+        assert_eq!(combined.max_workers, Some(1));
+        assert_eq!(combined.reason.as_deref(), Some("busy; hot; on battery"));
+
+        assert_eq!(combine([Throttle::default()]), Throttle::default());
+    }
+
+    #[test]
+    fn test_throttle_caps_configured_workers() {
+        assert_eq!(Throttle::default().workers(4), 4);
+        assert_eq!(reduce(1, "busy").workers(4), 1);
+        assert_eq!(reduce(8, "busy").workers(4), 4);
+        assert_eq!(reduce(0, "busy").workers(4), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_publishes_changes_only() {
+        let (throttle_sender, mut throttle) = watch::channel(Throttle::default());
+        let (status_sender, _status) = watch::channel(Vec::new());
+        let (event_sender, mut events) = mpsc::channel(8);
+        let (shutdown_sender, shutdown) = broadcast::channel(1);
+        let script = Scripted(VecDeque::from([
+            Throttle::default(),
+            reduce(1, "busy"),
+            reduce(1, "busy"),
+            Throttle::default(),
+        ]));
+
+        let handle = spawn_throttle(
+            vec![Box::new(script)],
+            throttle_sender,
+            status_sender,
+            event_sender,
+            shutdown,
+        );
+
+        throttle.changed().await.unwrap();
+        assert_eq!(throttle.borrow_and_update().max_workers, Some(1));
+        let event = events.recv().await.unwrap();
+        assert!(event.msg.starts_with("Throttling: busy"));
+
+        throttle.changed().await.unwrap();
+        assert!(!throttle.borrow_and_update().is_active());
+        let event = events.recv().await.unwrap();
+        assert!(event.msg.starts_with("Throttle lifted"));
+
+        shutdown_sender.send(ShutdownMode::Drain).unwrap();
+        handle.await.unwrap();
+        assert!(events.try_recv().is_err());
+    }
+}
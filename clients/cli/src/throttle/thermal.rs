@@ -0,0 +1,402 @@
+//! Back off when the machine runs hot or on battery (Linux)
+//!
+//! Temperatures come from `class/thermal/thermal_zone*/temp` and
+//! `class/hwmon/hwmon*/temp*_input`, power from `class/power_supply/*`, all under
+//! a configurable sysfs root. Proving resumes once the machine has cooled, or the
+//! battery recovered, by a margin past the limit.
+
+use super::{ThrottleAction, ThrottlePolicy};
+use crate::consts::cli_consts::throttle::{BATTERY_HYSTERESIS_PERCENT, TEMPERATURE_HYSTERESIS_C};
+use crate::workers::core::Throttle;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Readings outside this range (in °C) are broken or disconnected sensors
+const PLAUSIBLE_TEMPERATURE_C: std::ops::RangeInclusive<f32> = 1.0..=150.0;
+
+/// Thermal sensors and power supplies under a sysfs root
+#[derive(Debug, Clone)]
+pub struct Sysfs {
+    root: PathBuf,
+}
+
+/// Power supply state
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct PowerSupply {
+    /// Whether the machine has a system battery at all
+    pub has_battery: bool,
+    /// Running on battery rather than mains or USB power
+    pub on_battery: bool,
+    /// Charge of the emptiest system battery
+    pub battery_percent: Option<u8>,
+}
+
+/// One sample of the host's thermal and power state
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct PowerReading {
+    /// Hottest sensor, in °C
+    pub temperature_c: Option<f32>,
+    pub supply: PowerSupply,
+}
+
+impl Sysfs {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn read(&self) -> PowerReading {
+        PowerReading {
+            temperature_c: self.max_temperature(),
+            supply: self.power_supply(),
+        }
+    }
+
+    /// The hottest plausible reading across thermal zones and hwmon sensors, in °C
+    pub fn max_temperature(&self) -> Option<f32> {
+        let zones = entries(&self.root.join("class/thermal"), "thermal_zone")
+            .into_iter()
+            .map(|zone| zone.join("temp"));
+        let hwmon = entries(&self.root.join("class/hwmon"), "hwmon")
+            .into_iter()
+            .flat_map(|dir| entries(&dir, "temp"))
+            .filter(|path| path.to_string_lossy().ends_with("_input"));
+
+        zones
+            .chain(hwmon)
+            .filter_map(|path| read_trimmed(&path)?.parse::<i64>().ok())
+            // Millidegrees Celsius
+            .map(|millidegrees| millidegrees as f32 / 1000.0)
+            .filter(|celsius| PLAUSIBLE_TEMPERATURE_C.contains(celsius))
+            .reduce(f32::max)
+    }
+
+    /// Whether we're on battery, and how full it is
+    pub fn power_supply(&self) -> PowerSupply {
+        let mut supply = PowerSupply::default();
+        let mut external_online = None;
+        let mut discharging = false;
+
+        for dir in entries(&self.root.join("class/power_supply"), "") {
+            let Some(kind) = read_trimmed(&dir.join("type")) else {
+                continue;
+            };
+            if kind == "Battery" {
+                // Mice, keyboards and the like report their own batteries
+                if read_trimmed(&dir.join("scope")).as_deref() == Some("Device") {
+                    continue;
+                }
+                supply.has_battery = true;
+                discharging |= read_trimmed(&dir.join("status")).as_deref() == Some("Discharging");
+                if let Some(percent) =
+                    read_trimmed(&dir.join("capacity")).and_then(|s| s.parse::<u8>().ok())
+                {
+                    supply.battery_percent = Some(
+                        supply
+                            .battery_percent
+                            .map_or(percent, |current| current.min(percent)),
+                    );
+                }
+            } else if let Some(online) = read_trimmed(&dir.join("online")) {
+                // Mains, USB and the like
+                *external_online.get_or_insert(false) |= online == "1";
+            }
+        }
+
+        // Without an external supply to ask, go by what the battery says
+        supply.on_battery =
+            supply.has_battery && external_online.map_or(discharging, |online| !online);
+        supply
+    }
+}
+
+/// Entries of `dir` whose names start with `prefix`, in name order
+fn entries(dir: &Path, prefix: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// When to back off
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PowerLimits {
+    /// Back off above this temperature (°C)
+    pub max_temperature_c: Option<f32>,
+    /// Back off whenever running on battery
+    pub on_battery: bool,
+    /// Back off on battery below this charge
+    pub min_battery_percent: Option<u8>,
+    pub action: ThrottleAction,
+}
+
+impl PowerLimits {
+    pub fn is_empty(&self) -> bool {
+        self.max_temperature_c.is_none() && !self.on_battery && self.min_battery_percent.is_none()
+    }
+}
+
+/// Throttles proving while the machine is hot or short on power
+pub struct ThermalPolicy {
+    sysfs: Sysfs,
+    limits: PowerLimits,
+    last: Option<PowerReading>,
+    /// The temperature that triggered the current throttle, if too hot
+    hot: Option<f32>,
+    on_battery: bool,
+    /// The charge that triggered the current throttle, if too low
+    low_battery: Option<u8>,
+}
+
+impl ThermalPolicy {
+    pub fn new(sysfs: Sysfs, limits: PowerLimits) -> Self {
+        Self {
+            sysfs,
+            limits,
+            last: None,
+            hot: None,
+            on_battery: false,
+            low_battery: None,
+        }
+    }
+
+    /// Limits that can never trigger on this machine, for warning at startup
+    pub fn unsupported(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.limits.max_temperature_c.is_some() && self.sysfs.max_temperature().is_none() {
+            problems.push(format!(
+                "No temperature sensors found under {}; the temperature limit is ignored",
+                self.sysfs.root().display()
+            ));
+        }
+        if (self.limits.on_battery || self.limits.min_battery_percent.is_some())
+            && !self.sysfs.power_supply().has_battery
+        {
+            problems.push(format!(
+                "No battery found under {}; battery limits are ignored",
+                self.sysfs.root().display()
+            ));
+        }
+        problems
+    }
+
+    /// Fold in a reading
+    fn update(&mut self, reading: PowerReading) -> Throttle {
+        self.last = Some(reading);
+
+        match (self.limits.max_temperature_c, reading.temperature_c) {
+            (Some(max), Some(temperature)) if temperature > max => {
+                self.hot.get_or_insert(temperature);
+            }
+            (Some(max), Some(temperature)) if temperature > max - TEMPERATURE_HYSTERESIS_C => {}
+            _ => self.hot = None,
+        }
+
+        self.on_battery = self.limits.on_battery && reading.supply.on_battery;
+
+        let supply = reading.supply;
+        match (self.limits.min_battery_percent, supply.battery_percent) {
+            (Some(min), Some(percent)) if supply.on_battery && percent < min => {
+                self.low_battery.get_or_insert(percent);
+            }
+            (Some(min), Some(percent))
+                if supply.on_battery
+                    && percent < min.saturating_add(BATTERY_HYSTERESIS_PERCENT) => {}
+            // Plugged in again, or charged past the margin
+            _ => self.low_battery = None,
+        }
+
+        let mut reasons = Vec::new();
+        if let Some(temperature) = self.hot {
+            reasons.push(format!("temperature {:.0}°C", temperature));
+        }
+        if let Some(percent) = self.low_battery {
+            reasons.push(format!("battery {}%", percent));
+        } else if self.on_battery {
+            reasons.push("on battery".to_string());
+        }
+
+        if reasons.is_empty() {
+            Throttle::default()
+        } else {
+            self.limits.action.throttle(reasons.join(", "))
+        }
+    }
+}
+
+impl ThrottlePolicy for ThermalPolicy {
+    fn sample(&mut self) -> Throttle {
+        let reading = self.sysfs.read();
+        self.update(reading)
+    }
+
+    fn status(&self) -> Option<String> {
+        let reading = self.last?;
+        let mut parts = Vec::new();
+        if let Some(temperature) = reading.temperature_c {
+            parts.push(format!("{:.0}°C", temperature));
+        }
+        let supply = reading.supply;
+        match (supply.on_battery, supply.battery_percent) {
+            (true, Some(percent)) => parts.push(format!("battery {}%", percent)),
+            (true, None) => parts.push("battery".to_string()),
+            (false, Some(percent)) => parts.push(format!("AC, battery {}%", percent)),
+            (false, None) if supply.has_battery => parts.push("AC".to_string()),
+            (false, None) => {}
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// A laptop: two thermal zones, a hwmon sensor, mains and a battery
+    fn fake_laptop(root: &Path, temperature_millis: &str, ac_online: &str, capacity: &str) {
+        write(root, "class/thermal/thermal_zone0/temp", "45000\n");
+        write(root, "class/thermal/thermal_zone1/temp", temperature_millis);
+        write(root, "class/hwmon/hwmon0/temp1_input", "52000\n");
+        write(root, "class/hwmon/hwmon0/temp1_label", "Package id 0\n");
+        write(root, "class/power_supply/AC/type", "Mains\n");
+        write(root, "class/power_supply/AC/online", ac_online);
+        write(root, "class/power_supply/BAT0/type", "Battery\n");
+        write(root, "class/power_supply/BAT0/capacity", capacity);
+        write(root, "class/power_supply/BAT0/status", "Discharging\n");
+        // A wireless mouse must not count as the system battery
+        write(root, "class/power_supply/hidpp_battery_0/type", "Battery\n");
+        write(root, "class/power_supply/hidpp_battery_0/scope", "Device\n");
+        write(root, "class/power_supply/hidpp_battery_0/capacity", "5\n");
+    }
+
+    fn limits() -> PowerLimits {
+        PowerLimits {
+            max_temperature_c: Some(80.0),
+            on_battery: false,
+            min_battery_percent: Some(20),
+            action: ThrottleAction::Pause,
+        }
+    }
+
+    fn reading(temperature_c: f32, on_battery: bool, battery_percent: u8) -> PowerReading {
+        PowerReading {
+            temperature_c: Some(temperature_c),
+            supply: PowerSupply {
+                has_battery: true,
+                on_battery,
+                battery_percent: Some(battery_percent),
+            },
+        }
+    }
+
+    #[test]
+    fn test_read_fake_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        fake_laptop(dir.path(), "61500\n", "0\n", "42\n");
+        // Disconnected sensors report nonsense
+        write(dir.path(), "class/thermal/thermal_zone2/temp", "-273000\n");
+
+        let reading = Sysfs::new(dir.path()).read();
+        assert_eq!(reading.temperature_c, Some(61.5));
+        assert_eq!(
+            reading.supply,
+            PowerSupply {
+                has_battery: true,
+                on_battery: true,
+                battery_percent: Some(42),
+            }
+        );
+
+        fake_laptop(dir.path(), "61500\n", "1\n", "42\n");
+        assert!(!Sysfs::new(dir.path()).power_supply().on_battery);
+    }
+
+    #[test]
+    fn test_missing_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = ThermalPolicy::new(Sysfs::new(dir.path()), limits());
+
+        assert_eq!(Sysfs::new(dir.path()).read(), PowerReading::default());
+        assert_eq!(policy.unsupported().len(), 2);
+    }
+
+    #[test]
+    fn test_temperature_hysteresis() {
+        let mut policy = ThermalPolicy::new(Sysfs::new("/nonexistent"), limits());
+
+        assert!(!policy.update(reading(79.0, false, 100)).is_active());
+        let throttle = policy.update(reading(85.0, false, 100));
+        assert!(throttle.paused);
+        assert_eq!(throttle.reason.as_deref(), Some("temperature 85°C"));
+
+        // Stays throttled, with the same reason, until well below the limit
+        assert_eq!(policy.update(reading(88.0, false, 100)), throttle);
+        assert_eq!(policy.update(reading(76.0, false, 100)), throttle);
+        assert!(!policy.update(reading(75.0, false, 100)).is_active());
+    }
+
+    #[test]
+    fn test_battery_hysteresis() {
+        let mut policy = ThermalPolicy::new(
+            Sysfs::new("/nonexistent"),
+            PowerLimits {
+                action: ThrottleAction::Reduce,
+                ..limits()
+            },
+        );
+
+        assert!(!policy.update(reading(50.0, true, 30)).is_active());
+        let throttle = policy.update(reading(50.0, true, 19));
+        assert_eq!(throttle.max_workers, Some(1));
+        assert_eq!(throttle.reason.as_deref(), Some("battery 19%"));
+
+        // Charging back over the minimum isn't enough while still on battery...
+        assert!(policy.update(reading(50.0, true, 22)).is_active());
+        assert!(!policy.update(reading(50.0, true, 25)).is_active());
+
+        // ...but plugging in lifts the throttle right away
+        policy.update(reading(50.0, true, 10));
+        assert!(!policy.update(reading(50.0, false, 10)).is_active());
+    }
+
+    #[test]
+    fn test_on_battery_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        fake_laptop(dir.path(), "90000\n", "0\n", "64\n");
+        let mut policy = ThermalPolicy::new(
+            Sysfs::new(dir.path()),
+            PowerLimits {
+                on_battery: true,
+                min_battery_percent: None,
+                ..limits()
+            },
+        );
+
+        assert_eq!(policy.status(), None);
+        let throttle = policy.sample();
+        assert_eq!(
+            throttle.reason.as_deref(),
+            Some("temperature 90°C, on battery")
+        );
+        assert_eq!(policy.status().as_deref(), Some("90°C, battery 64%"));
+        assert!(policy.unsupported().is_empty());
+    }
+}
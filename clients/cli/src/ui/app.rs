@@ -7,10 +7,11 @@ use crate::events::Event as WorkerEvent;
 use crate::ui::dashboard::{DashboardState, render_dashboard};
 use crate::ui::login::render_login;
 use crate::ui::splash::render_splash;
-use crossterm::event::{self, Event, KeyCode};
+use crate::workers::core::{RunState, ShutdownMode};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers};
 use ratatui::{Frame, Terminal, backend::Backend};
 use std::time::{Duration, Instant};
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 
 /// UI configuration data grouped by concern
 #[derive(Debug, Clone)]
@@ -19,6 +20,8 @@ pub struct UIConfig {
     pub num_threads: usize,
     pub update_available: bool,
     pub latest_version: Option<String>,
+    /// Proofs are kept locally instead of being submitted
+    pub dry_run: bool,
 }
 
 impl UIConfig {
@@ -27,12 +30,14 @@ impl UIConfig {
         num_threads: usize,
         update_available: bool,
         latest_version: Option<String>,
+        dry_run: bool,
     ) -> Self {
         Self {
             with_background_color,
             num_threads,
             update_available,
             latest_version,
+            dry_run,
         }
     }
 }
@@ -67,11 +72,23 @@ pub struct App {
     /// Receives events from worker threads.
     event_receiver: mpsc::Receiver<WorkerEvent>,
 
-    /// Broadcasts shutdown signal to worker threads.
-    shutdown_sender: broadcast::Sender<()>,
+    /// Broadcasts shutdown requests to worker threads.
+    shutdown_sender: broadcast::Sender<ShutdownMode>,
 
-    /// Receives max tasks completion signal.
-    max_tasks_shutdown_receiver: broadcast::Receiver<()>,
+    /// Observes shutdown requests, including those sent by signal handlers.
+    shutdown_receiver: broadcast::Receiver<ShutdownMode>,
+
+    /// The most recent shutdown request, if any.
+    shutdown_mode: Option<ShutdownMode>,
+
+    /// Pauses, suspends, or resumes the worker.
+    run_state_sender: watch::Sender<RunState>,
+
+    /// Temperature and power readings from the throttle policies.
+    host_status: watch::Receiver<Vec<String>>,
+
+    /// Receives the signal that the workers have exited (shutdown or max tasks).
+    worker_exit_receiver: broadcast::Receiver<()>,
 
     /// Whether to disable background colors
     with_background_color: bool,
@@ -84,18 +101,25 @@ pub struct App {
 
     /// Latest version available, if any.
     latest_version: Option<String>,
+
+    /// Whether proofs are kept locally instead of being submitted.
+    dry_run: bool,
 }
 
 impl App {
     /// Creates a new instance of the application.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         node_id: Option<u64>,
         environment: Environment,
         event_receiver: mpsc::Receiver<WorkerEvent>,
-        shutdown_sender: broadcast::Sender<()>,
-        max_tasks_shutdown_receiver: broadcast::Receiver<()>,
+        shutdown_sender: broadcast::Sender<ShutdownMode>,
+        run_state_sender: watch::Sender<RunState>,
+        host_status: watch::Receiver<Vec<String>>,
+        worker_exit_receiver: broadcast::Receiver<()>,
         ui_config: UIConfig,
     ) -> Self {
+        let shutdown_receiver = shutdown_sender.subscribe();
         Self {
             start_time: Instant::now(),
             node_id,
@@ -103,11 +127,16 @@ impl App {
             current_screen: Screen::Splash,
             event_receiver,
             shutdown_sender,
-            max_tasks_shutdown_receiver,
+            shutdown_receiver,
+            shutdown_mode: None,
+            run_state_sender,
+            host_status,
+            worker_exit_receiver,
             with_background_color: ui_config.with_background_color,
             num_threads: ui_config.num_threads,
             version_update_available: ui_config.update_available,
             latest_version: ui_config.latest_version,
+            dry_run: ui_config.dry_run,
         }
     }
 
@@ -120,6 +149,7 @@ impl App {
             self.num_threads,
             self.version_update_available,
             self.latest_version.clone(),
+            self.dry_run,
         );
         let state = DashboardState::new(
             node_id,
@@ -129,6 +159,24 @@ impl App {
         );
         self.current_screen = Screen::Dashboard(Box::new(state));
     }
+
+    /// Requests a drain the first time, and an immediate shutdown after that.
+    fn request_shutdown(&mut self) {
+        let mode = ShutdownMode::escalate(self.shutdown_mode);
+        self.shutdown_mode = Some(mode);
+        let _ = self.shutdown_sender.send(mode);
+    }
+
+    /// Toggles between running and paused, or switches to `target` if given.
+    fn request_run_state(&mut self, target: Option<RunState>) {
+        let current = *self.run_state_sender.borrow();
+        let next = match target {
+            Some(state) => state,
+            None if current == RunState::Running => RunState::Paused,
+            None => RunState::Running,
+        };
+        self.run_state_sender.send_replace(next);
+    }
 }
 
 /// Runs the application UI in a loop, handling events and rendering the appropriate screen.
@@ -138,13 +186,16 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> std::i
 
     // UI event loop
     loop {
-        // Check for max tasks completion signal (non-blocking)
-        if app.max_tasks_shutdown_receiver.try_recv().is_ok() {
-            // Send shutdown signal to workers and exit
-            let _ = app.shutdown_sender.send(());
+        // Exit once the workers are done (shutdown completed or max tasks reached)
+        if app.worker_exit_receiver.try_recv().is_ok() {
             return Ok(());
         }
 
+        // Pick up shutdown requests sent from outside the UI (e.g. SIGTERM)
+        while let Ok(mode) = app.shutdown_receiver.try_recv() {
+            app.shutdown_mode = Some(mode);
+        }
+
         // Queue all incoming events for processing
         while let Ok(event) = app.event_receiver.try_recv() {
             // Add event to dashboard queue if it exists
@@ -159,6 +210,9 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> std::i
             Screen::Login => {}
             Screen::Dashboard(state) => {
                 // Update the dashboard with new tick and metrics
+                state.shutdown_mode = app.shutdown_mode;
+                state.run_state = *app.run_state_sender.borrow();
+                state.host_status = app.host_status.borrow().clone();
                 state.update();
             }
         }
@@ -172,6 +226,7 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> std::i
                     app.num_threads,
                     app.version_update_available,
                     app.latest_version.clone(),
+                    app.dry_run,
                 );
                 app.current_screen = Screen::Dashboard(Box::new(DashboardState::new(
                     app.node_id,
@@ -191,11 +246,13 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> std::i
                     continue;
                 }
 
-                // Handle exit events
-                if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
-                    // Send shutdown signal to workers
-                    let _ = app.shutdown_sender.send(());
-                    return Ok(());
+                // Handle exit events: the first press drains, the second quits now.
+                // Raw mode swallows SIGINT, so Ctrl+C is handled here too.
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if ctrl_c || matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+                    app.request_shutdown();
+                    continue;
                 }
 
                 match &mut app.current_screen {
@@ -207,6 +264,7 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> std::i
                                 app.num_threads,
                                 app.version_update_available,
                                 app.latest_version.clone(),
+                                app.dry_run,
                             );
                             app.current_screen = Screen::Dashboard(Box::new(DashboardState::new(
                                 app.node_id,
@@ -221,7 +279,13 @@ pub async fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> std::i
                             app.login();
                         }
                     }
-                    Screen::Dashboard(_dashboard_state) => {}
+                    Screen::Dashboard(_dashboard_state) => match key.code {
+                        // [P] pauses after the current task, or resumes
+                        KeyCode::Char('p') => app.request_run_state(None),
+                        // [S] suspends the current task right away
+                        KeyCode::Char('s') => app.request_run_state(Some(RunState::Suspended)),
+                        _ => {}
+                    },
                 }
             }
         }
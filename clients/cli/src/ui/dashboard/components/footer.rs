@@ -2,16 +2,31 @@
 //!
 //! Renders footer with quit instructions and version info
 
+use super::super::state::DashboardState;
+use crate::workers::core::ShutdownMode;
+
 use ratatui::Frame;
 use ratatui::layout::Alignment;
 use ratatui::prelude::{Color, Modifier, Style};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 
 /// Render enhanced footer.
-pub fn render_footer(f: &mut Frame, area: ratatui::layout::Rect) {
-    let footer_text = "[Q] Quit | Nexus Prover Dashboard".to_string();
-
-    let footer_color = Color::Cyan;
+pub fn render_footer(f: &mut Frame, area: ratatui::layout::Rect, state: &DashboardState) {
+    let (footer_text, footer_color) = match state.shutdown_mode {
+        None => (
+            "[Q] Quit after current task | [P] Pause/Resume | [S] Suspend | Nexus Prover Dashboard"
+                .to_string(),
+            Color::Cyan,
+        ),
+        Some(ShutdownMode::Drain) => (
+            "[Q] Quit now, abandoning current task | Nexus Prover Dashboard".to_string(),
+            Color::LightYellow,
+        ),
+        Some(ShutdownMode::Immediate) => (
+            "Stopping... | Nexus Prover Dashboard".to_string(),
+            Color::LightRed,
+        ),
+    };
 
     let footer = Paragraph::new(footer_text)
         .alignment(Alignment::Center)
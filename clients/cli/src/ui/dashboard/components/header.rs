@@ -4,6 +4,7 @@
 
 use super::super::state::DashboardState;
 use crate::events::ProverState;
+use crate::workers::core::{RunState, ShutdownMode};
 
 use ratatui::Frame;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout};
@@ -28,8 +29,15 @@ pub fn render_header(f: &mut Frame, area: ratatui::layout::Rect, state: &Dashboa
     } else {
         format!("NEXUS PROVER v{}", version)
     };
+    let title_text = if state.dry_run {
+        format!("{} - DRY RUN, PROOFS ARE NOT SUBMITTED", title_text)
+    } else {
+        title_text
+    };
 
-    let title_color = if state.update_available {
+    let title_color = if state.dry_run {
+        Color::LightMagenta
+    } else if state.update_available {
         Color::LightYellow
     } else {
         Color::Cyan
@@ -49,19 +57,63 @@ pub fn render_header(f: &mut Frame, area: ratatui::layout::Rect, state: &Dashboa
         );
     f.render_widget(title, header_chunks[0]);
 
-    // Gauge logic: proving takes priority, then task fetching countdown
+    // Gauge logic: shutdown takes priority, then proving, then task fetching countdown
     let (progress_text, gauge_color, progress_percent) = {
-        // Check if we're currently proving
+        let shutdown = state.shutdown_mode;
         match state.current_prover_state() {
-            ProverState::Proving => {
-                // Animated proving gauge - loops every 20 ticks for smooth animation
+            _ if shutdown == Some(ShutdownMode::Immediate) => (
+                "STOPPING - Abandoning current task".to_string(),
+                Color::LightRed,
+                100,
+            ),
+            ProverState::Proving if shutdown == Some(ShutdownMode::Drain) => {
                 let progress = ((state.tick % 20) as f64 / 20.0 * 100.0) as u16;
                 (
-                    "PROVING - Generating proof".to_string(),
-                    Color::LightGreen,
+                    "DRAINING - Finishing current task before exit".to_string(),
+                    Color::LightYellow,
                     progress,
                 )
             }
+            ProverState::Waiting | ProverState::Paused if shutdown == Some(ShutdownMode::Drain) => {
+                ("DRAINING - Exiting".to_string(), Color::LightYellow, 100)
+            }
+            ProverState::Proving => {
+                // Animated proving gauge - loops every 20 ticks for smooth animation
+                let progress = ((state.tick % 20) as f64 / 20.0 * 100.0) as u16;
+                let text = if state.run_state == RunState::Paused {
+                    "PROVING - Pausing after current task"
+                } else {
+                    "PROVING - Generating proof"
+                };
+                match state.input_progress {
+                    // Tasks submitted input by input fill the gauge as their inputs go out
+                    Some((submitted, total)) if total > 0 => (
+                        format!("{} ({} of {} inputs submitted)", text, submitted, total),
+                        Color::LightGreen,
+                        (submitted * 100 / total).min(100) as u16,
+                    ),
+                    _ => (text.to_string(), Color::LightGreen, progress),
+                }
+            }
+            ProverState::Paused => {
+                let text = match (&state.next_window, state.run_state) {
+                    (Some(next), RunState::Suspended) => {
+                        format!("PARKED - Outside operating window, resumes {}", next)
+                    }
+                    (Some(next), _) => {
+                        format!("IDLE - Outside operating window, next opens {}", next)
+                    }
+                    (None, RunState::Suspended) => {
+                        "SUSPENDED - Current task on hold ([P] to resume)".to_string()
+                    }
+                    (None, RunState::Running) if state.throttle.is_some() => format!(
+                        "THROTTLED - {}",
+                        state.throttle.as_deref().unwrap_or_default()
+                    ),
+                    (None, _) => "PAUSED - Not fetching new tasks ([P] to resume)".to_string(),
+                };
+                (text, Color::Magenta, 100)
+            }
             ProverState::Waiting => {
                 // Task fetching countdown logic
                 let fetch_info = &state.task_fetch_info;
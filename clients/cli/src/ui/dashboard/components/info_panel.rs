@@ -28,9 +28,7 @@ pub fn render_info_panel(f: &mut Frame, area: ratatui::layout::Rect, state: &Das
     // Environment with color coding
     let env_color = match state.environment {
         Environment::Production => Color::Green,
-        Environment::Custom {
-            orchestrator_url: _,
-        } => Color::Yellow,
+        Environment::Custom { .. } => Color::Yellow,
     };
     info_lines.push(Line::from(vec![Span::styled(
         format!("Env: {}", state.environment),
@@ -84,6 +82,20 @@ pub fn render_info_panel(f: &mut Frame, area: ratatui::layout::Rect, state: &Das
         Style::default().fg(Color::LightCyan),
     )]));
 
+    // Temperature and power, when watched
+    for status in &state.host_status {
+        info_lines.push(Line::from(vec![Span::styled(
+            format!("Host: {}", status),
+            Style::default().fg(Color::LightMagenta),
+        )]));
+    }
+    if let Some(throttle) = &state.throttle {
+        info_lines.push(Line::from(vec![Span::styled(
+            format!("Throttled: {}", throttle),
+            Style::default().fg(Color::LightRed),
+        )]));
+    }
+
     // Note: Task ID removed from system info as requested
 
     let info_block = Block::default()
@@ -153,6 +153,31 @@ pub fn render_zkvm_metrics(f: &mut Frame, area: ratatui::layout::Rect, state: &D
         Span::styled(metrics.format_runtime(), Style::default().fg(Color::Cyan)),
     ]));
 
+    // Energy and its estimated cost, when RAPL counters are available
+    if let Some(kwh) = metrics.energy_kwh {
+        zkvm_lines.push(Line::from(vec![
+            Span::styled("Energy: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{:.4} kWh", kwh),
+                Style::default().fg(Color::LightMagenta),
+            ),
+        ]));
+    }
+    if let Some(cost) = metrics.energy_cost {
+        let currency = metrics
+            .cost_currency
+            .as_deref()
+            .map(|currency| format!(" {}", currency))
+            .unwrap_or_default();
+        zkvm_lines.push(Line::from(vec![
+            Span::styled("Cost: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{:.4}{}", cost, currency),
+                Style::default().fg(Color::LightMagenta),
+            ),
+        ]));
+    }
+
     // Last task info
     let status_color = match metrics.last_task_status.as_str() {
         "Success" => Color::Green,
@@ -36,5 +36,5 @@ pub fn render_dashboard(f: &mut Frame, state: &DashboardState) {
     info_panel::render_info_panel(f, content_chunks[0], state);
     logs::render_logs_panel(f, content_chunks[1], state);
     metrics::render_metrics_section(f, main_chunks[2], state);
-    footer::render_footer(f, main_chunks[3]);
+    footer::render_footer(f, main_chunks[3], state);
 }
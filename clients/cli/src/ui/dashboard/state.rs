@@ -7,6 +7,7 @@ use crate::environment::Environment;
 use crate::events::{Event as WorkerEvent, ProverState};
 use crate::ui::app::UIConfig;
 use crate::ui::metrics::{SystemMetrics, TaskFetchInfo, ZkVMMetrics};
+use crate::workers::core::{RunState, ShutdownMode};
 
 use std::collections::VecDeque;
 use std::time::Instant;
@@ -45,6 +46,8 @@ pub struct DashboardState {
     pub update_available: bool,
     /// The latest version string, if known.
     pub latest_version: Option<String>,
+    /// Whether proofs are kept locally instead of being submitted
+    pub dry_run: bool,
     /// Whether to enable background colors
     pub with_background_color: bool,
 
@@ -56,6 +59,18 @@ pub struct DashboardState {
     pub task_fetch_info: TaskFetchInfo,
     /// Animation tick counter
     pub tick: usize,
+    /// Shutdown requested by the user or a signal, if any
+    pub shutdown_mode: Option<ShutdownMode>,
+    /// Pause/resume state requested by the user or a signal
+    pub run_state: RunState,
+    /// When the next operating window opens, while outside one
+    pub next_window: Option<String>,
+    /// Why proving is being throttled, while it is
+    pub throttle: Option<String>,
+    /// Temperature and power readings, when a thermal policy is active
+    pub host_status: Vec<String>,
+    /// Inputs submitted so far and inputs in all, while a task is submitted input by input
+    pub input_progress: Option<(usize, usize)>,
 
     /// Timestamp of last successful proof submission
     last_submission_timestamp: Option<String>,
@@ -91,12 +106,19 @@ impl DashboardState {
             activity_logs: VecDeque::new(),
             update_available: ui_config.update_available,
             latest_version: ui_config.latest_version,
+            dry_run: ui_config.dry_run,
             with_background_color: ui_config.with_background_color,
 
             system_metrics: SystemMetrics::default(),
             zkvm_metrics: ZkVMMetrics::default(),
             task_fetch_info: TaskFetchInfo::default(),
             tick: 0,
+            shutdown_mode: None,
+            run_state: RunState::Running,
+            next_window: None,
+            throttle: None,
+            host_status: Vec::new(),
+            input_progress: None,
             last_submission_timestamp: None,
             fetching_state: FetchingState::Idle,
             sysinfo: System::new_all(), // Initialize with all data for first refresh
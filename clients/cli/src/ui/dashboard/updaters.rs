@@ -52,6 +52,10 @@ impl DashboardState {
             if let Some(state) = event.prover_state {
                 self.set_current_prover_state(state);
             }
+            // Completed tasks carry their energy, if it was measured
+            if let Some(energy) = &event.energy {
+                self.zkvm_metrics.add_energy(energy);
+            }
         }
     }
 
@@ -64,6 +68,7 @@ impl DashboardState {
             if let Some(task_id) = Self::extract_task_id(&event.msg) {
                 self.last_task = self.current_task.clone();
                 self.current_task = Some(task_id);
+                self.input_progress = None;
 
                 // Count this as a task fetch if we haven't seen this task before
                 self.zkvm_metrics.tasks_fetched += 1;
@@ -83,6 +88,26 @@ impl DashboardState {
             });
         }
 
+        // Track operating windows: "Outside operating window, next window opens <time>"
+        if event.msg.starts_with("Outside operating window") {
+            self.next_window = event
+                .msg
+                .split_once("next window opens ")
+                .map(|(_, next)| next.to_string());
+        } else if event.msg.starts_with("Operating window open") {
+            self.next_window = None;
+        }
+
+        // Track throttling: "Throttling: <reason>, <action>" until "Throttle lifted"
+        if let Some(throttle) = event.msg.strip_prefix("Throttling: ") {
+            let reason = throttle
+                .rsplit_once(", ")
+                .map_or(throttle, |(reason, _)| reason);
+            self.throttle = Some(reason.to_string());
+        } else if event.msg.starts_with("Throttle lifted") {
+            self.throttle = None;
+        }
+
         // Handle waiting messages for task fetch info
         if event.msg.contains("ready for next task") {
             if let Some(seconds) = Self::extract_wait_seconds(&event.msg) {
@@ -117,6 +142,12 @@ impl DashboardState {
 
     /// Handle ProofSubmitter events
     fn handle_proof_submitter_event(&mut self, event: &WorkerEvent) {
+        // Track per-input submission: "X of Y inputs submitted for task TASK_ID"
+        if let Some(progress) = Self::extract_input_progress(&event.msg) {
+            self.input_progress = Some(progress);
+            return;
+        }
+
         if matches!(event.event_type, EventType::Success)
             && event
                 .msg
@@ -135,6 +166,10 @@ impl DashboardState {
 
             // Update total points
             self.zkvm_metrics._total_points = (self.zkvm_metrics.tasks_submitted as u64) * 300;
+        } else if matches!(event.event_type, EventType::Success)
+            && event.msg.contains("Step 4 of 4: Dry run")
+        {
+            self.zkvm_metrics.last_task_status = "Kept (dry run)".to_string();
         } else if matches!(event.event_type, EventType::Error) {
             self.zkvm_metrics.last_task_status = "Submit Failed".to_string();
         }
@@ -195,6 +230,14 @@ impl DashboardState {
         }
     }
 
+    /// Extract submitted and total inputs from message.
+    /// Expected format: "X of Y inputs submitted for task TASK_ID"
+    fn extract_input_progress(msg: &str) -> Option<(usize, usize)> {
+        let (progress, _) = msg.split_once(" inputs submitted for task ")?;
+        let (submitted, total) = progress.split_once(" of ")?;
+        Some((submitted.parse().ok()?, total.parse().ok()?))
+    }
+
     /// Extract wait seconds from message. Expected format: "...ready for next task (30) seconds"
     fn extract_wait_seconds(msg: &str) -> Option<u64> {
         let start = msg.find("(")?;
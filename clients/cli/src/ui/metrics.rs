@@ -1,5 +1,6 @@
 //! System metrics collection and display.
 
+use crate::prover::energy::TaskEnergy;
 use std::time::Instant;
 use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
 
@@ -186,6 +187,31 @@ impl SystemMetrics {
     }
 }
 
+/// System-wide CPU usage (0.0 to 100.0) from everything except `excluded` processes.
+///
+/// Uses the same refresh as [`SystemMetrics::update`], so calls should be at least
+/// `sysinfo::MINIMUM_CPU_UPDATE_INTERVAL` apart for a meaningful reading.
+/// A process's usage only shows from its second refresh on, so the first reading
+/// after an excluded process appears counts its usage as foreground load.
+pub fn foreground_cpu_percent(sysinfo: &mut System, excluded: &[u32]) -> f32 {
+    sysinfo.refresh_cpu_usage();
+    sysinfo.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cpu(),
+    );
+
+    let excluded_cpu: f32 = excluded
+        .iter()
+        .filter_map(|&pid| sysinfo.process(Pid::from(pid as usize)))
+        .map(|process| process.cpu_usage())
+        .sum();
+
+    // Per-process usage is per core, the global figure is already normalized
+    let num_cores = crate::system::num_cores() as f32;
+    (sysinfo.global_cpu_usage() - excluded_cpu / num_cores).clamp(0.0, 100.0)
+}
+
 /// zkVM task metrics for display.
 #[derive(Debug, Clone)]
 pub struct ZkVMMetrics {
@@ -199,6 +225,12 @@ pub struct ZkVMMetrics {
     pub last_task_status: String,
     /// Total points earned from successful proofs (300 points each).
     pub _total_points: u64,
+    /// Energy used by completed tasks, when RAPL counters are available.
+    pub energy_kwh: Option<f64>,
+    /// Estimated cost of that energy, when a price is configured.
+    pub energy_cost: Option<f64>,
+    /// Currency shown after the cost.
+    pub cost_currency: Option<String>,
 }
 
 impl Default for ZkVMMetrics {
@@ -209,6 +241,9 @@ impl Default for ZkVMMetrics {
             zkvm_runtime_secs: 0,
             last_task_status: "None".to_string(),
             _total_points: 0,
+            energy_kwh: None,
+            energy_cost: None,
+            cost_currency: None,
         }
     }
 }
@@ -248,6 +283,15 @@ impl ZkVMMetrics {
         }
     }
 
+    /// Add the energy a completed task used
+    pub fn add_energy(&mut self, energy: &TaskEnergy) {
+        *self.energy_kwh.get_or_insert(0.0) += energy.kwh;
+        if let Some(cost) = energy.cost {
+            *self.energy_cost.get_or_insert(0.0) += cost;
+            self.cost_currency = energy.currency.clone();
+        }
+    }
+
     /// Format zkVM runtime as human-readable string.
     pub fn format_runtime(&self) -> String {
         let hours = self.zkvm_runtime_secs / 3600;
@@ -6,3 +6,4 @@ mod metrics;
 pub mod splash;
 // Re-exports for external use
 pub use app::{App, UIConfig, run};
+pub use metrics::foreground_cpu_percent;
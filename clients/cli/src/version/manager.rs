@@ -1,9 +1,12 @@
 //! Version management and validation with improved error messages
-use super::{ConstraintType, VersionRequirements};
+use super::{ConstraintType, VersionCheckResult, VersionRequirements};
 use std::error::Error;
 
-/// Validates version requirements before application startup
-pub async fn validate_version_requirements() -> Result<(), Box<dyn Error>> {
+/// Validates version requirements before application startup.
+///
+/// Returns the constraint this version violates, if any, once it has been reported.
+/// Exiting on a blocking violation is left to the caller, so it can notify hooks first.
+pub async fn validate_version_requirements() -> Result<Option<VersionCheckResult>, Box<dyn Error>> {
     // Single attempt since VersionRequirements::fetch already tries multiple hostnames
     let requirements = match VersionRequirements::fetch().await {
         Ok(requirements) => requirements,
@@ -39,9 +42,11 @@ pub async fn validate_version_requirements() -> Result<(), Box<dyn Error>> {
     match requirements.check_version_constraints(current_version, None, None) {
         Ok(Some(violation)) => {
             handle_version_violation(&violation.constraint_type, &violation.message);
+            Ok(Some(violation))
         }
         Ok(None) => {
             // No violations found, continue normally
+            Ok(None)
         }
         Err(e) => {
             eprintln!("❌ Failed to parse version requirements: {}", e);
@@ -51,8 +56,6 @@ pub async fn validate_version_requirements() -> Result<(), Box<dyn Error>> {
             std::process::exit(1);
         }
     }
-
-    Ok(())
 }
 
 /// Provides user-friendly error messages for fetch failures
@@ -72,7 +75,6 @@ fn handle_version_violation(constraint_type: &ConstraintType, message: &str) {
             eprintln!("To resolve this issue:");
             eprintln!("  • Download the latest version from:");
             eprintln!("    https://github.com/nexus-xyz/nexus-cli/releases");
-            std::process::exit(1);
         }
         ConstraintType::Warning => {
             eprintln!("⚠️  Version Warning");
@@ -2,4 +2,4 @@ pub mod checker;
 pub mod manager;
 pub mod requirements;
 
-pub use requirements::{ConstraintType, VersionRequirements};
+pub use requirements::{ConstraintType, VersionCheckResult, VersionRequirements};
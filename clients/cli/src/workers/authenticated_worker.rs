@@ -1,39 +1,94 @@
 //! Single authenticated worker that orchestrates fetch→prove→submit
 
-use super::core::{EventSender, WorkerConfig};
+use super::core::{
+    EventSender, LiveSettings, ResumeAssigned, RunState, ShutdownMode, Throttle, WorkerChannels,
+    WorkerConfig, WorkerExit, recv_shutdown, stopped_immediately,
+};
+use super::difficulty::{TaskOutcome, unreachable_deadline};
 use super::fetcher::TaskFetcher;
 use super::prover::TaskProver;
-use super::submitter::ProofSubmitter;
-use crate::events::{Event, ProverState};
-use crate::orchestrator::OrchestratorClient;
+use super::submitter::{ProofSubmitter, SubmitError, TaskAbandoner};
+use crate::consts::cli_consts::proof_submission;
+use crate::events::{Event, EventType, Lifecycle, ProverState};
+use crate::history::{HistoryStore, TaskRecord, TaskResult};
+use crate::logging::LogLevel;
+use crate::nexus_orchestrator::AbandonReason;
+use crate::orchestrator::Orchestrator;
+use crate::prover::ProverResult;
+use crate::prover::energy::EnergyConfig;
+use crate::prover::{memory, processes};
+use crate::systemd::Watchdog;
+use crate::task::Task;
 
+use chrono::Utc;
 use ed25519_dalek::SigningKey;
 use std::time::Duration;
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, watch};
 use tokio::task::JoinHandle;
 
+/// A proved task whose submission waits for the rest of its batch
+struct DeferredSubmission {
+    task: Task,
+    proof_result: ProverResult,
+    record: TaskRecord,
+    /// Difficulty the task was assigned at
+    difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    /// Time from getting the task until it was proved
+    duration: Duration,
+}
+
+/// Why a proved task whose proof was held back for a batch is abandoned at exit
+const UNSUBMITTED: &str = "exited before submitting the proof";
+
 /// Single authenticated worker that handles the complete task lifecycle
 pub struct AuthenticatedWorker {
+    node_id: u64,
     fetcher: TaskFetcher,
     prover: TaskProver,
     submitter: ProofSubmitter,
+    /// Hands tasks this worker gives up back to the orchestrator
+    abandoner: TaskAbandoner,
     event_sender: EventSender,
     max_tasks: Option<u32>,
     tasks_completed: u32,
-    shutdown_sender: broadcast::Sender<()>,
+    drain_timeout: Duration,
+    /// What a drain during the last task left of `drain_timeout`, for flushing held-back proofs
+    drain_left: Duration,
+    max_consecutive_failures: Option<u32>,
+    consecutive_failures: u32,
+    /// How per-task energy is reported
+    energy: EnergyConfig,
+    /// Where every task is recorded, if anywhere
+    history: Option<HistoryStore>,
+    /// systemd watchdog, pinged until a task overstays its deadline
+    watchdog: Option<Watchdog>,
+    /// What to do with tasks assigned before a restart
+    resume_assigned: ResumeAssigned,
+    /// Proved tasks waiting for the rest of their batch, to be submitted together
+    deferred: Vec<DeferredSubmission>,
+    /// Settings changed at runtime, picked up between tasks
+    settings: watch::Receiver<LiveSettings>,
+    /// Limits imposed by host load, temperature or power, picked up between tasks
+    throttle: watch::Receiver<Throttle>,
+    /// Shutdown requests, taken by the work loop when the worker runs
+    shutdown: broadcast::Receiver<ShutdownMode>,
+    /// Pause and resume requests
+    run_state: watch::Receiver<RunState>,
+    /// Notifies the session that the worker has exited, for whatever reason
+    exit_sender: broadcast::Sender<()>,
 }
 
 impl AuthenticatedWorker {
     pub fn new(
         node_id: u64,
         signing_key: SigningKey,
-        orchestrator: OrchestratorClient,
+        orchestrator: impl Orchestrator + Clone + 'static,
         config: WorkerConfig,
-        event_sender: mpsc::Sender<Event>,
         max_tasks: Option<u32>,
-        shutdown_sender: broadcast::Sender<()>,
+        channels: WorkerChannels,
+        exit_sender: broadcast::Sender<()>,
     ) -> Self {
-        let event_sender_helper = EventSender::new(event_sender);
+        let event_sender_helper = EventSender::new(channels.events);
 
         // Create the 3 specialized components
         let fetcher = TaskFetcher::new(
@@ -47,6 +102,7 @@ impl AuthenticatedWorker {
         let prover = TaskProver::new(event_sender_helper.clone(), config.clone());
 
         let submitter = ProofSubmitter::new(
+            node_id,
             signing_key,
             Box::new(orchestrator),
             event_sender_helper.clone(),
@@ -54,18 +110,33 @@ impl AuthenticatedWorker {
         );
 
         Self {
+            node_id,
             fetcher,
             prover,
+            abandoner: submitter.abandoner(),
             submitter,
             event_sender: event_sender_helper,
             max_tasks,
             tasks_completed: 0,
-            shutdown_sender,
+            drain_timeout: config.drain_timeout,
+            drain_left: config.drain_timeout,
+            max_consecutive_failures: config.max_consecutive_failures,
+            consecutive_failures: 0,
+            energy: config.energy.clone(),
+            history: config.task_history.clone().map(HistoryStore::new),
+            watchdog: config.watchdog.clone(),
+            resume_assigned: config.resume_assigned,
+            deferred: Vec::new(),
+            settings: channels.settings,
+            throttle: channels.throttle,
+            shutdown: channels.shutdown,
+            run_state: channels.run_state,
+            exit_sender,
         }
     }
 
     /// Start the worker
-    pub async fn run(mut self, mut shutdown: broadcast::Receiver<()>) -> Vec<JoinHandle<()>> {
+    pub async fn run(mut self) -> Vec<JoinHandle<WorkerExit>> {
         let mut join_handles = Vec::new();
 
         // Send initial state
@@ -78,40 +149,397 @@ impl AuthenticatedWorker {
 
         // Main work loop
         let worker_handle = tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    _ = shutdown.recv() => break,
-                    should_exit = self.work_cycle() => {
-                        if should_exit {
-                            break;
-                        }
-                        // Natural rate limiting through work cycle
-                        tokio::time::sleep(Duration::from_millis(100)).await;
+            // The loop gets the receivers themselves, with any request already sent
+            let mut shutdown = std::mem::replace(&mut self.shutdown, self.shutdown.resubscribe());
+            let mut run_state = self.run_state.clone();
+            let pinger = self.watchdog.as_ref().map(Watchdog::spawn);
+            // A second look at the shutdown requests, to tell how the worker was stopped
+            let mut requests = shutdown.resubscribe();
+            let mut exit = self.work_loop(&mut shutdown, &mut run_state).await;
+            // Proofs held back for a batch are done; don't throw them away, unless the
+            // worker has to stop right away, a drain already timed out, or the orchestrator
+            // won't take them. The flush only gets what the drain left of its timeout, and
+            // a further shutdown request cuts it short.
+            let flush = exit == WorkerExit::Completed && !stopped_immediately(&mut requests);
+            if !self.deferred.is_empty() && flush {
+                let drain_left = self.drain_left;
+                let flushed = tokio::select! {
+                    result = tokio::time::timeout(drain_left, self.submit_deferred()) => {
+                        result.ok().flatten()
                     }
+                    _ = recv_shutdown(&mut shutdown) => None,
+                };
+                if let Some(flushed) = flushed {
+                    exit = flushed;
                 }
             }
+            let unsubmitted = self.drop_deferred().await;
+            // Nobody will prove tasks still queued, so hand them back instead of letting them
+            // expire: all at once, and only for as long as a single abandonment may take
+            let queued = self.fetcher.take_queued();
+            let abandonments = unsubmitted
+                .iter()
+                .map(|task_id| (task_id.as_str(), UNSUBMITTED))
+                .chain(
+                    queued
+                        .iter()
+                        .map(|task| (task.task_id.as_str(), "exited before starting the task")),
+                )
+                .map(|(task_id, detail)| {
+                    self.abandoner
+                        .abandon(task_id, AbandonReason::Shutdown, detail)
+                });
+            let _ = tokio::time::timeout(
+                proof_submission::abandon_timeout(),
+                futures::future::join_all(abandonments),
+            )
+            .await;
+            if let Some(pinger) = pinger {
+                pinger.abort();
+            }
+            // Let the session know we're done, whether by shutdown or max tasks
+            let _ = self.exit_sender.send(());
+            exit
         });
         join_handles.push(worker_handle);
 
         join_handles
     }
 
-    /// Complete work cycle: fetch→prove→submit
-    /// Returns true if the worker should exit (max tasks reached)
-    async fn work_cycle(&mut self) -> bool {
-        // Step 1: Fetch task
-        let task = match self.fetcher.fetch_task().await {
-            Ok(task) => task,
-            Err(_) => {
+    /// Run work cycles until max tasks is reached or a shutdown is requested
+    async fn work_loop(
+        &mut self,
+        shutdown: &mut broadcast::Receiver<ShutdownMode>,
+        run_state: &mut watch::Receiver<RunState>,
+    ) -> WorkerExit {
+        // Tasks assigned before a restart are handed out before new ones are requested
+        let resume_assigned = self.resume_assigned;
+        tokio::select! {
+            _ = recv_shutdown(shutdown) => return WorkerExit::Completed,
+            _ = self.fetcher.load_assigned_tasks(resume_assigned) => {}
+        }
+
+        loop {
+            // Proofs held back for a batch go out once the batch is done, e.g. after a
+            // failed proof, or before idling
+            let idling = *run_state.borrow() != RunState::Running || self.throttle.borrow().paused;
+            if !self.deferred.is_empty() && (idling || !self.fetcher.has_queued()) {
+                if let Some(exit) = self.submit_deferred().await {
+                    return exit;
+                }
+            }
+
+            // Don't take on new work while paused
+            if !self.wait_while_paused(shutdown, run_state).await {
+                return WorkerExit::Completed;
+            }
+            if !self.wait_while_throttled(shutdown).await {
+                return WorkerExit::Completed;
+            }
+            self.apply_settings().await;
+
+            // Nothing is in flight while fetching, so either shutdown mode can stop here,
+            // and a pause simply abandons the fetch
+            let mut throttle = self.throttle.clone();
+            let task = tokio::select! {
+                _ = recv_shutdown(shutdown) => return WorkerExit::Completed,
+                _ = wait_for_pause(run_state) => continue,
+                _ = wait_for_throttle_pause(&mut throttle) => continue,
+                task = self.fetch_step() => task,
+            };
+            let task = match task {
+                Ok(Some(task)) => task,
+                Ok(None) => continue,
+                Err(exit) => return exit,
+            };
+
+            // Copy what we need while the in-flight cycle holds `self`
+            let drain_timeout = self.drain_timeout;
+            let event_sender = self.event_sender.clone();
+            let task_id = task.task_id.clone();
+            let history = self.history.clone();
+            let watchdog = self.watchdog.clone();
+            let abandoner = self.abandoner.clone();
+            let mut record = self.fetched_record(&task);
+            if let Some(watchdog) = &watchdog {
+                watchdog.busy();
+            }
+
+            // Boxed, so the shutdown branch can drop it and kill its subprocesses early
+            let mut cycle = Box::pin(self.prove_and_submit(task, record.clone()));
+
+            // A pause may have been requested while the task was being fetched
+            if *run_state.borrow_and_update() != RunState::Running {
+                run_state.mark_changed();
+            }
+
+            let exit = loop {
+                tokio::select! {
+                    // Poll the cycle first so its own state events precede any pause
+                    biased;
+                    exit = &mut cycle => break exit,
+                    Ok(()) = run_state.changed() => {
+                        let state = *run_state.borrow_and_update();
+                        // A suspended task can't make progress, so it doesn't count towards its deadline
+                        if let Some(watchdog) = &watchdog {
+                            match state {
+                                RunState::Suspended => watchdog.idle(),
+                                _ => watchdog.busy(),
+                            }
+                        }
+                        apply_run_state(state, &event_sender, &task_id).await;
+                    }
+                    mode = recv_shutdown(shutdown) => {
+                        // A suspended task can neither drain nor be reaped until it is continued
+                        if processes::is_suspended() {
+                            processes::resume_all();
+                        }
+
+                        let mut detail = "shutdown";
+                        if mode == ShutdownMode::Drain {
+                            event_sender
+                                .send_task_event(
+                                    format!(
+                                        "Shutdown requested: finishing task {} before exiting (up to {}s)",
+                                        task_id,
+                                        drain_timeout.as_secs()
+                                    ),
+                                    EventType::Refresh,
+                                    LogLevel::Info,
+                                )
+                                .await;
+
+                            // Any further request while draining escalates to an immediate shutdown
+                            let started = tokio::time::Instant::now();
+                            let drained = tokio::select! {
+                                result = tokio::time::timeout(drain_timeout, &mut cycle) => result.ok(),
+                                _ = recv_shutdown(shutdown) => None,
+                            };
+                            if let Some(exit) = drained {
+                                drop(cycle);
+                                self.drain_left = drain_timeout.saturating_sub(started.elapsed());
+                                // The drained task may have ended the worker itself, e.g. on a fatal error
+                                return exit.unwrap_or(WorkerExit::Completed);
+                            }
+                            detail = "drain timed out";
+                        }
+
+                        // Dropping the cycle kills any prover subprocesses still running,
+                        // before the orchestrator is told it can hand the task to another node
+                        drop(cycle);
+                        event_sender
+                            .send_task_event(
+                                format!("Shutdown: abandoned task {}", task_id),
+                                EventType::Error,
+                                LogLevel::Warn,
+                            )
+                            .await;
+                        abandoner.abandon(&task_id, AbandonReason::Shutdown, detail).await;
+                        record.finish(TaskResult::Abandoned, Some(detail.to_string()));
+                        save_record(history.as_ref(), &event_sender, &record).await;
+                        return WorkerExit::Abandoned { task_id };
+                    }
+                }
+            };
+
+            if let Some(watchdog) = &watchdog {
+                watchdog.idle();
+            }
+            if let Some(exit) = exit {
+                return exit;
+            }
+            // Natural rate limiting through work cycle
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Pick up settings and throttles changed since the last task
+    async fn apply_settings(&mut self) {
+        if self.settings.has_changed().unwrap_or(false) {
+            let settings = *self.settings.borrow_and_update();
+            self.fetcher.set_max_difficulty(settings.max_difficulty);
+
+            self.event_sender
+                .send_task_event(
+                    format!(
+                        "Settings updated: max difficulty {}, threads {}",
+                        settings
+                            .max_difficulty
+                            .map(|difficulty| difficulty.as_str_name())
+                            .unwrap_or("adaptive"),
+                        settings.num_workers
+                    ),
+                    EventType::Refresh,
+                    LogLevel::Info,
+                )
+                .await;
+        }
+
+        // A throttle caps the configured thread count without replacing it
+        let num_workers = self
+            .throttle
+            .borrow()
+            .workers(self.settings.borrow().num_workers);
+        self.prover.set_num_workers(num_workers);
+        self.fetcher.set_num_workers(num_workers);
+
+        // Don't fetch a bigger batch than `--max-tasks` leaves room for
+        self.fetcher.set_batch_cap(
+            self.max_tasks
+                .map(|max| max.saturating_sub(self.tasks_completed)),
+        );
+    }
+
+    /// Block between tasks while a throttle asks us to stop fetching.
+    /// Returns false if a shutdown was requested instead.
+    async fn wait_while_throttled(
+        &mut self,
+        shutdown: &mut broadcast::Receiver<ShutdownMode>,
+    ) -> bool {
+        let reason = {
+            let throttle = self.throttle.borrow_and_update();
+            if !throttle.paused {
+                return true;
+            }
+            throttle
+                .reason
+                .clone()
+                .unwrap_or_else(|| "host busy".to_string())
+        };
+
+        self.event_sender
+            .send_event(Event::state_change(
+                ProverState::Paused,
+                format!("Throttled: {}, not fetching new tasks", reason),
+            ))
+            .await;
+
+        tokio::select! {
+            _ = recv_shutdown(shutdown) => return false,
+            // Without a throttle task nothing can hold us back
+            _ = self.throttle.wait_for(|throttle| !throttle.paused) => {}
+        }
+
+        self.event_sender
+            .send_event(Event::state_change(
+                ProverState::Waiting,
+                "Throttle lifted, ready to fetch tasks".to_string(),
+            ))
+            .await;
+        true
+    }
+
+    /// Block between tasks while paused.
+    /// Returns false if a shutdown was requested instead of a resume.
+    async fn wait_while_paused(
+        &self,
+        shutdown: &mut broadcast::Receiver<ShutdownMode>,
+        run_state: &mut watch::Receiver<RunState>,
+    ) -> bool {
+        if *run_state.borrow_and_update() == RunState::Running {
+            return true;
+        }
+
+        self.event_sender
+            .send_event(Event::state_change(
+                ProverState::Paused,
+                "Paused: not fetching new tasks until resumed".to_string(),
+            ))
+            .await;
+
+        tokio::select! {
+            _ = recv_shutdown(shutdown) => return false,
+            // If the control side is gone nobody can resume us, so just carry on
+            _ = run_state.wait_for(|state| *state == RunState::Running) => {}
+        }
+
+        // Nothing is in flight, but don't leave the suspension flag behind
+        if processes::is_suspended() {
+            processes::resume_all();
+        }
+        self.event_sender
+            .send_event(Event::state_change(
+                ProverState::Waiting,
+                "Resumed, ready to fetch tasks".to_string(),
+            ))
+            .await;
+        true
+    }
+
+    /// Step 1: fetch the next task, returning None if the fetch failed,
+    /// or how to exit if the orchestrator refused the node for good
+    async fn fetch_step(&mut self) -> Result<Option<Task>, WorkerExit> {
+        match self.fetcher.fetch_task().await {
+            Ok(task) => Ok(Some(task)),
+            Err(e) => {
+                if let Some(reason) = e.fatal_reason() {
+                    return Err(self.halt(reason).await);
+                }
                 // Error already logged in fetcher, wait before retry
                 tokio::time::sleep(Duration::from_secs(1)).await;
-                return false; // Don't exit on fetch error, just retry
+                Ok(None) // Don't exit on fetch error, just retry
             }
-        };
+        }
+    }
+
+    /// Stop working because the orchestrator refused the node in a way retrying
+    /// can't fix; `reason` says what the operator has to do
+    async fn halt(&self, reason: String) -> WorkerExit {
+        self.event_sender
+            .send_task_event(
+                format!("Stopping: {}", reason),
+                EventType::Error,
+                LogLevel::Error,
+            )
+            .await;
+        WorkerExit::Fatal { reason }
+    }
 
+    /// A history record for the task just fetched
+    fn fetched_record(&self, task: &Task) -> TaskRecord {
+        TaskRecord::fetched(
+            self.node_id,
+            task.task_id.clone(),
+            task.all_inputs().len(),
+            self.fetcher
+                .last_desired_difficulty
+                .map(|difficulty| difficulty.as_str_name().to_string()),
+            self.fetcher
+                .assigned_difficulty()
+                .map(|difficulty| difficulty.as_str_name().to_string()),
+        )
+    }
+
+    /// Remaining work cycle for a fetched task: prove→submit
+    /// Returns how the worker should exit, if it should (max tasks or failures reached)
+    async fn prove_and_submit(&mut self, task: Task, mut record: TaskRecord) -> Option<WorkerExit> {
         // Time starts from successfully obtaining the task
         let start_time = std::time::Instant::now();
 
+        // A task that can't be submitted in time is better off with another node
+        let durations = self.fetcher.difficulty_history();
+        if let Some(reason) = unreachable_deadline(&task, &durations, Utc::now()) {
+            self.event_sender
+                .send_task_event(
+                    format!("Abandoning task {}: {}", task.task_id, reason),
+                    EventType::Refresh,
+                    LogLevel::Warn,
+                )
+                .await;
+            self.abandoner
+                .abandon(&task.task_id, AbandonReason::DeadlineUnreachable, &reason)
+                .await;
+            record.finish(TaskResult::Abandoned, Some(reason));
+            save_record(self.history.as_ref(), &self.event_sender, &record).await;
+            self.event_sender
+                .send_event(Event::state_change(
+                    ProverState::Waiting,
+                    "Task abandoned, ready for next task".to_string(),
+                ))
+                .await;
+            return None;
+        }
+
         // Step 2: Prove task
         // Send state change to Proving
         self.event_sender
@@ -121,9 +549,30 @@ impl AuthenticatedWorker {
             ))
             .await;
 
-        let proof_result = match self.prover.prove_task(&task).await {
+        // Long tasks have each input submitted as it is verified, if the orchestrator allows
+        let incremental = self
+            .submitter
+            .incremental_proving(&task, self.fetcher.incremental_min_inputs())
+            .await;
+        let (proof_result, peak_rss) =
+            memory::measure_peak_rss(self.prover.prove_task(&task, incremental)).await;
+        record.peak_rss_bytes = peak_rss;
+        let proof_result = match proof_result {
             Ok(proof_result) => proof_result,
-            Err(_) => {
+            Err(e) => {
+                let outcome = e.outcome();
+                self.fetcher.record_outcome(outcome).await;
+                let result = match outcome {
+                    TaskOutcome::Timeout => TaskResult::Timeout,
+                    TaskOutcome::OutOfMemory => TaskResult::OutOfMemory,
+                    _ => TaskResult::ProofFailed,
+                };
+                record.finish(result, Some(e.to_string()));
+                save_record(self.history.as_ref(), &self.event_sender, &record).await;
+                // Let the orchestrator reassign it now rather than once it expires
+                self.abandoner
+                    .abandon(&task.task_id, e.abandon_reason(), &e.to_string())
+                    .await;
                 // Send state change back to Waiting on proof failure
                 self.event_sender
                     .send_event(Event::state_change(
@@ -131,36 +580,190 @@ impl AuthenticatedWorker {
                         "Proof generation failed, ready for next task".to_string(),
                     ))
                     .await;
-                return false; // Don't exit on proof error, just retry
+                return self.count_failure().await; // Don't exit on proof error, just retry
             }
         };
 
+        // Hashes from a batch wait for the rest of it, to be submitted in one request
+        let max_batch = self.fetcher.batch_limits().max_submissions_per_batch as usize;
+        let in_batch = self.fetcher.has_queued() || !self.deferred.is_empty();
+        if max_batch > 1 && in_batch && self.submitter.can_batch(&task, &proof_result) {
+            let task_id = task.task_id.clone();
+            self.deferred.push(DeferredSubmission {
+                task,
+                proof_result,
+                record,
+                difficulty: self.fetcher.assigned_difficulty(),
+                duration: start_time.elapsed(),
+            });
+            if self.deferred.len() >= max_batch || !self.fetcher.has_queued() {
+                return self.submit_deferred().await;
+            }
+            self.event_sender
+                .send_event(Event::state_change(
+                    ProverState::Waiting,
+                    format!(
+                        "Proof for task {} will be submitted with the rest of its batch",
+                        task_id
+                    ),
+                ))
+                .await;
+            return None;
+        }
+
         // Step 3: Submit proof
         let submission_result = self.submitter.submit_proof(&task, &proof_result).await;
+        let fatal = submission_result
+            .as_ref()
+            .err()
+            .and_then(SubmitError::fatal_reason);
+        let difficulty = self.fetcher.assigned_difficulty();
+        let exit = self
+            .finish_task(
+                &task,
+                &proof_result,
+                record,
+                submission_result.map_err(|e| e.to_string()),
+                difficulty,
+                start_time.elapsed(),
+            )
+            .await;
+        match fatal {
+            Some(reason) => Some(self.halt(reason).await),
+            None => exit,
+        }
+    }
+
+    /// Submit the proofs held back for a batch, and finish each of their tasks
+    async fn submit_deferred(&mut self) -> Option<WorkerExit> {
+        let deferred = std::mem::take(&mut self.deferred);
+        let mut fatal = None;
+        let results: Vec<Result<(), String>> = match &deferred[..] {
+            [] => return None,
+            // A batch of one is just a submission
+            [single] => vec![
+                self.submitter
+                    .submit_proof(&single.task, &single.proof_result)
+                    .await
+                    .map_err(|e| {
+                        fatal = e.fatal_reason();
+                        e.to_string()
+                    }),
+            ],
+            _ => {
+                let batch: Vec<_> = deferred
+                    .iter()
+                    .map(|deferred| (&deferred.task, &deferred.proof_result))
+                    .collect();
+                match self.submitter.submit_batch(&batch).await {
+                    Ok(rejected) => deferred
+                        .iter()
+                        .map(|deferred| match rejected.get(&deferred.task.task_id) {
+                            Some(reason) => Err(format!("Rejected from batch: {}", reason)),
+                            None => Ok(()),
+                        })
+                        .collect(),
+                    Err(e) => {
+                        fatal = e.fatal_reason();
+                        vec![Err(e.to_string()); deferred.len()]
+                    }
+                }
+            }
+        };
+
+        // Every task is recorded, even once the worker has a reason to exit
+        let mut exit = None;
+        for (deferred, result) in deferred.into_iter().zip(results) {
+            let finished = self
+                .finish_task(
+                    &deferred.task,
+                    &deferred.proof_result,
+                    deferred.record,
+                    result,
+                    deferred.difficulty,
+                    deferred.duration,
+                )
+                .await;
+            exit = exit.or(finished);
+        }
+        match fatal {
+            Some(reason) => Some(self.halt(reason).await),
+            None => exit,
+        }
+    }
+
+    /// Give up on proofs held back for a batch, without submitting them.
+    /// Returns the IDs of their tasks, for the orchestrator to be told.
+    async fn drop_deferred(&mut self) -> Vec<String> {
+        let mut task_ids = Vec::new();
+        for deferred in std::mem::take(&mut self.deferred) {
+            let mut record = deferred.record;
+            record.finish(TaskResult::Abandoned, Some(UNSUBMITTED.to_string()));
+            save_record(self.history.as_ref(), &self.event_sender, &record).await;
+            task_ids.push(deferred.task.task_id);
+        }
+        task_ids
+    }
+
+    /// Record how a proved task's submission went.
+    /// Returns how the worker should exit, if it should (max tasks or failures reached)
+    async fn finish_task(
+        &mut self,
+        task: &Task,
+        proof_result: &ProverResult,
+        mut record: TaskRecord,
+        submission_result: Result<(), String>,
+        difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+        duration: Duration,
+    ) -> Option<WorkerExit> {
+        record.set_input_durations(&proof_result.input_durations);
+        record.energy_joules = proof_result.energy_joules;
+        record.submission_attempts = self.submitter.last_attempts;
+        match &submission_result {
+            Ok(()) if self.submitter.is_dry_run() => record.finish(TaskResult::DryRun, None),
+            Ok(()) => record.finish(TaskResult::Submitted, None),
+            Err(e) => record.finish(TaskResult::SubmitFailed, Some(e.clone())),
+        }
+        save_record(self.history.as_ref(), &self.event_sender, &record).await;
 
         // Only increment task counter on successful submission
         if submission_result.is_ok() {
             self.tasks_completed += 1;
+            self.consecutive_failures = 0;
 
             // Update success tracking for difficulty promotion
-            let duration_secs = start_time.elapsed().as_secs();
-            self.fetcher.update_success_tracking(duration_secs);
+            if let Some(difficulty) = difficulty {
+                self.fetcher
+                    .record_outcome_at(difficulty, TaskOutcome::Success { duration })
+                    .await;
+            }
 
-            // Send information about completing the task
+            // Send information about completing the task, with its energy if measured
+            let energy = proof_result
+                .energy_joules
+                .map(|joules| self.energy.measure(joules));
+            let difficulty = self.fetcher.last_success_difficulty;
             self.event_sender
-                .send_event(Event::state_change(
-                    ProverState::Waiting,
-                    format!(
-                        "{} completed, Task size: {}, Duration: {}s, Difficulty: {}",
-                        task.task_id,
-                        task.public_inputs_list.len(),
-                        self.fetcher.last_success_duration_secs.unwrap_or(0),
-                        self.fetcher
-                            .last_success_difficulty
-                            .map(|difficulty| difficulty.as_str_name())
-                            .unwrap_or("Unknown")
-                    ),
-                ))
+                .send_event(
+                    Event::state_change(
+                        ProverState::Waiting,
+                        format!(
+                            "{} completed, Task size: {}, Duration: {}s, Difficulty: {}{}",
+                            task.task_id,
+                            task.public_inputs_list.len(),
+                            self.fetcher.last_success_duration_secs.unwrap_or(0),
+                            difficulty
+                                .map(|difficulty| difficulty.as_str_name())
+                                .unwrap_or("Unknown"),
+                            energy
+                                .as_ref()
+                                .map(|energy| format!(", {}", energy))
+                                .unwrap_or_default()
+                        ),
+                    )
+                    .with_lifecycle(Lifecycle::TaskCompleted(difficulty), Some(&task.task_id))
+                    .with_energy(energy),
+                )
                 .await;
             // Check if we've reached the maximum number of tasks
             if let Some(max) = self.max_tasks {
@@ -176,11 +779,12 @@ impl AuthenticatedWorker {
                         ))
                         .await;
 
-                    // Send shutdown signal to trigger application exit
-                    let _ = self.shutdown_sender.send(());
-                    return true; // Signal to exit the worker loop
+                    // Exiting the worker loop triggers application exit
+                    return Some(WorkerExit::Completed);
                 }
             }
+        } else if let Some(exit) = self.count_failure().await {
+            return Some(exit);
         }
 
         // Send state change back to Waiting at the end of the work cycle
@@ -191,6 +795,103 @@ impl AuthenticatedWorker {
             ))
             .await;
 
-        false // Continue with more tasks
+        None // Continue with more tasks
+    }
+
+    /// Count a failed task; returns the exit once `--max-consecutive-failures` is reached
+    async fn count_failure(&mut self) -> Option<WorkerExit> {
+        self.consecutive_failures += 1;
+        let max = self.max_consecutive_failures?;
+        if self.consecutive_failures < max {
+            return None;
+        }
+
+        self.event_sender
+            .send_event(Event::state_change(
+                ProverState::Waiting,
+                format!(
+                    "{} tasks failed in a row, shutting down",
+                    self.consecutive_failures
+                ),
+            ))
+            .await;
+        Some(WorkerExit::FailureLimit {
+            failures: self.consecutive_failures,
+        })
+    }
+}
+
+/// Append a finished task to the history, warning if it can't be written
+async fn save_record(
+    history: Option<&HistoryStore>,
+    event_sender: &EventSender,
+    record: &TaskRecord,
+) {
+    let Some(history) = history else {
+        return;
+    };
+    if let Err(e) = history.append(record) {
+        event_sender
+            .send_task_event(
+                format!("Failed to record task {} in history: {}", record.task_id, e),
+                EventType::Error,
+                LogLevel::Warn,
+            )
+            .await;
+    }
+}
+
+/// Resolves once a pause or suspension is requested; never, if the sender is gone.
+async fn wait_for_pause(run_state: &mut watch::Receiver<RunState>) {
+    if run_state
+        .wait_for(|state| *state != RunState::Running)
+        .await
+        .is_err()
+    {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Resolves once a throttle asks to stop fetching; never, if the sender is gone.
+async fn wait_for_throttle_pause(throttle: &mut watch::Receiver<Throttle>) {
+    if throttle.wait_for(|throttle| throttle.paused).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Apply a pause/resume request to the task that is currently in flight.
+async fn apply_run_state(state: RunState, event_sender: &EventSender, task_id: &str) {
+    match state {
+        RunState::Suspended => {
+            processes::suspend_all();
+            event_sender
+                .send_event(Event::state_change(
+                    ProverState::Paused,
+                    format!("Paused: suspended task {}", task_id),
+                ))
+                .await;
+        }
+        RunState::Paused => {
+            if processes::is_suspended() {
+                processes::resume_all();
+            }
+            event_sender
+                .send_event(Event::state_change(
+                    ProverState::Proving,
+                    format!("Pause requested: finishing task {} before pausing", task_id),
+                ))
+                .await;
+        }
+        RunState::Running => {
+            if processes::is_suspended() {
+                processes::resume_all();
+            }
+            event_sender
+                .send_event(Event::state_change(
+                    ProverState::Proving,
+                    format!("Resumed task {}", task_id),
+                ))
+                .await;
+        }
     }
 }
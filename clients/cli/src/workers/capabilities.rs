@@ -0,0 +1,119 @@
+//! What this client can run, sent with every task request
+//!
+//! Lets the orchestrator route tasks by the programs and task types the client
+//! supports, the threads and memory it has, and how fast it has been at each
+//! difficulty. Orchestrators that predate it ignore the field.
+
+use super::difficulty::DifficultyHistory;
+use crate::nexus_orchestrator::{
+    ClientCapabilities, DifficultyThroughput, TaskDifficulty, TaskType,
+};
+use crate::prover::pipeline::PROGRAM_IDS;
+use crate::system::available_memory_mb;
+
+/// Task types the client can complete
+const TASK_TYPES: [TaskType; 3] = [
+    TaskType::ProofRequired,
+    TaskType::ProofHash,
+    TaskType::AllProofHashes,
+];
+
+/// Capabilities of a client proving with `thread_budget` threads, whose recent
+/// task durations are in `history`
+pub fn client_capabilities(
+    thread_budget: usize,
+    history: &DifficultyHistory,
+) -> ClientCapabilities {
+    ClientCapabilities {
+        cli_version: env!("CARGO_PKG_VERSION").to_string(),
+        program_ids: PROGRAM_IDS.iter().map(|id| id.to_string()).collect(),
+        task_types: TASK_TYPES
+            .iter()
+            .map(|task_type| *task_type as i32)
+            .collect(),
+        thread_budget: thread_budget as u32,
+        available_memory_mb: available_memory_mb(),
+        throughput: throughput(history),
+    }
+}
+
+/// Mean task duration per difficulty, from the durations the difficulty policy keeps
+fn throughput(history: &DifficultyHistory) -> Vec<DifficultyThroughput> {
+    history
+        .durations_secs
+        .iter()
+        .filter_map(|(name, durations)| {
+            let difficulty = TaskDifficulty::from_str_name(name)?;
+            let samples = durations.len() as u64;
+            (samples > 0).then(|| DifficultyThroughput {
+                difficulty: difficulty as i32,
+                mean_task_secs: durations.iter().sum::<u64>() / samples,
+                samples: samples as u32,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nexus_orchestrator::{GetProofTaskRequest, NodeType};
+    use prost::Message;
+
+    #[test]
+    fn test_capabilities() {
+        let mut history = DifficultyHistory::default();
+        history
+            .durations_secs
+            .insert("LARGE".to_string(), vec![100, 200, 300]);
+        history
+            .durations_secs
+            .insert("SMALL".to_string(), Vec::new());
+        history
+            .durations_secs
+            .insert("UNKNOWN".to_string(), vec![1]);
+
+        let capabilities = client_capabilities(4, &history);
+        assert_eq!(capabilities.cli_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(capabilities.program_ids, ["fib_input_initial"]);
+        assert_eq!(capabilities.task_types.len(), 3);
+        assert_eq!(capabilities.thread_budget, 4);
+        assert!(capabilities.available_memory_mb > 0);
+        assert_eq!(
+            capabilities.throughput,
+            [DifficultyThroughput {
+                difficulty: TaskDifficulty::Large as i32,
+                mean_task_secs: 200,
+                samples: 3,
+            }]
+        );
+    }
+
+    /// `GetProofTaskRequest` as orchestrators without capabilities know it
+    #[derive(Clone, PartialEq, Message)]
+    struct LegacyGetProofTaskRequest {
+        #[prost(string, tag = "1")]
+        node_id: String,
+        #[prost(enumeration = "NodeType", tag = "2")]
+        node_type: i32,
+        #[prost(bytes = "vec", tag = "3")]
+        ed25519_public_key: Vec<u8>,
+        #[prost(enumeration = "TaskDifficulty", tag = "4")]
+        max_difficulty: i32,
+    }
+
+    #[test]
+    fn test_older_orchestrators_ignore_capabilities() {
+        let request = GetProofTaskRequest {
+            node_id: "42".to_string(),
+            node_type: NodeType::CliProver as i32,
+            ed25519_public_key: vec![1; 32],
+            max_difficulty: TaskDifficulty::Large as i32,
+            capabilities: Some(client_capabilities(2, &DifficultyHistory::default())),
+        };
+        let legacy = LegacyGetProofTaskRequest::decode(&request.encode_to_vec()[..]).unwrap();
+        assert_eq!(legacy.node_id, "42");
+        assert_eq!(legacy.ed25519_public_key, vec![1; 32]);
+        assert_eq!(legacy.max_difficulty, TaskDifficulty::Large as i32);
+    }
+}
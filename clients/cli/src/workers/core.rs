@@ -1,8 +1,11 @@
 //! Core worker utilities and traits
 
+use crate::consts::cli_consts::shutdown;
 use crate::events::{Event, EventType};
 use crate::logging::LogLevel;
-use tokio::sync::mpsc;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
 
 /// Common event sending utilities for workers
 #[derive(Clone)]
@@ -70,7 +73,27 @@ pub struct WorkerConfig {
     pub environment: crate::environment::Environment,
     pub client_id: String,
     pub max_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    /// Floor for adaptive difficulty
+    pub min_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    /// Where adaptive difficulty state is persisted, if anywhere
+    pub difficulty_history: Option<PathBuf>,
+    /// Where every task is recorded, if anywhere
+    pub task_history: Option<PathBuf>,
+    /// Where the inputs submitted one by one are recorded, if anywhere
+    pub input_ledger: Option<PathBuf>,
     pub num_workers: usize,
+    /// How long a drain may wait for the in-flight task before abandoning it
+    pub drain_timeout: Duration,
+    /// Stop after this many tasks in a row fail to prove or submit
+    pub max_consecutive_failures: Option<u32>,
+    /// Where energy is measured and how it is priced
+    pub energy: crate::prover::energy::EnergyConfig,
+    /// systemd watchdog to ping while the worker makes progress, if enabled
+    pub watchdog: Option<crate::systemd::Watchdog>,
+    /// Where proofs are kept instead of being submitted, in a dry run
+    pub dry_run: Option<PathBuf>,
+    /// What to do at startup with tasks already assigned to the node
+    pub resume_assigned: ResumeAssigned,
 }
 
 impl WorkerConfig {
@@ -79,7 +102,292 @@ impl WorkerConfig {
             environment,
             client_id,
             max_difficulty: None,
+            min_difficulty: None,
+            difficulty_history: None,
+            task_history: None,
+            input_ledger: None,
             num_workers: 1,
+            drain_timeout: shutdown::drain_timeout(),
+            max_consecutive_failures: None,
+            energy: Default::default(),
+            watchdog: None,
+            dry_run: None,
+            resume_assigned: ResumeAssigned::default(),
         }
     }
 }
+
+/// What to do at startup with tasks the orchestrator assigned to the node before a
+/// restart, and that were never submitted
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum ResumeAssigned {
+    /// Prove and submit them before requesting new tasks
+    #[default]
+    Auto,
+    /// Only report them, so they can be resumed on the next start
+    Offer,
+    /// Don't look for them
+    Off,
+}
+
+/// Settings that can be changed while the worker runs; they apply from the next task
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LiveSettings {
+    pub max_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    pub num_workers: usize,
+}
+
+/// Limits imposed by the host (load, temperature, power); they apply from the next task
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Throttle {
+    /// Cap on proving threads, below the configured count
+    pub max_workers: Option<usize>,
+    /// Stop fetching new tasks until the throttle lifts
+    pub paused: bool,
+    /// Why the throttle is in effect, for display
+    pub reason: Option<String>,
+}
+
+impl Throttle {
+    pub fn is_active(&self) -> bool {
+        self.paused || self.max_workers.is_some()
+    }
+
+    /// Threads to prove with, given the configured count
+    pub fn workers(&self, configured: usize) -> usize {
+        self.max_workers
+            .map_or(configured, |max| configured.min(max))
+            .max(1)
+    }
+}
+
+/// How the worker should stop when a shutdown is requested
+#[derive(Debug, Copy, Clone, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownMode {
+    /// Finish and submit the in-flight task within the drain timeout, then exit
+    Drain,
+    /// Kill prover subprocesses and exit as soon as possible
+    Immediate,
+}
+
+impl ShutdownMode {
+    /// The mode to request given the one already in effect: the first request
+    /// drains, any further request escalates to an immediate shutdown.
+    pub fn escalate(current: Option<ShutdownMode>) -> ShutdownMode {
+        match current {
+            None => ShutdownMode::Drain,
+            Some(_) => ShutdownMode::Immediate,
+        }
+    }
+}
+
+/// Whether the worker should be taking on work, set by pause/resume requests
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// Fetch and prove tasks as usual
+    #[default]
+    Running,
+    /// Stop fetching new tasks, but let the current one finish and submit
+    Paused,
+    /// Stop fetching new tasks and suspend the current task's prover subprocesses
+    Suspended,
+}
+
+/// The channels a session steers the worker with, and hears back from it on
+pub struct WorkerChannels {
+    pub shutdown: broadcast::Receiver<ShutdownMode>,
+    pub run_state: watch::Receiver<RunState>,
+    pub settings: watch::Receiver<LiveSettings>,
+    pub throttle: watch::Receiver<Throttle>,
+    pub events: mpsc::Sender<Event>,
+}
+
+/// What the worker left behind when it exited
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum WorkerExit {
+    /// No task was in flight, or the in-flight task ran to completion
+    Completed,
+    /// The in-flight task was abandoned before it could be submitted
+    Abandoned { task_id: String },
+    /// Too many tasks in a row failed
+    FailureLimit { failures: u32 },
+    /// The orchestrator refused the node in a way retrying can't fix
+    Fatal { reason: String },
+}
+
+impl std::fmt::Display for WorkerExit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkerExit::Completed => write!(f, "completed"),
+            WorkerExit::Abandoned { task_id } => write!(f, "abandoned task {}", task_id),
+            WorkerExit::FailureLimit { failures } => {
+                write!(f, "{} tasks failed in a row", failures)
+            }
+            WorkerExit::Fatal { reason } => write!(f, "stopped: {}", reason),
+        }
+    }
+}
+
+impl WorkerExit {
+    /// Process exit code that reflects this outcome
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            WorkerExit::Completed => 0,
+            WorkerExit::Abandoned { .. } => shutdown::ABANDONED_TASK_EXIT_CODE,
+            WorkerExit::FailureLimit { .. } => shutdown::FAILURE_LIMIT_EXIT_CODE,
+            WorkerExit::Fatal { .. } => shutdown::FATAL_ERROR_EXIT_CODE,
+        }
+    }
+
+    /// Combine the exits of several workers, keeping the first fatal error, else the
+    /// first abandoned task, else the first failure limit
+    pub fn merge(exits: impl IntoIterator<Item = WorkerExit>) -> WorkerExit {
+        exits
+            .into_iter()
+            .min_by_key(|exit| match exit {
+                WorkerExit::Fatal { .. } => 0,
+                WorkerExit::Abandoned { .. } => 1,
+                WorkerExit::FailureLimit { .. } => 2,
+                WorkerExit::Completed => 3,
+            })
+            .unwrap_or(WorkerExit::Completed)
+    }
+}
+
+/// Wait for the next shutdown request.
+/// A closed channel means the session is gone, so it is treated as an immediate shutdown.
+pub async fn recv_shutdown(receiver: &mut broadcast::Receiver<ShutdownMode>) -> ShutdownMode {
+    loop {
+        match receiver.recv().await {
+            Ok(mode) => return mode,
+            // Missed requests are superseded by whatever was sent last
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return ShutdownMode::Immediate,
+        }
+    }
+}
+
+/// Whether the shutdown requests `requests` saw since it subscribed stop the worker right
+/// away: an immediate one, a second one cutting a drain short, or the session going away
+pub fn stopped_immediately(requests: &mut broadcast::Receiver<ShutdownMode>) -> bool {
+    let mut drains = 0;
+    loop {
+        match requests.try_recv() {
+            Ok(ShutdownMode::Immediate) => return true,
+            Ok(ShutdownMode::Drain) => drains += 1,
+            // Only a burst of requests can overflow the channel
+            Err(broadcast::error::TryRecvError::Lagged(_)) => return true,
+            Err(broadcast::error::TryRecvError::Closed) => return true,
+            Err(broadcast::error::TryRecvError::Empty) => return drains > 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stopped_immediately() {
+        let (sender, _) = broadcast::channel(4);
+        let requests = || sender.subscribe();
+
+        let mut none = requests();
+        assert!(!stopped_immediately(&mut none));
+
+        let mut drain = requests();
+        sender.send(ShutdownMode::Drain).unwrap();
+        assert!(!stopped_immediately(&mut drain));
+
+        let mut escalated = requests();
+        sender.send(ShutdownMode::Drain).unwrap();
+        sender.send(ShutdownMode::Drain).unwrap();
+        assert!(stopped_immediately(&mut escalated));
+
+        let mut immediate = requests();
+        sender.send(ShutdownMode::Immediate).unwrap();
+        assert!(stopped_immediately(&mut immediate));
+
+        let mut closed = requests();
+        drop(sender);
+        assert!(stopped_immediately(&mut closed));
+    }
+
+    #[test]
+    fn test_second_request_escalates_to_immediate() {
+        assert_eq!(ShutdownMode::escalate(None), ShutdownMode::Drain);
+        assert_eq!(
+            ShutdownMode::escalate(Some(ShutdownMode::Drain)),
+            ShutdownMode::Immediate
+        );
+        assert_eq!(
+            ShutdownMode::escalate(Some(ShutdownMode::Immediate)),
+            ShutdownMode::Immediate
+        );
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(WorkerExit::Completed.exit_code(), 0);
+        assert_eq!(
+            WorkerExit::Abandoned {
+                task_id: "task".to_string()
+            }
+            .exit_code(),
+            shutdown::ABANDONED_TASK_EXIT_CODE
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_abandoned_task() {
+        let merged = WorkerExit::merge(vec![
+            WorkerExit::Completed,
+            WorkerExit::Abandoned {
+                task_id: "task".to_string(),
+            },
+        ]);
+        assert_eq!(
+            merged,
+            WorkerExit::Abandoned {
+                task_id: "task".to_string()
+            }
+        );
+        assert_eq!(WorkerExit::merge(Vec::new()), WorkerExit::Completed);
+
+        let merged = WorkerExit::merge(vec![
+            WorkerExit::FailureLimit { failures: 3 },
+            WorkerExit::Completed,
+        ]);
+        assert_eq!(merged, WorkerExit::FailureLimit { failures: 3 });
+
+        // Nothing else matters once the node has to be fixed by hand
+        let fatal = WorkerExit::Fatal {
+            reason: "upgrade".to_string(),
+        };
+        let merged = WorkerExit::merge(vec![
+            WorkerExit::Abandoned {
+                task_id: "task".to_string(),
+            },
+            fatal.clone(),
+        ]);
+        assert_eq!(merged, fatal);
+        assert_eq!(merged.exit_code(), shutdown::FATAL_ERROR_EXIT_CODE);
+    }
+
+    #[tokio::test]
+    async fn test_recv_shutdown_returns_latest_mode_after_lag() {
+        let (sender, mut receiver) = broadcast::channel(1);
+        sender.send(ShutdownMode::Drain).unwrap();
+        sender.send(ShutdownMode::Immediate).unwrap();
+        assert_eq!(recv_shutdown(&mut receiver).await, ShutdownMode::Immediate);
+    }
+
+    #[tokio::test]
+    async fn test_recv_shutdown_treats_closed_channel_as_immediate() {
+        let (sender, mut receiver) = broadcast::channel::<ShutdownMode>(1);
+        drop(sender);
+        assert_eq!(recv_shutdown(&mut receiver).await, ShutdownMode::Immediate);
+    }
+}
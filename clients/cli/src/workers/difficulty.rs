@@ -0,0 +1,438 @@
+//! Difficulty selection
+//!
+//! A [`DifficultyPolicy`] picks the difficulty to request for the next task from
+//! the outcomes of previous ones. [`AdaptivePolicy`] is the default. Its state is
+//! kept per node in ~/.nexus/difficulty.json so a restart resumes where the last
+//! session left off instead of climbing the ladder again.
+
+use crate::consts::cli_consts::difficulty;
+use crate::nexus_orchestrator::TaskDifficulty;
+use crate::task::Task;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Difficulty levels from easiest to hardest
+pub const LEVELS: [TaskDifficulty; 9] = [
+    TaskDifficulty::Small,
+    TaskDifficulty::SmallMedium,
+    TaskDifficulty::Medium,
+    TaskDifficulty::Large,
+    TaskDifficulty::ExtraLarge,
+    TaskDifficulty::ExtraLarge2,
+    TaskDifficulty::ExtraLarge3,
+    TaskDifficulty::ExtraLarge4,
+    TaskDifficulty::ExtraLarge5,
+];
+
+/// Difficulty requested when there is nothing to go on
+pub const DEFAULT_DIFFICULTY: TaskDifficulty = TaskDifficulty::SmallMedium;
+
+fn level(difficulty: TaskDifficulty) -> usize {
+    LEVELS
+        .iter()
+        .position(|level| *level == difficulty)
+        .unwrap_or(0)
+}
+
+fn promote(difficulty: TaskDifficulty) -> TaskDifficulty {
+    LEVELS[(level(difficulty) + 1).min(LEVELS.len() - 1)]
+}
+
+fn demote(difficulty: TaskDifficulty) -> TaskDifficulty {
+    LEVELS[level(difficulty).saturating_sub(1)]
+}
+
+/// The harder of two difficulties
+pub fn harder(a: TaskDifficulty, b: TaskDifficulty) -> TaskDifficulty {
+    if level(a) >= level(b) { a } else { b }
+}
+
+/// How a task at a given difficulty ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskOutcome {
+    /// Proved and submitted in `duration`
+    Success { duration: Duration },
+    /// Proving ran past the proving timeout
+    Timeout,
+    /// A prover subprocess was killed, most likely by the OOM killer
+    OutOfMemory,
+    /// Proving failed for any other reason
+    Failed,
+}
+
+impl std::fmt::Display for TaskOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskOutcome::Success { duration } => write!(f, "task took {}s", duration.as_secs()),
+            TaskOutcome::Timeout => write!(f, "proving timed out"),
+            TaskOutcome::OutOfMemory => write!(f, "prover ran out of memory"),
+            TaskOutcome::Failed => write!(f, "repeated proof failures"),
+        }
+    }
+}
+
+/// Picks the difficulty to request for each task
+pub trait DifficultyPolicy: Send + Sync {
+    /// Difficulty to request for the next task
+    fn next_difficulty(&self) -> TaskDifficulty;
+
+    /// Learn from a task that ran at `difficulty`, as assigned by the server
+    fn record(&mut self, difficulty: TaskDifficulty, outcome: TaskOutcome);
+
+    /// State to persist so the next session can resume from it
+    fn history(&self) -> DifficultyHistory;
+}
+
+/// Persisted difficulty state of one node
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DifficultyHistory {
+    /// Difficulty the policy will request next
+    #[serde(default)]
+    pub next: Option<String>,
+    /// Difficulty of the last successfully completed task
+    #[serde(default)]
+    pub last_success: Option<String>,
+    /// Fast tasks still required before promoting again
+    #[serde(default)]
+    pub promotion_cooldown: u32,
+    /// Recent successful task durations per difficulty, oldest first
+    #[serde(default)]
+    pub durations_secs: BTreeMap<String, Vec<u64>>,
+}
+
+impl DifficultyHistory {
+    /// Mean duration of the recent successful tasks at `difficulty`, if there were any
+    pub fn expected_duration(&self, difficulty: TaskDifficulty) -> Option<Duration> {
+        let durations = self.durations_secs.get(difficulty.as_str_name())?;
+        let samples = durations.len() as u64;
+        (samples > 0).then(|| Duration::from_secs(durations.iter().sum::<u64>() / samples))
+    }
+
+    fn record_duration(&mut self, difficulty: TaskDifficulty, duration: Duration) {
+        let durations = self
+            .durations_secs
+            .entry(difficulty.as_str_name().to_string())
+            .or_default();
+        durations.push(duration.as_secs());
+        if durations.len() > difficulty::DURATION_HISTORY_LEN {
+            let excess = durations.len() - difficulty::DURATION_HISTORY_LEN;
+            durations.drain(..excess);
+        }
+    }
+}
+
+/// Why `task` can't be finished before its deadline at `now`, if it can't: the
+/// deadline has passed, or less time is left than tasks at its difficulty have
+/// recently taken. Tasks without a deadline, or at a difficulty this node has no
+/// history at yet, are always attempted.
+pub fn unreachable_deadline(
+    task: &Task,
+    history: &DifficultyHistory,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    let left = task.time_to_deadline(now)?;
+    if left.is_zero() {
+        return Some("its deadline has passed".to_string());
+    }
+    let expected = history.expected_duration(task.difficulty)?;
+    (expected > left).then(|| {
+        format!(
+            "{} tasks take {}s here, but only {}s are left before its deadline",
+            task.difficulty.as_str_name(),
+            expected.as_secs(),
+            left.as_secs()
+        )
+    })
+}
+
+/// Default policy: climb one level after each fast task, step down after slow
+/// tasks, timeouts, OOM kills or repeated failures, and never go below the
+/// configured minimum.
+///
+/// Promotion and demotion thresholds are far apart, and after a demotion a few
+/// fast tasks are required before climbing again, so a node near its limit
+/// settles instead of oscillating between two levels.
+#[derive(Debug, Clone)]
+pub struct AdaptivePolicy {
+    min_difficulty: TaskDifficulty,
+    next: TaskDifficulty,
+    failure_streak: u32,
+    history: DifficultyHistory,
+}
+
+impl AdaptivePolicy {
+    /// Resume from a previous session's history
+    pub fn from_history(
+        min_difficulty: Option<TaskDifficulty>,
+        history: DifficultyHistory,
+    ) -> Self {
+        let min_difficulty = min_difficulty.unwrap_or(LEVELS[0]);
+        let next = history
+            .next
+            .as_deref()
+            .and_then(TaskDifficulty::from_str_name)
+            .unwrap_or(DEFAULT_DIFFICULTY);
+        Self {
+            min_difficulty,
+            next: harder(next, min_difficulty),
+            failure_streak: 0,
+            history,
+        }
+    }
+
+    fn demote_from(&mut self, difficulty: TaskDifficulty) -> TaskDifficulty {
+        self.history.promotion_cooldown = difficulty::PROMOTION_COOLDOWN_TASKS;
+        demote(difficulty)
+    }
+}
+
+impl DifficultyPolicy for AdaptivePolicy {
+    fn next_difficulty(&self) -> TaskDifficulty {
+        self.next
+    }
+
+    fn record(&mut self, difficulty: TaskDifficulty, outcome: TaskOutcome) {
+        let next = match outcome {
+            TaskOutcome::Success { duration } => {
+                self.failure_streak = 0;
+                self.history.last_success = Some(difficulty.as_str_name().to_string());
+                self.history.record_duration(difficulty, duration);
+
+                let secs = duration.as_secs();
+                if secs >= difficulty::DEMOTION_THRESHOLD_SECS {
+                    self.demote_from(difficulty)
+                } else if secs >= difficulty::PROMOTION_THRESHOLD_SECS {
+                    difficulty
+                } else if self.history.promotion_cooldown > 0 {
+                    self.history.promotion_cooldown -= 1;
+                    difficulty
+                } else {
+                    promote(difficulty)
+                }
+            }
+            TaskOutcome::Timeout | TaskOutcome::OutOfMemory => {
+                self.failure_streak = 0;
+                self.demote_from(difficulty)
+            }
+            TaskOutcome::Failed => {
+                self.failure_streak += 1;
+                if self.failure_streak >= difficulty::FAILURES_BEFORE_DEMOTION {
+                    self.failure_streak = 0;
+                    self.demote_from(difficulty)
+                } else {
+                    self.next
+                }
+            }
+        };
+
+        self.next = harder(next, self.min_difficulty);
+        self.history.next = Some(self.next.as_str_name().to_string());
+    }
+
+    fn history(&self) -> DifficultyHistory {
+        self.history.clone()
+    }
+}
+
+/// Difficulty history of every node run from this machine, keyed by node ID
+pub struct DifficultyStore {
+    path: PathBuf,
+    node_id: u64,
+}
+
+impl DifficultyStore {
+    pub fn new(path: PathBuf, node_id: u64) -> Self {
+        Self { path, node_id }
+    }
+
+    /// Default store location, next to the config file in ~/.nexus
+    pub fn default_path() -> Result<PathBuf, std::io::Error> {
+        Ok(crate::config::get_config_path()?.with_file_name(difficulty::HISTORY_FILE_NAME))
+    }
+
+    fn read_all(&self) -> BTreeMap<String, DifficultyHistory> {
+        fs::read(&self.path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// This node's history; empty if there is none or the file is unreadable
+    pub fn load(&self) -> DifficultyHistory {
+        self.read_all()
+            .remove(&self.node_id.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Replace this node's history, leaving other nodes' entries alone
+    pub fn save(&self, history: &DifficultyHistory) -> Result<(), std::io::Error> {
+        let mut all = self.read_all();
+        all.insert(self.node_id.to_string(), history.clone());
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write then rename, so a crash never leaves a truncated file behind
+        let tmp = self.path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&all)?)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_policy(min_difficulty: Option<TaskDifficulty>) -> AdaptivePolicy {
+        AdaptivePolicy::from_history(min_difficulty, DifficultyHistory::default())
+    }
+
+    fn success(secs: u64) -> TaskOutcome {
+        TaskOutcome::Success {
+            duration: Duration::from_secs(secs),
+        }
+    }
+
+    #[test]
+    fn test_starts_at_default_or_minimum() {
+        assert_eq!(
+            new_policy(None).next_difficulty(),
+            TaskDifficulty::SmallMedium
+        );
+        assert_eq!(
+            new_policy(Some(TaskDifficulty::Large)).next_difficulty(),
+            TaskDifficulty::Large
+        );
+    }
+
+    #[test]
+    fn test_slow_task_demotes_and_holds_band_keeps_level() {
+        let mut policy = new_policy(None);
+
+        policy.record(TaskDifficulty::Large, success(10 * 60));
+        assert_eq!(policy.next_difficulty(), TaskDifficulty::Large);
+
+        policy.record(TaskDifficulty::Large, success(25 * 60));
+        assert_eq!(policy.next_difficulty(), TaskDifficulty::Medium);
+    }
+
+    #[test]
+    fn test_oom_and_timeout_demote_immediately() {
+        let mut policy = new_policy(None);
+
+        policy.record(TaskDifficulty::ExtraLarge, TaskOutcome::OutOfMemory);
+        assert_eq!(policy.next_difficulty(), TaskDifficulty::Large);
+
+        policy.record(TaskDifficulty::Large, TaskOutcome::Timeout);
+        assert_eq!(policy.next_difficulty(), TaskDifficulty::Medium);
+    }
+
+    #[test]
+    fn test_repeated_failures_demote() {
+        let mut policy = new_policy(Some(TaskDifficulty::Medium));
+        policy.record(TaskDifficulty::Large, success(8 * 60));
+
+        for _ in 1..difficulty::FAILURES_BEFORE_DEMOTION {
+            policy.record(TaskDifficulty::Large, TaskOutcome::Failed);
+            assert_eq!(policy.next_difficulty(), TaskDifficulty::Large);
+        }
+        policy.record(TaskDifficulty::Large, TaskOutcome::Failed);
+        assert_eq!(policy.next_difficulty(), TaskDifficulty::Medium);
+
+        // Never below the minimum
+        policy.record(TaskDifficulty::Medium, TaskOutcome::OutOfMemory);
+        assert_eq!(policy.next_difficulty(), TaskDifficulty::Medium);
+    }
+
+    #[test]
+    fn test_cooldown_after_demotion() {
+        let mut policy = new_policy(None);
+        policy.record(TaskDifficulty::Large, TaskOutcome::OutOfMemory);
+
+        for _ in 0..difficulty::PROMOTION_COOLDOWN_TASKS {
+            policy.record(TaskDifficulty::Medium, success(60));
+            assert_eq!(policy.next_difficulty(), TaskDifficulty::Medium);
+        }
+        policy.record(TaskDifficulty::Medium, success(60));
+        assert_eq!(policy.next_difficulty(), TaskDifficulty::Large);
+    }
+
+    #[test]
+    fn test_unreachable_deadline() {
+        let mut history = DifficultyHistory::default();
+        history
+            .durations_secs
+            .insert("LARGE".to_string(), vec![500, 700]);
+        assert_eq!(
+            history.expected_duration(TaskDifficulty::Large),
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(history.expected_duration(TaskDifficulty::Small), None);
+
+        let now = DateTime::from_timestamp(1_750_000_000, 0).unwrap();
+        let mut task = Task::new(
+            "task".to_string(),
+            "fib_input_initial".to_string(),
+            Vec::new(),
+            crate::nexus_orchestrator::TaskType::ProofHash,
+            TaskDifficulty::Large,
+        );
+        assert_eq!(unreachable_deadline(&task, &history, now), None);
+
+        task.deadline = Some(now + chrono::Duration::seconds(900));
+        assert_eq!(unreachable_deadline(&task, &history, now), None);
+
+        task.deadline = Some(now + chrono::Duration::seconds(300));
+        assert_eq!(
+            unreachable_deadline(&task, &history, now).unwrap(),
+            "LARGE tasks take 600s here, but only 300s are left before its deadline"
+        );
+
+        // Without history at the task's difficulty there is nothing to go on but the deadline
+        task.difficulty = TaskDifficulty::Small;
+        assert_eq!(unreachable_deadline(&task, &history, now), None);
+        task.deadline = Some(now - chrono::Duration::seconds(1));
+        assert!(unreachable_deadline(&task, &history, now).is_some());
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("difficulty.json");
+
+        let mut policy = new_policy(None);
+        policy.record(TaskDifficulty::Large, success(60));
+        policy.record(TaskDifficulty::ExtraLarge, TaskOutcome::OutOfMemory);
+
+        let store = DifficultyStore::new(path.clone(), 1);
+        store.save(&policy.history()).unwrap();
+        DifficultyStore::new(path.clone(), 2)
+            .save(&new_policy(None).history())
+            .unwrap();
+
+        let history = DifficultyStore::new(path.clone(), 1).load();
+        assert_eq!(history, policy.history());
+        assert_eq!(history.durations_secs["LARGE"], vec![60]);
+        assert_eq!(
+            history.promotion_cooldown,
+            difficulty::PROMOTION_COOLDOWN_TASKS
+        );
+
+        let resumed = AdaptivePolicy::from_history(None, history);
+        assert_eq!(resumed.next_difficulty(), TaskDifficulty::Large);
+
+        // The minimum still applies to a resumed level
+        let resumed =
+            AdaptivePolicy::from_history(Some(TaskDifficulty::ExtraLarge), policy.history());
+        assert_eq!(resumed.next_difficulty(), TaskDifficulty::ExtraLarge);
+
+        assert_eq!(
+            DifficultyStore::new(dir.path().join("missing.json"), 1).load(),
+            DifficultyHistory::default()
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Local sink for `start --dry-run`
+//!
+//! Proofs are generated and verified as usual, but instead of being submitted
+//! each one is summarised as a JSON line in `~/.nexus/dry-run.jsonl`.
+
+use crate::config::get_config_path;
+use crate::prover::ProverResult;
+use crate::task::Task;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const DRY_RUN_FILE_NAME: &str = "dry-run.jsonl";
+
+/// A proof kept locally instead of being submitted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRunRecord {
+    pub task_id: String,
+    pub program_id: String,
+    pub task_type: String,
+    pub difficulty: String,
+    pub inputs: usize,
+    pub combined_hash: String,
+    pub individual_proof_hashes: Vec<String>,
+    /// Serialized size of each proof, as it would have been submitted
+    pub proof_bytes: Vec<usize>,
+    pub proving_ms: u64,
+    pub proved_at: DateTime<Utc>,
+}
+
+/// Appends dry-run results to a JSON Lines file
+#[derive(Debug, Clone)]
+pub struct DryRunSink {
+    path: PathBuf,
+}
+
+impl DryRunSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// ~/.nexus/dry-run.jsonl
+    pub fn default_path() -> std::io::Result<PathBuf> {
+        Ok(get_config_path()?.with_file_name(DRY_RUN_FILE_NAME))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Summarise a proof that would have been submitted for `task`
+    pub fn record(
+        &self,
+        task: &Task,
+        proof_result: &ProverResult,
+        proofs_bytes: &[Vec<u8>],
+    ) -> std::io::Result<DryRunRecord> {
+        let record = DryRunRecord {
+            task_id: task.task_id.clone(),
+            program_id: task.program_id.clone(),
+            task_type: task.task_type.as_str_name().to_string(),
+            difficulty: task.difficulty.as_str_name().to_string(),
+            inputs: task.all_inputs().len(),
+            combined_hash: proof_result.combined_hash().to_string(),
+            individual_proof_hashes: proof_result.individual_proof_hashes().to_vec(),
+            proof_bytes: proofs_bytes.iter().map(Vec::len).collect(),
+            proving_ms: proof_result
+                .input_durations
+                .iter()
+                .map(|duration| duration.as_millis() as u64)
+                .sum(),
+            proved_at: Utc::now(),
+        };
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nexus_orchestrator::{TaskDifficulty, TaskType};
+    use crate::prover::verifier::tests::{fixture_input_bytes, verified_fixture};
+    use std::time::Duration;
+
+    #[test]
+    fn test_record_appends_summary() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = DryRunSink::new(dir.path().join("nested").join("dry-run.jsonl"));
+        let task = Task {
+            task_id: "abc".to_string(),
+            program_id: "fib_input_initial".to_string(),
+            public_inputs: fixture_input_bytes(),
+            public_inputs_list: vec![fixture_input_bytes(), fixture_input_bytes()],
+            task_type: TaskType::ProofHash,
+            difficulty: TaskDifficulty::Large,
+            deadline: None,
+        };
+        let proof_result = ProverResult::new(
+            &task,
+            vec![verified_fixture(), verified_fixture()],
+            vec![Duration::from_millis(1500), Duration::from_millis(500)],
+        )
+        .unwrap();
+
+        sink.record(&task, &proof_result, &[vec![0; 10], vec![0; 12]])
+            .unwrap();
+        sink.record(&task, &proof_result, &[]).unwrap();
+
+        let contents = std::fs::read_to_string(sink.path()).unwrap();
+        let records: Vec<DryRunRecord> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].task_id, "abc");
+        assert_eq!(records[0].task_type, "PROOF_HASH");
+        assert_eq!(records[0].difficulty, "LARGE");
+        assert_eq!(records[0].inputs, 2);
+        assert_eq!(records[0].proof_bytes, vec![10, 12]);
+        assert_eq!(records[0].proving_ms, 2000);
+    }
+}
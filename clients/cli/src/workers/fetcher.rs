@@ -1,14 +1,20 @@
 //! Task fetching with network retry logic
 
-use super::core::{EventSender, WorkerConfig};
+use super::capabilities::client_capabilities;
+use super::core::{EventSender, ResumeAssigned, WorkerConfig};
+use super::difficulty::{
+    AdaptivePolicy, DifficultyHistory, DifficultyPolicy, DifficultyStore, TaskOutcome, harder,
+};
 use crate::analytics::track_got_task;
-use crate::consts::cli_consts::{difficulty, rate_limiting, task_fetching};
-use crate::events::EventType;
+use crate::consts::cli_consts::{rate_limiting, task_fetching};
+use crate::events::{Event, EventType, Lifecycle};
 use crate::logging::LogLevel;
 use crate::network::{NetworkClient, RequestTimer, RequestTimerConfig};
-use crate::orchestrator::Orchestrator;
-use crate::task::Task;
+use crate::nexus_orchestrator::BatchLimits;
+use crate::orchestrator::{Orchestrator, get_all_tasks};
+use crate::task::{Task, TaskSignatureError};
 use ed25519_dalek::VerifyingKey;
+use std::collections::VecDeque;
 use std::time::Duration;
 use thiserror::Error;
 use tokio::time::sleep;
@@ -19,6 +25,15 @@ pub enum FetchError {
     Network(#[from] crate::orchestrator::error::OrchestratorError),
 }
 
+impl FetchError {
+    /// Why the node has to stop, if the orchestrator refused it for good
+    pub fn fatal_reason(&self) -> Option<String> {
+        match self {
+            FetchError::Network(e) => e.fatal_reason(),
+        }
+    }
+}
+
 /// Task fetcher with built-in retry and error handling
 pub struct TaskFetcher {
     node_id: u64,
@@ -30,6 +45,21 @@ pub struct TaskFetcher {
     pub last_success_duration_secs: Option<u64>,
     pub last_success_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
     last_requested_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    /// The difficulty asked for in the last successful fetch
+    pub last_desired_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    policy: Box<dyn DifficultyPolicy>,
+    history_store: Option<DifficultyStore>,
+    /// Tasks already assigned to this node, handed out before new ones are requested:
+    /// those left from before a restart, then the rest of a batch. Each comes with
+    /// the difficulty that was asked for, if it was asked for in this session.
+    queued: VecDeque<(Task, Option<crate::nexus_orchestrator::TaskDifficulty>)>,
+    /// Batching the orchestrator supports, as of the last fetch
+    batch_limits: BatchLimits,
+    /// Fewest inputs a task needs to be submitted input by input, as of the last
+    /// fetch or lookup of assigned tasks; None if the orchestrator doesn't support it
+    incremental_min_inputs: Option<u32>,
+    /// Most tasks worth fetching at once, e.g. until `--max-tasks` is reached
+    batch_cap: Option<u32>,
 }
 
 impl TaskFetcher {
@@ -52,6 +82,17 @@ impl TaskFetcher {
         // Create network client with retry logic
         let network_client = NetworkClient::new(request_timer, task_fetching::MAX_RETRIES);
 
+        // Resume adaptive difficulty from the previous session, if it was persisted
+        let history_store = config
+            .difficulty_history
+            .clone()
+            .map(|path| DifficultyStore::new(path, node_id));
+        let history = history_store
+            .as_ref()
+            .map(DifficultyStore::load)
+            .unwrap_or_default();
+        let policy = Box::new(AdaptivePolicy::from_history(config.min_difficulty, history));
+
         Self {
             node_id,
             verifying_key,
@@ -62,11 +103,169 @@ impl TaskFetcher {
             last_success_duration_secs: None,
             last_success_difficulty: None,
             last_requested_difficulty: None,
+            last_desired_difficulty: None,
+            policy,
+            history_store,
+            queued: VecDeque::new(),
+            batch_limits: BatchLimits::default(),
+            incremental_min_inputs: None,
+            batch_cap: None,
+        }
+    }
+
+    /// Report the tasks left out of a response for their signature; the rest is still used
+    async fn report_rejected(&self, rejected: &[TaskSignatureError]) {
+        for e in rejected {
+            self.event_sender
+                .send_task_event(
+                    format!("Skipping untrusted task: {}", e),
+                    EventType::Error,
+                    LogLevel::Warn,
+                )
+                .await;
         }
     }
 
+    /// Look for tasks the orchestrator assigned to this node before a restart.
+    /// Under `Auto` they are queued for `fetch_task`; under `Offer` they are only reported.
+    pub async fn load_assigned_tasks(&mut self, mode: ResumeAssigned) {
+        let resume = match mode {
+            ResumeAssigned::Off => return,
+            ResumeAssigned::Auto => true,
+            ResumeAssigned::Offer => false,
+        };
+        let tasks = match get_all_tasks(self.orchestrator.as_ref(), &self.node_id.to_string()).await
+        {
+            Ok(page) => {
+                self.incremental_min_inputs = page.incremental_min_inputs;
+                self.report_rejected(&page.rejected).await;
+                page.tasks
+            }
+            Err(e) => {
+                // Not being able to look is no reason not to take new tasks
+                self.event_sender
+                    .send_task_event(
+                        format!("Failed to look for assigned tasks: {}", e),
+                        EventType::Error,
+                        LogLevel::Warn,
+                    )
+                    .await;
+                return;
+            }
+        };
+        if tasks.is_empty() {
+            return;
+        }
+
+        let task_ids = tasks
+            .iter()
+            .map(|task| task.task_id.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if resume {
+            self.event_sender
+                .send_task_event(
+                    format!(
+                        "Resuming {} assigned task(s) before fetching new ones: {}",
+                        tasks.len(),
+                        task_ids
+                    ),
+                    EventType::Refresh,
+                    LogLevel::Info,
+                )
+                .await;
+            self.queued
+                .extend(tasks.into_iter().map(|task| (task, None)));
+        } else {
+            self.event_sender
+                .send_task_event(
+                    format!(
+                        "{} task(s) already assigned to this node: {}. Start with --resume-assigned auto to prove them first",
+                        tasks.len(),
+                        task_ids
+                    ),
+                    EventType::Refresh,
+                    LogLevel::Warn,
+                )
+                .await;
+        }
+    }
+
+    /// Whether tasks are queued from a batch or a restart, so the next fetch needs no request
+    pub fn has_queued(&self) -> bool {
+        !self.queued.is_empty()
+    }
+
+    /// Remove and return the queued tasks, which will not be started
+    pub fn take_queued(&mut self) -> Vec<Task> {
+        self.queued.drain(..).map(|(task, _)| task).collect()
+    }
+
+    /// Recent task durations per difficulty, as the difficulty policy keeps them
+    pub fn difficulty_history(&self) -> DifficultyHistory {
+        self.policy.history()
+    }
+
+    /// Batching the orchestrator supports, as of the last fetch
+    pub fn batch_limits(&self) -> BatchLimits {
+        self.batch_limits
+    }
+
+    /// Fewest inputs a task needs to be submitted input by input, if the orchestrator
+    /// supports that at all
+    pub fn incremental_min_inputs(&self) -> Option<u32> {
+        self.incremental_min_inputs
+    }
+
+    /// Fetch at most this many tasks at once, from the next fetch (None = no cap)
+    pub fn set_batch_cap(&mut self, cap: Option<u32>) {
+        self.batch_cap = cap;
+    }
+
+    /// Tasks to ask for in the next fetch: one per proving thread, within what the
+    /// orchestrator allows. One if it doesn't support batching.
+    fn batch_size(&self) -> u32 {
+        let slots = self.config.num_workers as u32;
+        let size = self
+            .batch_limits
+            .max_tasks_per_fetch
+            .min(slots)
+            .min(self.batch_cap.unwrap_or(u32::MAX));
+        size.max(1)
+    }
+
+    /// Change the thread count advertised from the next fetch
+    pub fn set_num_workers(&mut self, num_workers: usize) {
+        self.config.num_workers = num_workers;
+    }
+
+    /// Change the difficulty override used for the next fetch (None = adaptive)
+    pub fn set_max_difficulty(
+        &mut self,
+        max_difficulty: Option<crate::nexus_orchestrator::TaskDifficulty>,
+    ) {
+        self.config.max_difficulty = max_difficulty;
+    }
+
     /// Fetch a single task with automatic retry and proper logging
     pub async fn fetch_task(&mut self) -> Result<Task, FetchError> {
+        // Tasks already assigned go first, and need no request
+        if let Some((task, desired)) = self.queued.pop_front() {
+            self.event_sender
+                .send_event(
+                    Event::task_fetcher_with_level(
+                        format!("Step 1 of 4: Got task {}", task.task_id),
+                        EventType::Success,
+                        LogLevel::Info,
+                    )
+                    .with_lifecycle(Lifecycle::TaskFetched, Some(&task.task_id)),
+                )
+                .await;
+            self.last_requested_difficulty = Some(task.difficulty);
+            self.last_desired_difficulty = desired;
+            return Ok(task);
+        }
+
         // Check if we can proceed immediately
         let can_proceed_immediately = self.network_client.request_timer_mut().can_proceed();
 
@@ -100,60 +299,11 @@ impl TaskFetcher {
         }
 
         // Attempt to fetch task through network client
-        // Determine desired max difficulty
-        let desired = if let Some(override_diff) = self.config.max_difficulty {
-            override_diff
-        } else {
-            // Adaptive difficulty system:
-            // - Starts at SmallMedium by default
-            // - Promotes if previous task completed in < PROMOTION_THRESHOLD_SECS
-            // - Small difficulty does not auto-promote (manual override only)
-            if let Some(current) = self.last_success_difficulty {
-                // If last success took >= promotion threshold, don't increase difficulty
-                let promote = !matches!(
-                    self.last_success_duration_secs,
-                    Some(secs) if secs >= difficulty::PROMOTION_THRESHOLD_SECS
-                );
-                if promote {
-                    match current {
-                        crate::nexus_orchestrator::TaskDifficulty::Small => {
-                            // Small promotes to SmallMedium
-                            crate::nexus_orchestrator::TaskDifficulty::SmallMedium
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::SmallMedium => {
-                            crate::nexus_orchestrator::TaskDifficulty::Medium
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::Medium => {
-                            crate::nexus_orchestrator::TaskDifficulty::Large
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::Large => {
-                            crate::nexus_orchestrator::TaskDifficulty::ExtraLarge
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::ExtraLarge => {
-                            crate::nexus_orchestrator::TaskDifficulty::ExtraLarge2
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::ExtraLarge2 => {
-                            crate::nexus_orchestrator::TaskDifficulty::ExtraLarge3
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::ExtraLarge3 => {
-                            crate::nexus_orchestrator::TaskDifficulty::ExtraLarge4
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::ExtraLarge4 => {
-                            crate::nexus_orchestrator::TaskDifficulty::ExtraLarge5
-                        }
-                        crate::nexus_orchestrator::TaskDifficulty::ExtraLarge5 => {
-                            // Already at maximum difficulty
-                            crate::nexus_orchestrator::TaskDifficulty::ExtraLarge5
-                        }
-                    }
-                } else {
-                    current
-                }
-            } else {
-                // No previous success - start at SmallMedium
-                crate::nexus_orchestrator::TaskDifficulty::SmallMedium
-            }
-        };
+        // A manual override bypasses the adaptive policy
+        let desired = self
+            .config
+            .max_difficulty
+            .unwrap_or_else(|| self.policy.next_difficulty());
 
         // Log the difficulty we're requesting vs what we receive
         let requested_difficulty = desired;
@@ -165,6 +315,8 @@ impl TaskFetcher {
                 &self.node_id.to_string(),
                 self.verifying_key,
                 desired,
+                &client_capabilities(self.config.num_workers, &self.policy.history()),
+                self.batch_size(),
             )
             .await
         {
@@ -186,10 +338,16 @@ impl TaskFetcher {
 
                 // Log successful fetch
                 self.event_sender
-                    .send_task_event(
-                        format!("Step 1 of 4: Got task {}", proof_task_result.task.task_id),
-                        EventType::Success,
-                        LogLevel::Info,
+                    .send_event(
+                        Event::task_fetcher_with_level(
+                            format!("Step 1 of 4: Got task {}", proof_task_result.task.task_id),
+                            EventType::Success,
+                            LogLevel::Info,
+                        )
+                        .with_lifecycle(
+                            Lifecycle::TaskFetched,
+                            Some(&proof_task_result.task.task_id),
+                        ),
                     )
                     .await;
 
@@ -200,8 +358,34 @@ impl TaskFetcher {
                     self.config.client_id.clone(),
                 ));
 
+                // The rest of a batch is handed out by the next fetches
+                self.batch_limits = proof_task_result.batch_limits;
+                self.incremental_min_inputs = proof_task_result.incremental_min_inputs;
+                self.report_rejected(&proof_task_result.rejected).await;
+                if !proof_task_result.additional_tasks.is_empty() {
+                    self.event_sender
+                        .send_task_event(
+                            format!(
+                                "Got {} more task(s) in the same batch",
+                                proof_task_result.additional_tasks.len()
+                            ),
+                            EventType::Refresh,
+                            LogLevel::Info,
+                        )
+                        .await;
+                }
+                for task in proof_task_result.additional_tasks {
+                    tokio::spawn(track_got_task(
+                        task.clone(),
+                        self.config.environment.clone(),
+                        self.config.client_id.clone(),
+                    ));
+                    self.queued.push_back((task, Some(requested_difficulty)));
+                }
+
                 // Store the actual difficulty received from server for success tracking
                 self.last_requested_difficulty = Some(proof_task_result.actual_difficulty);
+                self.last_desired_difficulty = Some(requested_difficulty);
 
                 Ok(proof_task_result.task)
             }
@@ -209,10 +393,13 @@ impl TaskFetcher {
                 // Log fetch failure with appropriate level
                 let log_level = self.network_client.classify_error(&e);
                 self.event_sender
-                    .send_task_event(
-                        format!("Failed to fetch task: {}", e),
-                        EventType::Error,
-                        log_level,
+                    .send_event(
+                        Event::task_fetcher_with_level(
+                            format!("Failed to fetch task: {}", e),
+                            EventType::Error,
+                            log_level,
+                        )
+                        .with_lifecycle(Lifecycle::FetchFailed, None),
                     )
                     .await;
 
@@ -221,12 +408,60 @@ impl TaskFetcher {
         }
     }
 
-    /// Update success tracking after completing a task
-    /// Uses the actual difficulty received from the server
-    pub fn update_success_tracking(&mut self, duration_secs: u64) {
-        if let Some(difficulty) = self.last_requested_difficulty {
+    /// The difficulty the server assigned to the last fetched task
+    pub fn assigned_difficulty(&self) -> Option<crate::nexus_orchestrator::TaskDifficulty> {
+        self.last_requested_difficulty
+    }
+
+    /// Feed the outcome of the last fetched task to the difficulty policy.
+    /// Uses the actual difficulty received from the server.
+    pub async fn record_outcome(&mut self, outcome: TaskOutcome) {
+        let Some(difficulty) = self.last_requested_difficulty else {
+            return;
+        };
+        self.record_outcome_at(difficulty, outcome).await;
+    }
+
+    /// Feed the outcome of a task assigned at `difficulty` to the difficulty policy,
+    /// for tasks whose outcome is only known after later ones were fetched
+    pub async fn record_outcome_at(
+        &mut self,
+        difficulty: crate::nexus_orchestrator::TaskDifficulty,
+        outcome: TaskOutcome,
+    ) {
+        if let TaskOutcome::Success { duration } = outcome {
             self.last_success_difficulty = Some(difficulty);
-            self.last_success_duration_secs = Some(duration_secs);
+            self.last_success_duration_secs = Some(duration.as_secs());
+        }
+
+        let before = self.policy.next_difficulty();
+        self.policy.record(difficulty, outcome);
+        let after = self.policy.next_difficulty();
+        // Demotions are worth a warning; under a manual override they have no effect yet
+        if harder(before, after) != after && self.config.max_difficulty.is_none() {
+            self.event_sender
+                .send_task_event(
+                    format!(
+                        "Lowering difficulty to {} ({})",
+                        after.as_str_name(),
+                        outcome
+                    ),
+                    EventType::Refresh,
+                    LogLevel::Warn,
+                )
+                .await;
+        }
+
+        if let Some(store) = &self.history_store {
+            if let Err(e) = store.save(&self.policy.history()) {
+                self.event_sender
+                    .send_task_event(
+                        format!("Failed to save difficulty history: {}", e),
+                        EventType::Error,
+                        LogLevel::Warn,
+                    )
+                    .await;
+            }
         }
     }
 }
@@ -257,6 +492,8 @@ mod tests {
             _node_id: &str,
             _verifying_key: VerifyingKey,
             max_difficulty: crate::nexus_orchestrator::TaskDifficulty,
+            _capabilities: crate::nexus_orchestrator::ClientCapabilities,
+            max_tasks: u32,
         ) -> Result<crate::orchestrator::client::ProofTaskResult, OrchestratorError> {
             // Return a mock task with the requested difficulty as actual difficulty
             let task = Task {
@@ -266,11 +503,27 @@ mod tests {
                 public_inputs_list: vec![vec![1, 2, 3]],
                 task_type: crate::nexus_orchestrator::TaskType::ProofHash,
                 difficulty: crate::nexus_orchestrator::TaskDifficulty::Medium,
+                deadline: None,
             };
 
+            // Batches of up to four, numbered after the first task
+            let additional_tasks = (2..=max_tasks.min(4))
+                .map(|n| Task {
+                    task_id: format!("test_task_{}", n),
+                    ..task.clone()
+                })
+                .collect();
+
             Ok(crate::orchestrator::client::ProofTaskResult {
                 task,
                 actual_difficulty: max_difficulty,
+                additional_tasks,
+                rejected: Vec::new(),
+                batch_limits: BatchLimits {
+                    max_tasks_per_fetch: 4,
+                    max_submissions_per_batch: 4,
+                },
+                incremental_min_inputs: None,
             })
         }
 
@@ -278,6 +531,10 @@ mod tests {
             &Environment::Production
         }
 
+        fn take_rate_limit(&self) -> Option<crate::orchestrator::error::RateLimit> {
+            None
+        }
+
         async fn get_user(&self, _wallet_address: &str) -> Result<String, OrchestratorError> {
             Ok("test_user".to_string())
         }
@@ -296,6 +553,7 @@ mod tests {
 
         async fn submit_proof(
             &self,
+            _node_id: &str,
             _task_id: &str,
             _proof_hash: &str,
             _proof: Vec<u8>,
@@ -311,12 +569,96 @@ mod tests {
         async fn get_node(&self, _node_id: &str) -> Result<String, OrchestratorError> {
             Ok("test_node".to_string())
         }
+
+        async fn submit_proof_batch(
+            &self,
+            _node_id: &str,
+            _submissions: &[crate::nexus_orchestrator::BatchedSubmission],
+            _signing_key: SigningKey,
+            _num_provers: usize,
+        ) -> Result<Vec<crate::nexus_orchestrator::RejectedSubmission>, OrchestratorError> {
+            Ok(Vec::new())
+        }
+
+        async fn get_tasks(
+            &self,
+            _node_id: &str,
+            next_cursor: &str,
+        ) -> Result<crate::orchestrator::client::TaskPage, OrchestratorError> {
+            // Two pages of one assigned task each
+            let (task_id, next_cursor) = match next_cursor {
+                "" => ("assigned_1", "page_2"),
+                _ => ("assigned_2", ""),
+            };
+            Ok(crate::orchestrator::client::TaskPage {
+                tasks: vec![Task::new(
+                    task_id.to_string(),
+                    "test_program".to_string(),
+                    vec![1, 2, 3],
+                    crate::nexus_orchestrator::TaskType::ProofHash,
+                    crate::nexus_orchestrator::TaskDifficulty::Large,
+                )],
+                rejected: Vec::new(),
+                next_cursor: next_cursor.to_string(),
+                incremental_min_inputs: Some(8),
+            })
+        }
+
+        async fn submit_input_proof(
+            &self,
+            _node_id: &str,
+            _task_id: &str,
+            _input_index: u32,
+            _proof_hash: &str,
+            _proof: Vec<u8>,
+            _signing_key: SigningKey,
+        ) -> Result<(), OrchestratorError> {
+            Ok(())
+        }
+
+        async fn get_task_progress(
+            &self,
+            _node_id: &str,
+            _task_id: &str,
+        ) -> Result<Vec<crate::nexus_orchestrator::InputProof>, OrchestratorError> {
+            Ok(Vec::new())
+        }
+
+        async fn finalize_task(
+            &self,
+            _node_id: &str,
+            _task_id: &str,
+            _task_type: crate::nexus_orchestrator::TaskType,
+            _proof_hash: &str,
+            _all_proof_hashes: &[String],
+            _signing_key: SigningKey,
+            _num_provers: usize,
+        ) -> Result<(), OrchestratorError> {
+            Ok(())
+        }
+
+        async fn abandon_task(
+            &self,
+            _node_id: &str,
+            _task_id: &str,
+            _reason: crate::nexus_orchestrator::AbandonReason,
+            _detail: &str,
+            _signing_key: SigningKey,
+        ) -> Result<(), OrchestratorError> {
+            Ok(())
+        }
     }
 
     fn create_test_fetcher() -> TaskFetcher {
+        create_test_fetcher_with_config(WorkerConfig::new(
+            Environment::Production,
+            "test_client".to_string(),
+        ))
+    }
+
+    fn create_test_fetcher_with_config(config: WorkerConfig) -> TaskFetcher {
         let (event_sender, _event_receiver) = mpsc::channel(100);
         let event_sender = crate::workers::core::EventSender::new(event_sender);
-        let config = WorkerConfig::new(Environment::Production, "test_client".to_string());
 
         TaskFetcher::new(
             12345,
@@ -328,6 +670,79 @@ mod tests {
         )
     }
 
+    async fn record_success(fetcher: &mut TaskFetcher, secs: u64) {
+        fetcher
+            .record_outcome(TaskOutcome::Success {
+                duration: Duration::from_secs(secs),
+            })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_assigned_tasks_are_resumed_first() {
+        let mut fetcher = create_test_fetcher();
+        fetcher.load_assigned_tasks(ResumeAssigned::Auto).await;
+        // Resumed tasks can be submitted input by input before anything is fetched
+        assert_eq!(fetcher.incremental_min_inputs(), Some(8));
+
+        for task_id in ["assigned_1", "assigned_2"] {
+            let task = fetcher.fetch_task().await.unwrap();
+            assert_eq!(task.task_id, task_id);
+            // Outcomes count towards the difficulty the task was assigned at
+            assert_eq!(
+                fetcher.assigned_difficulty(),
+                Some(crate::nexus_orchestrator::TaskDifficulty::Large)
+            );
+            assert_eq!(fetcher.last_desired_difficulty, None);
+        }
+        assert_eq!(fetcher.fetch_task().await.unwrap().task_id, "test_task");
+        assert_eq!(fetcher.incremental_min_inputs(), None);
+    }
+
+    #[tokio::test]
+    async fn test_batches_fill_proving_threads() {
+        let mut config = WorkerConfig::new(Environment::Production, "test_client".to_string());
+        config.num_workers = 3;
+        let mut fetcher = create_test_fetcher_with_config(config);
+
+        // Batching is only used once the orchestrator has advertised it
+        assert_eq!(fetcher.fetch_task().await.unwrap().task_id, "test_task");
+        assert!(!fetcher.has_queued());
+        assert_eq!(fetcher.batch_limits().max_submissions_per_batch, 4);
+
+        let mut fetched = Vec::new();
+        for _ in 0..3 {
+            fetched.push(fetcher.fetch_task().await.unwrap().task_id);
+        }
+        assert_eq!(fetched, ["test_task", "test_task_2", "test_task_3"]);
+        assert!(!fetcher.has_queued());
+
+        // Never more than `--max-tasks` leaves room for
+        fetcher.set_batch_cap(Some(1));
+        fetcher.fetch_task().await.unwrap();
+        assert!(!fetcher.has_queued());
+
+        // Tasks still queued when the worker exits are taken out to be abandoned
+        fetcher.set_batch_cap(None);
+        assert_eq!(fetcher.fetch_task().await.unwrap().task_id, "test_task");
+        let left: Vec<_> = fetcher
+            .take_queued()
+            .into_iter()
+            .map(|task| task.task_id)
+            .collect();
+        assert_eq!(left, ["test_task_2", "test_task_3"]);
+        assert!(!fetcher.has_queued());
+    }
+
+    #[tokio::test]
+    async fn test_offered_assigned_tasks_are_not_resumed() {
+        for mode in [ResumeAssigned::Offer, ResumeAssigned::Off] {
+            let mut fetcher = create_test_fetcher();
+            fetcher.load_assigned_tasks(mode).await;
+            assert_eq!(fetcher.fetch_task().await.unwrap().task_id, "test_task");
+        }
+    }
+
     #[tokio::test]
     async fn test_default_difficulty_is_small_medium() {
         let mut fetcher = create_test_fetcher();
@@ -351,8 +766,8 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was Small
-        fetcher.last_success_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Small);
-        fetcher.last_success_duration_secs = Some(300); // 5 minutes - should promote
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Small);
+        record_success(&mut fetcher, 300).await; // 5 minutes - should promote
 
         let task = fetcher
             .fetch_task()
@@ -372,9 +787,9 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was SmallMedium
-        fetcher.last_success_difficulty =
+        fetcher.last_requested_difficulty =
             Some(crate::nexus_orchestrator::TaskDifficulty::SmallMedium);
-        fetcher.last_success_duration_secs = Some(300); // 5 minutes - should promote
+        record_success(&mut fetcher, 300).await; // 5 minutes - should promote
 
         let task = fetcher
             .fetch_task()
@@ -394,8 +809,8 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was Medium
-        fetcher.last_success_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
-        fetcher.last_success_duration_secs = Some(300); // 5 minutes - should promote
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
+        record_success(&mut fetcher, 300).await; // 5 minutes - should promote
 
         let task = fetcher
             .fetch_task()
@@ -415,8 +830,8 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was Large
-        fetcher.last_success_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Large);
-        fetcher.last_success_duration_secs = Some(300); // 5 minutes - should promote
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Large);
+        record_success(&mut fetcher, 300).await; // 5 minutes - should promote
 
         let task = fetcher
             .fetch_task()
@@ -436,8 +851,8 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was Medium, but took 8 minutes (too long)
-        fetcher.last_success_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
-        fetcher.last_success_duration_secs = Some(480); // 8 minutes - should NOT promote
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
+        record_success(&mut fetcher, 480).await; // 8 minutes - should NOT promote
 
         let task = fetcher
             .fetch_task()
@@ -504,7 +919,7 @@ mod tests {
         fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
 
         // Update success tracking
-        fetcher.update_success_tracking(300); // 5 minutes
+        record_success(&mut fetcher, 300).await; // 5 minutes
 
         // Verify tracking was updated
         assert_eq!(
@@ -522,7 +937,7 @@ mod tests {
         fetcher.last_requested_difficulty = None;
 
         // Update success tracking
-        fetcher.update_success_tracking(300);
+        record_success(&mut fetcher, 300).await;
 
         // Should not update tracking when no requested difficulty
         assert_eq!(fetcher.last_success_difficulty, None);
@@ -534,9 +949,9 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was ExtraLarge
-        fetcher.last_success_difficulty =
+        fetcher.last_requested_difficulty =
             Some(crate::nexus_orchestrator::TaskDifficulty::ExtraLarge);
-        fetcher.last_success_duration_secs = Some(300); // 5 minutes - should promote
+        record_success(&mut fetcher, 300).await; // 5 minutes - should promote
 
         let task = fetcher
             .fetch_task()
@@ -556,9 +971,9 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was ExtraLarge2
-        fetcher.last_success_difficulty =
+        fetcher.last_requested_difficulty =
             Some(crate::nexus_orchestrator::TaskDifficulty::ExtraLarge2);
-        fetcher.last_success_duration_secs = Some(300); // 5 minutes - should promote
+        record_success(&mut fetcher, 300).await; // 5 minutes - should promote
 
         let task = fetcher.fetch_task().await.unwrap();
         assert_eq!(task.task_id, "test_task");
@@ -575,9 +990,9 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Set up initial state: last success was ExtraLarge5 (maximum difficulty)
-        fetcher.last_success_difficulty =
+        fetcher.last_requested_difficulty =
             Some(crate::nexus_orchestrator::TaskDifficulty::ExtraLarge5);
-        fetcher.last_success_duration_secs = Some(300); // 5 minutes - would normally promote
+        record_success(&mut fetcher, 300).await; // 5 minutes - would normally promote
 
         let task = fetcher.fetch_task().await.unwrap();
         assert_eq!(task.task_id, "test_task");
@@ -594,8 +1009,8 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Test exactly 7 minutes (420 seconds) - should NOT promote
-        fetcher.last_success_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
-        fetcher.last_success_duration_secs = Some(420); // Exactly 7 minutes
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
+        record_success(&mut fetcher, 420).await; // Exactly 7 minutes
 
         let task = fetcher
             .fetch_task()
@@ -615,8 +1030,8 @@ mod tests {
         let mut fetcher = create_test_fetcher();
 
         // Test just under 7 minutes (419 seconds) - should promote
-        fetcher.last_success_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
-        fetcher.last_success_duration_secs = Some(419); // Just under 7 minutes
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Medium);
+        record_success(&mut fetcher, 419).await; // Just under 7 minutes
 
         let task = fetcher
             .fetch_task()
@@ -630,4 +1045,56 @@ mod tests {
             Some(crate::nexus_orchestrator::TaskDifficulty::Large)
         );
     }
+
+    #[tokio::test]
+    async fn test_out_of_memory_demotes() {
+        let mut fetcher = create_test_fetcher();
+
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Large);
+        fetcher.record_outcome(TaskOutcome::OutOfMemory).await;
+
+        fetcher.fetch_task().await.unwrap();
+        assert_eq!(
+            fetcher.last_requested_difficulty,
+            Some(crate::nexus_orchestrator::TaskDifficulty::Medium)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restart_resumes_persisted_difficulty() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = WorkerConfig::new(Environment::Production, "test_client".to_string());
+        config.difficulty_history = Some(dir.path().join("difficulty.json"));
+
+        let mut fetcher = create_test_fetcher_with_config(config.clone());
+        fetcher.last_requested_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Large);
+        record_success(&mut fetcher, 300).await;
+
+        // A fresh fetcher, as after a restart, picks up where the last one stopped
+        let mut fetcher = create_test_fetcher_with_config(config);
+        fetcher.fetch_task().await.unwrap();
+        assert_eq!(
+            fetcher.last_requested_difficulty,
+            Some(crate::nexus_orchestrator::TaskDifficulty::ExtraLarge)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_min_difficulty_is_floor() {
+        let mut config = WorkerConfig::new(Environment::Production, "test_client".to_string());
+        config.min_difficulty = Some(crate::nexus_orchestrator::TaskDifficulty::Large);
+        let mut fetcher = create_test_fetcher_with_config(config);
+
+        fetcher.fetch_task().await.unwrap();
+        assert_eq!(
+            fetcher.last_requested_difficulty,
+            Some(crate::nexus_orchestrator::TaskDifficulty::Large)
+        );
+
+        fetcher.record_outcome(TaskOutcome::Timeout).await;
+        assert_eq!(
+            fetcher.policy.next_difficulty(),
+            crate::nexus_orchestrator::TaskDifficulty::Large
+        );
+    }
 }
@@ -1,5 +1,8 @@
 pub mod authenticated_worker;
+pub mod capabilities;
 pub mod core;
+pub mod difficulty;
+pub mod dry_run;
 pub mod fetcher;
 pub mod prover;
 pub mod submitter;
@@ -1,45 +1,118 @@
 //! Proof generation using existing prover module
 
 use super::core::{EventSender, WorkerConfig};
+use super::difficulty::TaskOutcome;
 use crate::analytics::track_authenticated_proof_analytics;
-use crate::events::EventType;
+use crate::consts::cli_consts::difficulty;
+use crate::events::{Event, EventType, Lifecycle};
 use crate::logging::LogLevel;
-use crate::prover::{ProverError, ProverResult, authenticated_proving};
+use crate::nexus_orchestrator::AbandonReason;
+use crate::prover::energy::{self, Rapl};
+use crate::prover::{
+    IncrementalProving, ProverError, ProverResult, authenticated_proving, processes,
+};
 use crate::task::Task;
+use std::future::Future;
+use std::time::Duration;
 use thiserror::Error;
+use tokio::time::{Instant, MissedTickBehavior};
+
+/// How often the proving timeout checks whether the provers are suspended
+const TIMEOUT_TICK: Duration = Duration::from_secs(1);
 
 #[derive(Error, Debug)]
 pub enum ProveError {
     #[error("Proof generation failed: {0}")]
     Generation(#[from] ProverError),
+
+    #[error("Proof generation timed out after {}s", .0.as_secs())]
+    Timeout(Duration),
+}
+
+impl ProveError {
+    /// How this failure should count towards difficulty selection
+    pub fn outcome(&self) -> TaskOutcome {
+        match self {
+            ProveError::Generation(ProverError::OutOfMemory(_)) => TaskOutcome::OutOfMemory,
+            ProveError::Generation(_) => TaskOutcome::Failed,
+            ProveError::Timeout(_) => TaskOutcome::Timeout,
+        }
+    }
+
+    /// What to tell the orchestrator when giving up the task after this failure
+    pub fn abandon_reason(&self) -> AbandonReason {
+        match self {
+            ProveError::Generation(ProverError::OutOfMemory(_)) => AbandonReason::OutOfMemory,
+            ProveError::Generation(ProverError::UnsupportedProgram(_)) => {
+                AbandonReason::UnsupportedProgram
+            }
+            ProveError::Generation(_) => AbandonReason::ProofFailed,
+            ProveError::Timeout(_) => AbandonReason::ProvingTimeout,
+        }
+    }
 }
 
 /// Task prover that generates proofs using the existing prover module
 pub struct TaskProver {
     event_sender: EventSender,
     config: WorkerConfig,
+    /// Energy counters, if this machine has readable ones
+    rapl: Option<Rapl>,
 }
 
 impl TaskProver {
     pub fn new(event_sender: EventSender, config: WorkerConfig) -> Self {
         Self {
             event_sender,
+            rapl: Rapl::discover(&config.energy.sysfs_root),
             config,
         }
     }
 
-    /// Generate proof for a task with proper logging
-    pub async fn prove_task(&self, task: &Task) -> Result<ProverResult, ProveError> {
+    /// Change how many inputs are proved in parallel, starting with the next task
+    pub fn set_num_workers(&mut self, num_workers: usize) {
+        self.config.num_workers = num_workers;
+    }
+
+    /// Generate proof for a task with proper logging, submitting each input as it is
+    /// verified if `incremental` is given
+    pub async fn prove_task(
+        &self,
+        task: &Task,
+        incremental: Option<IncrementalProving>,
+    ) -> Result<ProverResult, ProveError> {
         // Use existing prover module for proof generation
-        match authenticated_proving(
-            task,
-            &self.config.environment,
-            &self.config.client_id,
-            self.config.num_workers,
-        )
-        .await
-        {
-            Ok((proofs, combined_hash, individual_proof_hashes)) => {
+        let proving = energy::measure(
+            self.rapl.as_ref(),
+            authenticated_proving(
+                task,
+                &self.config.environment,
+                &self.config.client_id,
+                self.config.num_workers,
+                incremental,
+            ),
+        );
+        let Some((result, energy_joules)) =
+            with_proving_timeout(proving, difficulty::proving_timeout()).await
+        else {
+            // Dropping the proving future kills its subprocesses
+            let error = ProveError::Timeout(difficulty::proving_timeout());
+            self.event_sender
+                .send_prover_event(
+                    self.config.num_workers,
+                    format!(
+                        "Proof generation failed for task {}: {}",
+                        task.task_id, error
+                    ),
+                    EventType::Error,
+                    LogLevel::Error,
+                )
+                .await;
+            return Err(error);
+        };
+
+        match result {
+            Ok(mut proof_result) => {
                 // Log successful proof generation
                 self.event_sender
                     .send_prover_event(
@@ -59,27 +132,87 @@ impl TaskProver {
                     self.config.client_id.clone(),
                 ));
 
-                Ok(ProverResult {
-                    proofs,
-                    combined_hash,
-                    individual_proof_hashes,
-                })
+                proof_result.energy_joules = energy_joules;
+                Ok(proof_result)
             }
             Err(e) => {
                 // Log proof generation failure
-                self.event_sender
-                    .send_prover_event(
-                        self.config.num_workers, // Use num_workers as thread identifier for multi-threaded prover
-                        format!(
-                            "Proof generation failed for task {} (using {} workers): {}",
-                            task.task_id, self.config.num_workers, e
-                        ),
-                        EventType::Error,
-                        LogLevel::Error,
-                    )
-                    .await;
+                let mut event = Event::prover_with_level(
+                    self.config.num_workers, // Use num_workers as thread identifier for multi-threaded prover
+                    format!(
+                        "Proof generation failed for task {} (using {} workers): {}",
+                        task.task_id, self.config.num_workers, e
+                    ),
+                    EventType::Error,
+                    LogLevel::Error,
+                );
+                if matches!(e, ProverError::OutOfMemory(_)) {
+                    event = event.with_lifecycle(Lifecycle::OutOfMemory, Some(&task.task_id));
+                }
+                self.event_sender.send_event(event).await;
                 Err(ProveError::Generation(e))
             }
         }
     }
 }
+
+/// Run `future` until it completes, or until it has been running for `limit`.
+/// Time during which the prover subprocesses are suspended does not count.
+async fn with_proving_timeout<F: Future>(future: F, limit: Duration) -> Option<F::Output> {
+    tokio::pin!(future);
+    let mut ticker = tokio::time::interval_at(Instant::now() + TIMEOUT_TICK, TIMEOUT_TICK);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut active = Duration::ZERO;
+
+    loop {
+        tokio::select! {
+            output = &mut future => return Some(output),
+            _ = ticker.tick() => {
+                if !processes::is_suspended() {
+                    active += TIMEOUT_TICK;
+                }
+                if active >= limit {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_proving_timeout() {
+        let limit = Duration::from_secs(5);
+
+        let output = with_proving_timeout(tokio::time::sleep(Duration::from_secs(3)), limit).await;
+        assert!(output.is_some());
+
+        let output = with_proving_timeout(std::future::pending::<()>(), limit).await;
+        assert!(output.is_none());
+    }
+
+    #[test]
+    fn test_failure_outcomes() {
+        let oom = ProveError::Generation(ProverError::OutOfMemory("killed".to_string()));
+        assert_eq!(oom.outcome(), TaskOutcome::OutOfMemory);
+        assert_eq!(oom.abandon_reason(), AbandonReason::OutOfMemory);
+
+        let failed = ProveError::Generation(ProverError::Subprocess("boom".to_string()));
+        assert_eq!(failed.outcome(), TaskOutcome::Failed);
+        assert_eq!(failed.abandon_reason(), AbandonReason::ProofFailed);
+
+        let unsupported = ProveError::Generation(ProverError::UnsupportedProgram("x".to_string()));
+        assert_eq!(unsupported.outcome(), TaskOutcome::Failed);
+        assert_eq!(
+            unsupported.abandon_reason(),
+            AbandonReason::UnsupportedProgram
+        );
+
+        let timeout = ProveError::Timeout(Duration::from_secs(1));
+        assert_eq!(timeout.outcome(), TaskOutcome::Timeout);
+        assert_eq!(timeout.abandon_reason(), AbandonReason::ProvingTimeout);
+    }
+}
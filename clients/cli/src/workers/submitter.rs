@@ -1,17 +1,25 @@
 //! Proof submission with network retry logic
 
 use super::core::{EventSender, WorkerConfig};
+use super::dry_run::DryRunSink;
 use crate::analytics::{
     track_proof_accepted, track_proof_submission_error, track_proof_submission_success,
 };
 use crate::consts::cli_consts::{proof_submission, rate_limiting};
-use crate::events::EventType;
+use crate::events::{Event, EventType, Lifecycle};
 use crate::logging::LogLevel;
-use crate::network::{NetworkClient, ProofSubmission, RequestTimer, RequestTimerConfig};
+use crate::network::{
+    InputContext, NetworkClient, ProofSubmission, RequestTimer, RequestTimerConfig,
+};
+use crate::nexus_orchestrator::AbandonReason;
 use crate::orchestrator::Orchestrator;
-use crate::prover::ProverResult;
+use crate::orchestrator::error::OrchestratorError;
+use crate::prover::verifier::VerifiedProof;
+use crate::prover::{IncrementalProving, InputLedger, InputSink, ProverResult};
 use crate::task::Task;
 use ed25519_dalek::SigningKey;
+use std::collections::HashMap;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -20,24 +28,57 @@ pub enum SubmitError {
     Network(#[from] crate::orchestrator::error::OrchestratorError),
     #[error("Serialization error: {0}")]
     Serialization(#[from] postcard::Error),
+    #[error("Could not keep dry-run proof: {0}")]
+    DryRun(#[source] std::io::Error),
+}
+
+impl SubmitError {
+    /// Why the node has to stop, if the orchestrator refused it for good
+    pub fn fatal_reason(&self) -> Option<String> {
+        match self {
+            SubmitError::Network(e) => e.fatal_reason(),
+            _ => None,
+        }
+    }
 }
 
 /// Proof submitter with built-in retry and error handling
 pub struct ProofSubmitter {
+    /// Node the proofs are submitted for, which version 1 signatures bind
+    node_id: u64,
     signing_key: SigningKey,
-    orchestrator: Box<dyn Orchestrator>,
+    orchestrator: Arc<dyn Orchestrator>,
     network_client: NetworkClient,
     event_sender: EventSender,
     config: WorkerConfig,
+    /// Keeps proofs locally instead of submitting them, in a dry run
+    dry_run: Option<DryRunSink>,
+    /// Submission attempts made for the last proof
+    pub last_attempts: u32,
 }
 
 impl ProofSubmitter {
     pub fn new(
+        node_id: u64,
         signing_key: SigningKey,
         orchestrator: Box<dyn Orchestrator>,
         event_sender: EventSender,
         config: &WorkerConfig,
     ) -> Self {
+        Self {
+            node_id,
+            signing_key,
+            orchestrator: Arc::from(orchestrator),
+            network_client: Self::network_client(),
+            event_sender,
+            config: config.clone(),
+            dry_run: config.dry_run.clone().map(DryRunSink::new),
+            last_attempts: 0,
+        }
+    }
+
+    /// Network client for submissions, with their pacing and retries
+    fn network_client() -> NetworkClient {
         // Configure request timer for proof submission
         let timer_config = RequestTimerConfig::combined(
             proof_submission::rate_limit_interval(),
@@ -48,70 +89,288 @@ impl ProofSubmitter {
         let request_timer = RequestTimer::new(timer_config);
 
         // Create network client with more retries for critical submissions
-        let network_client = NetworkClient::new(request_timer, proof_submission::MAX_RETRIES);
+        NetworkClient::new(request_timer, proof_submission::MAX_RETRIES)
+    }
 
-        Self {
-            signing_key,
-            orchestrator,
-            network_client,
-            event_sender,
-            config: config.clone(),
+    /// Whether proofs are kept locally instead of being submitted
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// A handle to give up tasks with, usable while the submitter is busy
+    pub fn abandoner(&self) -> TaskAbandoner {
+        TaskAbandoner {
+            node_id: self.node_id,
+            signing_key: self.signing_key.clone(),
+            orchestrator: Arc::clone(&self.orchestrator),
+            event_sender: self.event_sender.clone(),
+            dry_run: self.is_dry_run(),
         }
     }
 
-    /// Submit proof with automatic retry and proper logging
-    pub async fn submit_proof(
-        &mut self,
+    /// Whether `task`'s proof can go in a batch: only hashes are batched, never full proofs,
+    /// and tasks submitted input by input are finalized on their own
+    pub fn can_batch(&self, task: &Task, proof_result: &ProverResult) -> bool {
+        !self.is_dry_run()
+            && !proof_result.is_incremental()
+            && task.task_type != crate::nexus_orchestrator::TaskType::ProofRequired
+    }
+
+    /// How to prove `task` if each input is to be submitted as soon as it is verified:
+    /// only once the orchestrator takes tasks with at least `min_inputs` inputs that way,
+    /// and never in a dry run. Inputs this node submitted before are left out, if the
+    /// orchestrator still has them.
+    pub async fn incremental_proving(
+        &self,
         task: &Task,
-        proof_result: &ProverResult,
-    ) -> Result<(), SubmitError> {
-        // Log start of submission
+        min_inputs: Option<u32>,
+    ) -> Option<IncrementalProving> {
+        let inputs = task.all_inputs().len();
+        let min_inputs = min_inputs? as usize;
+        if self.is_dry_run() || inputs < min_inputs {
+            return None;
+        }
+
+        let node_id = self.node_id.to_string();
+        let progress: HashMap<usize, String> = match self
+            .orchestrator
+            .get_task_progress(&node_id, &task.task_id)
+            .await
+        {
+            Ok(progress) => progress
+                .into_iter()
+                .map(|input| (input.input_index as usize, input.proof_hash))
+                .filter(|(index, _)| *index < inputs)
+                .collect(),
+            Err(e) => {
+                // Proving every input again is only slower
+                self.event_sender
+                    .send_proof_event(
+                        format!(
+                            "Could not check which inputs of task {} were already submitted, proving all of them: {}",
+                            task.task_id, e
+                        ),
+                        EventType::Refresh,
+                        LogLevel::Warn,
+                    )
+                    .await;
+                HashMap::new()
+            }
+        };
+        let incremental =
+            IncrementalProving::new(&task.task_id, self.input_ledger(), &progress, |received| {
+                Box::new(InputSubmitter {
+                    node_id,
+                    task: task.clone(),
+                    signing_key: self.signing_key.clone(),
+                    orchestrator: Arc::clone(&self.orchestrator),
+                    network_client: Self::network_client(),
+                    event_sender: self.event_sender.clone(),
+                    submitted: received,
+                })
+            });
+        let received = incremental.received_count();
+        if received > 0 {
+            self.event_sender
+                .send_proof_event(
+                    format!(
+                        "{} of {} inputs submitted for task {} before, proving the rest",
+                        received, inputs, task.task_id
+                    ),
+                    EventType::Refresh,
+                    LogLevel::Info,
+                )
+                .await;
+        }
+
+        Some(incremental)
+    }
+
+    /// This node's ledger of inputs submitted one by one, if it keeps one
+    fn input_ledger(&self) -> Option<InputLedger> {
+        self.config
+            .input_ledger
+            .clone()
+            .map(|path| InputLedger::new(path, self.node_id))
+    }
+
+    /// Submit the proofs of several tasks in one signed request, with automatic retry.
+    /// Returns why each task the orchestrator didn't accept was rejected, by task ID;
+    /// an error means none of them were submitted.
+    pub async fn submit_batch(
+        &mut self,
+        batch: &[(&Task, &ProverResult)],
+    ) -> Result<HashMap<String, String>, SubmitError> {
+        self.last_attempts = 0;
+        let task_ids = batch
+            .iter()
+            .map(|(task, _)| task.task_id.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
         self.event_sender
             .send_proof_event(
-                format!("Step 3 of 4: Submitting proof for task {}...", task.task_id),
+                format!(
+                    "Step 3 of 4: Submitting proofs for tasks {} in one batch...",
+                    task_ids
+                ),
                 EventType::StateChange,
                 LogLevel::Info,
             )
             .await;
 
-        // Serialize proofs
-        let proofs_bytes: Vec<Vec<u8>> = proof_result
-            .proofs
-            .iter()
-            .map(postcard::to_allocvec)
-            .collect::<Result<_, _>>()?;
-        let legacy_proof_bytes = proofs_bytes.first().cloned().unwrap_or_default();
-
-        // Submit through network client with retry logic
-        let mut submission = ProofSubmission::new(
-            task.task_id.clone(),
-            proof_result.combined_hash.clone(),
-            legacy_proof_bytes,
-            task.task_type,
-        );
-
-        // Populate individual hashes for ALL_PROOF_HASHES and optionally for ProofHash
-        if task.task_type == crate::nexus_orchestrator::TaskType::AllProofHashes {
-            submission =
-                submission.with_individual_hashes(proof_result.individual_proof_hashes.clone());
-        }
-
-        // Populate proofs for PROOF_REQUIRED; leave empty otherwise
-        if task.task_type == crate::nexus_orchestrator::TaskType::ProofRequired {
-            submission = submission.with_proofs(proofs_bytes);
+        let mut submissions = Vec::new();
+        for (task, proof_result) in batch {
+            submissions.extend(ProofSubmission::new(task, proof_result)?.batched());
         }
 
         match self
             .network_client
-            .submit_proof(
+            .submit_proof_batch(
                 self.orchestrator.as_ref(),
-                submission,
+                &submissions,
+                &self.node_id.to_string(),
                 self.signing_key.clone(),
                 1, // num_provers (single worker)
             )
             .await
         {
+            Ok((rejected, attempts)) => {
+                self.last_attempts = attempts;
+                let rejected: HashMap<String, String> = rejected
+                    .into_iter()
+                    .map(|rejection| (rejection.task_id, rejection.reason))
+                    .collect();
+                for (task, _) in batch {
+                    match rejected.get(&task.task_id) {
+                        None => {
+                            self.event_sender
+                                .send_event(
+                                    Event::proof_submitter_with_level(
+                                        format!(
+                                            "Step 4 of 4: Proof submitted successfully for task {}\n",
+                                            task.task_id
+                                        ),
+                                        EventType::Success,
+                                        LogLevel::Info,
+                                    )
+                                    .with_lifecycle(Lifecycle::ProofSubmitted, Some(&task.task_id)),
+                                )
+                                .await;
+                            self.track_successful_submission(task).await;
+                        }
+                        Some(reason) => {
+                            self.event_sender
+                                .send_event(
+                                    Event::proof_submitter_with_level(
+                                        format!(
+                                            "Failed to submit proof for task {}: rejected from batch: {}",
+                                            task.task_id, reason
+                                        ),
+                                        EventType::Error,
+                                        LogLevel::Error,
+                                    )
+                                    .with_lifecycle(
+                                        Lifecycle::SubmissionFailed,
+                                        Some(&task.task_id),
+                                    ),
+                                )
+                                .await;
+                            tokio::spawn(track_proof_submission_error(
+                                (*task).clone(),
+                                reason.clone(),
+                                None,
+                                self.config.environment.clone(),
+                                self.config.client_id.clone(),
+                            ));
+                        }
+                    }
+                }
+                Ok(rejected)
+            }
+            Err((e, attempts)) => {
+                self.last_attempts = attempts;
+                let log_level = self.network_client.classify_error(&e);
+                for (task, _) in batch {
+                    self.event_sender
+                        .send_event(
+                            Event::proof_submitter_with_level(
+                                format!(
+                                    "Failed to submit proof for task {} after {} attempts: {}",
+                                    task.task_id, attempts, e
+                                ),
+                                EventType::Error,
+                                log_level,
+                            )
+                            .with_lifecycle(Lifecycle::SubmissionFailed, Some(&task.task_id)),
+                        )
+                        .await;
+                    tokio::spawn(track_proof_submission_error(
+                        (*task).clone(),
+                        e.to_string(),
+                        None,
+                        self.config.environment.clone(),
+                        self.config.client_id.clone(),
+                    ));
+                }
+                Err(SubmitError::Network(e))
+            }
+        }
+    }
+
+    /// Submit proof with automatic retry and proper logging
+    pub async fn submit_proof(
+        &mut self,
+        task: &Task,
+        proof_result: &ProverResult,
+    ) -> Result<(), SubmitError> {
+        self.last_attempts = 0;
+        // Nothing reaches the orchestrator in a dry run
+        if let Some(sink) = &self.dry_run {
+            return self.keep_locally(sink, task, proof_result).await;
+        }
+
+        let outcome = if proof_result.is_incremental() {
+            // Inputs the orchestrator didn't take while proving are sent again first
+            let unsent: Vec<_> = proof_result.held_inputs().collect();
+            self.event_sender
+                .send_proof_event(
+                    format!(
+                        "Step 3 of 4: Finalizing task {} ({} of {} inputs already submitted)...",
+                        task.task_id,
+                        proof_result.individual_proof_hashes().len() - unsent.len(),
+                        proof_result.individual_proof_hashes().len()
+                    ),
+                    EventType::StateChange,
+                    LogLevel::Info,
+                )
+                .await;
+            self.finalize(task, proof_result, unsent).await
+        } else {
+            // Log start of submission
+            self.event_sender
+                .send_proof_event(
+                    format!("Step 3 of 4: Submitting proof for task {}...", task.task_id),
+                    EventType::StateChange,
+                    LogLevel::Info,
+                )
+                .await;
+
+            // Submit through network client with retry logic
+            let submission = ProofSubmission::new(task, proof_result)?;
+            self.network_client
+                .submit_proof(
+                    self.orchestrator.as_ref(),
+                    submission,
+                    &self.node_id.to_string(),
+                    self.signing_key.clone(),
+                    1, // num_provers (single worker)
+                )
+                .await
+        };
+
+        match outcome {
             Ok(attempts) => {
+                self.last_attempts = attempts;
                 // Log successful submission with attempt count
                 let attempt_text = if attempts == 1 {
                     "".to_string()
@@ -120,13 +379,16 @@ impl ProofSubmitter {
                 };
 
                 self.event_sender
-                    .send_proof_event(
-                        format!(
-                            "Step 4 of 4: Proof submitted successfully for task {}{}\n",
-                            task.task_id, attempt_text
-                        ),
-                        EventType::Success,
-                        LogLevel::Info,
+                    .send_event(
+                        Event::proof_submitter_with_level(
+                            format!(
+                                "Step 4 of 4: Proof submitted successfully for task {}{}\n",
+                                task.task_id, attempt_text
+                            ),
+                            EventType::Success,
+                            LogLevel::Info,
+                        )
+                        .with_lifecycle(Lifecycle::ProofSubmitted, Some(&task.task_id)),
                     )
                     .await;
 
@@ -138,16 +400,20 @@ impl ProofSubmitter {
                 Ok(())
             }
             Err((e, attempts)) => {
+                self.last_attempts = attempts;
                 // Log submission failure with attempt count and appropriate level
                 let log_level = self.network_client.classify_error(&e);
                 self.event_sender
-                    .send_proof_event(
-                        format!(
-                            "Failed to submit proof for task {} after {} attempts: {}",
-                            task.task_id, attempts, e
-                        ),
-                        EventType::Error,
-                        log_level,
+                    .send_event(
+                        Event::proof_submitter_with_level(
+                            format!(
+                                "Failed to submit proof for task {} after {} attempts: {}",
+                                task.task_id, attempts, e
+                            ),
+                            EventType::Error,
+                            log_level,
+                        )
+                        .with_lifecycle(Lifecycle::SubmissionFailed, Some(&task.task_id)),
                     )
                     .await;
 
@@ -165,6 +431,98 @@ impl ProofSubmitter {
         }
     }
 
+    /// Send the inputs still unsent, then finalize a task submitted input by input.
+    /// Returns the attempts made in all, as `NetworkClient::submit_proof` does.
+    async fn finalize(
+        &mut self,
+        task: &Task,
+        proof_result: &ProverResult,
+        unsent: Vec<(usize, &VerifiedProof)>,
+    ) -> Result<u32, (OrchestratorError, u32)> {
+        let node_id = self.node_id.to_string();
+        let context = InputContext {
+            node_id: &node_id,
+            task,
+            signing_key: &self.signing_key,
+        };
+        let mut attempts = 0;
+        for (index, proof) in unsent {
+            attempts += self
+                .network_client
+                .submit_input_proof(self.orchestrator.as_ref(), &context, index, proof)
+                .await
+                .map_err(|(e, tries)| (e, attempts + tries))?;
+        }
+        let attempts = self
+            .network_client
+            .finalize_task(
+                self.orchestrator.as_ref(),
+                task,
+                proof_result,
+                &node_id,
+                self.signing_key.clone(),
+                1, // num_provers (single worker)
+            )
+            .await
+            .map(|tries| attempts + tries)
+            .map_err(|(e, tries)| (e, attempts + tries))?;
+        if let Some(ledger) = self.input_ledger() {
+            // A stale entry is only dropped later, once newer tasks push it out
+            let _ = ledger.forget(&task.task_id);
+        }
+        Ok(attempts)
+    }
+
+    /// Record the proof in the dry-run sink instead of submitting it
+    async fn keep_locally(
+        &self,
+        sink: &DryRunSink,
+        task: &Task,
+        proof_result: &ProverResult,
+    ) -> Result<(), SubmitError> {
+        // Serialize as a submission would, so proof sizes are real
+        let proofs_bytes: Vec<Vec<u8>> = proof_result
+            .proofs()
+            .iter()
+            .map(VerifiedProof::to_bytes)
+            .collect::<Result<_, _>>()?;
+
+        match sink.record(task, proof_result, &proofs_bytes) {
+            Ok(_) => {
+                self.event_sender
+                    .send_event(
+                        Event::proof_submitter_with_level(
+                            format!(
+                                "Step 4 of 4: Dry run: proof for task {} kept in {}, not submitted\n",
+                                task.task_id,
+                                sink.path().display()
+                            ),
+                            EventType::Success,
+                            LogLevel::Info,
+                        )
+                        .with_lifecycle(Lifecycle::ProofKept, Some(&task.task_id)),
+                    )
+                    .await;
+                Ok(())
+            }
+            Err(e) => {
+                self.event_sender
+                    .send_proof_event(
+                        format!(
+                            "Dry run: failed to keep proof for task {} in {}: {}",
+                            task.task_id,
+                            sink.path().display(),
+                            e
+                        ),
+                        EventType::Error,
+                        LogLevel::Error,
+                    )
+                    .await;
+                Err(SubmitError::DryRun(e))
+            }
+        }
+    }
+
     /// Track successful submission analytics based on task type
     async fn track_successful_submission(&self, task: &Task) {
         if task.task_type == crate::nexus_orchestrator::TaskType::ProofHash {
@@ -182,3 +540,475 @@ impl ProofSubmitter {
         }
     }
 }
+
+/// Tells the orchestrator a task is given up, so it can be reassigned instead of expiring
+#[derive(Clone)]
+pub struct TaskAbandoner {
+    node_id: u64,
+    signing_key: SigningKey,
+    orchestrator: Arc<dyn Orchestrator>,
+    event_sender: EventSender,
+    /// Nothing reaches the orchestrator in a dry run
+    dry_run: bool,
+}
+
+impl TaskAbandoner {
+    /// Give up `task_id` for `reason`. Only a courtesy: a single request with a short
+    /// timeout, and if it fails the task just expires as it would have.
+    /// Returns whether the orchestrator acknowledged it.
+    pub async fn abandon(&self, task_id: &str, reason: AbandonReason, detail: &str) -> bool {
+        if self.dry_run {
+            return false;
+        }
+        let request = self.orchestrator.abandon_task(
+            &self.node_id.to_string(),
+            task_id,
+            reason,
+            detail,
+            self.signing_key.clone(),
+        );
+        let error = match tokio::time::timeout(proof_submission::abandon_timeout(), request).await {
+            Ok(Ok(())) => {
+                self.event_sender
+                    .send_proof_event(
+                        format!(
+                            "Task {} handed back to the orchestrator ({})",
+                            task_id,
+                            reason.as_str_name()
+                        ),
+                        EventType::Refresh,
+                        LogLevel::Info,
+                    )
+                    .await;
+                return true;
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => format!(
+                "no answer within {}s",
+                proof_submission::abandon_timeout().as_secs()
+            ),
+        };
+        self.event_sender
+            .send_proof_event(
+                format!(
+                    "Could not hand task {} back to the orchestrator, it will expire instead: {}",
+                    task_id, error
+                ),
+                EventType::Refresh,
+                LogLevel::Warn,
+            )
+            .await;
+        false
+    }
+}
+
+/// Submits each input of a task as soon as its proof is verified
+struct InputSubmitter {
+    node_id: String,
+    task: Task,
+    signing_key: SigningKey,
+    orchestrator: Arc<dyn Orchestrator>,
+    /// Used by every input in turn, so their retries are paced together
+    network_client: NetworkClient,
+    event_sender: EventSender,
+    /// Inputs the orchestrator has from this node, including those from before this run
+    submitted: usize,
+}
+
+#[async_trait::async_trait]
+impl InputSink for InputSubmitter {
+    async fn submit(&mut self, index: usize, proof: &VerifiedProof) -> bool {
+        let context = InputContext {
+            node_id: &self.node_id,
+            task: &self.task,
+            signing_key: &self.signing_key,
+        };
+        let result = self
+            .network_client
+            .submit_input_proof(self.orchestrator.as_ref(), &context, index, proof)
+            .await;
+
+        match result {
+            Ok(_) => {
+                self.submitted += 1;
+                self.event_sender
+                    .send_proof_event(
+                        format!(
+                            "{} of {} inputs submitted for task {}",
+                            self.submitted,
+                            self.task.all_inputs().len(),
+                            self.task.task_id
+                        ),
+                        EventType::Refresh,
+                        LogLevel::Info,
+                    )
+                    .await;
+                true
+            }
+            Err((e, attempts)) => {
+                // Not a failure yet: the proof is kept and sent again when finalizing
+                self.event_sender
+                    .send_proof_event(
+                        format!(
+                            "Could not submit input {} of task {} after {} attempts, will retry when finalizing: {}",
+                            index, self.task.task_id, attempts, e
+                        ),
+                        EventType::Refresh,
+                        LogLevel::Warn,
+                    )
+                    .await;
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::Environment;
+    use crate::orchestrator::MockOrchestrator;
+    use crate::prover::verifier::generate_proof_hash;
+    use crate::prover::verifier::tests::{fixture_input_bytes, verified_fixture};
+    use crate::workers::dry_run::DryRunRecord;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    fn task(task_type: crate::nexus_orchestrator::TaskType) -> Task {
+        Task {
+            task_id: "abc".to_string(),
+            program_id: "fib_input_initial".to_string(),
+            public_inputs: fixture_input_bytes(),
+            public_inputs_list: vec![fixture_input_bytes()],
+            task_type,
+            difficulty: crate::nexus_orchestrator::TaskDifficulty::Small,
+            deadline: None,
+        }
+    }
+
+    /// An orchestrator whose successful responses advertise no rate limit
+    fn mock_orchestrator() -> MockOrchestrator {
+        let mut orchestrator = MockOrchestrator::new();
+        orchestrator.expect_take_rate_limit().returning(|| None);
+        orchestrator
+    }
+
+    #[tokio::test]
+    async fn test_submits_hash_of_verified_proof() {
+        crate::analytics::suppress_analytics();
+        let task = task(crate::nexus_orchestrator::TaskType::ProofHash);
+        let proof_result = ProverResult::new(
+            &task,
+            vec![verified_fixture()],
+            vec![Duration::from_secs(1)],
+        )
+        .unwrap();
+        let expected = Task::combine_hashes(&[generate_proof_hash(&proof_result.proofs()[0])]);
+        assert_ne!(expected, generate_proof_hash(&proof_result.proofs()[0]));
+
+        let mut orchestrator = mock_orchestrator();
+        orchestrator
+            .expect_submit_proof()
+            .withf(
+                move |node_id, task_id, proof_hash, _, proofs, _, _, _, hashes| {
+                    node_id == "42"
+                        && task_id == "abc"
+                        && proof_hash == expected
+                        && proofs.is_empty()
+                        && hashes.is_empty()
+                },
+            )
+            .times(1)
+            .returning(|_, _, _, _, _, _, _, _, _| Ok(()));
+        let (sender, _receiver) = mpsc::channel::<Event>(8);
+        let config = WorkerConfig::new(Environment::Production, "test".to_string());
+        let mut submitter = ProofSubmitter::new(
+            42,
+            SigningKey::from_bytes(&[7; 32]),
+            Box::new(orchestrator),
+            EventSender::new(sender),
+            &config,
+        );
+
+        submitter.submit_proof(&task, &proof_result).await.unwrap();
+        assert_eq!(submitter.last_attempts, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_submit_retry_waits_out_retry_after() {
+        crate::analytics::suppress_analytics();
+        let task = task(crate::nexus_orchestrator::TaskType::ProofHash);
+        let proof_result = ProverResult::new(
+            &task,
+            vec![verified_fixture()],
+            vec![Duration::from_secs(1)],
+        )
+        .unwrap();
+
+        let mut orchestrator = mock_orchestrator();
+        let tries = Arc::new(AtomicUsize::new(0));
+        orchestrator
+            .expect_submit_proof()
+            .times(2)
+            .returning(move |_, _, _, _, _, _, _, _, _| {
+                if tries.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(OrchestratorError::Http {
+                        status: 503,
+                        message: "Service Unavailable".to_string(),
+                        headers: HashMap::from([("retry-after".to_string(), "20".to_string())]),
+                    })
+                } else {
+                    Ok(())
+                }
+            });
+        let (sender, _receiver) = mpsc::channel::<Event>(8);
+        let config = WorkerConfig::new(Environment::Production, "test".to_string());
+        let mut submitter = ProofSubmitter::new(
+            42,
+            SigningKey::from_bytes(&[7; 32]),
+            Box::new(orchestrator),
+            EventSender::new(sender),
+            &config,
+        );
+
+        let started = tokio::time::Instant::now();
+        submitter.submit_proof(&task, &proof_result).await.unwrap();
+        assert_eq!(submitter.last_attempts, 2);
+        assert!(started.elapsed() >= Duration::from_secs(20) + rate_limiting::extra_retry_delay());
+    }
+
+    #[tokio::test]
+    async fn test_batch_reports_rejected_tasks() {
+        let first = task(crate::nexus_orchestrator::TaskType::ProofHash);
+        let second = Task {
+            task_id: "def".to_string(),
+            ..task(crate::nexus_orchestrator::TaskType::AllProofHashes)
+        };
+        let result = |task: &Task| {
+            ProverResult::new(task, vec![verified_fixture()], vec![Duration::from_secs(1)]).unwrap()
+        };
+        let (first_result, second_result) = (result(&first), result(&second));
+        let hash = generate_proof_hash(&verified_fixture());
+
+        let mut orchestrator = mock_orchestrator();
+        orchestrator
+            .expect_submit_proof_batch()
+            .withf(move |node_id, submissions, _, _| {
+                node_id == "42"
+                    && submissions.len() == 2
+                    && submissions[0].task_id == "abc"
+                    && submissions[0].all_proof_hashes.is_empty()
+                    && submissions[1].task_id == "def"
+                    && submissions[1].all_proof_hashes == [hash.clone()]
+            })
+            .times(1)
+            .returning(|_, _, _, _| {
+                Ok(vec![crate::nexus_orchestrator::RejectedSubmission {
+                    task_id: "def".to_string(),
+                    reason: "expired".to_string(),
+                }])
+            });
+        let (sender, _receiver) = mpsc::channel::<Event>(8);
+        let config = WorkerConfig::new(Environment::Production, "test".to_string());
+        let mut submitter = ProofSubmitter::new(
+            42,
+            SigningKey::from_bytes(&[7; 32]),
+            Box::new(orchestrator),
+            EventSender::new(sender),
+            &config,
+        );
+        assert!(submitter.can_batch(&first, &first_result));
+        let full = task(crate::nexus_orchestrator::TaskType::ProofRequired);
+        assert!(!submitter.can_batch(&full, &result(&full)));
+
+        let rejected = submitter
+            .submit_batch(&[(&first, &first_result), (&second, &second_result)])
+            .await
+            .unwrap();
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected["def"], "expired");
+        assert_eq!(submitter.last_attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_incremental_task_resends_unsent_inputs_before_finalizing() {
+        crate::analytics::suppress_analytics();
+        let task = task(crate::nexus_orchestrator::TaskType::ProofHash);
+        let hash = generate_proof_hash(&verified_fixture());
+        let expected = Task::combine_hashes(&[hash.clone()]);
+
+        let mut orchestrator = mock_orchestrator();
+        // The orchestrator claims to have the input, but this node never submitted it
+        let echoed = hash.clone();
+        orchestrator
+            .expect_get_task_progress()
+            .times(1)
+            .returning(move |_, _| {
+                Ok(vec![crate::nexus_orchestrator::InputProof {
+                    input_index: 0,
+                    proof_hash: echoed.clone(),
+                }])
+            });
+        // The first try, while proving, is turned away; the second, when finalizing, isn't
+        let tries = Arc::new(AtomicUsize::new(0));
+        let input_hash = hash.clone();
+        orchestrator
+            .expect_submit_input_proof()
+            .withf(move |node_id, task_id, index, proof_hash, proof, _| {
+                node_id == "42"
+                    && task_id == "abc"
+                    && *index == 0
+                    && proof_hash == input_hash
+                    && proof.is_empty()
+            })
+            .times(2)
+            .returning(move |_, _, _, _, _, _| {
+                if tries.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(OrchestratorError::Http {
+                        status: 429,
+                        message: "slow down".to_string(),
+                        headers: HashMap::new(),
+                    })
+                } else {
+                    Ok(())
+                }
+            });
+        let hashes = vec![hash.clone()];
+        orchestrator
+            .expect_finalize_task()
+            .withf(move |_, task_id, _, proof_hash, all_proof_hashes, _, _| {
+                task_id == "abc" && proof_hash == expected && all_proof_hashes == hashes
+            })
+            .times(1)
+            .returning(|_, _, _, _, _, _, _| Ok(()));
+        let (sender, _receiver) = mpsc::channel::<Event>(16);
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = WorkerConfig::new(Environment::Production, "test".to_string());
+        config.input_ledger = Some(dir.path().join("submitted_inputs.json"));
+        let mut submitter = ProofSubmitter::new(
+            42,
+            SigningKey::from_bytes(&[7; 32]),
+            Box::new(orchestrator),
+            EventSender::new(sender),
+            &config,
+        );
+
+        // Tasks smaller than the orchestrator's threshold are submitted whole
+        assert!(submitter.incremental_proving(&task, None).await.is_none());
+        assert!(
+            submitter
+                .incremental_proving(&task, Some(2))
+                .await
+                .is_none()
+        );
+        let incremental = submitter.incremental_proving(&task, Some(1)).await.unwrap();
+        assert_eq!(incremental.received_count(), 0);
+        let submission = incremental.start();
+        submission.send(0, verified_fixture()).unwrap();
+        let inputs = submission.finish().await.unwrap();
+        assert!(matches!(inputs[&0], crate::prover::ProvedInput::Held(_)));
+
+        let proof_result = ProverResult::incremental(
+            &task,
+            inputs.into_values().collect(),
+            vec![Duration::from_secs(1)],
+        )
+        .unwrap();
+        assert!(!submitter.can_batch(&task, &proof_result));
+        submitter.submit_proof(&task, &proof_result).await.unwrap();
+        assert_eq!(submitter.last_attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_abandon_is_best_effort() {
+        let mut orchestrator = mock_orchestrator();
+        let tries = Arc::new(AtomicUsize::new(0));
+        orchestrator
+            .expect_abandon_task()
+            .withf(|node_id, task_id, reason, detail, _| {
+                node_id == "42"
+                    && task_id == "abc"
+                    && *reason == AbandonReason::OutOfMemory
+                    && detail == "killed"
+            })
+            .times(2)
+            .returning(move |_, _, _, _, _| {
+                if tries.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Err(OrchestratorError::Http {
+                        status: 404,
+                        message: "not found".to_string(),
+                        headers: HashMap::new(),
+                    })
+                } else {
+                    Ok(())
+                }
+            });
+        let (sender, _receiver) = mpsc::channel::<Event>(8);
+        let config = WorkerConfig::new(Environment::Production, "test".to_string());
+        let submitter = ProofSubmitter::new(
+            42,
+            SigningKey::from_bytes(&[7; 32]),
+            Box::new(orchestrator),
+            EventSender::new(sender),
+            &config,
+        );
+
+        // A failure isn't retried, the task just expires as before
+        let abandoner = submitter.abandoner();
+        assert!(
+            !abandoner
+                .abandon("abc", AbandonReason::OutOfMemory, "killed")
+                .await
+        );
+        assert!(
+            abandoner
+                .abandon("abc", AbandonReason::OutOfMemory, "killed")
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_never_submits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = WorkerConfig::new(Environment::Production, "test".to_string());
+        config.dry_run = Some(dir.path().join("dry-run.jsonl"));
+        let (sender, mut receiver) = mpsc::channel::<Event>(8);
+        // No expectations: any call to the orchestrator panics
+        let mut submitter = ProofSubmitter::new(
+            42,
+            SigningKey::from_bytes(&[7; 32]),
+            Box::new(MockOrchestrator::new()),
+            EventSender::new(sender),
+            &config,
+        );
+        assert!(submitter.is_dry_run());
+
+        let task = task(crate::nexus_orchestrator::TaskType::ProofRequired);
+        let proof_result = ProverResult::new(
+            &task,
+            vec![verified_fixture()],
+            vec![Duration::from_secs(1)],
+        )
+        .unwrap();
+        submitter.submit_proof(&task, &proof_result).await.unwrap();
+        assert_eq!(submitter.last_attempts, 0);
+        assert!(
+            !submitter
+                .abandoner()
+                .abandon("abc", AbandonReason::Shutdown, "shutdown")
+                .await
+        );
+
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.event_type, EventType::Success);
+        assert!(
+            event
+                .msg
+                .starts_with("Step 4 of 4: Dry run: proof for task abc kept in ")
+        );
+        let contents = std::fs::read_to_string(dir.path().join("dry-run.jsonl")).unwrap();
+        let record: DryRunRecord = serde_json::from_str(contents.trim()).unwrap();
+        assert_eq!(record.task_id, "abc");
+    }
+}